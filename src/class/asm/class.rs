@@ -0,0 +1,429 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use crate::class::constantpool::{BuildError, Index, PoolBuilder};
+use crate::class::write::BinaryWriter;
+use crate::class::CLASS_FILE_MAGIC_NUMBER;
+use crate::loader::Serialize;
+use crate::loader::classfile::attribute::UnknownAttribute;
+use crate::loader::classfile::attribute::classfile::BootstrapMethod;
+use crate::types::{methodhandle, Array, ClassAccessFlags, FieldAccessFlags, MethodAccessFlags};
+use super::code;
+use super::lexer::{parse_int, Cursor, Line, Token};
+use super::{AssembleError, AssembleErrorKind};
+
+/// Goes through the source a line at a time, adding everything it refers to to the constant
+/// pool as it goes, so entries are in the order they first appear.
+pub struct Assembler<'a> {
+    lines: &'a [Line],
+    pos: usize,
+    pool: PoolBuilder,
+    bootstrap_methods: Vec<BootstrapMethod>,
+}
+
+impl<'a> Assembler<'a> {
+    pub fn new(lines: &'a [Line]) -> Self {
+        Self { lines, pos: 0, pool: PoolBuilder::new(), bootstrap_methods: Vec::new() }
+    }
+
+    /// The next line of a block, such as a method, which has to end with `.end` and the
+    /// name of the block before the end of the source.
+    pub fn block_line(&mut self, block: &'static str) -> Result<Option<Cursor<'a>>, AssembleError> {
+        let Some(line) = self.lines.get(self.pos) else {
+            return Err(self.end_error(AssembleErrorKind::MissingEnd(block)));
+        };
+        self.pos += 1;
+
+        let mut cursor = Cursor::new(line);
+        if cursor.eat(".end") {
+            cursor.expect(block)?;
+            cursor.finish()?;
+            return Ok(None);
+        }
+        Ok(Some(cursor))
+    }
+
+    // Errors that aren't found until the end of the source are reported on the last line
+    fn end_error(&self, kind: AssembleErrorKind) -> AssembleError {
+        AssembleError { line: self.lines.last().map_or(1, |line| line.number), kind }
+    }
+
+    pub fn assemble(mut self) -> Result<Vec<u8>, AssembleError> {
+        let mut version = (69, 0);
+        let mut header = None;
+        let mut super_class = None;
+        let mut interfaces = Vec::new();
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        let mut attributes = Vec::new();
+
+        while let Some(line) = self.lines.get(self.pos) {
+            self.pos += 1;
+            let mut cursor = Cursor::new(line);
+            match cursor.word()? {
+                ".version" => version = (cursor.number()?, cursor.number()?),
+                ".class" => {
+                    let flags = flags(&mut cursor, ClassAccessFlags::from_name)?;
+                    header = Some((flags, self.class(&mut cursor)?));
+                }
+                ".super" => super_class = Some(self.class(&mut cursor)?),
+                ".implements" => interfaces.push(self.class(&mut cursor)?),
+                ".const" => {
+                    self.constant(&mut cursor)?;
+                }
+                ".field" => fields.push(self.field(&mut cursor)?),
+                ".method" => methods.push(self.method(&mut cursor)?),
+                ".source" => {
+                    let source = self.utf8(&mut cursor)?;
+                    attributes.push(self.attribute_with(&cursor, "SourceFile", |buf| buf.write_u16(source))?);
+                }
+                ".bootstrap" => {
+                    let handle = self.constant(&mut cursor)?;
+                    let mut arguments = Vec::new();
+                    while !cursor.is_empty() {
+                        arguments.push(self.constant(&mut cursor)?);
+                    }
+                    let arguments = Array::from_vec(arguments)
+                        .map_err(|_| cursor.error(AssembleErrorKind::OutOfMemory))?;
+                    self.bootstrap_methods.push(BootstrapMethod::new(handle, arguments));
+                }
+                ".end" => {
+                    cursor.expect("class")?;
+                    cursor.finish()?;
+                    if let Some(line) = self.lines.get(self.pos) {
+                        return Err(Cursor::new(line).unexpected(&line.tokens[0]));
+                    }
+                }
+                directive => match self.member_attribute(directive, &mut cursor)? {
+                    Some(attribute) => attributes.push(attribute),
+                    None => return Err(cursor.error(AssembleErrorKind::UnknownDirective(directive.into()))),
+                },
+            }
+            cursor.finish()?;
+        }
+
+        let Some((flags, this_class)) = header else {
+            return Err(self.end_error(AssembleErrorKind::MissingDirective(".class")));
+        };
+        let super_class = match super_class {
+            Some(index) => index,
+            None => self.pool.class("java/lang/Object").map_err(|err| self.end_error(AssembleErrorKind::Pool(err)))?,
+        };
+        if !self.bootstrap_methods.is_empty() {
+            let name_index = self.pool.utf8("BootstrapMethods")
+                .map_err(|err| self.end_error(AssembleErrorKind::Pool(err)))?;
+            let mut body = BinaryWriter::new();
+            body.write_arr(&self.bootstrap_methods);
+            attributes.push(UnknownAttribute::new(name_index, body.into_bytes().into()));
+        }
+
+        let out_of_memory = self.end_error(AssembleErrorKind::OutOfMemory);
+        let pool = self.pool.build().map_err(|_| out_of_memory)?;
+        let mut buf = BinaryWriter::new();
+        buf.write_u32(CLASS_FILE_MAGIC_NUMBER);
+        buf.write_u16(version.1);
+        buf.write_u16(version.0);
+        pool.serialize(&mut buf);
+        buf.write_u16(flags);
+        buf.write_u16(this_class);
+        buf.write_u16(super_class);
+        buf.write_u16_arr(&interfaces);
+        for members in [fields, methods] {
+            buf.write_u16(members.len() as u16);
+            for member in members {
+                buf.write_bytes(&member);
+            }
+        }
+        buf.write_arr(&attributes);
+        Ok(buf.into_bytes())
+    }
+
+    // .field <flags> <name> <descriptor> [= <constant>] [.fieldattributes]
+    fn field(&mut self, cursor: &mut Cursor) -> Result<Vec<u8>, AssembleError> {
+        let flags = flags(cursor, FieldAccessFlags::from_name)?;
+        let name_index = self.utf8(cursor)?;
+        let descriptor_index = self.utf8(cursor)?;
+
+        let mut attributes = Vec::new();
+        if cursor.eat("=") {
+            let value = self.constant(cursor)?;
+            attributes.push(self.attribute_with(cursor, "ConstantValue", |buf| buf.write_u16(value))?);
+        }
+        if cursor.eat(".fieldattributes") {
+            cursor.finish()?;
+            while let Some(mut cursor) = self.block_line("fieldattributes")? {
+                let directive = cursor.word()?;
+                match self.member_attribute(directive, &mut cursor)? {
+                    Some(attribute) => attributes.push(attribute),
+                    None => return Err(cursor.error(AssembleErrorKind::UnknownDirective(directive.into()))),
+                }
+                cursor.finish()?;
+            }
+        }
+        Ok(member(flags, name_index, descriptor_index, &attributes))
+    }
+
+    // .method <flags> <name> : <descriptor>, up to .end method
+    fn method(&mut self, cursor: &mut Cursor) -> Result<Vec<u8>, AssembleError> {
+        let flags = flags(cursor, MethodAccessFlags::from_name)?;
+        let name_index = self.utf8(cursor)?;
+        cursor.colon()?;
+        let descriptor_index = self.utf8(cursor)?;
+
+        let mut attributes = Vec::new();
+        let mut exceptions = Vec::new();
+        while let Some(mut cursor) = self.block_line("method")? {
+            match cursor.word()? {
+                ".code" => attributes.push(code::assemble(self, &mut cursor)?),
+                ".throws" => exceptions.push(self.class(&mut cursor)?),
+                directive => match self.member_attribute(directive, &mut cursor)? {
+                    Some(attribute) => attributes.push(attribute),
+                    None => return Err(cursor.error(AssembleErrorKind::UnknownDirective(directive.into()))),
+                },
+            }
+            cursor.finish()?;
+        }
+
+        if !exceptions.is_empty() {
+            attributes.push(self.attribute_with(cursor, "Exceptions", |buf| buf.write_u16_arr(&exceptions))?);
+        }
+        Ok(member(flags, name_index, descriptor_index, &attributes))
+    }
+
+    /// The attributes that can be given to anything, or `None` if the directive isn't one
+    /// of them.
+    pub fn member_attribute(&mut self, directive: &str,
+                            cursor: &mut Cursor) -> Result<Option<UnknownAttribute>, AssembleError> {
+        Ok(Some(match directive {
+            ".signature" => {
+                let signature = self.utf8(cursor)?;
+                self.attribute_with(cursor, "Signature", |buf| buf.write_u16(signature))?
+            }
+            ".deprecated" => self.attribute(cursor, "Deprecated", Vec::new())?,
+            ".synthetic" => self.attribute(cursor, "Synthetic", Vec::new())?,
+            // Any attribute at all, with its body given as bytes
+            ".attribute" => {
+                let name = cursor.name()?;
+                let body = cursor.bytes()?;
+                self.attribute(cursor, name, body.to_vec())?
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    /// An attribute with the given body. Attributes are kept as unknown attributes, which
+    /// are written out as they are, as they don't have to be valid.
+    pub fn attribute(&mut self, cursor: &Cursor, name: &str, body: Vec<u8>) -> Result<UnknownAttribute, AssembleError> {
+        let name_index = self.pool.utf8(name).map_err(pool_error(cursor))?;
+        Ok(UnknownAttribute::new(name_index, body.into()))
+    }
+
+    /// An attribute with whatever `body` writes as its body.
+    pub fn attribute_with(&mut self, cursor: &Cursor, name: &str,
+                          body: impl FnOnce(&mut BinaryWriter)) -> Result<UnknownAttribute, AssembleError> {
+        let mut buf = BinaryWriter::new();
+        body(&mut buf);
+        self.attribute(cursor, name, buf.into_bytes())
+    }
+
+    pub fn utf8(&mut self, cursor: &mut Cursor) -> Result<Index, AssembleError> {
+        let value = cursor.name()?;
+        self.pool.utf8(value).map_err(pool_error(cursor))
+    }
+
+    /// A class given by its name, or a raw constant pool index such as `#0`.
+    pub fn class(&mut self, cursor: &mut Cursor) -> Result<Index, AssembleError> {
+        let name = cursor.name()?;
+        if let Some(index) = raw_index(cursor, name)? {
+            return Ok(index);
+        }
+        self.pool.class(name).map_err(pool_error(cursor))
+    }
+
+    /// A constant that an instruction refers to, which is written out in full unless it's the
+    /// kind the instruction usually takes, in which case the kind can be left out. For
+    /// example, `getfield Field Point x I` can be written as `getfield Point x I`.
+    pub fn constant_or(&mut self, cursor: &mut Cursor, kind: &str) -> Result<Index, AssembleError> {
+        match cursor.peek_word() {
+            Some(word) if word.starts_with('#') || CONSTANT_KINDS.contains(&word) => self.constant(cursor),
+            _ => self.constant_of(cursor, kind),
+        }
+    }
+
+    /// A constant, which is one of:
+    ///  - a raw index in to the constant pool, such as `#5`
+    ///  - a quoted string, for a `String` entry
+    ///  - a number, for an `Int`, or if it has an `L` suffix, a `Long`. A number with a
+    ///    decimal point or exponent is a `Double`, or a `Float` if it has an `f` suffix
+    ///  - the kind of entry followed by its value, such as `Class java/lang/Object` or
+    ///    `Method java/lang/Object <init> ()V`
+    pub fn constant(&mut self, cursor: &mut Cursor) -> Result<Index, AssembleError> {
+        let word = match cursor.next()? {
+            Token::Str(value) => return self.pool.string(value).map_err(pool_error(cursor)),
+            Token::Word(word) => word,
+            token => return Err(cursor.unexpected(token)),
+        };
+        if let Some(index) = raw_index(cursor, word)? {
+            return Ok(index);
+        }
+        if CONSTANT_KINDS.contains(&word.as_str()) {
+            return self.constant_of(cursor, word);
+        }
+
+        let result = match literal(word) {
+            Some(Literal::Int(value)) => self.pool.integer(value),
+            Some(Literal::Long(value)) => self.pool.long(value),
+            Some(Literal::Float(value)) => self.pool.float(value),
+            Some(Literal::Double(value)) => self.pool.double(value),
+            None => return Err(cursor.error(AssembleErrorKind::InvalidNumber(word.clone()))),
+        };
+        result.map_err(pool_error(cursor))
+    }
+
+    fn constant_of(&mut self, cursor: &mut Cursor, kind: &str) -> Result<Index, AssembleError> {
+        let result = match kind {
+            "Utf8" => self.pool.utf8(cursor.name()?),
+            "Int" => self.pool.integer(cursor.number()?),
+            "Long" => self.pool.long(cursor.number()?),
+            // Floats and doubles can be given by their bits in hexadecimal, for NaNs with a
+            // particular payload
+            "Float" => {
+                let word = cursor.word()?;
+                let value = match parse_int(word) {
+                    Some(bits) if is_hex(word) => u32::try_from(bits).ok().map(f32::from_bits),
+                    _ => word.strip_suffix(['f', 'F']).unwrap_or(word).parse().ok(),
+                };
+                self.pool.float(value.ok_or_else(|| cursor.error(AssembleErrorKind::InvalidNumber(word.into())))?)
+            }
+            "Double" => {
+                let word = cursor.word()?;
+                let value = match parse_int(word) {
+                    Some(bits) if is_hex(word) => u64::try_from(bits).ok().map(f64::from_bits),
+                    _ => word.strip_suffix(['d', 'D']).unwrap_or(word).parse().ok(),
+                };
+                self.pool.double(value.ok_or_else(|| cursor.error(AssembleErrorKind::InvalidNumber(word.into())))?)
+            }
+            "String" => self.pool.string(cursor.name()?),
+            "Class" => self.pool.class(cursor.name()?),
+            "NameAndType" => self.pool.name_and_type(cursor.name()?, cursor.name()?),
+            "Field" => self.pool.field_ref(cursor.name()?, cursor.name()?, cursor.name()?),
+            "Method" => self.pool.method_ref(cursor.name()?, cursor.name()?, cursor.name()?),
+            "InterfaceMethod" => self.pool.interface_method_ref(cursor.name()?, cursor.name()?, cursor.name()?),
+            "MethodType" => self.pool.method_type(cursor.name()?),
+            "MethodHandle" => {
+                let word = cursor.word()?;
+                let kind = handle_kind(word).ok_or_else(|| cursor.error(AssembleErrorKind::Unexpected(word.into())))?;
+                let reference = self.constant(cursor)?;
+                self.pool.method_handle(kind, reference)
+            }
+            "Dynamic" => self.pool.dynamic(cursor.number()?, cursor.name()?, cursor.name()?),
+            "InvokeDynamic" => self.pool.invoke_dynamic(cursor.number()?, cursor.name()?, cursor.name()?),
+            "Module" => self.pool.module(cursor.name()?),
+            "Package" => self.pool.package(cursor.name()?),
+            _ => unreachable!("unknown constant kind {kind}"),
+        };
+        result.map_err(pool_error(cursor))
+    }
+}
+
+const CONSTANT_KINDS: &[&str] = &[
+    "Utf8", "Int", "Long", "Float", "Double", "String", "Class", "NameAndType", "Field", "Method",
+    "InterfaceMethod", "MethodType", "MethodHandle", "Dynamic", "InvokeDynamic", "Module", "Package",
+];
+
+fn member(flags: u16, name_index: Index, descriptor_index: Index, attributes: &[UnknownAttribute]) -> Vec<u8> {
+    let mut buf = BinaryWriter::new();
+    buf.write_u16(flags);
+    buf.write_u16(name_index);
+    buf.write_u16(descriptor_index);
+    buf.write_arr(attributes);
+    buf.into_bytes()
+}
+
+// Flags are given by their lowercase names, such as `public`, or as numbers in hexadecimal,
+// for bits that don't have a name
+fn flags(cursor: &mut Cursor, from_name: fn(&str) -> Option<u16>) -> Result<u16, AssembleError> {
+    let mut flags = 0;
+    while let Some(word) = cursor.peek_word() {
+        if is_hex(word) {
+            flags |= cursor.number::<u16>()?;
+            continue;
+        }
+        if !word.bytes().all(|b| b.is_ascii_lowercase()) {
+            break;
+        }
+        let Some(flag) = from_name(&word.to_ascii_uppercase()) else {
+            break;
+        };
+        cursor.word()?;
+        flags |= flag;
+    }
+    Ok(flags)
+}
+
+fn pool_error<'a>(cursor: &'a Cursor) -> impl Fn(BuildError) -> AssembleError + 'a {
+    |err| cursor.error(AssembleErrorKind::Pool(err))
+}
+
+fn raw_index(cursor: &Cursor, word: &str) -> Result<Option<Index>, AssembleError> {
+    let Some(digits) = word.strip_prefix('#') else {
+        return Ok(None);
+    };
+    let index = parse_int(digits).ok_or_else(|| cursor.error(AssembleErrorKind::InvalidNumber(word.into())))?;
+    Index::try_from(index).map(Some).map_err(|_| cursor.error(AssembleErrorKind::OutOfRange(word.into())))
+}
+
+fn is_hex(word: &str) -> bool {
+    let digits = word.strip_prefix('-').unwrap_or(word);
+    digits.starts_with("0x") || digits.starts_with("0X")
+}
+
+enum Literal {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+}
+
+// Numbers are typed the same way as in Java source
+fn literal(word: &str) -> Option<Literal> {
+    if let Some(value) = parse_int(word) {
+        return i32::try_from(value).ok().map(Literal::Int);
+    }
+    if let Some(value) = word.strip_suffix(['L', 'l']).and_then(parse_int) {
+        return i64::try_from(value).ok().map(Literal::Long);
+    }
+    // Rust's float parsing also takes `inf`, `NaN` and `infinity` in any case
+    match word.strip_suffix(['f', 'F']) {
+        Some(float) => float.parse().ok().map(Literal::Float),
+        None => word.strip_suffix(['d', 'D']).unwrap_or(word).parse().ok().map(Literal::Double),
+    }
+}
+
+// The names used in the JVMS, such as `invokeStatic` for `REF_invokeStatic`
+fn handle_kind(name: &str) -> Option<methodhandle::Ref> {
+    Some(match name {
+        "getField" => methodhandle::Ref::GetField,
+        "getStatic" => methodhandle::Ref::GetStatic,
+        "putField" => methodhandle::Ref::PutField,
+        "putStatic" => methodhandle::Ref::PutStatic,
+        "invokeVirtual" => methodhandle::Ref::InvokeVirtual,
+        "invokeStatic" => methodhandle::Ref::InvokeStatic,
+        "invokeSpecial" => methodhandle::Ref::InvokeSpecial,
+        "newInvokeSpecial" => methodhandle::Ref::NewInvokeSpecial,
+        "invokeInterface" => methodhandle::Ref::InvokeInterface,
+        _ => return None,
+    })
+}
@@ -0,0 +1,552 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use crate::class::bytecode::{ArrayType, Instruction, LookupSwitch, Opcode, TableSwitch, WideInstruction};
+use crate::class::constantpool::Index;
+use crate::class::write::BinaryWriter;
+use crate::loader::classfile::attribute::{code, stackmap, UnknownAttribute};
+use crate::types::Array;
+use super::class::Assembler;
+use super::lexer::{parse_int, Cursor};
+use super::{AssembleError, AssembleErrorKind};
+
+// A label, or an address given as a number, for pointing in to the middle of an instruction
+enum Address {
+    Label { name: String, line: usize },
+    Pc(u32),
+}
+
+// A branch offset to fill in once the label it goes to is known
+struct Fixup {
+    name: String,
+    line: usize,
+    // The address of the branch instruction, which the offset is relative to
+    pc: u32,
+    // Where the offset is in the code
+    at: usize,
+    wide: bool,
+}
+
+enum VerificationType {
+    // The types without any info
+    Simple(stackmap::VerificationType),
+    Object(Index),
+    Uninitialized(Address),
+}
+
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.4
+enum Frame {
+    Same,
+    SameExtended,
+    SameLocals1StackItem(VerificationType),
+    SameLocals1StackItemExtended(VerificationType),
+    Chop(u8),
+    Append(Vec<VerificationType>),
+    Full(Vec<VerificationType>, Vec<VerificationType>),
+}
+
+struct Catch {
+    start: Address,
+    end: Address,
+    handler: Address,
+    catch_type: Index,
+}
+
+struct LocalVariable {
+    start: Address,
+    end: Address,
+    name_index: Index,
+    descriptor_index: Index,
+    index: u16,
+}
+
+#[derive(Default)]
+struct Code {
+    code: Vec<u8>,
+    labels: HashMap<String, u32>,
+    fixups: Vec<Fixup>,
+    catches: Vec<Catch>,
+    // Along with the address of the instruction after them, and the line they're on
+    frames: Vec<(u32, usize, Frame)>,
+    line_numbers: Vec<(u32, u16)>,
+    local_variables: Vec<LocalVariable>,
+    attributes: Vec<UnknownAttribute>,
+}
+
+/// Assembles a `Code` attribute, from `.code stack <max stack> locals <max locals>` up to
+/// `.end code`. Labels are defined with `name:`, either on their own line or before an
+/// instruction, and can be used before they're defined.
+pub fn assemble<'a>(asm: &mut Assembler<'a>, header: &mut Cursor) -> Result<UnknownAttribute, AssembleError> {
+    header.expect("stack")?;
+    let max_stack = header.number()?;
+    header.expect("locals")?;
+    let max_locals = header.number()?;
+
+    let mut code = Code::default();
+    while let Some(mut cursor) = asm.block_line("code")? {
+        if let Some(name) = cursor.label() {
+            if code.labels.insert(name.into(), code.pc()).is_some() {
+                return Err(cursor.error(AssembleErrorKind::DuplicateLabel(name.into())));
+            }
+            if cursor.is_empty() {
+                continue;
+            }
+        }
+
+        match cursor.word()? {
+            // .catch <class or any> from <start> to <end> using <handler>
+            ".catch" => {
+                let catch_type = if cursor.eat("any") { 0 } else { asm.class(&mut cursor)? };
+                cursor.expect("from")?;
+                let start = address(&mut cursor)?;
+                cursor.expect("to")?;
+                let end = address(&mut cursor)?;
+                cursor.expect("using")?;
+                let handler = address(&mut cursor)?;
+                code.catches.push(Catch { start, end, handler, catch_type });
+            }
+            // A frame for the instruction that comes next
+            ".stack" => {
+                let frame = frame(asm, &mut cursor)?;
+                code.frames.push((code.pc(), cursor.line(), frame));
+            }
+            ".line" => {
+                let line = cursor.number()?;
+                code.line_numbers.push((code.pc(), line));
+            }
+            // .var <index> is <name> <descriptor> from <start> to <end>
+            ".var" => {
+                let index = cursor.number()?;
+                cursor.expect("is")?;
+                let name_index = asm.utf8(&mut cursor)?;
+                let descriptor_index = asm.utf8(&mut cursor)?;
+                cursor.expect("from")?;
+                let start = address(&mut cursor)?;
+                cursor.expect("to")?;
+                let end = address(&mut cursor)?;
+                code.local_variables.push(LocalVariable { start, end, name_index, descriptor_index, index });
+            }
+            ".attribute" => {
+                let name = cursor.name()?;
+                let body = cursor.bytes()?;
+                code.attributes.push(asm.attribute(&cursor, name, body.to_vec())?);
+            }
+            mnemonic if mnemonic.starts_with('.') => {
+                return Err(cursor.error(AssembleErrorKind::UnknownDirective(mnemonic.into())));
+            }
+            mnemonic => instruction(asm, &mut code, &mut cursor, mnemonic)?,
+        }
+        cursor.finish()?;
+    }
+
+    code.finish(asm, header, max_stack, max_locals)
+}
+
+fn instruction<'a>(asm: &mut Assembler<'a>, code: &mut Code, cursor: &mut Cursor<'a>,
+                   mnemonic: &str) -> Result<(), AssembleError> {
+    let opcode = Opcode::from_mnemonic(mnemonic)
+        .ok_or_else(|| cursor.error(AssembleErrorKind::UnknownInstruction(mnemonic.into())))?;
+    if let Some(instruction) = Instruction::from_opcode(opcode) {
+        instruction.encode(&mut code.code);
+        return Ok(());
+    }
+
+    let pc = code.pc();
+    let instruction = match opcode {
+        Opcode::Bipush => Instruction::Bipush(cursor.number()?),
+        Opcode::Sipush => Instruction::Sipush(cursor.number()?),
+        Opcode::Ldc => {
+            let index = asm.constant(cursor)?;
+            let out_of_range = || cursor.error(AssembleErrorKind::OutOfRange(format!("#{index}")));
+            Instruction::Ldc(u8::try_from(index).map_err(|_| out_of_range())?)
+        }
+        Opcode::LdcW => Instruction::LdcW(asm.constant(cursor)?),
+        Opcode::Ldc2W => Instruction::Ldc2W(asm.constant(cursor)?),
+
+        Opcode::Iload => Instruction::Iload(cursor.number()?),
+        Opcode::Lload => Instruction::Lload(cursor.number()?),
+        Opcode::Fload => Instruction::Fload(cursor.number()?),
+        Opcode::Dload => Instruction::Dload(cursor.number()?),
+        Opcode::Aload => Instruction::Aload(cursor.number()?),
+        Opcode::Istore => Instruction::Istore(cursor.number()?),
+        Opcode::Lstore => Instruction::Lstore(cursor.number()?),
+        Opcode::Fstore => Instruction::Fstore(cursor.number()?),
+        Opcode::Dstore => Instruction::Dstore(cursor.number()?),
+        Opcode::Astore => Instruction::Astore(cursor.number()?),
+        Opcode::Ret => Instruction::Ret(cursor.number()?),
+        Opcode::Iinc => Instruction::Iinc(cursor.number()?, cursor.number()?),
+        Opcode::Wide => Instruction::Wide(wide(cursor)?),
+
+        Opcode::Ifeq => Instruction::Ifeq(code.branch(cursor, pc)?),
+        Opcode::Ifne => Instruction::Ifne(code.branch(cursor, pc)?),
+        Opcode::Iflt => Instruction::Iflt(code.branch(cursor, pc)?),
+        Opcode::Ifge => Instruction::Ifge(code.branch(cursor, pc)?),
+        Opcode::Ifgt => Instruction::Ifgt(code.branch(cursor, pc)?),
+        Opcode::Ifle => Instruction::Ifle(code.branch(cursor, pc)?),
+        Opcode::IfIcmpeq => Instruction::IfIcmpeq(code.branch(cursor, pc)?),
+        Opcode::IfIcmpne => Instruction::IfIcmpne(code.branch(cursor, pc)?),
+        Opcode::IfIcmplt => Instruction::IfIcmplt(code.branch(cursor, pc)?),
+        Opcode::IfIcmpge => Instruction::IfIcmpge(code.branch(cursor, pc)?),
+        Opcode::IfIcmpgt => Instruction::IfIcmpgt(code.branch(cursor, pc)?),
+        Opcode::IfIcmple => Instruction::IfIcmple(code.branch(cursor, pc)?),
+        Opcode::IfAcmpeq => Instruction::IfAcmpeq(code.branch(cursor, pc)?),
+        Opcode::IfAcmpne => Instruction::IfAcmpne(code.branch(cursor, pc)?),
+        Opcode::Goto => Instruction::Goto(code.branch(cursor, pc)?),
+        Opcode::Jsr => Instruction::Jsr(code.branch(cursor, pc)?),
+        Opcode::Ifnull => Instruction::Ifnull(code.branch(cursor, pc)?),
+        Opcode::Ifnonnull => Instruction::Ifnonnull(code.branch(cursor, pc)?),
+        Opcode::GotoW => Instruction::GotoW(code.target(cursor, pc, pc as usize + 1, true)?),
+        Opcode::JsrW => Instruction::JsrW(code.target(cursor, pc, pc as usize + 1, true)?),
+
+        // tableswitch <low>, followed by the targets for each value in turn, then
+        // default : <target>, over as many lines as needed
+        Opcode::Tableswitch => {
+            let low: i32 = cursor.number()?;
+            let base = switch_base(pc);
+            let mut offsets = Vec::new();
+            let default = loop {
+                switch_line(asm, cursor)?;
+                if cursor.eat("default") {
+                    cursor.colon()?;
+                    break code.target(cursor, pc, base, true)?;
+                }
+                let at = base + 12 + 4 * offsets.len();
+                offsets.push(code.target(cursor, pc, at, true)?);
+            };
+            let high = i32::try_from(low as i64 + offsets.len() as i64 - 1)
+                .map_err(|_| cursor.error(AssembleErrorKind::OutOfRange(low.to_string())))?;
+            Instruction::Tableswitch(TableSwitch { default, low, high, offsets })
+        }
+        // lookupswitch, followed by <value> : <target> for each match, then
+        // default : <target>. Matches are kept in the order they're given.
+        Opcode::Lookupswitch => {
+            let base = switch_base(pc);
+            let mut pairs = Vec::new();
+            let default = loop {
+                switch_line(asm, cursor)?;
+                if cursor.eat("default") {
+                    cursor.colon()?;
+                    break code.target(cursor, pc, base, true)?;
+                }
+                let value = cursor.number()?;
+                cursor.colon()?;
+                let at = base + 12 + 8 * pairs.len();
+                pairs.push((value, code.target(cursor, pc, at, true)?));
+            };
+            Instruction::Lookupswitch(LookupSwitch { default, pairs })
+        }
+
+        Opcode::Getstatic => Instruction::Getstatic(asm.constant_or(cursor, "Field")?),
+        Opcode::Putstatic => Instruction::Putstatic(asm.constant_or(cursor, "Field")?),
+        Opcode::Getfield => Instruction::Getfield(asm.constant_or(cursor, "Field")?),
+        Opcode::Putfield => Instruction::Putfield(asm.constant_or(cursor, "Field")?),
+        Opcode::Invokevirtual => Instruction::Invokevirtual(asm.constant_or(cursor, "Method")?),
+        Opcode::Invokespecial => Instruction::Invokespecial(asm.constant_or(cursor, "Method")?),
+        Opcode::Invokestatic => Instruction::Invokestatic(asm.constant_or(cursor, "Method")?),
+        Opcode::Invokeinterface => {
+            Instruction::Invokeinterface(asm.constant_or(cursor, "InterfaceMethod")?, cursor.number()?)
+        }
+        Opcode::Invokedynamic => Instruction::Invokedynamic(asm.constant_or(cursor, "InvokeDynamic")?),
+        Opcode::New => Instruction::New(asm.constant_or(cursor, "Class")?),
+        Opcode::Anewarray => Instruction::Anewarray(asm.constant_or(cursor, "Class")?),
+        Opcode::Checkcast => Instruction::Checkcast(asm.constant_or(cursor, "Class")?),
+        Opcode::Instanceof => Instruction::Instanceof(asm.constant_or(cursor, "Class")?),
+        Opcode::Multianewarray => Instruction::Multianewarray(asm.constant_or(cursor, "Class")?, cursor.number()?),
+        Opcode::Newarray => {
+            let word = cursor.word()?;
+            let ty = array_type(word).ok_or_else(|| cursor.error(AssembleErrorKind::Unexpected(word.into())))?;
+            Instruction::Newarray(ty)
+        }
+        _ => unreachable!("{} takes operands", opcode.mnemonic()),
+    };
+    instruction.encode(&mut code.code);
+    Ok(())
+}
+
+// wide <instruction> <index> [<value>]
+fn wide(cursor: &mut Cursor) -> Result<WideInstruction, AssembleError> {
+    let word = cursor.word()?;
+    let index = cursor.number()?;
+    Ok(match Opcode::from_mnemonic(word) {
+        Some(Opcode::Iload) => WideInstruction::Iload(index),
+        Some(Opcode::Lload) => WideInstruction::Lload(index),
+        Some(Opcode::Fload) => WideInstruction::Fload(index),
+        Some(Opcode::Dload) => WideInstruction::Dload(index),
+        Some(Opcode::Aload) => WideInstruction::Aload(index),
+        Some(Opcode::Istore) => WideInstruction::Istore(index),
+        Some(Opcode::Lstore) => WideInstruction::Lstore(index),
+        Some(Opcode::Fstore) => WideInstruction::Fstore(index),
+        Some(Opcode::Dstore) => WideInstruction::Dstore(index),
+        Some(Opcode::Astore) => WideInstruction::Astore(index),
+        Some(Opcode::Ret) => WideInstruction::Ret(index),
+        Some(Opcode::Iinc) => WideInstruction::Iinc(index, cursor.number()?),
+        _ => return Err(cursor.error(AssembleErrorKind::Unexpected(word.into()))),
+    })
+}
+
+fn array_type(name: &str) -> Option<ArrayType> {
+    Some(match name {
+        "boolean" => ArrayType::Boolean,
+        "char" => ArrayType::Char,
+        "float" => ArrayType::Float,
+        "double" => ArrayType::Double,
+        "byte" => ArrayType::Byte,
+        "short" => ArrayType::Short,
+        "int" => ArrayType::Int,
+        "long" => ArrayType::Long,
+        _ => return None,
+    })
+}
+
+// Where the default offset of a switch at `pc` is, after the padding that aligns it
+fn switch_base(pc: u32) -> usize {
+    let after_opcode = pc as usize + 1;
+    after_opcode + (4 - after_opcode % 4) % 4
+}
+
+// Moves on to the next line once the current one runs out, as switches carry on up to their
+// default
+fn switch_line<'a>(asm: &mut Assembler<'a>, cursor: &mut Cursor<'a>) -> Result<(), AssembleError> {
+    while cursor.is_empty() {
+        let line = cursor.line();
+        *cursor = asm.block_line("code")?.ok_or(AssembleError { line, kind: AssembleErrorKind::UnexpectedEnd })?;
+    }
+    Ok(())
+}
+
+fn address(cursor: &mut Cursor) -> Result<Address, AssembleError> {
+    let word = cursor.word()?;
+    match parse_int(word) {
+        Some(pc) => {
+            let pc = u32::try_from(pc).map_err(|_| cursor.error(AssembleErrorKind::OutOfRange(word.into())))?;
+            Ok(Address::Pc(pc))
+        }
+        None => Ok(Address::Label { name: word.into(), line: cursor.line() }),
+    }
+}
+
+// .stack <frame type> <types>, using the names of the frame types in the JVMS. Offset deltas
+// are worked out from where the frames are, and the smallest form of frame is used unless
+// one of the `extended` forms is given.
+fn frame(asm: &mut Assembler, cursor: &mut Cursor) -> Result<Frame, AssembleError> {
+    let word = cursor.word()?;
+    Ok(match word {
+        "same" => Frame::Same,
+        "same_extended" => Frame::SameExtended,
+        "same_locals_1_stack_item" => Frame::SameLocals1StackItem(verification_type(asm, cursor)?),
+        "same_locals_1_stack_item_extended" => Frame::SameLocals1StackItemExtended(verification_type(asm, cursor)?),
+        "chop" => {
+            let word = cursor.word()?;
+            match parse_int(word) {
+                Some(count @ 1..=3) => Frame::Chop(count as u8),
+                _ => return Err(cursor.error(AssembleErrorKind::OutOfRange(word.into()))),
+            }
+        }
+        "append" => {
+            let mut locals = Vec::new();
+            while !cursor.is_empty() {
+                locals.push(verification_type(asm, cursor)?);
+            }
+            if !(1..=3).contains(&locals.len()) {
+                return Err(cursor.error(AssembleErrorKind::OutOfRange(locals.len().to_string())));
+            }
+            Frame::Append(locals)
+        }
+        // full locals <types> stack <types>
+        "full" => {
+            cursor.expect("locals")?;
+            let mut locals = Vec::new();
+            while !cursor.eat("stack") {
+                locals.push(verification_type(asm, cursor)?);
+            }
+            let mut stack = Vec::new();
+            while !cursor.is_empty() {
+                stack.push(verification_type(asm, cursor)?);
+            }
+            Frame::Full(locals, stack)
+        }
+        _ => return Err(cursor.error(AssembleErrorKind::Unexpected(word.into()))),
+    })
+}
+
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.4
+fn verification_type(asm: &mut Assembler, cursor: &mut Cursor) -> Result<VerificationType, AssembleError> {
+    let word = cursor.word()?;
+    Ok(match word {
+        "Top" => VerificationType::Simple(stackmap::VerificationType::Top),
+        "Integer" => VerificationType::Simple(stackmap::VerificationType::Integer),
+        "Float" => VerificationType::Simple(stackmap::VerificationType::Float),
+        "Double" => VerificationType::Simple(stackmap::VerificationType::Double),
+        "Long" => VerificationType::Simple(stackmap::VerificationType::Long),
+        "Null" => VerificationType::Simple(stackmap::VerificationType::Null),
+        "UninitializedThis" => VerificationType::Simple(stackmap::VerificationType::UninitializedThis),
+        "Object" => VerificationType::Object(asm.class(cursor)?),
+        "Uninitialized" => VerificationType::Uninitialized(address(cursor)?),
+        _ => return Err(cursor.error(AssembleErrorKind::Unexpected(word.into()))),
+    })
+}
+
+impl Code {
+    fn pc(&self) -> u32 {
+        self.code.len() as u32
+    }
+
+    fn branch(&mut self, cursor: &mut Cursor, pc: u32) -> Result<i16, AssembleError> {
+        self.target(cursor, pc, pc as usize + 1, false).map(|offset| offset as i16)
+    }
+
+    // A label to branch to, or an offset relative to the instruction. Labels are filled in
+    // at the end, so the offset for them is zero for now.
+    fn target(&mut self, cursor: &mut Cursor, pc: u32, at: usize, wide: bool) -> Result<i32, AssembleError> {
+        let word = cursor.word()?;
+        if let Some(offset) = parse_int(word) {
+            let offset = if wide { i32::try_from(offset).ok() } else { i16::try_from(offset).ok().map(i32::from) };
+            return offset.ok_or_else(|| cursor.error(AssembleErrorKind::OutOfRange(word.into())));
+        }
+        self.fixups.push(Fixup { name: word.into(), line: cursor.line(), pc, at, wide });
+        Ok(0)
+    }
+
+    fn resolve(&self, address: &Address) -> Result<u32, AssembleError> {
+        match address {
+            Address::Label { name, line } => self.labels.get(name).copied()
+                .ok_or_else(|| AssembleError { line: *line, kind: AssembleErrorKind::UndefinedLabel(name.clone()) }),
+            Address::Pc(pc) => Ok(*pc),
+        }
+    }
+
+    // Addresses in the tables in attributes are only two bytes
+    fn resolve_u16(&self, address: &Address, line: usize) -> Result<u16, AssembleError> {
+        let pc = self.resolve(address)?;
+        u16::try_from(pc).map_err(|_| AssembleError { line, kind: AssembleErrorKind::OutOfRange(pc.to_string()) })
+    }
+
+    fn finish(mut self, asm: &mut Assembler, header: &Cursor, max_stack: u16,
+              max_locals: u16) -> Result<UnknownAttribute, AssembleError> {
+        for fixup in &self.fixups {
+            let target = self.resolve(&Address::Label { name: fixup.name.clone(), line: fixup.line })?;
+            let offset = target as i64 - fixup.pc as i64;
+            let out_of_range = || {
+                AssembleError { line: fixup.line, kind: AssembleErrorKind::BranchOutOfRange(fixup.name.clone()) }
+            };
+            if fixup.wide {
+                let offset = i32::try_from(offset).map_err(|_| out_of_range())?;
+                self.code[fixup.at..fixup.at + 4].copy_from_slice(&offset.to_be_bytes());
+            } else {
+                let offset = i16::try_from(offset).map_err(|_| out_of_range())?;
+                self.code[fixup.at..fixup.at + 2].copy_from_slice(&offset.to_be_bytes());
+            }
+        }
+
+        let line = header.line();
+        let catches = self.catches.iter()
+            .map(|catch| Ok(code::Exception::new(self.resolve_u16(&catch.start, line)?,
+                self.resolve_u16(&catch.end, line)?, self.resolve_u16(&catch.handler, line)?, catch.catch_type)))
+            .collect::<Result<Vec<_>, AssembleError>>()?;
+
+        let mut attributes = Vec::new();
+        if !self.line_numbers.is_empty() {
+            let line_numbers = self.line_numbers.iter()
+                .map(|&(pc, line_number)| Ok(code::LineNumber::new(self.resolve_u16(&Address::Pc(pc), line)?, line_number)))
+                .collect::<Result<Vec<_>, AssembleError>>()?;
+            attributes.push(asm.attribute_with(header, "LineNumberTable", |buf| buf.write_arr(&line_numbers))?);
+        }
+        if !self.local_variables.is_empty() {
+            let local_variables = self.local_variables.iter()
+                .map(|var| {
+                    let start = self.resolve_u16(&var.start, line)?;
+                    let end = self.resolve_u16(&var.end, line)?;
+                    Ok(code::LocalVariable::new(start, end.wrapping_sub(start), var.name_index,
+                        var.descriptor_index, var.index))
+                })
+                .collect::<Result<Vec<_>, AssembleError>>()?;
+            attributes.push(asm.attribute_with(header, "LocalVariableTable", |buf| buf.write_arr(&local_variables))?);
+        }
+        if !self.frames.is_empty() {
+            let frames = self.stack_map_frames()?;
+            attributes.push(asm.attribute_with(header, "StackMapTable", |buf| buf.write_arr(&frames))?);
+        }
+        attributes.append(&mut self.attributes);
+
+        asm.attribute_with(header, "Code", |body| {
+            body.write_u16(max_stack);
+            body.write_u16(max_locals);
+            body.write_u32(self.code.len() as u32);
+            body.write_bytes(&self.code);
+            body.write_arr(&catches);
+            body.write_arr(&attributes);
+        })
+    }
+
+    fn stack_map_frames(&self) -> Result<Vec<stackmap::Frame>, AssembleError> {
+        let mut frames = Vec::with_capacity(self.frames.len());
+        let mut previous = None;
+        for (pc, line, frame) in &self.frames {
+            // Every frame after the first is at least one byte after the previous one
+            let delta = match previous {
+                None => Some(*pc),
+                Some(previous) => pc.checked_sub(previous + 1),
+            };
+            let delta = delta.ok_or(AssembleError { line: *line, kind: AssembleErrorKind::FrameOutOfOrder })?;
+            let delta = u16::try_from(delta)
+                .map_err(|_| AssembleError { line: *line, kind: AssembleErrorKind::OutOfRange(pc.to_string()) })?;
+            previous = Some(*pc);
+
+            frames.push(match frame {
+                Frame::Same if delta < 64 => stackmap::Frame::Same { frame_type: delta as u8 },
+                Frame::Same | Frame::SameExtended => stackmap::Frame::SameExtended { offset_delta: delta },
+                Frame::SameLocals1StackItem(ty) if delta < 64 => stackmap::Frame::SameLocalsOneStackItem {
+                    frame_type: 64 + delta as u8,
+                    stack: self.verification_type(ty, *line)?,
+                },
+                Frame::SameLocals1StackItem(ty) | Frame::SameLocals1StackItemExtended(ty) => {
+                    stackmap::Frame::SameLocalsOneStackItemExtended {
+                        offset_delta: delta,
+                        stack: self.verification_type(ty, *line)?,
+                    }
+                }
+                Frame::Chop(count) => stackmap::Frame::Chop { frame_type: 251 - count, offset_delta: delta },
+                Frame::Append(locals) => stackmap::Frame::Append {
+                    frame_type: 251 + locals.len() as u8,
+                    offset_delta: delta,
+                    locals: self.verification_types(locals, *line)?,
+                },
+                Frame::Full(locals, stack) => stackmap::Frame::Full {
+                    offset_delta: delta,
+                    locals: self.verification_types(locals, *line)?,
+                    stack: self.verification_types(stack, *line)?,
+                },
+            });
+        }
+        Ok(frames)
+    }
+
+    fn verification_types(&self, types: &[VerificationType],
+                          line: usize) -> Result<Array<stackmap::VerificationType>, AssembleError> {
+        let types = types.iter().map(|ty| self.verification_type(ty, line)).collect::<Result<Vec<_>, _>>()?;
+        Array::from_vec(types).map_err(|_| AssembleError { line, kind: AssembleErrorKind::OutOfMemory })
+    }
+
+    fn verification_type(&self, ty: &VerificationType, line: usize) -> Result<stackmap::VerificationType, AssembleError> {
+        Ok(match ty {
+            VerificationType::Simple(ty) => *ty,
+            VerificationType::Object(index) => stackmap::VerificationType::Object { pool_index: *index },
+            VerificationType::Uninitialized(address) => {
+                stackmap::VerificationType::Uninitialized { offset: self.resolve_u16(address, line)? }
+            }
+        })
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+use super::{AssembleError, AssembleErrorKind};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token {
+    /// Anything that isn't quoted, such as a directive, mnemonic, name or number.
+    Word(String),
+    /// A quoted string, with its escapes decoded.
+    Str(String),
+    /// A quoted byte string, written `b"..."`.
+    Bytes(Vec<u8>),
+    Colon,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => f.write_str(word),
+            Token::Str(value) => write!(f, "{value:?}"),
+            Token::Bytes(bytes) => write!(f, "b\"{}\"", bytes.escape_ascii()),
+            Token::Colon => f.write_str(":"),
+        }
+    }
+}
+
+/// The tokens on one line of the source. Everything is assembled a line at a time.
+pub struct Line {
+    pub number: usize,
+    pub tokens: Vec<Token>,
+}
+
+/// Splits the source up in to lines of tokens, leaving out blank lines and comments. Comments
+/// start with a `;` at the start of a token, so that descriptors can still contain them.
+pub fn tokenize(source: &str) -> Result<Vec<Line>, AssembleError> {
+    let mut lines = Vec::new();
+    for (i, text) in source.lines().enumerate() {
+        let number = i + 1;
+        let mut tokens = Vec::new();
+        let mut chars = text.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                _ if c.is_whitespace() => {
+                    chars.next();
+                }
+                ';' => break,
+                ':' => {
+                    chars.next();
+                    tokens.push(Token::Colon);
+                }
+                '"' => {
+                    chars.next();
+                    let value = quoted(&mut chars, number)?.into_iter().map(|unit| match unit {
+                        Unit::Char(c) => c,
+                        Unit::Byte(b) => char::from(b),
+                    });
+                    tokens.push(Token::Str(value.collect()));
+                }
+                'b' if chars.clone().nth(1) == Some('"') => {
+                    chars.next();
+                    chars.next();
+                    let mut bytes = Vec::new();
+                    for unit in quoted(&mut chars, number)? {
+                        match unit {
+                            Unit::Char(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                            Unit::Byte(b) => bytes.push(b),
+                        }
+                    }
+                    tokens.push(Token::Bytes(bytes));
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c.is_whitespace() || c == '"' || c == ':' {
+                            break;
+                        }
+                        word.push(c);
+                        chars.next();
+                    }
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+
+        if !tokens.is_empty() {
+            lines.push(Line { number, tokens });
+        }
+    }
+    Ok(lines)
+}
+
+// A character in a quoted string, or a byte given by a `\x` escape, which is a raw byte in
+// a byte string and the character with that value in a string
+enum Unit {
+    Char(char),
+    Byte(u8),
+}
+
+// Reads up to and including the closing quote
+fn quoted(chars: &mut Peekable<Chars>, line: usize) -> Result<Vec<Unit>, AssembleError> {
+    let invalid = || AssembleError { line, kind: AssembleErrorKind::InvalidString };
+    let mut units = Vec::new();
+    loop {
+        let c = chars.next().ok_or_else(invalid)?;
+        let unit = match c {
+            '"' => return Ok(units),
+            '\\' => match chars.next().ok_or_else(invalid)? {
+                'n' => Unit::Char('\n'),
+                'r' => Unit::Char('\r'),
+                't' => Unit::Char('\t'),
+                '0' => Unit::Char('\0'),
+                c @ ('\\' | '"' | '\'') => Unit::Char(c),
+                'x' => Unit::Byte(hex(chars, 2).ok_or_else(invalid)? as u8),
+                'u' => Unit::Char(hex(chars, 4).and_then(char::from_u32).ok_or_else(invalid)?),
+                _ => return Err(invalid()),
+            },
+            c => Unit::Char(c),
+        };
+        units.push(unit);
+    }
+}
+
+fn hex(chars: &mut Peekable<Chars>, digits: usize) -> Option<u32> {
+    let mut value = 0;
+    for _ in 0..digits {
+        value = value * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(value)
+}
+
+/// Reads the tokens on a line in turn.
+pub struct Cursor<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(line: &'a Line) -> Self {
+        Self { tokens: &line.tokens, pos: 0, line: line.number }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn error(&self, kind: AssembleErrorKind) -> AssembleError {
+        AssembleError { line: self.line, kind }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.tokens.len()
+    }
+
+    pub fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    pub fn peek_word(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    pub fn next(&mut self) -> Result<&'a Token, AssembleError> {
+        let token = self.tokens.get(self.pos).ok_or_else(|| self.error(AssembleErrorKind::UnexpectedEnd))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    pub fn word(&mut self) -> Result<&'a str, AssembleError> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => Err(self.unexpected(token)),
+        }
+    }
+
+    /// A name or descriptor, which can be quoted if it has spaces or other characters that
+    /// would otherwise split it up.
+    pub fn name(&mut self) -> Result<&'a str, AssembleError> {
+        match self.next()? {
+            Token::Word(word) | Token::Str(word) => Ok(word),
+            token => Err(self.unexpected(token)),
+        }
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], AssembleError> {
+        match self.next()? {
+            Token::Bytes(bytes) => Ok(bytes),
+            token => Err(self.unexpected(token)),
+        }
+    }
+
+    pub fn number<T: TryFrom<i128>>(&mut self) -> Result<T, AssembleError> {
+        let word = self.word()?;
+        let value = parse_int(word).ok_or_else(|| self.error(AssembleErrorKind::InvalidNumber(word.into())))?;
+        T::try_from(value).map_err(|_| self.error(AssembleErrorKind::OutOfRange(word.into())))
+    }
+
+    /// Moves past a label, such as `start:`, if that's what's next.
+    pub fn label(&mut self) -> Option<&'a str> {
+        match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            (Some(Token::Word(name)), Some(Token::Colon)) if !name.starts_with('.') => {
+                self.pos += 2;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves past the next token if it's the given word.
+    pub fn eat(&mut self, word: &str) -> bool {
+        let found = self.peek_word() == Some(word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    pub fn expect(&mut self, word: &str) -> Result<(), AssembleError> {
+        match self.next()? {
+            Token::Word(found) if found == word => Ok(()),
+            token => Err(self.unexpected(token)),
+        }
+    }
+
+    pub fn colon(&mut self) -> Result<(), AssembleError> {
+        match self.next()? {
+            Token::Colon => Ok(()),
+            token => Err(self.unexpected(token)),
+        }
+    }
+
+    /// Checks there's nothing left on the line.
+    pub fn finish(&self) -> Result<(), AssembleError> {
+        match self.peek() {
+            Some(token) => Err(self.unexpected(token)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn unexpected(&self, token: &Token) -> AssembleError {
+        self.error(AssembleErrorKind::Unexpected(token.to_string()))
+    }
+}
+
+/// Parses a decimal or `0x` prefixed hexadecimal integer, either of which can be negative.
+pub fn parse_int(word: &str) -> Option<i128> {
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word.strip_prefix('+').unwrap_or(word)),
+    };
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
@@ -0,0 +1,408 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! An assembler for class files, for writing classes by hand that a compiler wouldn't
+//! produce, such as ones that should fail verification.
+//!
+//! The syntax is line based and close to Krakatau's. Nothing is checked beyond what's
+//! needed to encode it, so the output doesn't have to be a valid class:
+//!
+//! ```text
+//! .version 69 0
+//! .class public super Hello
+//! .super java/lang/Object
+//!
+//! .method public static main : ([Ljava/lang/String;)V
+//!     .code stack 2 locals 1
+//!         getstatic java/lang/System out Ljava/io/PrintStream;
+//!         ldc "Hello"
+//!         invokevirtual java/io/PrintStream println (Ljava/lang/String;)V
+//!     loop:
+//!         goto loop
+//!     .end code
+//! .end method
+//! ```
+//!
+//! Constants are added to the pool in the order they first appear, and any operand that
+//! refers to one can be given as a raw index, such as `#5`, instead. `.const` adds an entry
+//! without using it, and `.attribute <name> b"<bytes>"` adds any attribute to the class, a
+//! field, a method or some code, so that indexes and attributes can be laid out exactly.
+//!
+//! Unlike everything else that writes classes, this doesn't build a [Class](crate::class::Class)
+//! and write it with [write_class](crate::class::write_class), as a `Class` can only hold what
+//! parses and passes its checks, and what's assembled doesn't have to. Only the header and
+//! the `field_info` and `method_info` structures are written by hand. Attributes are kept as
+//! [UnknownAttribute](crate::loader::classfile::attribute::UnknownAttribute)s, and the tables
+//! in them, such as exception tables and stack map frames, are built from the same types that
+//! parsing gives back, so they're written the same way as they are by `write_class`.
+
+mod class;
+mod code;
+mod lexer;
+
+use std::fmt;
+use crate::class::constantpool::BuildError;
+
+/// Assembles the source in to a class file.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssembleError> {
+    let lines = lexer::tokenize(source)?;
+    class::Assembler::new(&lines).assemble()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AssembleError {
+    /// The line the error is on, starting from 1.
+    pub line: usize,
+    pub kind: AssembleErrorKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssembleErrorKind {
+    /// A string without a closing quote, or with an escape that isn't valid.
+    InvalidString,
+    /// The line ends before everything that's needed has been given.
+    UnexpectedEnd,
+    Unexpected(String),
+    UnknownDirective(String),
+    UnknownInstruction(String),
+    InvalidNumber(String),
+    /// A number, or a constant pool index, that's too big for where it's used.
+    OutOfRange(String),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    /// A label that's further away than the branch to it can reach.
+    BranchOutOfRange(String),
+    /// A stack map frame at or before the address of the one before it.
+    FrameOutOfOrder,
+    MissingDirective(&'static str),
+    /// A block, such as a method, without an `.end`.
+    MissingEnd(&'static str),
+    OutOfMemory,
+    Pool(BuildError),
+}
+
+impl std::error::Error for AssembleError {}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AssembleErrorKind::InvalidString => f.write_str("invalid string"),
+            AssembleErrorKind::UnexpectedEnd => f.write_str("unexpected end of line"),
+            AssembleErrorKind::Unexpected(token) => write!(f, "unexpected {token}"),
+            AssembleErrorKind::UnknownDirective(name) => write!(f, "unknown directive {name}"),
+            AssembleErrorKind::UnknownInstruction(name) => write!(f, "unknown instruction {name}"),
+            AssembleErrorKind::InvalidNumber(word) => write!(f, "invalid number {word}"),
+            AssembleErrorKind::OutOfRange(word) => write!(f, "{word} is out of range"),
+            AssembleErrorKind::UndefinedLabel(name) => write!(f, "label {name} is not defined"),
+            AssembleErrorKind::DuplicateLabel(name) => write!(f, "label {name} is already defined"),
+            AssembleErrorKind::BranchOutOfRange(name) => write!(f, "label {name} is too far away to branch to"),
+            AssembleErrorKind::FrameOutOfOrder => f.write_str("stack map frame is not after the previous frame"),
+            AssembleErrorKind::MissingDirective(name) => write!(f, "missing {name}"),
+            AssembleErrorKind::MissingEnd(block) => write!(f, "missing .end {block}"),
+            AssembleErrorKind::OutOfMemory => f.write_str("out of memory"),
+            AssembleErrorKind::Pool(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::bytecode::{ArrayType, Instruction, LookupSwitch, TableSwitch, WideInstruction};
+    use crate::class::constantpool::{Index, Tag};
+    use crate::class::parse::BinaryReader;
+    use crate::class::{parse_class, write_class, Class};
+    use crate::loader::classfile::attribute::FieldAttribute;
+    use crate::loader::classfile::attribute::stackmap::{Frame, VerificationType};
+    use crate::types::methodhandle;
+    use super::*;
+
+    // Assembles and parses the class, checking it writes back out as the same bytes
+    fn parse(source: &str) -> Class {
+        let bytes = assemble(source).unwrap_or_else(|err| panic!("{err}"));
+        let class = parse_class(&mut BinaryReader::new(bytes.clone())).expect("assembled class should parse");
+        assert_eq!(write_class(&class), bytes);
+        class
+    }
+
+    fn instructions(class: &Class) -> Vec<(u32, Instruction)> {
+        let code = class.methods()[0].code().expect("method should have code");
+        code.instructions().collect::<Result<_, _>>().expect("code should decode")
+    }
+
+    fn member_class(class: &Class, class_index: Index) -> String {
+        class.constant_pool().resolve_class(class_index).unwrap().name().to_string()
+    }
+
+    #[test]
+    fn hello_world() {
+        let class = parse(r#"
+            ; Comments run to the end of the line
+            .version 52 0
+            .class public super Hello
+            .super java/lang/Object
+
+            .method public static main : ([Ljava/lang/String;)V
+                .code stack 2 locals 1
+                    getstatic java/lang/System out Ljava/io/PrintStream;
+                    ldc "Hello"
+                    invokevirtual java/io/PrintStream println (Ljava/lang/String;)V
+                loop: goto loop
+                .end code
+            .end method
+            .end class
+        "#);
+        assert_eq!(class.version().major(), 52);
+        assert_eq!(class.name(), "Hello");
+        assert_eq!(class.super_class().unwrap(), "java/lang/Object");
+        assert!(class.access_flags().is_public() && class.access_flags().is_super());
+        let method = &class.methods()[0];
+        assert_eq!(method.name(), "main");
+        assert!(method.access_flags().is_static());
+        assert_eq!(method.code().unwrap().max_stack(), 2);
+
+        let pool = class.constant_pool();
+        let [(0, Instruction::Getstatic(field)), (3, Instruction::Ldc(string)), (5, Instruction::Invokevirtual(method)),
+             (8, Instruction::Goto(0))] = instructions(&class)[..] else {
+            panic!("unexpected instructions {:?}", instructions(&class));
+        };
+        let field = pool.get_field_ref(field).unwrap();
+        assert_eq!(member_class(&class, field.class_index()), "java/lang/System");
+        assert_eq!(pool.resolve_string(string as Index).unwrap().as_str(), "Hello");
+        let method = pool.get_method_ref(method).unwrap();
+        assert_eq!(pool.resolve_name_and_type(method.name_and_type_index()).unwrap().name(), "println");
+    }
+
+    #[test]
+    fn operands() {
+        let class = parse(r#"
+            .class Operands
+            .method static pick : (I)I
+                .code stack 4 locals 400
+                    iload_0
+                    tableswitch 1
+                        one
+                        two
+                        default : other
+                one:
+                    iconst_1
+                    ireturn
+                two: iload_0
+                    lookupswitch
+                        -1 : one
+                        7 : other
+                        default : two
+                other:
+                    goto_w one
+                    ifeq -4
+                    wide iinc 300 -1000
+                    iinc 1 -1
+                    bipush -128
+                    sipush 0x7fff
+                    newarray int
+                    multianewarray [[I 2
+                    invokeinterface java/util/List size ()I 1
+                    invokedynamic 0 run ()Ljava/lang/Runnable;
+                    ldc2_w 5L
+                .end code
+            .end method
+            .bootstrap MethodHandle invokeStatic Method Operands bootstrap ()V
+        "#);
+
+        let instructions = instructions(&class);
+        assert_eq!(instructions[..7], [
+            (0, Instruction::Iload0),
+            (1, Instruction::Tableswitch(TableSwitch { default: 51, low: 1, high: 2, offsets: vec![23, 25] })),
+            (24, Instruction::Iconst1),
+            (25, Instruction::Ireturn),
+            (26, Instruction::Iload0),
+            (27, Instruction::Lookupswitch(LookupSwitch { default: -1, pairs: vec![(-1, -3), (7, 25)] })),
+            (52, Instruction::GotoW(-28)),
+        ]);
+        assert_eq!(instructions[7..13], [
+            (57, Instruction::Ifeq(-4)),
+            (60, Instruction::Wide(WideInstruction::Iinc(300, -1000))),
+            (66, Instruction::Iinc(1, -1)),
+            (69, Instruction::Bipush(-128)),
+            (71, Instruction::Sipush(0x7FFF)),
+            (74, Instruction::Newarray(ArrayType::Int)),
+        ]);
+
+        let pool = class.constant_pool();
+        let [(76, Instruction::Multianewarray(array, 2)), (80, Instruction::Invokeinterface(list_size, 1)),
+             (85, Instruction::Invokedynamic(call_site)), (90, Instruction::Ldc2W(long))] = instructions[13..] else {
+            panic!("unexpected instructions {:?}", &instructions[13..]);
+        };
+        assert_eq!(member_class(&class, array), "[[I");
+        assert!(pool.get_interface_method_ref(list_size).is_some());
+        assert_eq!(pool.get_invoke_dynamic(call_site).unwrap().bootstrap_method_attr_index(), 0);
+        assert_eq!(pool.get_long(long).unwrap().value(), 5);
+    }
+
+    #[test]
+    fn stack_maps_and_exceptions() {
+        let class = parse(r#"
+            .class Frames
+            .method static run : (I)V
+                .code stack 2 locals 3
+                start:
+                    new java/lang/Object
+                    dup
+                    .stack full locals Integer stack Uninitialized start Uninitialized 0
+                    invokespecial java/lang/Object <init> ()V
+                    astore_1
+                    .stack append Object java/lang/Object
+                    nop
+                    .stack same
+                    nop
+                    .stack same_extended
+                    nop
+                    .stack same_locals_1_stack_item Null
+                    nop
+                    .stack chop 1
+                    .line 7
+                end:
+                    return
+                handler:
+                    .stack same_locals_1_stack_item_extended Object java/lang/Throwable
+                    athrow
+                    .catch java/lang/Exception from start to end using handler
+                    .catch any from 0 to 3 using handler
+                    .var 0 is x I from start to end
+                .end code
+            .end method
+        "#);
+
+        let code = class.methods()[0].code().unwrap();
+        let frames: Vec<_> = code.stack_map_table().unwrap().entries().iter().map(|frame| match frame {
+            Frame::Same { frame_type } => (*frame_type, None),
+            Frame::SameLocalsOneStackItem { frame_type, .. } => (*frame_type, None),
+            Frame::SameLocalsOneStackItemExtended { offset_delta, .. } => (247, Some(*offset_delta)),
+            Frame::Chop { frame_type, offset_delta } => (*frame_type, Some(*offset_delta)),
+            Frame::SameExtended { offset_delta } => (251, Some(*offset_delta)),
+            Frame::Append { frame_type, offset_delta, .. } => (*frame_type, Some(*offset_delta)),
+            Frame::Full { offset_delta, .. } => (255, Some(*offset_delta)),
+        }).collect();
+        assert_eq!(frames, [(255, Some(4)), (252, Some(3)), (0, None), (251, Some(0)), (64, None), (250, Some(0)),
+                            (247, Some(0))]);
+        let Frame::Full { locals, stack, .. } = &code.stack_map_table().unwrap().entries()[0] else {
+            panic!("expected a full frame");
+        };
+        // SAFETY: Arrays in a parsed class are fully initialized
+        assert_eq!(unsafe { locals.as_slice() }, [VerificationType::Integer]);
+        assert_eq!(unsafe { stack.as_slice() }, [VerificationType::Uninitialized { offset: 0 }; 2]);
+
+        let exceptions: Vec<_> = code.exceptions().iter()
+            .map(|e| (e.start_pc(), e.end_pc(), e.handler_pc(), e.catch_type()))
+            .collect();
+        let exception = exceptions[0].3;
+        assert_eq!(member_class(&class, exception), "java/lang/Exception");
+        assert_eq!(exceptions, [(0, 12, 13, exception), (0, 3, 13, 0)]);
+        assert_eq!(code.line_number(12), Some(7));
+        let x = code.local_variable(0, 5).unwrap();
+        assert_eq!((x.start_pc(), x.length()), (0, 12));
+    }
+
+    #[test]
+    fn constant_pool_layout() {
+        let class = parse(r#"
+            .const Utf8 "first"
+            .const Long 0x10
+            .const Float 0x7fc00001
+            .const Double -0.0
+            .const MethodHandle invokeStatic Method Pool m ()V
+            .class public 0x1000 Pool
+
+            .field static final nan F = Float 0x7fc00001
+            .field "with spaces" I .fieldattributes
+                .signature "TT;"
+                .attribute Custom b"\x01\x02"
+            .end fieldattributes
+
+            .method abstract m : ()V
+                .deprecated
+            .end method
+            .method static n : ()V
+                .code stack 1 locals 0
+                    ldc #4
+                    ldc_w #1
+                    .attribute Custom b""
+                .end code
+                .throws java/lang/Exception
+            .end method
+        "#);
+
+        let pool = class.constant_pool();
+        assert_eq!(pool.resolve_utf8(1).unwrap().as_str(), "first");
+        assert_eq!(pool.get_long(2).unwrap().value(), 16);
+        assert_eq!(pool.get_float(4).unwrap().value().to_bits(), 0x7FC00001);
+        assert!(pool.get_double(5).unwrap().value().is_sign_negative());
+        let handle = pool.get_method_handle(13).unwrap();
+        assert_eq!(handle.reference_kind(), methodhandle::Ref::InvokeStatic);
+        assert_eq!(member_class(&class, pool.get_method_ref(handle.reference_index()).unwrap().class_index()), "Pool");
+        assert_eq!(class.this_class_index(), 8);
+        assert_eq!(class.declared_access_flags().flags(), 0x1001);
+        assert_eq!(pool.tag(14), Some(Tag::Utf8));
+
+        let [nan, spaced] = class.fields() else { panic!("expected two fields") };
+        assert_eq!(nan.name(), "nan");
+        assert_eq!(spaced.name(), "with spaces");
+        let Some(FieldAttribute::Unknown(custom)) = spaced.attributes().last() else {
+            panic!("expected an unknown attribute");
+        };
+        assert_eq!(custom.info(), [1, 2]);
+        assert!(class.methods()[0].code().is_none());
+        assert_eq!(class.methods()[1].code().unwrap().instructions().map(Result::unwrap).collect::<Vec<_>>(),
+                   [(0, Instruction::Ldc(4)), (2, Instruction::LdcW(1))]);
+    }
+
+    #[test]
+    fn rejects_bad_source() {
+        fn method(code: &str) -> String {
+            format!(".class A\n.method m : ()V\n.code stack 1 locals 1\n{code}\n.end code\n.end method")
+        }
+
+        let far = method(&format!("goto end\n{}end: return", "nop\n".repeat(40000)));
+        let cases = [
+            (String::new(), 1, AssembleErrorKind::MissingDirective(".class")),
+            (".class A B".into(), 1, AssembleErrorKind::Unexpected("B".into())),
+            (".class A\n.bogus".into(), 2, AssembleErrorKind::UnknownDirective(".bogus".into())),
+            (".class A\n.source \"open".into(), 2, AssembleErrorKind::InvalidString),
+            (".class A\n.method m : ()V".into(), 2, AssembleErrorKind::MissingEnd("method")),
+            (".class A\n.end class\n.const 1".into(), 3, AssembleErrorKind::Unexpected(".const".into())),
+            (".class A\n.field x".into(), 2, AssembleErrorKind::UnexpectedEnd),
+            (method("foo"), 4, AssembleErrorKind::UnknownInstruction("foo".into())),
+            (method("iload x"), 4, AssembleErrorKind::InvalidNumber("x".into())),
+            (method("bipush 200"), 4, AssembleErrorKind::OutOfRange("200".into())),
+            (method("ldc #300"), 4, AssembleErrorKind::OutOfRange("#300".into())),
+            (method("goto nowhere"), 4, AssembleErrorKind::UndefinedLabel("nowhere".into())),
+            (method("a: nop\na: nop"), 5, AssembleErrorKind::DuplicateLabel("a".into())),
+            (method(".stack same\n.stack same\nnop"), 5, AssembleErrorKind::FrameOutOfOrder),
+            (method("tableswitch 0"), 4, AssembleErrorKind::UnexpectedEnd),
+            (far, 4, AssembleErrorKind::BranchOutOfRange("end".into())),
+        ];
+        for (source, line, kind) in cases {
+            assert_eq!(assemble(&source), Err(AssembleError { line, kind }), "{source:.100}");
+        }
+
+        let err = assemble(".class A\n.const MethodHandle invokeVirtual Field A x I").unwrap_err();
+        assert_eq!(err.kind, AssembleErrorKind::Pool(BuildError::BadMethodHandleReference {
+            kind: methodhandle::Ref::InvokeVirtual,
+            index: 6,
+        }));
+        assert_eq!(err.to_string(),
+                   "line 2: constant pool index 6 is not a valid reference for a InvokeVirtual method handle");
+    }
+}
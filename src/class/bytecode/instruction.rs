@@ -0,0 +1,940 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+use num_traits::FromPrimitive;
+use crate::class::constantpool::Index;
+use crate::types::descriptor::BaseType;
+
+// Defines every opcode, along with the instruction it starts. Instructions with operands are
+// decoded by reading each of the operands in turn, except where they need checking as well,
+// and encoded by writing them back out in the same order.
+macro_rules! instructions {
+    (@simple $name: ident) => { Some(Instruction::$name) };
+    (@simple $name: ident ($($operand: ty),+)) => { None };
+    (@encode $self: ident, $code: ident, $name: ident) => { () };
+    (@encode $self: ident, $code: ident, $name: ident ($a: ty)) => {
+        if let Instruction::$name(a) = $self {
+            a.write($code);
+        }
+    };
+    (@encode $self: ident, $code: ident, $name: ident ($a: ty, $b: ty)) => {
+        if let Instruction::$name(a, b) = $self {
+            a.write($code);
+            b.write($code);
+        }
+    };
+    ($($name: ident = $opcode: literal $mnemonic: literal $(($($operand: ty),+))?),* $(,)?) => {
+        /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-7.html
+        #[repr(u8)]
+        #[derive(Primitive, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+        pub enum Opcode {
+            $($name = $opcode),*
+        }
+
+        impl Opcode {
+            /// The name of the opcode, as used by `javap` and the JVMS, such as `iload_0`.
+            pub const fn mnemonic(&self) -> &'static str {
+                match self {
+                    $(Opcode::$name => $mnemonic),*
+                }
+            }
+
+            pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+                match mnemonic {
+                    $($mnemonic => Some(Opcode::$name),)*
+                    _ => None,
+                }
+            }
+        }
+
+        /// An instruction and its operands. Constant pool indexes aren't resolved, and branch
+        /// offsets are relative to the address of the instruction, as they're encoded.
+        #[derive(Debug, PartialEq, Clone)]
+        pub enum Instruction {
+            $($name $(($($operand),+))?),*
+        }
+
+        impl Instruction {
+            pub const fn opcode(&self) -> Opcode {
+                match self {
+                    $(Instruction::$name { .. } => Opcode::$name),*
+                }
+            }
+
+            /// The instruction for an opcode that doesn't take any operands, or `None` if it does.
+            pub const fn from_opcode(opcode: Opcode) -> Option<Instruction> {
+                match opcode {
+                    $(Opcode::$name => instructions!(@simple $name $(($($operand),+))?)),*
+                }
+            }
+
+            fn encode_operands(&self, code: &mut Vec<u8>) {
+                #[allow(unused_imports)]
+                use _encode::Operand;
+                match self.opcode() {
+                    $(Opcode::$name => instructions!(@encode self, code, $name $(($($operand),+))?)),*
+                }
+            }
+
+            fn decode_operands(opcode: Opcode, code: &mut _decode::CodeReader) -> Result<Instruction, DecodeError> {
+                #[allow(unused_imports)]
+                use _decode::Operand;
+                Ok(match opcode {
+                    $(Opcode::$name => Instruction::$name $(($(<$operand>::read(code)?),+))?),*
+                })
+            }
+        }
+    };
+}
+
+instructions!(
+    Nop = 0x00 "nop",
+    AconstNull = 0x01 "aconst_null",
+    IconstM1 = 0x02 "iconst_m1",
+    Iconst0 = 0x03 "iconst_0",
+    Iconst1 = 0x04 "iconst_1",
+    Iconst2 = 0x05 "iconst_2",
+    Iconst3 = 0x06 "iconst_3",
+    Iconst4 = 0x07 "iconst_4",
+    Iconst5 = 0x08 "iconst_5",
+    Lconst0 = 0x09 "lconst_0",
+    Lconst1 = 0x0A "lconst_1",
+    Fconst0 = 0x0B "fconst_0",
+    Fconst1 = 0x0C "fconst_1",
+    Fconst2 = 0x0D "fconst_2",
+    Dconst0 = 0x0E "dconst_0",
+    Dconst1 = 0x0F "dconst_1",
+    Bipush = 0x10 "bipush" (i8),
+    Sipush = 0x11 "sipush" (i16),
+    Ldc = 0x12 "ldc" (u8),
+    LdcW = 0x13 "ldc_w" (Index),
+    Ldc2W = 0x14 "ldc2_w" (Index),
+    Iload = 0x15 "iload" (u8),
+    Lload = 0x16 "lload" (u8),
+    Fload = 0x17 "fload" (u8),
+    Dload = 0x18 "dload" (u8),
+    Aload = 0x19 "aload" (u8),
+    Iload0 = 0x1A "iload_0",
+    Iload1 = 0x1B "iload_1",
+    Iload2 = 0x1C "iload_2",
+    Iload3 = 0x1D "iload_3",
+    Lload0 = 0x1E "lload_0",
+    Lload1 = 0x1F "lload_1",
+    Lload2 = 0x20 "lload_2",
+    Lload3 = 0x21 "lload_3",
+    Fload0 = 0x22 "fload_0",
+    Fload1 = 0x23 "fload_1",
+    Fload2 = 0x24 "fload_2",
+    Fload3 = 0x25 "fload_3",
+    Dload0 = 0x26 "dload_0",
+    Dload1 = 0x27 "dload_1",
+    Dload2 = 0x28 "dload_2",
+    Dload3 = 0x29 "dload_3",
+    Aload0 = 0x2A "aload_0",
+    Aload1 = 0x2B "aload_1",
+    Aload2 = 0x2C "aload_2",
+    Aload3 = 0x2D "aload_3",
+    Iaload = 0x2E "iaload",
+    Laload = 0x2F "laload",
+    Faload = 0x30 "faload",
+    Daload = 0x31 "daload",
+    Aaload = 0x32 "aaload",
+    Baload = 0x33 "baload",
+    Caload = 0x34 "caload",
+    Saload = 0x35 "saload",
+    Istore = 0x36 "istore" (u8),
+    Lstore = 0x37 "lstore" (u8),
+    Fstore = 0x38 "fstore" (u8),
+    Dstore = 0x39 "dstore" (u8),
+    Astore = 0x3A "astore" (u8),
+    Istore0 = 0x3B "istore_0",
+    Istore1 = 0x3C "istore_1",
+    Istore2 = 0x3D "istore_2",
+    Istore3 = 0x3E "istore_3",
+    Lstore0 = 0x3F "lstore_0",
+    Lstore1 = 0x40 "lstore_1",
+    Lstore2 = 0x41 "lstore_2",
+    Lstore3 = 0x42 "lstore_3",
+    Fstore0 = 0x43 "fstore_0",
+    Fstore1 = 0x44 "fstore_1",
+    Fstore2 = 0x45 "fstore_2",
+    Fstore3 = 0x46 "fstore_3",
+    Dstore0 = 0x47 "dstore_0",
+    Dstore1 = 0x48 "dstore_1",
+    Dstore2 = 0x49 "dstore_2",
+    Dstore3 = 0x4A "dstore_3",
+    Astore0 = 0x4B "astore_0",
+    Astore1 = 0x4C "astore_1",
+    Astore2 = 0x4D "astore_2",
+    Astore3 = 0x4E "astore_3",
+    Iastore = 0x4F "iastore",
+    Lastore = 0x50 "lastore",
+    Fastore = 0x51 "fastore",
+    Dastore = 0x52 "dastore",
+    Aastore = 0x53 "aastore",
+    Bastore = 0x54 "bastore",
+    Castore = 0x55 "castore",
+    Sastore = 0x56 "sastore",
+    Pop = 0x57 "pop",
+    Pop2 = 0x58 "pop2",
+    Dup = 0x59 "dup",
+    DupX1 = 0x5A "dup_x1",
+    DupX2 = 0x5B "dup_x2",
+    Dup2 = 0x5C "dup2",
+    Dup2X1 = 0x5D "dup2_x1",
+    Dup2X2 = 0x5E "dup2_x2",
+    Swap = 0x5F "swap",
+    Iadd = 0x60 "iadd",
+    Ladd = 0x61 "ladd",
+    Fadd = 0x62 "fadd",
+    Dadd = 0x63 "dadd",
+    Isub = 0x64 "isub",
+    Lsub = 0x65 "lsub",
+    Fsub = 0x66 "fsub",
+    Dsub = 0x67 "dsub",
+    Imul = 0x68 "imul",
+    Lmul = 0x69 "lmul",
+    Fmul = 0x6A "fmul",
+    Dmul = 0x6B "dmul",
+    Idiv = 0x6C "idiv",
+    Ldiv = 0x6D "ldiv",
+    Fdiv = 0x6E "fdiv",
+    Ddiv = 0x6F "ddiv",
+    Irem = 0x70 "irem",
+    Lrem = 0x71 "lrem",
+    Frem = 0x72 "frem",
+    Drem = 0x73 "drem",
+    Ineg = 0x74 "ineg",
+    Lneg = 0x75 "lneg",
+    Fneg = 0x76 "fneg",
+    Dneg = 0x77 "dneg",
+    Ishl = 0x78 "ishl",
+    Lshl = 0x79 "lshl",
+    Ishr = 0x7A "ishr",
+    Lshr = 0x7B "lshr",
+    Iushr = 0x7C "iushr",
+    Lushr = 0x7D "lushr",
+    Iand = 0x7E "iand",
+    Land = 0x7F "land",
+    Ior = 0x80 "ior",
+    Lor = 0x81 "lor",
+    Ixor = 0x82 "ixor",
+    Lxor = 0x83 "lxor",
+    Iinc = 0x84 "iinc" (u8, i8),
+    I2l = 0x85 "i2l",
+    I2f = 0x86 "i2f",
+    I2d = 0x87 "i2d",
+    L2i = 0x88 "l2i",
+    L2f = 0x89 "l2f",
+    L2d = 0x8A "l2d",
+    F2i = 0x8B "f2i",
+    F2l = 0x8C "f2l",
+    F2d = 0x8D "f2d",
+    D2i = 0x8E "d2i",
+    D2l = 0x8F "d2l",
+    D2f = 0x90 "d2f",
+    I2b = 0x91 "i2b",
+    I2c = 0x92 "i2c",
+    I2s = 0x93 "i2s",
+    Lcmp = 0x94 "lcmp",
+    Fcmpl = 0x95 "fcmpl",
+    Fcmpg = 0x96 "fcmpg",
+    Dcmpl = 0x97 "dcmpl",
+    Dcmpg = 0x98 "dcmpg",
+    Ifeq = 0x99 "ifeq" (i16),
+    Ifne = 0x9A "ifne" (i16),
+    Iflt = 0x9B "iflt" (i16),
+    Ifge = 0x9C "ifge" (i16),
+    Ifgt = 0x9D "ifgt" (i16),
+    Ifle = 0x9E "ifle" (i16),
+    IfIcmpeq = 0x9F "if_icmpeq" (i16),
+    IfIcmpne = 0xA0 "if_icmpne" (i16),
+    IfIcmplt = 0xA1 "if_icmplt" (i16),
+    IfIcmpge = 0xA2 "if_icmpge" (i16),
+    IfIcmpgt = 0xA3 "if_icmpgt" (i16),
+    IfIcmple = 0xA4 "if_icmple" (i16),
+    IfAcmpeq = 0xA5 "if_acmpeq" (i16),
+    IfAcmpne = 0xA6 "if_acmpne" (i16),
+    Goto = 0xA7 "goto" (i16),
+    Jsr = 0xA8 "jsr" (i16),
+    Ret = 0xA9 "ret" (u8),
+    Tableswitch = 0xAA "tableswitch" (TableSwitch),
+    Lookupswitch = 0xAB "lookupswitch" (LookupSwitch),
+    Ireturn = 0xAC "ireturn",
+    Lreturn = 0xAD "lreturn",
+    Freturn = 0xAE "freturn",
+    Dreturn = 0xAF "dreturn",
+    Areturn = 0xB0 "areturn",
+    Return = 0xB1 "return",
+    Getstatic = 0xB2 "getstatic" (Index),
+    Putstatic = 0xB3 "putstatic" (Index),
+    Getfield = 0xB4 "getfield" (Index),
+    Putfield = 0xB5 "putfield" (Index),
+    Invokevirtual = 0xB6 "invokevirtual" (Index),
+    Invokespecial = 0xB7 "invokespecial" (Index),
+    Invokestatic = 0xB8 "invokestatic" (Index),
+    // Followed by a zero byte
+    Invokeinterface = 0xB9 "invokeinterface" (Index, u8),
+    // Followed by two zero bytes
+    Invokedynamic = 0xBA "invokedynamic" (Index),
+    New = 0xBB "new" (Index),
+    Newarray = 0xBC "newarray" (ArrayType),
+    Anewarray = 0xBD "anewarray" (Index),
+    Arraylength = 0xBE "arraylength",
+    Athrow = 0xBF "athrow",
+    Checkcast = 0xC0 "checkcast" (Index),
+    Instanceof = 0xC1 "instanceof" (Index),
+    Monitorenter = 0xC2 "monitorenter",
+    Monitorexit = 0xC3 "monitorexit",
+    Wide = 0xC4 "wide" (WideInstruction),
+    Multianewarray = 0xC5 "multianewarray" (Index, u8),
+    Ifnull = 0xC6 "ifnull" (i16),
+    Ifnonnull = 0xC7 "ifnonnull" (i16),
+    GotoW = 0xC8 "goto_w" (i32),
+    JsrW = 0xC9 "jsr_w" (i32),
+);
+
+/// The instructions that `wide` can modify, which take a local variable index that's two bytes
+/// wide, and for `iinc`, a constant that's two bytes wide too.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum WideInstruction {
+    Iload(u16),
+    Lload(u16),
+    Fload(u16),
+    Dload(u16),
+    Aload(u16),
+    Istore(u16),
+    Lstore(u16),
+    Fstore(u16),
+    Dstore(u16),
+    Astore(u16),
+    Ret(u16),
+    Iinc(u16, i16),
+}
+
+impl WideInstruction {
+    /// The opcode of the instruction being modified.
+    pub const fn opcode(&self) -> Opcode {
+        match self {
+            WideInstruction::Iload(_) => Opcode::Iload,
+            WideInstruction::Lload(_) => Opcode::Lload,
+            WideInstruction::Fload(_) => Opcode::Fload,
+            WideInstruction::Dload(_) => Opcode::Dload,
+            WideInstruction::Aload(_) => Opcode::Aload,
+            WideInstruction::Istore(_) => Opcode::Istore,
+            WideInstruction::Lstore(_) => Opcode::Lstore,
+            WideInstruction::Fstore(_) => Opcode::Fstore,
+            WideInstruction::Dstore(_) => Opcode::Dstore,
+            WideInstruction::Astore(_) => Opcode::Astore,
+            WideInstruction::Ret(_) => Opcode::Ret,
+            WideInstruction::Iinc(_, _) => Opcode::Iinc,
+        }
+    }
+
+    pub const fn index(&self) -> u16 {
+        match *self {
+            WideInstruction::Iload(index) | WideInstruction::Lload(index) | WideInstruction::Fload(index)
+            | WideInstruction::Dload(index) | WideInstruction::Aload(index) | WideInstruction::Istore(index)
+            | WideInstruction::Lstore(index) | WideInstruction::Fstore(index) | WideInstruction::Dstore(index)
+            | WideInstruction::Astore(index) | WideInstruction::Ret(index) | WideInstruction::Iinc(index, _) => index,
+        }
+    }
+}
+
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-6.html#jvms-6.5.tableswitch
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TableSwitch {
+    pub default: i32,
+    pub low: i32,
+    pub high: i32,
+    /// The offset for each value from `low` to `high`, inclusive.
+    pub offsets: Vec<i32>,
+}
+
+impl TableSwitch {
+    /// The offset jumped to for the given value.
+    pub fn offset_for(&self, value: i32) -> i32 {
+        if value < self.low || value > self.high {
+            return self.default;
+        }
+        self.offsets[(value as i64 - self.low as i64) as usize]
+    }
+}
+
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-6.html#jvms-6.5.lookupswitch
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LookupSwitch {
+    pub default: i32,
+    /// Each match and the offset jumped to for it, sorted by the match.
+    pub pairs: Vec<(i32, i32)>,
+}
+
+impl LookupSwitch {
+    /// The offset jumped to for the given value.
+    pub fn offset_for(&self, value: i32) -> i32 {
+        match self.pairs.binary_search_by_key(&value, |&(key, _)| key) {
+            Ok(i) => self.pairs[i].1,
+            Err(_) => self.default,
+        }
+    }
+}
+
+/// The type of array created by `newarray`.
+///
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-6.html#jvms-6.5.newarray
+#[repr(u8)]
+#[derive(Primitive, Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ArrayType {
+    Boolean = 4,
+    Char = 5,
+    Float = 6,
+    Double = 7,
+    Byte = 8,
+    Short = 9,
+    Int = 10,
+    Long = 11,
+}
+
+impl ArrayType {
+    pub const fn base_type(self) -> BaseType {
+        match self {
+            ArrayType::Boolean => BaseType::Boolean,
+            ArrayType::Char => BaseType::Char,
+            ArrayType::Float => BaseType::Float,
+            ArrayType::Double => BaseType::Double,
+            ArrayType::Byte => BaseType::Byte,
+            ArrayType::Short => BaseType::Short,
+            ArrayType::Int => BaseType::Int,
+            ArrayType::Long => BaseType::Long,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DecodeError {
+    /// The address of the instruction that couldn't be decoded.
+    pub pc: u32,
+    pub kind: DecodeErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DecodeErrorKind {
+    /// The code ends part way through the instruction.
+    Truncated,
+    /// One of the opcodes reserved for debuggers and the JVM's own use, which can't appear in a
+    /// class file.
+    ReservedOpcode(u8),
+    UnknownOpcode(u8),
+    /// `wide` followed by an opcode it can't modify.
+    InvalidWide(u8),
+    InvalidArrayType(u8),
+    /// A `tableswitch` with its low value above its high value, or a `lookupswitch` with a
+    /// negative number of pairs or matches that aren't sorted.
+    InvalidSwitch,
+    /// One of the bytes that must be zero after `invokeinterface` or `invokedynamic` isn't.
+    NonZeroOperand,
+}
+
+impl std::error::Error for DecodeError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid instruction at pc {}: ", self.pc)?;
+        match self.kind {
+            DecodeErrorKind::Truncated => f.write_str("code ends part way through the instruction"),
+            DecodeErrorKind::ReservedOpcode(op) => write!(f, "reserved opcode {op:#04X}"),
+            DecodeErrorKind::UnknownOpcode(op) => write!(f, "unknown opcode {op:#04X}"),
+            DecodeErrorKind::InvalidWide(op) => write!(f, "wide cannot modify opcode {op:#04X}"),
+            DecodeErrorKind::InvalidArrayType(ty) => write!(f, "invalid newarray type {ty}"),
+            DecodeErrorKind::InvalidSwitch => f.write_str("switch has an invalid range or number of pairs"),
+            DecodeErrorKind::NonZeroOperand => f.write_str("operand that must be zero is not zero"),
+        }
+    }
+}
+
+// breakpoint, impdep1 and impdep2
+const RESERVED_OPCODES: [u8; 3] = [0xCA, 0xFE, 0xFF];
+
+impl Instruction {
+    /// Decodes the instruction at `pc` in the given code, returning it along with the address of
+    /// the next instruction. The whole code array is needed, as the padding in switches is
+    /// relative to the start of it.
+    pub fn decode(code: &[u8], pc: u32) -> Result<(Instruction, u32), DecodeError> {
+        let mut reader = _decode::CodeReader { code, pc, off: pc as usize };
+        let opcode = reader.u8()?;
+        let Some(opcode) = Opcode::from_u8(opcode) else {
+            let kind = if RESERVED_OPCODES.contains(&opcode) {
+                DecodeErrorKind::ReservedOpcode(opcode)
+            } else {
+                DecodeErrorKind::UnknownOpcode(opcode)
+            };
+            return Err(DecodeError { pc, kind });
+        };
+
+        let instruction = Instruction::decode_operands(opcode, &mut reader)?;
+        let zeroes = match opcode {
+            Opcode::Invokeinterface => 1,
+            Opcode::Invokedynamic => 2,
+            _ => 0,
+        };
+        for _ in 0..zeroes {
+            if reader.u8()? != 0 {
+                return Err(reader.error(DecodeErrorKind::NonZeroOperand));
+            }
+        }
+        Ok((instruction, reader.off as u32))
+    }
+
+    /// Appends the instruction to the end of `code`. The code up to the instruction is needed,
+    /// as the padding in switches is relative to the start of it.
+    pub fn encode(&self, code: &mut Vec<u8>) {
+        code.push(self.opcode() as u8);
+        self.encode_operands(code);
+        let zeroes = match self {
+            Instruction::Invokeinterface(..) => 1,
+            Instruction::Invokedynamic(_) => 2,
+            _ => 0,
+        };
+        code.resize(code.len() + zeroes, 0);
+    }
+
+    /// The offsets of the branches the instruction makes, relative to its own address, not
+    /// including the next instruction it falls through to.
+    pub fn branch_offsets(&self) -> Vec<i32> {
+        match self {
+            Instruction::Ifeq(off) | Instruction::Ifne(off) | Instruction::Iflt(off) | Instruction::Ifge(off)
+            | Instruction::Ifgt(off) | Instruction::Ifle(off) | Instruction::IfIcmpeq(off)
+            | Instruction::IfIcmpne(off) | Instruction::IfIcmplt(off) | Instruction::IfIcmpge(off)
+            | Instruction::IfIcmpgt(off) | Instruction::IfIcmple(off) | Instruction::IfAcmpeq(off)
+            | Instruction::IfAcmpne(off) | Instruction::Goto(off) | Instruction::Jsr(off)
+            | Instruction::Ifnull(off) | Instruction::Ifnonnull(off) => vec![*off as i32],
+            Instruction::GotoW(off) | Instruction::JsrW(off) => vec![*off],
+            Instruction::Tableswitch(switch) => {
+                let mut offsets = vec![switch.default];
+                offsets.extend_from_slice(&switch.offsets);
+                offsets
+            }
+            Instruction::Lookupswitch(switch) => {
+                let mut offsets = vec![switch.default];
+                offsets.extend(switch.pairs.iter().map(|&(_, off)| off));
+                offsets
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Iterates over the instructions in a code array along with their addresses, stopping after the
+/// first one that can't be decoded.
+pub struct Instructions<'a> {
+    code: &'a [u8],
+    pc: u32,
+    failed: bool,
+}
+
+impl<'a> Instructions<'a> {
+    pub fn new(code: &'a [u8]) -> Self {
+        Self { code, pc: 0, failed: false }
+    }
+}
+
+impl Iterator for Instructions<'_> {
+    type Item = Result<(u32, Instruction), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.pc as usize >= self.code.len() {
+            return None;
+        }
+
+        let pc = self.pc;
+        match Instruction::decode(self.code, pc) {
+            Ok((instruction, next)) => {
+                self.pc = next;
+                Some(Ok((pc, instruction)))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+mod _decode {
+    use super::*;
+
+    // Reads the operands of the instruction at `pc`, which starts `off` at the opcode
+    pub struct CodeReader<'a> {
+        pub code: &'a [u8],
+        pub pc: u32,
+        pub off: usize,
+    }
+
+    impl CodeReader<'_> {
+        pub fn error(&self, kind: DecodeErrorKind) -> DecodeError {
+            DecodeError { pc: self.pc, kind }
+        }
+
+        fn bytes<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+            let Some(bytes) = self.code.get(self.off..self.off + N) else {
+                return Err(self.error(DecodeErrorKind::Truncated));
+            };
+            self.off += N;
+            Ok(bytes.try_into().unwrap())
+        }
+
+        pub fn u8(&mut self) -> Result<u8, DecodeError> {
+            self.bytes::<1>().map(|b| b[0])
+        }
+
+        fn i32(&mut self) -> Result<i32, DecodeError> {
+            self.bytes().map(i32::from_be_bytes)
+        }
+
+        // Skips the padding after a switch opcode, which aligns its operands to a multiple of
+        // four bytes from the start of the code. Its value doesn't matter.
+        fn align(&mut self) -> Result<(), DecodeError> {
+            let padding = (4 - self.off % 4) % 4;
+            if self.off + padding > self.code.len() {
+                return Err(self.error(DecodeErrorKind::Truncated));
+            }
+            self.off += padding;
+            Ok(())
+        }
+    }
+
+    pub trait Operand: Sized {
+        fn read(code: &mut CodeReader) -> Result<Self, DecodeError>;
+    }
+
+    macro_rules! be_operand {
+        ($($ty: ty),*) => {
+            $(
+                impl Operand for $ty {
+                    fn read(code: &mut CodeReader) -> Result<Self, DecodeError> {
+                        code.bytes().map(<$ty>::from_be_bytes)
+                    }
+                }
+            )*
+        };
+    }
+
+    be_operand!(u8, i8, u16, i16, i32);
+
+    impl Operand for ArrayType {
+        fn read(code: &mut CodeReader) -> Result<Self, DecodeError> {
+            let ty = code.u8()?;
+            ArrayType::from_u8(ty).ok_or_else(|| code.error(DecodeErrorKind::InvalidArrayType(ty)))
+        }
+    }
+
+    impl Operand for WideInstruction {
+        fn read(code: &mut CodeReader) -> Result<Self, DecodeError> {
+            let opcode = code.u8()?;
+            let Some(modified) = Opcode::from_u8(opcode) else {
+                return Err(code.error(DecodeErrorKind::InvalidWide(opcode)));
+            };
+            let index = u16::read(code)?;
+            Ok(match modified {
+                Opcode::Iload => WideInstruction::Iload(index),
+                Opcode::Lload => WideInstruction::Lload(index),
+                Opcode::Fload => WideInstruction::Fload(index),
+                Opcode::Dload => WideInstruction::Dload(index),
+                Opcode::Aload => WideInstruction::Aload(index),
+                Opcode::Istore => WideInstruction::Istore(index),
+                Opcode::Lstore => WideInstruction::Lstore(index),
+                Opcode::Fstore => WideInstruction::Fstore(index),
+                Opcode::Dstore => WideInstruction::Dstore(index),
+                Opcode::Astore => WideInstruction::Astore(index),
+                Opcode::Ret => WideInstruction::Ret(index),
+                Opcode::Iinc => WideInstruction::Iinc(index, i16::read(code)?),
+                _ => return Err(code.error(DecodeErrorKind::InvalidWide(opcode))),
+            })
+        }
+    }
+
+    impl Operand for TableSwitch {
+        fn read(code: &mut CodeReader) -> Result<Self, DecodeError> {
+            code.align()?;
+            let default = code.i32()?;
+            let low = code.i32()?;
+            let high = code.i32()?;
+            if low > high {
+                return Err(code.error(DecodeErrorKind::InvalidSwitch));
+            }
+
+            // Check the whole table is there before allocating for it
+            let count = (high as i64 - low as i64 + 1) as usize;
+            if (code.code.len() - code.off) / 4 < count {
+                return Err(code.error(DecodeErrorKind::Truncated));
+            }
+            let mut offsets = Vec::with_capacity(count);
+            for _ in 0..count {
+                offsets.push(code.i32()?);
+            }
+            Ok(TableSwitch { default, low, high, offsets })
+        }
+    }
+
+    impl Operand for LookupSwitch {
+        fn read(code: &mut CodeReader) -> Result<Self, DecodeError> {
+            code.align()?;
+            let default = code.i32()?;
+            let npairs = code.i32()?;
+            if npairs < 0 {
+                return Err(code.error(DecodeErrorKind::InvalidSwitch));
+            }
+
+            let count = npairs as usize;
+            if (code.code.len() - code.off) / 8 < count {
+                return Err(code.error(DecodeErrorKind::Truncated));
+            }
+            let mut pairs = Vec::with_capacity(count);
+            for _ in 0..count {
+                pairs.push((code.i32()?, code.i32()?));
+            }
+            if !pairs.is_sorted_by(|a, b| a.0 < b.0) {
+                return Err(code.error(DecodeErrorKind::InvalidSwitch));
+            }
+            Ok(LookupSwitch { default, pairs })
+        }
+    }
+}
+
+mod _encode {
+    use super::*;
+
+    pub trait Operand {
+        fn write(&self, code: &mut Vec<u8>);
+    }
+
+    macro_rules! be_operand {
+        ($($ty: ty),*) => {
+            $(
+                impl Operand for $ty {
+                    fn write(&self, code: &mut Vec<u8>) {
+                        code.extend_from_slice(&self.to_be_bytes());
+                    }
+                }
+            )*
+        };
+    }
+
+    be_operand!(u8, i8, u16, i16, i32);
+
+    // Pads the code with zeroes after a switch opcode, up to a multiple of four bytes from the
+    // start of the code
+    fn align(code: &mut Vec<u8>) {
+        let padding = (4 - code.len() % 4) % 4;
+        code.resize(code.len() + padding, 0);
+    }
+
+    impl Operand for ArrayType {
+        fn write(&self, code: &mut Vec<u8>) {
+            code.push(*self as u8);
+        }
+    }
+
+    impl Operand for WideInstruction {
+        fn write(&self, code: &mut Vec<u8>) {
+            code.push(self.opcode() as u8);
+            self.index().write(code);
+            if let WideInstruction::Iinc(_, value) = self {
+                value.write(code);
+            }
+        }
+    }
+
+    impl Operand for TableSwitch {
+        fn write(&self, code: &mut Vec<u8>) {
+            align(code);
+            self.default.write(code);
+            self.low.write(code);
+            self.high.write(code);
+            for offset in &self.offsets {
+                offset.write(code);
+            }
+        }
+    }
+
+    impl Operand for LookupSwitch {
+        fn write(&self, code: &mut Vec<u8>) {
+            align(code);
+            self.default.write(code);
+            (self.pairs.len() as i32).write(code);
+            for (value, offset) in &self.pairs {
+                value.write(code);
+                offset.write(code);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(code: &[u8]) -> Result<Vec<(u32, Instruction)>, DecodeError> {
+        Instructions::new(code).collect()
+    }
+
+    #[test]
+    fn simple_instructions() {
+        let code = [
+            0x2A,             // aload_0
+            0xB7, 0x00, 0x01, // invokespecial #1
+            0x10, 0xFF,       // bipush -1
+            0x11, 0x80, 0x00, // sipush -32768
+            0x84, 0x02, 0xFE, // iinc 2, -2
+            0xB9, 0x00, 0x05, 0x02, 0x00, // invokeinterface #5, 2
+            0xBA, 0x00, 0x06, 0x00, 0x00, // invokedynamic #6
+            0xBC, 0x0A,       // newarray int
+            0xC5, 0x00, 0x07, 0x03, // multianewarray #7, 3
+            0xA7, 0xFF, 0xE4, // goto -28
+            0xB1,             // return
+        ];
+        assert_eq!(decode_all(&code).unwrap(), vec![
+            (0, Instruction::Aload0),
+            (1, Instruction::Invokespecial(1)),
+            (4, Instruction::Bipush(-1)),
+            (6, Instruction::Sipush(-32768)),
+            (9, Instruction::Iinc(2, -2)),
+            (12, Instruction::Invokeinterface(5, 2)),
+            (17, Instruction::Invokedynamic(6)),
+            (22, Instruction::Newarray(ArrayType::Int)),
+            (24, Instruction::Multianewarray(7, 3)),
+            (28, Instruction::Goto(-28)),
+            (31, Instruction::Return),
+        ]);
+        assert_eq!(Instruction::Goto(-28).branch_offsets(), vec![-28]);
+        assert_eq!(Opcode::IfIcmpeq.mnemonic(), "if_icmpeq");
+    }
+
+    #[test]
+    fn wide() {
+        let code = [
+            0xC4, 0x15, 0x01, 0x00,             // wide iload 256
+            0xC4, 0x84, 0x01, 0x00, 0x80, 0x00, // wide iinc 256, -32768
+        ];
+        assert_eq!(decode_all(&code).unwrap(), vec![
+            (0, Instruction::Wide(WideInstruction::Iload(256))),
+            (4, Instruction::Wide(WideInstruction::Iinc(256, -32768))),
+        ]);
+
+        let err = decode_all(&[0xC4, 0x60, 0x00, 0x00]).unwrap_err();
+        assert_eq!(err, DecodeError { pc: 0, kind: DecodeErrorKind::InvalidWide(0x60) });
+    }
+
+    #[test]
+    fn tableswitch_padding_depends_on_pc() {
+        let table = [
+            0, 0, 0, 20, // default
+            0, 0, 0, 1,  // low
+            0, 0, 0, 2,  // high
+            0, 0, 0, 7,
+            0, 0, 0, 8,
+        ];
+        let expected = TableSwitch { default: 20, low: 1, high: 2, offsets: vec![7, 8] };
+
+        for nops in 0..4 {
+            let mut code = vec![0x00; nops];
+            code.push(0xAA);
+            let padding = (4 - (nops + 1) % 4) % 4;
+            code.extend(std::iter::repeat_n(0xCC, padding));
+            code.extend_from_slice(&table);
+            code.push(0xB1);
+
+            let decoded = decode_all(&code).unwrap();
+            assert_eq!(decoded[nops], (nops as u32, Instruction::Tableswitch(expected.clone())));
+            assert_eq!(decoded[nops + 1], (code.len() as u32 - 1, Instruction::Return));
+        }
+        assert_eq!(expected.offset_for(2), 8);
+        assert_eq!(expected.offset_for(3), 20);
+    }
+
+    #[test]
+    fn lookupswitch() {
+        let code = [
+            0xAB, 0x00, 0x00, 0x00, // lookupswitch, padded
+            0, 0, 0, 30,            // default
+            0, 0, 0, 2,             // npairs
+            0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 10,
+            0, 0, 0, 5, 0, 0, 0, 20,
+        ];
+        let (instruction, next) = Instruction::decode(&code, 0).unwrap();
+        let Instruction::Lookupswitch(switch) = &instruction else { panic!("expected lookupswitch") };
+        assert_eq!(switch.pairs, vec![(-1, 10), (5, 20)]);
+        assert_eq!(switch.offset_for(5), 20);
+        assert_eq!(switch.offset_for(0), 30);
+        assert_eq!(instruction.branch_offsets(), vec![30, 10, 20]);
+        assert_eq!(next, code.len() as u32);
+
+        let mut unsorted = code;
+        unsorted[12..16].copy_from_slice(&6i32.to_be_bytes());
+        let err = Instruction::decode(&unsorted, 0).unwrap_err();
+        assert_eq!(err.kind, DecodeErrorKind::InvalidSwitch);
+    }
+
+    #[test]
+    fn encodes_what_it_decodes() {
+        let mut code = Vec::new();
+        let instructions = [
+            Instruction::Nop,
+            Instruction::Tableswitch(TableSwitch { default: 20, low: -1, high: 0, offsets: vec![7, 8] }),
+            Instruction::Wide(WideInstruction::Iinc(256, -32768)),
+            Instruction::Lookupswitch(LookupSwitch { default: 30, pairs: vec![(-1, 10), (5, 20)] }),
+            Instruction::Invokeinterface(5, 2),
+            Instruction::Invokedynamic(6),
+            Instruction::Newarray(ArrayType::Long),
+            Instruction::GotoW(-70000),
+            Instruction::Return,
+        ];
+        let mut addresses = Vec::new();
+        for instruction in &instructions {
+            addresses.push(code.len() as u32);
+            instruction.encode(&mut code);
+        }
+
+        let decoded = decode_all(&code).unwrap();
+        assert_eq!(decoded, addresses.into_iter().zip(instructions).collect::<Vec<_>>());
+        assert_eq!(Opcode::from_mnemonic("if_icmpeq"), Some(Opcode::IfIcmpeq));
+        assert_eq!(Opcode::from_mnemonic("breakpoint"), None);
+        assert_eq!(Instruction::from_opcode(Opcode::Iload0), Some(Instruction::Iload0));
+        assert_eq!(Instruction::from_opcode(Opcode::Iload), None);
+    }
+
+    #[test]
+    fn rejects_bad_code() {
+        let cases: &[(&[u8], u32, DecodeErrorKind)] = &[
+            (&[0x00, 0x11, 0x00], 1, DecodeErrorKind::Truncated),
+            (&[0xAA, 0x00, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 3], 0, DecodeErrorKind::Truncated),
+            (&[0xAA, 0x00, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1], 0, DecodeErrorKind::InvalidSwitch),
+            (&[0xAB, 0x00, 0x00, 0x00, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF], 0, DecodeErrorKind::InvalidSwitch),
+            (&[0xCA], 0, DecodeErrorKind::ReservedOpcode(0xCA)),
+            (&[0x00, 0xFE], 1, DecodeErrorKind::ReservedOpcode(0xFE)),
+            (&[0xCB], 0, DecodeErrorKind::UnknownOpcode(0xCB)),
+            (&[0xBC, 0x03], 0, DecodeErrorKind::InvalidArrayType(3)),
+            (&[0xB9, 0x00, 0x01, 0x01, 0x01], 0, DecodeErrorKind::NonZeroOperand),
+            (&[0xBA, 0x00, 0x01, 0x00, 0x01], 0, DecodeErrorKind::NonZeroOperand),
+        ];
+        for (code, pc, kind) in cases {
+            let mut instructions = Instructions::new(code);
+            let err = instructions.find_map(Result::err).expect("code should be rejected");
+            assert_eq!(err, DecodeError { pc: *pc, kind: kind.clone() });
+            assert!(instructions.next().is_none());
+        }
+    }
+}
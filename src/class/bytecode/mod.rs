@@ -0,0 +1,25 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding of the bytecode in `Code` attributes into instructions.
+//!
+//! Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-6.html
+
+mod instruction;
+
+pub use instruction::{
+    ArrayType, DecodeError, DecodeErrorKind, Instruction, Instructions, LookupSwitch, Opcode, TableSwitch,
+    WideInstruction,
+};
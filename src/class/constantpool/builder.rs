@@ -0,0 +1,421 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::class::parse::BinaryReader;
+use crate::class::write::BinaryWriter;
+use crate::types::{methodhandle, mutf8, Array, Jdouble, Jfloat, Jint, Jlong, OutOfMemoryError};
+use super::entry::{self, *};
+use super::{Entry, Index, Pool, Tag};
+
+/// The largest index an entry can have, as the constant pool count is a u2 that is one more
+/// than the number of entries.
+const MAX_INDEX: usize = u16::MAX as usize - 1;
+
+/// Builds up a constant pool, for generating classes or adding to an existing class.
+///
+/// Every method adds an entry and gives back its index, or gives back the index of the same
+/// entry if it's already in the pool, along with any entries it refers to.
+pub struct PoolBuilder {
+    tags: Vec<u8>,
+    constants: Vec<Entry>,
+    // Keyed by the tag and the entry's bytes in the class file, which identify it exactly
+    indexes: HashMap<(u8, Vec<u8>), Index>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BuildError {
+    /// There's no room left in the pool for another entry
+    PoolFull,
+    /// The string is longer than 65535 bytes once encoded as modified UTF-8
+    Utf8TooLong(usize),
+    /// A method handle refers to an entry that isn't a field or method of the right kind
+    BadMethodHandleReference { kind: methodhandle::Ref, index: Index },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::PoolFull => write!(f, "constant pool has more than {MAX_INDEX} entries"),
+            BuildError::Utf8TooLong(len) => write!(f, "utf8 entry is {len} bytes long (max is 65535)"),
+            BuildError::BadMethodHandleReference { kind, index } =>
+                write!(f, "constant pool index {index} is not a valid reference for a {kind:?} method handle"),
+        }
+    }
+}
+
+impl PoolBuilder {
+    pub fn new() -> Self {
+        Self { tags: Vec::new(), constants: Vec::new(), indexes: HashMap::new() }
+    }
+
+    /// Starts from the entries of an existing pool, which all keep their indexes, so that
+    /// entries can be added to a class without changing the ones it already refers to.
+    pub fn from_pool(pool: &Pool) -> Self {
+        let mut builder = Self::new();
+        // SAFETY: Every tag is initialized during parsing, before the pool is handed out
+        let tags = unsafe { pool.tags.as_slice() };
+        for (&tag, existing) in tags.iter().zip(pool.entries()) {
+            if tag == Tag::INVALID {
+                // The unusable slot after a long or double, which was pushed along with it
+                continue;
+            }
+
+            // Entries can't be cloned, so go through their class file form instead
+            let body = entry_bytes(existing);
+            let copy = entry::parse_entry(&mut BinaryReader::new(body.clone()), tag)
+                .expect("entry from a valid pool should parse");
+            let index = builder.push(tag, copy);
+            builder.indexes.entry((tag, body)).or_insert(index);
+        }
+        builder
+    }
+
+    /// The constant pool count, which is one more than the index of the last entry.
+    pub fn size(&self) -> u16 {
+        (self.tags.len() + 1) as u16
+    }
+
+    pub fn utf8(&mut self, value: &str) -> Result<Index, BuildError> {
+        let bytes = mutf8::encode(value);
+        if bytes.len() > u16::MAX as usize {
+            return Err(BuildError::Utf8TooLong(bytes.len()));
+        }
+        self.add(Tag::UTF8, Entry::Utf8(UnresolvedUtf8Info::new(bytes.into())))
+    }
+
+    pub fn integer(&mut self, value: Jint) -> Result<Index, BuildError> {
+        self.add(Tag::INTEGER, Entry::Integer(IntegerInfo::from_bytes(value as u32)))
+    }
+
+    pub fn float(&mut self, value: Jfloat) -> Result<Index, BuildError> {
+        self.add(Tag::FLOAT, Entry::Float(FloatInfo::from_bytes(value.to_bits())))
+    }
+
+    pub fn long(&mut self, value: Jlong) -> Result<Index, BuildError> {
+        let bits = value as u64;
+        self.add(Tag::LONG, Entry::Long(LongInfo::from_bytes(bits as u32, (bits >> 32) as u32)))
+    }
+
+    pub fn double(&mut self, value: Jdouble) -> Result<Index, BuildError> {
+        let bits = value.to_bits();
+        self.add(Tag::DOUBLE, Entry::Double(DoubleInfo::from_bytes(bits as u32, (bits >> 32) as u32)))
+    }
+
+    /// Adds a class with the given binary name, such as `java/lang/String`, or array
+    /// descriptor, such as `[I`.
+    pub fn class(&mut self, name: &str) -> Result<Index, BuildError> {
+        let name_index = self.utf8(name)?;
+        self.add(Tag::CLASS, Entry::Class(UnresolvedClassInfo::new(name_index)))
+    }
+
+    pub fn string(&mut self, value: &str) -> Result<Index, BuildError> {
+        let string_index = self.utf8(value)?;
+        self.add(Tag::STRING, Entry::String(UnresolvedStringInfo::new(string_index)))
+    }
+
+    pub fn name_and_type(&mut self, name: &str, descriptor: &str) -> Result<Index, BuildError> {
+        let name_index = self.utf8(name)?;
+        let descriptor_index = self.utf8(descriptor)?;
+        self.add(Tag::NAME_AND_TYPE, Entry::NameAndType(UnresolvedNameAndTypeInfo::new(name_index, descriptor_index)))
+    }
+
+    pub fn field_ref(&mut self, class: &str, name: &str, descriptor: &str) -> Result<Index, BuildError> {
+        let (class_index, name_and_type_index) = self.member(class, name, descriptor)?;
+        self.add(Tag::FIELDREF, Entry::Fieldref(FieldrefInfo::new(class_index, name_and_type_index)))
+    }
+
+    pub fn method_ref(&mut self, class: &str, name: &str, descriptor: &str) -> Result<Index, BuildError> {
+        let (class_index, name_and_type_index) = self.member(class, name, descriptor)?;
+        self.add(Tag::METHODREF, Entry::Methodref(MethodrefInfo::new(class_index, name_and_type_index)))
+    }
+
+    pub fn interface_method_ref(&mut self, class: &str, name: &str, descriptor: &str) -> Result<Index, BuildError> {
+        let (class_index, name_and_type_index) = self.member(class, name, descriptor)?;
+        let info = InterfaceMethodrefInfo::new(class_index, name_and_type_index);
+        self.add(Tag::INTERFACE_METHODREF, Entry::InterfaceMethodref(info))
+    }
+
+    fn member(&mut self, class: &str, name: &str, descriptor: &str) -> Result<(Index, Index), BuildError> {
+        Ok((self.class(class)?, self.name_and_type(name, descriptor)?))
+    }
+
+    /// Adds a method handle for the field or method at the given index, which must be the
+    /// kind of entry the kind of handle refers to. Only `NewInvokeSpecial` handles can refer
+    /// to `<init>`, and they can't refer to anything else, and no handle can refer to `<clinit>`.
+    ///
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.8
+    pub fn method_handle(&mut self, kind: methodhandle::Ref, reference: Index) -> Result<Index, BuildError> {
+        let tag = self.tag(reference);
+        let valid = match kind {
+            methodhandle::Ref::GetField | methodhandle::Ref::GetStatic
+                | methodhandle::Ref::PutField | methodhandle::Ref::PutStatic => tag == Some(Tag::FIELDREF),
+            methodhandle::Ref::InvokeVirtual | methodhandle::Ref::NewInvokeSpecial => tag == Some(Tag::METHODREF),
+            methodhandle::Ref::InvokeStatic | methodhandle::Ref::InvokeSpecial => {
+                tag == Some(Tag::METHODREF) || tag == Some(Tag::INTERFACE_METHODREF)
+            },
+            methodhandle::Ref::InvokeInterface => tag == Some(Tag::INTERFACE_METHODREF),
+        };
+        let valid = valid && match self.member_name(reference) {
+            Some(name) if kind == methodhandle::Ref::NewInvokeSpecial => name == b"<init>",
+            Some(name) => name != b"<init>" && name != b"<clinit>",
+            None => true,
+        };
+        if !valid {
+            return Err(BuildError::BadMethodHandleReference { kind, index: reference });
+        }
+        self.add(Tag::METHOD_HANDLE, Entry::MethodHandle(MethodHandleInfo::new(kind, reference)))
+    }
+
+    pub fn method_type(&mut self, descriptor: &str) -> Result<Index, BuildError> {
+        let descriptor_index = self.utf8(descriptor)?;
+        self.add(Tag::METHOD_TYPE, Entry::MethodType(MethodTypeInfo::new(descriptor_index)))
+    }
+
+    /// Adds a dynamically computed constant. The bootstrap method is an index in to the
+    /// class's `BootstrapMethods` attribute, which is up to the caller to build.
+    pub fn dynamic(&mut self, bootstrap_method: Index, name: &str, descriptor: &str) -> Result<Index, BuildError> {
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.add(Tag::DYNAMIC, Entry::Dynamic(DynamicInfo::new(bootstrap_method, name_and_type_index)))
+    }
+
+    /// Adds a dynamically computed call site, for use by `invokedynamic`. The bootstrap
+    /// method is an index in to the class's `BootstrapMethods` attribute, which is up to the
+    /// caller to build.
+    pub fn invoke_dynamic(&mut self, bootstrap_method: Index, name: &str, descriptor: &str) -> Result<Index, BuildError> {
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        let info = InvokeDynamicInfo::new(bootstrap_method, name_and_type_index);
+        self.add(Tag::INVOKE_DYNAMIC, Entry::InvokeDynamic(info))
+    }
+
+    pub fn module(&mut self, name: &str) -> Result<Index, BuildError> {
+        let name_index = self.utf8(name)?;
+        self.add(Tag::MODULE, Entry::Module(ModuleInfo::new(name_index)))
+    }
+
+    /// Adds a package with the given internal name, such as `java/lang`.
+    pub fn package(&mut self, name: &str) -> Result<Index, BuildError> {
+        let name_index = self.utf8(name)?;
+        self.add(Tag::PACKAGE, Entry::Package(PackageInfo::new(name_index)))
+    }
+
+    pub fn build(self) -> Result<Pool, OutOfMemoryError> {
+        let mut pool = Pool::new(self.tags.len())?;
+        for (i, (tag, entry)) in self.tags.into_iter().zip(self.constants).enumerate() {
+            pool.put_raw(i, tag, entry);
+        }
+        Ok(pool)
+    }
+
+    fn tag(&self, index: Index) -> Option<u8> {
+        let index = (index as usize).checked_sub(1)?;
+        self.tags.get(index).copied()
+    }
+
+    /// The name of the field or method at the given index, if it's a member reference.
+    fn member_name(&self, index: Index) -> Option<&[u8]> {
+        let get = |index: Index| (index as usize).checked_sub(1).and_then(|i| self.constants.get(i));
+        let name_and_type_index = match get(index)? {
+            Entry::Fieldref(info) => info.name_and_type_index(),
+            Entry::Methodref(info) => info.name_and_type_index(),
+            Entry::InterfaceMethodref(info) => info.name_and_type_index(),
+            _ => return None,
+        };
+        let Entry::NameAndType(name_and_type) = get(name_and_type_index)? else {
+            return None;
+        };
+        match get(name_and_type.name_index())? {
+            Entry::Utf8(name) => Some(name.bytes()),
+            _ => None,
+        }
+    }
+
+    fn add(&mut self, tag: u8, entry: Entry) -> Result<Index, BuildError> {
+        let key = (tag, entry_bytes(&entry));
+        if let Some(&index) = self.indexes.get(&key) {
+            return Ok(index);
+        }
+
+        // Longs and doubles take up two entries
+        // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.5
+        let width = if tag == Tag::LONG || tag == Tag::DOUBLE { 2 } else { 1 };
+        if self.tags.len() + width > MAX_INDEX {
+            return Err(BuildError::PoolFull);
+        }
+
+        let index = self.push(tag, entry);
+        self.indexes.insert(key, index);
+        Ok(index)
+    }
+
+    fn push(&mut self, tag: u8, entry: Entry) -> Index {
+        self.tags.push(tag);
+        self.constants.push(entry);
+        let index = self.tags.len() as Index;
+        if tag == Tag::LONG || tag == Tag::DOUBLE {
+            self.tags.push(Tag::INVALID);
+            self.constants.push(Entry::Invalid);
+        }
+        index
+    }
+}
+
+impl Default for PoolBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn entry_bytes(entry: &Entry) -> Vec<u8> {
+    let mut buf = BinaryWriter::new();
+    entry::serialize_entry(entry, &mut buf);
+    buf.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::constantpool::parse_pool;
+    use crate::loader::Serialize;
+    use crate::types::Version;
+    use super::*;
+
+    // Writes the pool out and parses it back in, which also validates it
+    fn reparse(pool: &Pool) -> Pool {
+        let mut buf = BinaryWriter::new();
+        pool.serialize(&mut buf);
+        parse_pool(&mut BinaryReader::new(buf.into_bytes()), Version::new(69, 0)).expect("built pool should be valid")
+    }
+
+    #[test]
+    fn deduplicates_entries() {
+        let mut builder = PoolBuilder::new();
+        let class = builder.class("java/lang/String").unwrap();
+        let string = builder.string("java/lang/String").unwrap();
+        assert_eq!(builder.class("java/lang/String").unwrap(), class);
+        assert_eq!(builder.string("java/lang/String").unwrap(), string);
+
+        // The class and string share the utf8 for the name
+        assert_eq!(builder.utf8("java/lang/String").unwrap(), 1);
+        assert_eq!(builder.size(), 4);
+
+        let method = builder.method_ref("java/lang/String", "length", "()I").unwrap();
+        assert_eq!(builder.method_ref("java/lang/String", "length", "()I").unwrap(), method);
+        // An interface method with the same name is a different entry
+        assert_ne!(builder.interface_method_ref("java/lang/String", "length", "()I").unwrap(), method);
+    }
+
+    #[test]
+    fn wide_entries_take_two_slots() {
+        let mut builder = PoolBuilder::new();
+        assert_eq!(builder.long(i64::MIN).unwrap(), 1);
+        assert_eq!(builder.double(-0.0).unwrap(), 3);
+        assert_eq!(builder.integer(0).unwrap(), 5);
+
+        // Doubles are compared by their bits, so these are all different entries
+        assert_eq!(builder.double(0.0).unwrap(), 6);
+        assert_eq!(builder.double(f64::NAN).unwrap(), 8);
+        assert_eq!(builder.double(f64::NAN).unwrap(), 8);
+
+        let pool = reparse(&builder.build().unwrap());
+        assert_eq!(pool.get_long(1).map(LongInfo::value), Some(i64::MIN));
+        assert!(pool.get_double(3).is_some_and(|info| info.value().is_sign_negative()));
+        assert!(pool.tag(4).is_none());
+    }
+
+    #[test]
+    fn builds_a_valid_pool() {
+        let mut builder = PoolBuilder::new();
+        let getter = builder.field_ref("Point", "x", "I").unwrap();
+        let handle = builder.method_handle(methodhandle::Ref::GetField, getter).unwrap();
+        let method_type = builder.method_type("(LPoint;)I").unwrap();
+        let call_site = builder.invoke_dynamic(0, "applyAsInt", "()Ljava/util/function/ToIntFunction;").unwrap();
+        let constant = builder.dynamic(1, "ZERO", "LPoint;").unwrap();
+        let module = builder.module("com.example").unwrap();
+        let package = builder.package("com/example").unwrap();
+        let value = builder.float(1.5).unwrap();
+
+        let pool = reparse(&builder.build().unwrap());
+        assert_eq!(pool.get_method_handle(handle).map(MethodHandleInfo::reference_index), Some(getter));
+        assert!(pool.get_method_type(method_type).is_some());
+        assert!(pool.get_invoke_dynamic(call_site).is_some());
+        assert!(pool.get_dynamic(constant).is_some());
+        assert!(pool.get_module(module).is_some());
+        assert!(pool.get_package(package).is_some());
+        assert_eq!(pool.get_float(value).map(FloatInfo::value), Some(1.5));
+
+        let field = pool.get_field_ref(getter).unwrap();
+        assert_eq!(pool.resolve_class(field.class_index()).unwrap().name(), "Point");
+        let name_and_type = pool.resolve_name_and_type(field.name_and_type_index()).unwrap();
+        assert_eq!(name_and_type.name(), "x");
+        assert_eq!(name_and_type.descriptor(), "I");
+    }
+
+    #[test]
+    fn extends_an_existing_pool() {
+        let mut original = PoolBuilder::new();
+        original.long(1).unwrap();
+        let string = original.string("hello").unwrap();
+        let pool = original.build().unwrap();
+
+        let mut builder = PoolBuilder::from_pool(&pool);
+        assert_eq!(builder.size(), pool.size());
+        assert_eq!(builder.string("hello").unwrap(), string);
+        assert_eq!(builder.string("world").unwrap(), pool.size() + 1);
+
+        let pool = reparse(&builder.build().unwrap());
+        assert_eq!(pool.get_long(1).map(LongInfo::value), Some(1));
+        assert_eq!(pool.resolve_string(string).unwrap().as_str(), "hello");
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let mut builder = PoolBuilder::new();
+        let field = builder.field_ref("Point", "x", "I").unwrap();
+        let error = builder.method_handle(methodhandle::Ref::InvokeVirtual, field);
+        assert_eq!(error, Err(BuildError::BadMethodHandleReference { kind: methodhandle::Ref::InvokeVirtual, index: field }));
+
+        let constructor = builder.method_ref("Point", "<init>", "()V").unwrap();
+        let method = builder.method_ref("Point", "length", "()D").unwrap();
+        let initializer = builder.method_ref("Point", "<clinit>", "()V").unwrap();
+        assert!(builder.method_handle(methodhandle::Ref::NewInvokeSpecial, constructor).is_ok());
+        assert!(builder.method_handle(methodhandle::Ref::InvokeVirtual, method).is_ok());
+        for (kind, index) in [
+            (methodhandle::Ref::NewInvokeSpecial, method),
+            (methodhandle::Ref::NewInvokeSpecial, initializer),
+            (methodhandle::Ref::InvokeSpecial, constructor),
+            (methodhandle::Ref::InvokeVirtual, constructor),
+            (methodhandle::Ref::InvokeStatic, initializer),
+        ] {
+            assert_eq!(builder.method_handle(kind, index), Err(BuildError::BadMethodHandleReference { kind, index }));
+        }
+
+        // Null is encoded as two bytes
+        let long = "\0".repeat(u16::MAX as usize / 2 + 1);
+        assert_eq!(builder.utf8(&long), Err(BuildError::Utf8TooLong(u16::MAX as usize + 1)));
+    }
+
+    #[test]
+    fn stops_when_full() {
+        let mut builder = PoolBuilder::new();
+        for i in 0..MAX_INDEX {
+            builder.integer(i as Jint).unwrap();
+        }
+        assert_eq!(builder.size(), u16::MAX);
+        assert_eq!(builder.integer(-1), Err(BuildError::PoolFull));
+        // Already in the pool, so doesn't need any more room
+        assert_eq!(builder.integer(0), Ok(1));
+    }
+}
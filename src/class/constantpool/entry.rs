@@ -14,9 +14,10 @@
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
 pub use _parse::parse_entry;
+pub use _write::serialize_entry;
 
 use crate::class::constantpool::Pool;
-use crate::types::{methodhandle, Array, Jdouble, Jfloat, Jint, Jlong};
+use crate::types::{methodhandle, mutf8, Array, Bytes, Jchar, Jdouble, Jfloat, Jint, Jlong, Symbol};
 
 macro_rules! tag {
     ($name: ident, $tag: ident) => {
@@ -40,6 +41,10 @@ macro_rules! name_info {
             pub(super) fn new(name_index: super::Index) -> Self {
                 Self { name_index }
             }
+
+            pub fn name_index(&self) -> super::Index {
+                self.name_index
+            }
         }
     };
 }
@@ -51,23 +56,17 @@ impl UnresolvedClassInfo {
         // TODO: Better handle this error
         let utf8_name = pool.resolve_utf8(self.name_index)
             .expect("name index for unresolved class info not in constant pool!");
-        ClassInfo { name: utf8_name.as_string() }
+        ClassInfo { name: utf8_name.symbol() }
     }
 }
 
 pub struct ClassInfo {
-    name: String
+    name: Symbol
 }
 
 impl ClassInfo {
-    pub fn name(&self) -> String {
-        // TODO: This isn't ideal as it clones every time. We need to pool strings in
-        //  the future, so will sort out when we do that
-        self.name.clone()
-    }
-
-    pub fn name_str(&self) -> &str {
-        self.name.as_str()
+    pub fn name(&self) -> Symbol {
+        self.name
     }
 }
 
@@ -84,6 +83,14 @@ macro_rules! ref_entry {
             pub fn new(class_index: super::Index, name_and_type_index: super::Index) -> Self {
                 Self { class_index, name_and_type_index }
             }
+
+            pub fn class_index(&self) -> super::Index {
+                self.class_index
+            }
+
+            pub fn name_and_type_index(&self) -> super::Index {
+                self.name_and_type_index
+            }
         }
     };
 }
@@ -102,28 +109,35 @@ impl UnresolvedStringInfo {
         Self { string_index }
     }
 
+    pub fn string_index(&self) -> super::Index {
+        self.string_index
+    }
+
     pub fn resolve(&self, pool: &Pool) -> StringInfo {
         // TODO: Better handle this error
         let utf8 = pool.resolve_utf8(self.string_index)
             .expect("string index for unresolved string info not in constant pool!");
-        StringInfo { value: utf8.as_string() }
+        StringInfo { value: utf8.symbol() }
     }
 }
 
 pub struct StringInfo {
-    value: String
+    value: Symbol
 }
 
 impl StringInfo {
-    pub fn as_string(&self) -> String {
-        // TODO: This isn't ideal as it clones every time. We need to pool strings in
-        //  the future, so will sort out when we do that
-        self.value.clone()
+    pub fn value(&self) -> Symbol {
+        self.value
     }
 
     pub fn as_str(&self) -> &str {
         self.value.as_str()
     }
+
+    /// The exact UTF-16 value of the string, which is what `ldc` loads.
+    pub fn to_utf16(&self) -> Vec<Jchar> {
+        self.value.to_utf16()
+    }
 }
 
 pub struct IntegerInfo {
@@ -141,9 +155,9 @@ impl IntegerInfo {
     }
 }
 
-impl Into<Jint> for IntegerInfo {
-    fn into(self) -> Jint {
-        self.value
+impl From<IntegerInfo> for Jint {
+    fn from(info: IntegerInfo) -> Jint {
+        info.value
     }
 }
 
@@ -161,9 +175,9 @@ impl FloatInfo {
     }
 }
 
-impl Into<Jfloat> for FloatInfo {
-    fn into(self) -> Jfloat {
-        self.value
+impl From<FloatInfo> for Jfloat {
+    fn from(info: FloatInfo) -> Jfloat {
+        info.value
     }
 }
 
@@ -182,9 +196,9 @@ impl LongInfo {
     }
 }
 
-impl Into<Jlong> for LongInfo {
-    fn into(self) -> Jlong {
-        self.value
+impl From<LongInfo> for Jlong {
+    fn from(info: LongInfo) -> Jlong {
+        info.value
     }
 }
 
@@ -203,9 +217,9 @@ impl DoubleInfo {
     }
 }
 
-impl Into<Jdouble> for DoubleInfo {
-    fn into(self) -> Jdouble {
-        self.value
+impl From<DoubleInfo> for Jdouble {
+    fn from(info: DoubleInfo) -> Jdouble {
+        info.value
     }
 }
 
@@ -220,75 +234,94 @@ impl UnresolvedNameAndTypeInfo {
         Self { name_index, descriptor_index }
     }
 
+    pub fn name_index(&self) -> super::Index {
+        self.name_index
+    }
+
+    pub fn descriptor_index(&self) -> super::Index {
+        self.descriptor_index
+    }
+
     pub fn resolve(&self, pool: &Pool) -> NameAndTypeInfo {
         // TODO: Better handle this error
         let utf8_name = pool.resolve_utf8(self.name_index)
             .expect("name index for unresolved name and type not in constant pool!");
-        let name = utf8_name.as_string();
+        let name = utf8_name.symbol();
 
         // TODO: Better handle this error
         let utf8_descriptor = pool.resolve_utf8(self.descriptor_index)
             .expect("descriptor index for unresolved name and type not in constant pool!");
-        let descriptor = utf8_descriptor.as_string();
+        let descriptor = utf8_descriptor.symbol();
 
         NameAndTypeInfo { name, descriptor }
     }
 }
 
 pub struct NameAndTypeInfo {
-    name: String,
-    descriptor: String
+    name: Symbol,
+    descriptor: Symbol
 }
 
 impl NameAndTypeInfo {
-    pub fn name(&self) -> String {
-        // TODO: This isn't ideal as it clones every time. We need to pool strings in
-        //  the future, so will sort out when we do that
-        self.name.clone()
+    pub fn name(&self) -> Symbol {
+        self.name
     }
 
-    pub fn descriptor(&self) -> String {
-        // TODO: This isn't ideal as it clones every time. We need to pool strings in
-        //  the future, so will sort out when we do that
-        self.descriptor.clone()
+    pub fn descriptor(&self) -> Symbol {
+        self.descriptor
     }
 }
 
 pub struct UnresolvedUtf8Info {
-    bytes: Array<u8>
+    // Shares the class file's buffer, rather than being copied out of it
+    bytes: Bytes
 }
 tag!(UnresolvedUtf8Info, Utf8);
 
 impl UnresolvedUtf8Info {
-    pub(super) fn new(bytes: Array<u8>) -> Self {
+    pub(super) fn new(bytes: Bytes) -> Self {
         Self { bytes }
     }
 
-    fn as_string(&self) -> String {
-        // SAFETY: The bytes Array is definitely initialized
-        let vec = unsafe { self.bytes.to_vec() };
-
-        // SAFETY: This is safe for 99% of cases.
-        // TODO: must figure out what to do about nulls being 2 bytes and any
-        //  chars more than 3 bytes
-        unsafe { String::from_utf8_unchecked(vec) }
+    /// The raw modified UTF-8 bytes of this entry.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
     }
 
     pub(super) fn resolve(&self) -> Utf8Info {
-        let string = self.as_string();
-        Utf8Info { value: string }
+        let bytes = self.bytes();
+
+        // Surrogates are always encoded starting with 0xED. If there are any, the string might
+        // not be able to represent them exactly (if they aren't part of a pair), so intern the
+        // UTF-16 in that case, which keeps them
+        let value = if bytes.contains(&0xED) {
+            Symbol::intern_utf16(&mutf8::decode_utf16(bytes).expect("utf8 entry was validated when parsed"))
+        } else {
+            Symbol::intern(&mutf8::decode(bytes).expect("utf8 entry was validated when parsed"))
+        };
+        Utf8Info { value }
     }
 }
 
 pub struct Utf8Info {
-    value: String
+    value: Symbol,
 }
 
 impl Utf8Info {
-    pub fn as_string(&self) -> String {
-        // TODO: This isn't ideal as it clones every time. We need to pool strings in
-        //  the future, so will sort out when we do that
-        self.value.clone()
+    /// The interned value of this entry, which keeps any surrogates that aren't part of a pair.
+    pub fn symbol(&self) -> Symbol {
+        self.value
+    }
+
+    /// The string value of this entry, which has U+FFFD in place of any surrogates that aren't
+    /// part of a pair.
+    pub fn as_str(&self) -> &str {
+        self.value.as_str()
+    }
+
+    /// The exact UTF-16 value of this entry.
+    pub fn to_utf16(&self) -> Vec<Jchar> {
+        self.value.to_utf16()
     }
 }
 
@@ -302,6 +335,14 @@ impl MethodHandleInfo {
     pub(super) fn new(reference_kind: methodhandle::Ref, reference_index: super::Index) -> Self {
         Self { reference_kind, reference_index }
     }
+
+    pub fn reference_kind(&self) -> methodhandle::Ref {
+        self.reference_kind
+    }
+
+    pub fn reference_index(&self) -> super::Index {
+        self.reference_index
+    }
 }
 
 pub struct MethodTypeInfo {
@@ -313,6 +354,10 @@ impl MethodTypeInfo {
     pub(super) fn new(descriptor_index: super::Index) -> Self {
         Self { descriptor_index }
     }
+
+    pub fn descriptor_index(&self) -> super::Index {
+        self.descriptor_index
+    }
 }
 
 macro_rules! dynamic {
@@ -328,6 +373,14 @@ macro_rules! dynamic {
                 name_and_type_index: super::Index) -> Self {
                 Self { bootstrap_method_attr_index, name_and_type_index }
             }
+
+            pub fn bootstrap_method_attr_index(&self) -> super::Index {
+                self.bootstrap_method_attr_index
+            }
+
+            pub fn name_and_type_index(&self) -> super::Index {
+                self.name_and_type_index
+            }
         }
     };
 }
@@ -341,7 +394,7 @@ name_info!(PackageInfo, Package);
 mod _parse {
     use num_traits::FromPrimitive;
     use crate::class::constantpool::{Entry, Tag};
-    use crate::class::parse::{BinaryReader, ParseError};
+    use crate::class::parse::{BinaryReader, ErrorKind, ParseError};
     use crate::types::{methodhandle, Array};
     use super::*;
 
@@ -364,7 +417,7 @@ mod _parse {
             Tag::INVOKE_DYNAMIC => InvokeDynamicInfo::parse(buf).map(Entry::InvokeDynamic),
             Tag::MODULE => ModuleInfo::parse(buf).map(Entry::Module),
             Tag::PACKAGE => PackageInfo::parse(buf).map(Entry::Package),
-            _ => ParseError::new("invalid entry tag {tag}").into(),
+            _ => ParseError::new(ErrorKind::BadConstantPoolTag(tag), buf.position() - 1).into(),
         }
     }
 
@@ -376,16 +429,13 @@ mod _parse {
             let len = unsafe { buf.unsafe_read_u16() } as usize;
             buf.check_bytes(len, "utf8")?;
 
-            // TODO: We shouldn't wrap this. When we have proper error handling,
-            //  propagate it.
-            let mut bytes = Array::new(len)
-                .map_err(|_| ParseError::new("cannot allocate array"))?;
-
-            // SAFETY: read only writes to the slice, doesn't read from it, so
-            // it being full of uninitialized memory is not a problem
-            let slice = unsafe { bytes.as_slice_mut() };
-            buf.read(slice);
+            let start = buf.position();
+            let bytes = buf.read_bytes(len, "utf8")?;
 
+            mutf8::validate(&bytes).map_err(|err| {
+                let kind = ErrorKind::Malformed(format!("utf8 - {err}"));
+                ParseError::new(kind, start + err.offset())
+            })?;
             Ok(UnresolvedUtf8Info::new(bytes))
         }
     }
@@ -475,8 +525,10 @@ mod _parse {
 
             // SAFETY: Guaranteed by check_bytes
             let ref_kind = unsafe { buf.unsafe_read_u8() };
-            let reference_kind = methodhandle::Ref::from_u8(ref_kind)
-                .ok_or_else(|| ParseError::new("method handle - reference kind invalid"))?;
+            let reference_kind = methodhandle::Ref::from_u8(ref_kind).ok_or_else(|| {
+                let msg = format!("method handle - reference kind {ref_kind} invalid");
+                ParseError::new(ErrorKind::Malformed(msg), buf.position() - 1)
+            })?;
             let reference_index = unsafe { buf.unsafe_read_u16() };
 
             Ok(MethodHandleInfo::new(reference_kind, reference_index))
@@ -529,3 +581,48 @@ mod _parse {
     parse_nameable!(ModuleInfo, "module");
     parse_nameable!(PackageInfo, "package");
 }
+
+mod _write {
+    use crate::class::constantpool::{Entry, Index};
+    use crate::class::write::BinaryWriter;
+    use super::*;
+
+    /// Writes the body of an entry, without its tag. The unusable slot after a long or
+    /// double has nothing to write.
+    pub fn serialize_entry(entry: &Entry, buf: &mut BinaryWriter) {
+        match entry {
+            Entry::Invalid => {},
+            Entry::Utf8(info) => {
+                buf.write_u16(info.bytes().len() as u16);
+                buf.write_bytes(info.bytes());
+            },
+            Entry::Integer(info) => buf.write_u32(info.value as u32),
+            Entry::Float(info) => buf.write_u32(info.value.to_bits()),
+            Entry::Long(info) => buf.write_bytes(&info.value.to_be_bytes()),
+            Entry::Double(info) => buf.write_bytes(&info.value.to_bits().to_be_bytes()),
+            Entry::Class(info) => buf.write_u16(info.name_index),
+            Entry::String(info) => buf.write_u16(info.string_index),
+            Entry::Fieldref(info) => serialize_ref(info.class_index, info.name_and_type_index, buf),
+            Entry::Methodref(info) => serialize_ref(info.class_index, info.name_and_type_index, buf),
+            Entry::InterfaceMethodref(info) => serialize_ref(info.class_index, info.name_and_type_index, buf),
+            Entry::NameAndType(info) => serialize_ref(info.name_index, info.descriptor_index, buf),
+            Entry::MethodHandle(info) => {
+                buf.write_u8(info.reference_kind as u8);
+                buf.write_u16(info.reference_index);
+            },
+            Entry::MethodType(info) => buf.write_u16(info.descriptor_index),
+            Entry::Dynamic(info) => serialize_ref(info.bootstrap_method_attr_index, info.name_and_type_index, buf),
+            Entry::InvokeDynamic(info) => {
+                serialize_ref(info.bootstrap_method_attr_index, info.name_and_type_index, buf)
+            },
+            Entry::Module(info) => buf.write_u16(info.name_index),
+            Entry::Package(info) => buf.write_u16(info.name_index),
+        }
+    }
+
+    // Every entry that refers to two other things is laid out the same way
+    fn serialize_ref(first: Index, second: Index, buf: &mut BinaryWriter) {
+        buf.write_u16(first);
+        buf.write_u16(second);
+    }
+}
@@ -13,33 +13,36 @@
 // You should have received a copy of the GNU General Public License along
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
+mod builder;
 mod entry;
+mod validate;
 
 // Export everything in submodules in this module so it appears as all one module
 pub use entry::{
     UnresolvedClassInfo, DoubleInfo, DynamicInfo, FieldrefInfo, FloatInfo,
     IntegerInfo, InterfaceMethodrefInfo, InvokeDynamicInfo, LongInfo, MethodHandleInfo,
     MethodTypeInfo, MethodrefInfo, ModuleInfo, UnresolvedNameAndTypeInfo, PackageInfo, UnresolvedStringInfo, UnresolvedUtf8Info,
+    ClassInfo, NameAndTypeInfo, StringInfo, Utf8Info,
 };
+pub use builder::{BuildError, PoolBuilder};
 pub use _parse::parse_pool;
+pub use validate::validate_bootstrap_indexes;
 
-use std::cell::UnsafeCell;
-use crate::class::constantpool::entry::{ClassInfo, NameAndTypeInfo, StringInfo, Utf8Info};
+use std::sync::OnceLock;
+use num_traits::FromPrimitive;
 use crate::types::{Array, OutOfMemoryError};
 
 pub struct Pool {
-    // These two being UnsafeCell basically means borrow checking is entirely on us.
-    // We know this type is thread safe though, as it is considered immutable once initialized,
-    // save for some operations like the resolve functions, which perform a set.
-    // It also means we don't need to have a mutable class reference everywhere just to perform
-    // on-the-fly resolution, which makes programming a little easier.
-
-    // In future the resolves will need to be locked, but we aren't quite that advanced yet.
-    // The initial population is always single threaded, and happens before any of the values
-    // can be used, so we safeguard at that point by making `put` and `put_two_wide`
-    // take &mut self.
-    tags: UnsafeCell<Array<u8>>,
-    constants: UnsafeCell<Array<Entry>>,
+    // The tags and constants are populated during parsing (which is single threaded and needs
+    // &mut self), and are never modified after that, so can be freely shared between threads.
+    tags: Array<u8>,
+    constants: Array<Entry>,
+
+    // Resolving an entry doesn't modify it, but instead stores the result alongside it. Each
+    // entry has its own cell, which is only ever set once, so references to a resolved value
+    // are valid for as long as the pool is. If multiple threads race to resolve the same entry,
+    // only one of them will store its result, and they'll all get that result back.
+    resolved: Array<OnceLock<Resolved>>,
 }
 
 pub type Index = u16;
@@ -59,11 +62,40 @@ macro_rules! pool_get_type {
     };
 }
 
+macro_rules! pool_resolve_type {
+    ($fn_name: ident, $get_unresolved: ident, $info: ty, $enum_part: ident, |$unresolved: ident, $pool: ident| $resolve: expr) => {
+        pub fn $fn_name(&self, idx: Index) -> Option<&$info> {
+            let $unresolved = self.$get_unresolved(idx)?;
+            let $pool = self;
+
+            // Guaranteed to be in bounds, as get_unresolved found an entry at this index
+            let cell = self.resolved.get(self.cp_idx_to_arr_idx(idx))?;
+            let resolved = cell.get_or_init(|| Resolved::$enum_part($resolve));
+            if let Resolved::$enum_part(info) = resolved {
+                Some(info)
+            } else {
+                // Each cell only ever holds the resolved form of the entry with the same index,
+                // and the tag check in get_unresolved means this is the right form
+                unreachable!("resolved entry does not match tag")
+            }
+        }
+    };
+}
+
 impl Pool {
     fn new(size: usize) -> Result<Self, OutOfMemoryError> {
-        let tags = UnsafeCell::new(Array::new(size)?);
-        let constants = UnsafeCell::new(Array::new(size)?);
-        Ok(Self { tags, constants })
+        let mut tags = Array::new(size)?;
+        let mut constants = Array::new(size)?;
+        let mut resolved = Array::new(size)?;
+
+        // Everything starts off invalid, so that a pool that fails to parse part way through
+        // never has uninitialized entries
+        for i in 0..size {
+            tags.set(i, Tag::INVALID).expect("array set was somehow out of bounds");
+            constants.set(i, Entry::Invalid).expect("array set was somehow out of bounds");
+            resolved.set(i, OnceLock::new()).expect("array set was somehow out of bounds");
+        }
+        Ok(Self { tags, constants, resolved })
     }
 
     pool_get_type!(get_unresolved_utf8, UTF8, UnresolvedUtf8Info, Utf8);
@@ -90,133 +122,47 @@ impl Pool {
             return None;
         }
 
-        let index = (idx - 1) as usize;
-        let tag = *self.tags().get(index)?;
+        let index = self.cp_idx_to_arr_idx(idx);
+        let tag = *self.tags.get(index)?;
         if tag != required_tag {
             return None;
         }
 
-        self.constants().get(index)
-    }
-
-    // Internal 'resolved' counterpart functions
-    pool_get_type!(get_resolved_utf8, RESOLVED_UTF8, Utf8Info, ResolvedUtf8);
-    pool_get_type!(get_resolved_string, RESOLVED_STRING, StringInfo, ResolvedString);
-    pool_get_type!(get_resolved_class, RESOLVED_CLASS, ClassInfo, ResolvedClass);
-    pool_get_type!(get_resolved_name_and_type, RESOLVED_NAME_AND_TYPE, NameAndTypeInfo, ResolvedNameAndType);
-
-    pub fn resolve_utf8(&self, idx: Index) -> Option<&Utf8Info> {
-        if let Some(info) = self.get_resolved_utf8(idx) {
-            return Some(info);
-        }
-
-        let unresolved = self.get_unresolved_utf8(idx)?;
-        let resolved = unresolved.resolve();
-
-        let entry = self.put_internal(idx, Tag::RESOLVED_UTF8, Entry::ResolvedUtf8(resolved));
-        if let Entry::ResolvedUtf8(info) = entry {
-            Some(info)
-        } else {
-            None
-        }
-    }
-
-    pub fn resolve_string(&self, idx: Index) -> Option<&StringInfo> {
-        if let Some(info) = self.get_resolved_string(idx) {
-            return Some(info);
-        }
-
-        let unresolved = self.get_unresolved_string(idx)?;
-        let resolved = unresolved.resolve(self);
-
-        let entry = self.put_internal(idx, Tag::RESOLVED_STRING, Entry::ResolvedString(resolved));
-        if let Entry::ResolvedString(info) = entry {
-            Some(info)
-        } else {
-            None
-        }
+        self.constants.get(index)
     }
 
-    pub fn resolve_class(&self, idx: Index) -> Option<&ClassInfo> {
-        if let Some(info) = self.get_resolved_class(idx) {
-            return Some(info);
-        }
-
-        let unresolved = self.get_unresolved_class(idx)?;
-        let resolved = unresolved.resolve(self);
-
-        let entry = self.put_internal(idx, Tag::RESOLVED_CLASS, Entry::ResolvedClass(resolved));
-        if let Entry::ResolvedClass(info) = entry {
-            Some(info)
-        } else {
-            None
+    pool_resolve_type!(resolve_utf8, get_unresolved_utf8, Utf8Info, Utf8,
+        |unresolved, _pool| unresolved.resolve());
+    pool_resolve_type!(resolve_string, get_unresolved_string, StringInfo, String,
+        |unresolved, pool| unresolved.resolve(pool));
+    pool_resolve_type!(resolve_class, get_unresolved_class, ClassInfo, Class,
+        |unresolved, pool| unresolved.resolve(pool));
+    pool_resolve_type!(resolve_name_and_type, get_unresolved_name_and_type, NameAndTypeInfo, NameAndType,
+        |unresolved, pool| unresolved.resolve(pool));
+
+    /// The tag of the entry at the given index, or `None` if the index is out of range or is
+    /// the unusable entry following a long or double.
+    pub fn tag(&self, idx: Index) -> Option<Tag> {
+        if idx == INDEX_INVALID {
+            return None;
         }
+        Tag::from_u8(*self.tags.get(self.cp_idx_to_arr_idx(idx))?)
     }
 
-    pub fn resolve_name_and_type(&self, idx: Index) -> Option<&NameAndTypeInfo> {
-        if let Some(info) = self.get_resolved_name_and_type(idx) {
-            return Some(info);
-        }
-
-        let unresolved = self.get_unresolved_name_and_type(idx)?;
-        let resolved = unresolved.resolve(self);
-
-        let entry = self.put_internal(idx, Tag::RESOLVED_NAME_AND_TYPE, Entry::ResolvedNameAndType(resolved));
-        if let Entry::ResolvedNameAndType(info) = entry {
-            Some(info)
-        } else {
-            None
-        }
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        // SAFETY: Every entry is initialized during parsing, before the pool is handed out
+        unsafe { self.constants.as_slice() }.iter()
     }
 
     pub fn size(&self) -> u16 {
         // CP is indexed from 1 so size is 1 more than array size
-        (self.tags().len() + 1) as u16
+        (self.tags.len() + 1) as u16
     }
 
     pub fn is_valid_index(&self, index: Index) -> bool {
         index >= 1 && index < self.size()
     }
 
-    fn put_internal(&self, idx: Index, tag: u8, entry: Entry) -> &Entry {
-        let arr_idx = self.cp_idx_to_arr_idx(idx);
-        self.put_raw(arr_idx, tag, entry)
-    }
-
-    fn put_raw(&self, idx: usize, tag: u8, entry: Entry) -> &Entry {
-        self.tags_mut().set(idx, tag).expect("array set was somehow out of bounds");
-        self.constants_mut().set_and_return(idx, entry)
-            .expect("array set was somehow out of bounds")
-    }
-
-    fn put_invalid_raw(&self, idx: usize) {
-        self.tags_mut().set(idx, Tag::INVALID).expect("array set was somehow out of bounds");
-    }
-
-    #[inline]
-    fn tags(&self) -> &Array<u8> {
-        // SAFETY: This is convertible, as long as we uphold the reference rules
-        unsafe { &*self.tags.get() }
-    }
-
-    #[inline]
-    fn tags_mut(&self) -> &mut Array<u8> {
-        // SAFETY: This is convertible, as long as we uphold the reference rules
-        unsafe { &mut *self.tags.get() }
-    }
-
-    #[inline]
-    fn constants(&self) -> &Array<Entry> {
-        // SAFETY: This is convertible, as long as we uphold the reference rules
-        unsafe { &*self.constants.get() }
-    }
-
-    #[inline]
-    fn constants_mut(&self) -> &mut Array<Entry> {
-        // SAFETY: This is convertible, as long as we uphold the reference rules
-        unsafe { &mut *self.constants.get() }
-    }
-
     #[inline]
     const fn cp_idx_to_arr_idx(&self, cp_idx: Index) -> usize {
         // Internal array starts from 0 but CP starts from 1
@@ -231,36 +177,47 @@ impl Pool {
 }
 
 mod _parse {
-    use crate::class::parse::{BinaryReader, ParseError};
+    use crate::class::parse::{BinaryReader, ErrorKind, ParseError};
+    use crate::types::Version;
     use super::*;
 
-    pub fn parse_pool(buf: &mut BinaryReader) -> Result<Pool, ParseError> {
+    pub fn parse_pool(buf: &mut BinaryReader, version: Version) -> Result<Pool, ParseError> {
         buf.check_bytes(2, "constant pool")?;
 
         // SAFETY: Guaranteed by check_bytes
         let len = unsafe { buf.unsafe_read_u16() };
-        // TODO: We shouldn't wrap this. When we have proper error handling,
-        //  propagate it.
-        let mut pool = Pool::new(len as usize)
-            .map_err(|_| ParseError::new("out of memory"))?;
+        if len == 0 {
+            let kind = ErrorKind::Malformed("constant pool count must be at least 1".into());
+            return ParseError::new(kind, buf.position() - 2).into();
+        }
+
+        // Constant pool count is one more than the number of entries, as index 0 is never valid
+        let mut pool = Pool::new((len - 1) as usize)
+            .map_err(|_| buf.error(ErrorKind::OutOfMemory))?;
+
+        // Only needed until we've validated the pool, to be able to report where errors are
+        let mut offsets = Vec::with_capacity((len - 1) as usize);
 
         // Constant pool index starts from 1
         let mut idx = 1;
         while idx < len {
             buf.check_bytes(1, format!("constant pool tag at {idx}"))?;
+            offsets.push(buf.position());
 
             let tag = unsafe { buf.unsafe_read_u8() };
             let r = entry::parse_entry(buf, tag);
 
-            let entry = r.map_err(|err| {
-                let msg = format!("bad constant pool entry {idx}: {err}");
-                return ParseError::new(msg);
-            })?;
+            let entry = r.map_err(ParseError::wrap(format!("entry {idx}")))?;
 
             if tag == Tag::LONG || tag == Tag::DOUBLE {
                 // Long and double take up 2 entries in constant pool
                 // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.5
+                if idx + 1 >= len {
+                    let msg = format!("constant pool entry {idx} is 8 bytes wide but is the last entry");
+                    return buf.error(ErrorKind::Malformed(msg)).into();
+                }
                 pool.put_two_wide(idx, tag, entry);
+                offsets.push(buf.position());
                 idx += 2;
             } else {
                 pool.put(idx, tag, entry);
@@ -268,18 +225,48 @@ mod _parse {
             }
         }
 
+        validate::validate_pool(&pool, version, &offsets)?;
         Ok(pool)
     }
 
     impl Pool {
         fn put(&mut self, idx: Index, tag: u8, entry: Entry) {
-            self.put_internal(idx, tag, entry);
+            let arr_idx = self.cp_idx_to_arr_idx(idx);
+            self.put_raw(arr_idx, tag, entry);
         }
 
         fn put_two_wide(&mut self, idx: Index, tag: u8, entry: Entry) {
             let arr_idx = self.cp_idx_to_arr_idx(idx);
             self.put_raw(arr_idx, tag, entry);
-            self.put_invalid_raw(arr_idx + 1);
+            self.put_raw(arr_idx + 1, Tag::INVALID, Entry::Invalid);
+        }
+
+        pub(super) fn put_raw(&mut self, idx: usize, tag: u8, entry: Entry) {
+            self.tags.set(idx, tag).expect("array set was somehow out of bounds");
+            self.constants.set(idx, entry).expect("array set was somehow out of bounds");
+        }
+    }
+}
+
+mod _write {
+    use crate::class::write::BinaryWriter;
+    use crate::loader::Serialize;
+    use super::*;
+
+    impl Serialize for Pool {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.size());
+
+            // SAFETY: Every tag is initialized during parsing, before the pool is handed out
+            let tags = unsafe { self.tags.as_slice() };
+            for (&tag, entry) in tags.iter().zip(self.entries()) {
+                // The slot after a long or double isn't in the class file
+                if tag == Tag::INVALID {
+                    continue;
+                }
+                buf.write_u8(tag);
+                entry::serialize_entry(entry, buf);
+            }
         }
     }
 }
@@ -325,15 +312,11 @@ impl Tag {
     const INVOKE_DYNAMIC: u8 = 18;
     const MODULE: u8 = 19;
     const PACKAGE: u8 = 20;
-
-    // Not part of spec - internal
-    const RESOLVED_UTF8: u8 = 200;
-    const RESOLVED_CLASS: u8 = 201;
-    const RESOLVED_STRING: u8 = 202;
-    const RESOLVED_NAME_AND_TYPE: u8 = 203;
 }
 
 enum Entry {
+    // The unusable slot after a long or double
+    Invalid,
     Utf8(UnresolvedUtf8Info),
     Integer(IntegerInfo),
     Float(FloatInfo),
@@ -351,10 +334,137 @@ enum Entry {
     InvokeDynamic(InvokeDynamicInfo),
     Module(ModuleInfo),
     Package(PackageInfo),
+}
 
-    // Internal ones
-    ResolvedUtf8(Utf8Info),
-    ResolvedClass(ClassInfo),
-    ResolvedString(StringInfo),
-    ResolvedNameAndType(NameAndTypeInfo),
+// The resolved forms of the entries that have them
+enum Resolved {
+    Utf8(Utf8Info),
+    Class(ClassInfo),
+    String(StringInfo),
+    NameAndType(NameAndTypeInfo),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::class::parse::BinaryReader;
+    use crate::types::Version;
+    use super::*;
+
+    // Kept small under Miri, which is orders of magnitude slower
+    const THREADS: usize = if cfg!(miri) { 4 } else { 16 };
+    const ROUNDS: usize = if cfg!(miri) { 2 } else { 200 };
+    const NAMES: u16 = if cfg!(miri) { 8 } else { 200 };
+
+    // Builds a pool where, for every name, there's a utf8, then a class, string and name and
+    // type that all refer to that utf8.
+    fn build_pool() -> Pool {
+        let mut bytes = Vec::new();
+        let count = 1 + NAMES * 4;
+        bytes.extend_from_slice(&count.to_be_bytes());
+
+        for i in 0..NAMES {
+            let utf8_idx = 1 + i * 4;
+            let name = format!("name{i}");
+            bytes.push(Tag::UTF8);
+            bytes.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+
+            bytes.push(Tag::CLASS);
+            bytes.extend_from_slice(&utf8_idx.to_be_bytes());
+            bytes.push(Tag::STRING);
+            bytes.extend_from_slice(&utf8_idx.to_be_bytes());
+            bytes.push(Tag::NAME_AND_TYPE);
+            bytes.extend_from_slice(&utf8_idx.to_be_bytes());
+            bytes.extend_from_slice(&utf8_idx.to_be_bytes());
+        }
+
+        parse_pool(&mut BinaryReader::new(bytes), Version::new(69, 0)).expect("pool should parse")
+    }
+
+    // Resolves everything in the pool, returning the addresses of the resolved values
+    fn resolve_all(pool: &Pool, reverse: bool) -> Vec<usize> {
+        let mut addresses = vec![0; (pool.size() - 1) as usize];
+        let indexes: Vec<Index> = match reverse {
+            true => (1..pool.size()).rev().collect(),
+            false => (1..pool.size()).collect(),
+        };
+
+        for idx in indexes {
+            let address = match (idx - 1) % 4 {
+                0 => pool.resolve_utf8(idx).map(|v| v as *const _ as usize),
+                1 => pool.resolve_class(idx).map(|v| v as *const _ as usize),
+                2 => pool.resolve_string(idx).map(|v| v as *const _ as usize),
+                _ => pool.resolve_name_and_type(idx).map(|v| v as *const _ as usize),
+            };
+            addresses[(idx - 1) as usize] = address.expect("entry should resolve");
+        }
+        addresses
+    }
+
+    #[test]
+    fn concurrent_resolution_agrees() {
+        for _ in 0..ROUNDS {
+            let pool = build_pool();
+
+            let results: Vec<Vec<usize>> = thread::scope(|scope| {
+                let handles: Vec<_> = (0..THREADS)
+                    .map(|i| {
+                        let pool = &pool;
+                        scope.spawn(move || resolve_all(pool, i % 2 == 0))
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().expect("thread panicked")).collect()
+            });
+
+            // Every thread must have been handed the same resolved value for every entry
+            for result in &results[1..] {
+                assert_eq!(result, &results[0]);
+            }
+
+            for i in 0..NAMES {
+                let utf8_idx = 1 + i * 4;
+                let name = format!("name{i}");
+                assert_eq!(pool.resolve_utf8(utf8_idx).unwrap().as_str(), name);
+                assert_eq!(pool.resolve_class(utf8_idx + 1).unwrap().name(), name.as_str());
+                assert_eq!(pool.resolve_string(utf8_idx + 2).unwrap().as_str(), name);
+                assert_eq!(pool.resolve_name_and_type(utf8_idx + 3).unwrap().name(), name.as_str());
+            }
+        }
+    }
+
+    #[test]
+    fn lone_surrogates_survive_resolution() {
+        // A string and class for "\uD800", and a string for the replacement character
+        let mut bytes = vec![0, 6];
+        bytes.extend_from_slice(&[Tag::UTF8, 0, 3, 0xED, 0xA0, 0x80]);
+        bytes.extend_from_slice(&[Tag::STRING, 0, 1]);
+        bytes.extend_from_slice(&[Tag::CLASS, 0, 1]);
+        bytes.extend_from_slice(&[Tag::UTF8, 0, 3, 0xEF, 0xBF, 0xBD]);
+        bytes.extend_from_slice(&[Tag::STRING, 0, 4]);
+        let pool = parse_pool(&mut BinaryReader::new(bytes), Version::new(69, 0)).expect("pool should parse");
+
+        let string = pool.resolve_string(2).unwrap();
+        assert_eq!(string.to_utf16(), [0xD800]);
+        assert_eq!(pool.resolve_class(3).unwrap().name().to_utf16(), [0xD800]);
+        assert_eq!(pool.resolve_utf8(1).unwrap().symbol(), string.value());
+
+        // Both have the same lossy string, but they're still different constants
+        let replacement = pool.resolve_string(5).unwrap();
+        assert_eq!(replacement.as_str(), string.as_str());
+        assert_ne!(replacement.value(), string.value());
+        assert_eq!(replacement.to_utf16(), [0xFFFD]);
+    }
+
+    #[test]
+    fn resolution_does_not_invalidate_unresolved() {
+        let pool = build_pool();
+
+        // Resolving used to overwrite the entry in place, so references to the unresolved
+        // entry taken beforehand would've been left dangling
+        let unresolved = pool.get_unresolved_utf8(1).unwrap();
+        pool.resolve_utf8(1).unwrap();
+        assert_eq!(unresolved.resolve().as_str(), "name0");
+        assert!(pool.get_unresolved_utf8(1).is_some());
+    }
 }
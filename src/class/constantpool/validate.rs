@@ -0,0 +1,352 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use crate::class::parse::{ErrorKind, ParseError};
+use crate::types::descriptor::{FieldType, MethodDescriptor};
+use crate::types::{methodhandle, mutf8, Feature, Version};
+use super::{Entry, Index, Pool, Tag};
+
+/// Checks that every entry that refers to another entry in the pool refers to one of the
+/// kind required by the JVMS, so that resolution can rely on this later.
+///
+/// This has to happen once the whole pool has been read, as entries can refer to entries
+/// that come after them. `offsets` holds the offset in to the class file of each entry.
+///
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4
+pub(super) fn validate_pool(pool: &Pool, version: Version, offsets: &[usize]) -> Result<(), ParseError> {
+    for (arr_idx, entry) in pool.entries().enumerate() {
+        let idx = pool.arr_idx_to_cp_idx(arr_idx);
+        validate_entry(pool, entry, version)
+            .map_err(|kind| ParseError::new(kind, offsets[arr_idx]))
+            .map_err(ParseError::wrap(format!("entry {idx}")))?;
+    }
+    Ok(())
+}
+
+/// Checks that every dynamically-computed constant and call site refers to a bootstrap
+/// method that exists. This can only happen after the `BootstrapMethods` attribute has been
+/// read, which is at the very end of the class file. `pool_start` is the offset in to the
+/// class file of the `constant_pool_count`.
+pub fn validate_bootstrap_indexes(pool: &Pool, bootstrap_methods: usize, pool_start: usize) -> Result<(), ParseError> {
+    for (arr_idx, entry) in pool.entries().enumerate() {
+        let bootstrap_idx = match entry {
+            Entry::Dynamic(info) => info.bootstrap_method_attr_index(),
+            Entry::InvokeDynamic(info) => info.bootstrap_method_attr_index(),
+            _ => continue,
+        };
+
+        if bootstrap_idx as usize >= bootstrap_methods {
+            let idx = pool.arr_idx_to_cp_idx(arr_idx);
+            let msg = format!("bootstrap method index {bootstrap_idx} out of range \
+                ({bootstrap_methods} bootstrap methods)");
+            let err = ParseError::new(ErrorKind::Malformed(msg), entry_offset(pool, pool_start, arr_idx));
+            return Err(ParseError::wrap(format!("entry {idx}"))(err));
+        }
+    }
+    Ok(())
+}
+
+// The offsets of the entries aren't kept after parsing, as they're only needed for errors, so
+// this works them out again from the sizes of the entries before it
+fn entry_offset(pool: &Pool, pool_start: usize, arr_idx: usize) -> usize {
+    let before = pool.entries().take(arr_idx)
+        // The slot after a long or double isn't in the class file
+        .filter(|entry| !matches!(entry, Entry::Invalid))
+        .map(|entry| 1 + entry_len(entry))
+        .sum::<usize>();
+    // Skip the count
+    pool_start + 2 + before
+}
+
+// The size of the entry in the class file, not counting its tag
+fn entry_len(entry: &Entry) -> usize {
+    match entry {
+        Entry::Invalid => 0,
+        Entry::Utf8(info) => 2 + info.bytes().len(),
+        Entry::Class(_) | Entry::String(_) | Entry::MethodType(_) | Entry::Module(_) |
+            Entry::Package(_) => 2,
+        Entry::MethodHandle(_) => 3,
+        Entry::Integer(_) | Entry::Float(_) | Entry::Fieldref(_) | Entry::Methodref(_) |
+            Entry::InterfaceMethodref(_) | Entry::NameAndType(_) | Entry::Dynamic(_) |
+            Entry::InvokeDynamic(_) => 4,
+        Entry::Long(_) | Entry::Double(_) => 8,
+    }
+}
+
+fn validate_entry(pool: &Pool, entry: &Entry, version: Version) -> Result<(), ErrorKind> {
+    if let Some(feature) = required_feature(entry) && !version.supports(feature) {
+        let msg = format!("constant not supported by class file version {version}");
+        return Err(ErrorKind::Malformed(msg));
+    }
+
+    match entry {
+        Entry::Class(info) => expect_tag(pool, info.name_index(), Tag::Utf8),
+        Entry::String(info) => expect_tag(pool, info.string_index(), Tag::Utf8),
+        Entry::Fieldref(info) => {
+            expect_tag(pool, info.class_index(), Tag::Class)?;
+            expect_tag(pool, info.name_and_type_index(), Tag::NameAndType)
+        },
+        Entry::Methodref(info) => {
+            expect_tag(pool, info.class_index(), Tag::Class)?;
+            expect_tag(pool, info.name_and_type_index(), Tag::NameAndType)
+        },
+        Entry::InterfaceMethodref(info) => {
+            expect_tag(pool, info.class_index(), Tag::Class)?;
+            expect_tag(pool, info.name_and_type_index(), Tag::NameAndType)
+        },
+        Entry::NameAndType(info) => {
+            expect_tag(pool, info.name_index(), Tag::Utf8)?;
+            expect_tag(pool, info.descriptor_index(), Tag::Utf8)
+        },
+        Entry::MethodHandle(info) => {
+            validate_method_handle(pool, info.reference_kind(), info.reference_index(), version)
+        },
+        Entry::MethodType(info) => expect_tag(pool, info.descriptor_index(), Tag::Utf8),
+        Entry::Dynamic(info) => {
+            expect_tag(pool, info.name_and_type_index(), Tag::NameAndType)?;
+            expect_descriptor(pool, info.name_and_type_index(), DescriptorKind::Field)
+        },
+        Entry::InvokeDynamic(info) => {
+            expect_tag(pool, info.name_and_type_index(), Tag::NameAndType)?;
+            expect_descriptor(pool, info.name_and_type_index(), DescriptorKind::Method)
+        },
+        Entry::Module(info) => expect_tag(pool, info.name_index(), Tag::Utf8),
+        Entry::Package(info) => expect_tag(pool, info.name_index(), Tag::Utf8),
+        Entry::Invalid | Entry::Utf8(_) | Entry::Integer(_) | Entry::Float(_) |
+            Entry::Long(_) | Entry::Double(_) => Ok(()),
+    }
+}
+
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4-310
+fn required_feature(entry: &Entry) -> Option<Feature> {
+    match entry {
+        Entry::MethodHandle(_) | Entry::MethodType(_) | Entry::InvokeDynamic(_) => Some(Feature::InvokeDynamic),
+        Entry::Dynamic(_) => Some(Feature::DynamicConstants),
+        Entry::Module(_) | Entry::Package(_) => Some(Feature::Modules),
+        _ => None,
+    }
+}
+
+fn expect_tag(pool: &Pool, index: Index, expected: Tag) -> Result<(), ErrorKind> {
+    match pool.tag(index) {
+        Some(tag) if tag == expected => Ok(()),
+        _ => Err(ErrorKind::BadConstantPoolReference { index, expected }),
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum DescriptorKind {
+    Field,
+    Method,
+}
+
+// Checks the descriptor of the name and type at the given index is of the given kind
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.10
+fn expect_descriptor(pool: &Pool, name_and_type_index: Index, kind: DescriptorKind) -> Result<(), ErrorKind> {
+    // If the descriptor isn't a Utf8 entry, the name and type will fail validation on its own
+    let Some(descriptor) = pool.get_unresolved_name_and_type(name_and_type_index)
+        .and_then(|name_and_type| pool.get_unresolved_utf8(name_and_type.descriptor_index())) else {
+        return Ok(());
+    };
+    let descriptor = mutf8::decode(descriptor.bytes()).expect("utf8 entry was validated when parsed");
+    let result = match kind {
+        DescriptorKind::Field => FieldType::parse(&descriptor).map(|_| ()),
+        DescriptorKind::Method => MethodDescriptor::parse(&descriptor).map(|_| ()),
+    };
+    result.map_err(|err| {
+        let kind = format!("{kind:?}").to_lowercase();
+        ErrorKind::Malformed(format!("descriptor {descriptor} is not a valid {kind} descriptor: {err}"))
+    })
+}
+
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.8
+fn validate_method_handle(pool: &Pool, kind: methodhandle::Ref, index: Index,
+                          version: Version) -> Result<(), ErrorKind> {
+    use methodhandle::Ref;
+
+    let tag = pool.tag(index);
+    match kind {
+        Ref::GetField | Ref::GetStatic | Ref::PutField | Ref::PutStatic => {
+            expect_tag(pool, index, Tag::Fieldref)?
+        },
+        Ref::InvokeVirtual | Ref::NewInvokeSpecial => expect_tag(pool, index, Tag::Methodref)?,
+        Ref::InvokeStatic | Ref::InvokeSpecial => {
+            let interface_allowed = version.supports(Feature::InterfaceMethods);
+            if !(tag == Some(Tag::Methodref) || interface_allowed && tag == Some(Tag::InterfaceMethodref)) {
+                return Err(ErrorKind::BadConstantPoolReference { index, expected: Tag::Methodref });
+            }
+        },
+        Ref::InvokeInterface => expect_tag(pool, index, Tag::InterfaceMethodref)?,
+    }
+
+    // If the member's name and type is invalid, that entry will fail validation on its own
+    let Some(name) = member_name(pool, index) else {
+        return Ok(());
+    };
+    let is_init = name == b"<init>";
+    let is_clinit = name == b"<clinit>";
+
+    match kind {
+        Ref::NewInvokeSpecial if !is_init => {
+            let msg = format!("method handle of kind {kind:?} must refer to a constructor");
+            Err(ErrorKind::Malformed(msg))
+        },
+        Ref::InvokeVirtual | Ref::InvokeStatic | Ref::InvokeSpecial | Ref::InvokeInterface
+            if is_init || is_clinit => {
+            let msg = format!("method handle of kind {kind:?} must not refer to an initialization method");
+            Err(ErrorKind::Malformed(msg))
+        },
+        _ => Ok(())
+    }
+}
+
+// Gets the name of the field or method that the ref at the given index refers to
+fn member_name(pool: &Pool, index: Index) -> Option<&[u8]> {
+    let name_and_type_index = match pool.tag(index)? {
+        Tag::Fieldref => pool.get_field_ref(index)?.name_and_type_index(),
+        Tag::Methodref => pool.get_method_ref(index)?.name_and_type_index(),
+        Tag::InterfaceMethodref => pool.get_interface_method_ref(index)?.name_and_type_index(),
+        _ => return None,
+    };
+    let name_and_type = pool.get_unresolved_name_and_type(name_and_type_index)?;
+    Some(pool.get_unresolved_utf8(name_and_type.name_index())?.bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::parse::BinaryReader;
+    use crate::class::testing::*;
+    use crate::class::parse_class;
+    use super::*;
+    use super::super::parse_pool;
+
+    fn utf8(value: &str) -> Vec<u8> {
+        [vec![Tag::UTF8], u16_bytes(value.len() as u16), value.as_bytes().to_vec()].concat()
+    }
+
+    fn entry(tag: u8, indexes: &[u16]) -> Vec<u8> {
+        [vec![tag], indexes.iter().flat_map(|&index| u16_bytes(index)).collect()].concat()
+    }
+
+    // Parses a pool with the given entries, which are each one slot wide, returning the
+    // error and the offset of the entry it should be for
+    fn parse_err(version: u16, entries: &[Vec<u8>], bad: usize) -> (ParseError, usize) {
+        let bytes = [u16_bytes(entries.len() as u16 + 1), entries.concat()].concat();
+        let offset = 2 + entries[..bad - 1].iter().map(Vec::len).sum::<usize>();
+        let err = parse_pool(&mut BinaryReader::new(bytes), Version::new(version, 0)).err().expect("pool should be invalid");
+        assert_eq!(err.context(), [format!("entry {bad}")]);
+        (err, offset)
+    }
+
+    fn assert_bad_reference(entries: &[Vec<u8>], bad: usize, index: Index, tag: Tag) {
+        let (err, offset) = parse_err(69, entries, bad);
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { index: i, expected } if *i == index && *expected == tag),
+            "{err}");
+        assert_eq!(err.offset(), offset);
+    }
+
+    fn assert_malformed(version: u16, entries: &[Vec<u8>], bad: usize, message: &str) {
+        let (err, offset) = parse_err(version, entries, bad);
+        assert!(matches!(err.kind(), ErrorKind::Malformed(msg) if msg.contains(message)), "{err}");
+        assert_eq!(err.offset(), offset);
+    }
+
+    #[test]
+    fn rejects_references_to_the_wrong_tag() {
+        let int = entry(Tag::INTEGER, &[0, 0]);
+        assert_bad_reference(&[int.clone(), entry(Tag::CLASS, &[1])], 2, 1, Tag::Utf8);
+        assert_bad_reference(&[entry(Tag::STRING, &[2]), int.clone()], 1, 2, Tag::Utf8);
+        assert_bad_reference(&[entry(Tag::METHOD_TYPE, &[1])], 1, 1, Tag::Utf8);
+        assert_bad_reference(&[entry(Tag::MODULE, &[0])], 1, 0, Tag::Utf8);
+        assert_bad_reference(&[utf8("x"), entry(Tag::NAME_AND_TYPE, &[1, 3]), int.clone()], 2, 3, Tag::Utf8);
+
+        // Refs need a class, then a name and type
+        let name_and_type = entry(Tag::NAME_AND_TYPE, &[1, 1]);
+        let class = entry(Tag::CLASS, &[1]);
+        for tag in [Tag::FIELDREF, Tag::METHODREF, Tag::INTERFACE_METHODREF] {
+            let entries = [utf8("x"), name_and_type.clone(), class.clone(), entry(tag, &[2, 3])];
+            assert_bad_reference(&entries, 4, 2, Tag::Class);
+            let entries = [utf8("x"), name_and_type.clone(), class.clone(), entry(tag, &[3, 1])];
+            assert_bad_reference(&entries, 4, 1, Tag::NameAndType);
+        }
+        // Out of range is the same as the wrong tag
+        assert_bad_reference(&[entry(Tag::DYNAMIC, &[0, 9])], 1, 9, Tag::NameAndType);
+        assert_bad_reference(&[entry(Tag::INVOKE_DYNAMIC, &[0, 1])], 1, 1, Tag::NameAndType);
+    }
+
+    #[test]
+    fn rejects_method_handles_to_the_wrong_member() {
+        // 1-4 make a field ref, 5-7 a method ref to <init>, and 8-10 one to a method
+        let mut entries = vec![
+            utf8("x"), utf8("I"), entry(Tag::NAME_AND_TYPE, &[1, 2]), entry(Tag::CLASS, &[1]),
+            utf8("<init>"), utf8("()V"), entry(Tag::NAME_AND_TYPE, &[5, 6]),
+            entry(Tag::FIELDREF, &[4, 3]), entry(Tag::METHODREF, &[4, 7]), entry(Tag::METHODREF, &[4, 3]),
+        ];
+        let handle = |kind: u8, index: u16| [vec![Tag::METHOD_HANDLE, kind], u16_bytes(index)].concat();
+
+        entries.push(handle(methodhandle::Ref::GetField as u8, 9));
+        assert_bad_reference(&entries, 11, 9, Tag::Fieldref);
+        entries[10] = handle(methodhandle::Ref::InvokeInterface as u8, 10);
+        assert_bad_reference(&entries, 11, 10, Tag::InterfaceMethodref);
+        entries[10] = handle(methodhandle::Ref::NewInvokeSpecial as u8, 10);
+        assert_malformed(69, &entries, 11, "must refer to a constructor");
+        entries[10] = handle(methodhandle::Ref::InvokeVirtual as u8, 9);
+        assert_malformed(69, &entries, 11, "must not refer to an initialization method");
+
+        // Interface methods can only be invoked statically or specially from Java 8 on
+        entries[9] = entry(Tag::INTERFACE_METHODREF, &[4, 3]);
+        entries[10] = handle(methodhandle::Ref::InvokeStatic as u8, 10);
+        let (err, _) = parse_err(51, &entries, 11);
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { index: 10, expected: Tag::Methodref }));
+        let bytes = [u16_bytes(entries.len() as u16 + 1), entries.concat()].concat();
+        assert!(parse_pool(&mut BinaryReader::new(bytes), Version::new(52, 0)).is_ok());
+    }
+
+    #[test]
+    fn rejects_dynamic_constants_with_the_wrong_descriptor() {
+        let entries = |descriptor: &str, tag: u8| {
+            vec![utf8("x"), utf8(descriptor), entry(Tag::NAME_AND_TYPE, &[1, 2]), entry(tag, &[0, 3])]
+        };
+        assert_malformed(69, &entries("()I", Tag::DYNAMIC), 4, "is not a valid field descriptor");
+        assert_malformed(69, &entries("I", Tag::INVOKE_DYNAMIC), 4, "is not a valid method descriptor");
+        assert_malformed(69, &entries("Lx", Tag::DYNAMIC), 4, "is not a valid field descriptor");
+
+        let bytes = [u16_bytes(5), entries("I", Tag::DYNAMIC).concat()].concat();
+        assert!(parse_pool(&mut BinaryReader::new(bytes), Version::new(69, 0)).is_ok());
+    }
+
+    #[test]
+    fn rejects_entries_newer_than_the_class_file() {
+        let method_type = [utf8("()V"), entry(Tag::METHOD_TYPE, &[1])];
+        assert_malformed(50, &method_type, 2, "not supported by class file version");
+        let package = [utf8("p"), entry(Tag::PACKAGE, &[1])];
+        assert_malformed(52, &package, 2, "not supported by class file version");
+    }
+
+    #[test]
+    fn rejects_missing_bootstrap_methods() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let name_and_type = builder.name_and_type("run", "()Ljava/lang/Runnable;");
+        let call_site = builder.entry(INVOKE_DYNAMIC, &[u16_bytes(0), u16_bytes(name_and_type)].concat());
+        let bytes = builder.build(69, 0);
+
+        // The entry is the last one in the pool, which ends just before the class's flags
+        let pool_end = bytes.len() - 2 * 7;
+        let err = parse_class(&mut BinaryReader::new(bytes)).err().expect("class should be invalid");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(msg) if msg.contains("out of range (0 bootstrap methods)")));
+        assert_eq!(err.context(), ["constant pool", &format!("entry {call_site}")]);
+        assert_eq!(err.offset(), pool_end - 5);
+    }
+}
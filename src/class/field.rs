@@ -1,32 +1,164 @@
 use std::cell::{Cell, RefCell};
 use crate::class::Class;
-use crate::class::constantpool::Pool;
-use crate::class::parse::{BinaryReader, ParseError};
-use crate::types::{AccessFlags, Array, Jboolean, Jbyte, Jchar, Jdouble, Jfloat, Jint, Jlong, Jshort};
+use crate::class::constantpool::{Index, Pool};
+use crate::class::constantpool::Tag;
+use crate::class::parse::{BinaryReader, ErrorKind, ParseError};
+use crate::loader::classfile::attribute::{parse_attributes, serialize_attributes, FieldAttribute, WriteContext};
+use crate::loader::BinaryWriter;
+use crate::types::descriptor::FieldType;
+use crate::types::{Array, ClassAccessFlags, ClassFileVersion, FieldAccessFlags, Version, Jboolean, Jbyte, Jchar, Jdouble, Jfloat, Jint, Jlong, Jshort, Symbol};
 
 pub struct Field {
-    name: String,
-    descriptor: String,
-    access_flags: AccessFlags
+    name: Symbol,
+    name_index: Index,
+    descriptor: Symbol,
+    descriptor_index: Index,
+    field_type: FieldType,
+    access_flags: FieldAccessFlags,
+    attributes: Array<FieldAttribute>,
 }
 
-pub(super) fn parse_field(pool: &Pool, buf: &mut BinaryReader) -> Result<Field, ParseError> {
+impl Field {
+    pub fn name(&self) -> Symbol {
+        self.name
+    }
+
+    pub fn name_index(&self) -> Index {
+        self.name_index
+    }
+
+    pub fn descriptor(&self) -> Symbol {
+        self.descriptor
+    }
+
+    pub fn descriptor_index(&self) -> Index {
+        self.descriptor_index
+    }
+
+    pub fn field_type(&self) -> &FieldType {
+        &self.field_type
+    }
+
+    pub fn access_flags(&self) -> FieldAccessFlags {
+        self.access_flags
+    }
+
+    pub fn attributes(&self) -> &[FieldAttribute] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.attributes.as_slice() }
+    }
+}
+
+pub(super) fn parse_field(pool: &Pool, version: Version, class_flags: ClassAccessFlags,
+                          buf: &mut BinaryReader) -> Result<Field, ParseError> {
     // 2 access flags, 2 name index, 2 descriptor index
     buf.check_bytes(2 + 2 + 2, "access flags, name index, descriptor index")?;
 
     // SAFETY: Guaranteed by check_bytes
-    let flags = unsafe { buf.unsafe_read_u16() };
+    let flags = FieldAccessFlags::new(unsafe { buf.unsafe_read_u16() });
     let name_index = unsafe { buf.unsafe_read_u16() };
     let descriptor_index = unsafe { buf.unsafe_read_u16() };
 
+    // Offset of the name index, as we've read past it by now
+    let offset = buf.position() - 4;
     let name = pool.resolve_utf8(name_index)
-        .expect("cannot resolve name").as_string();
+        .ok_or_else(|| ParseError::bad_reference(name_index, Tag::Utf8, offset))?.symbol();
     let descriptor = pool.resolve_utf8(descriptor_index)
-        .expect("cannot resolve descriptor").as_string();
+        .ok_or_else(|| ParseError::bad_reference(descriptor_index, Tag::Utf8, offset + 2))?.symbol();
+    check_flags(flags, class_flags, version)
+        .map_err(|msg| {
+            let msg = format!("field {name}: illegal access flags {flags:?}: {msg}");
+            ParseError::new(ErrorKind::Malformed(msg), offset - 2)
+        })?;
+    let field_type = FieldType::parse(descriptor.as_str())
+        .map_err(|err| {
+            ParseError::new(ErrorKind::Malformed(format!("field descriptor {descriptor}: {err}")), offset + 2)
+        })?;
+
+    let attributes = parse_attributes(pool, version, buf)
+        .map_err(ParseError::wrap("field - attributes"))?;
 
     Ok(Field {
         name,
+        name_index,
         descriptor,
-        access_flags: AccessFlags::new(flags),
+        descriptor_index,
+        field_type,
+        access_flags: flags,
+        attributes,
     })
 }
+
+pub(super) fn write_field(field: &Field, ctx: &WriteContext, buf: &mut BinaryWriter) {
+    buf.write_u16(field.access_flags.flags());
+    buf.write_u16(field.name_index);
+    buf.write_u16(field.descriptor_index);
+    serialize_attributes(field.attributes(), ctx, buf);
+}
+
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.5-200-A.2
+fn check_flags(flags: FieldAccessFlags, class_flags: ClassAccessFlags, version: Version) -> Result<(), &'static str> {
+    const VISIBILITY: u16 = FieldAccessFlags::PUBLIC | FieldAccessFlags::PRIVATE | FieldAccessFlags::PROTECTED;
+
+    if (flags.flags() & VISIBILITY).count_ones() > 1 {
+        return Err("more than one of public, private and protected");
+    }
+    if flags.is_final() && flags.is_volatile() {
+        return Err("both final and volatile");
+    }
+
+    if class_flags.is_interface() {
+        const REQUIRED: u16 = FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL;
+        // Enum only has a meaning from Java 5 on, so older class files might have it set
+        let mut forbidden = FieldAccessFlags::VOLATILE | FieldAccessFlags::TRANSIENT;
+        if version.major() >= ClassFileVersion::Java5.major() {
+            forbidden |= FieldAccessFlags::ENUM;
+        }
+
+        if !flags.contains(REQUIRED) || flags.intersects(forbidden) {
+            return Err("interface fields must be public, static and final, and nothing else");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::parse::{ErrorKind, ParseError};
+    use crate::class::testing::*;
+
+    const ACC_PROTECTED: u16 = 0x0004;
+    const ACC_VOLATILE: u16 = 0x0040;
+    const ACC_ENUM: u16 = 0x4000;
+
+    fn parse_field(class_flags: u16, flags: u16, major: u16) -> Result<(), ParseError> {
+        let mut builder = ClassBuilder::new("Test", class_flags);
+        let field = builder.member(flags, "x", "I", &[]);
+        builder.fields.push(field);
+        builder.parse(major).map(|_| ())
+    }
+
+    fn is_malformed(result: Result<(), ParseError>) -> bool {
+        result.is_err_and(|err| matches!(err.kind(), ErrorKind::Malformed(_)))
+    }
+
+    #[test]
+    fn class_field_flags() {
+        let class = ACC_PUBLIC | ACC_SUPER;
+        assert!(parse_field(class, ACC_PRIVATE | ACC_STATIC | ACC_VOLATILE, 69).is_ok());
+        assert!(is_malformed(parse_field(class, ACC_PUBLIC | ACC_PROTECTED, 69)));
+        assert!(is_malformed(parse_field(class, ACC_FINAL | ACC_VOLATILE, 69)));
+    }
+
+    #[test]
+    fn interface_field_flags() {
+        let interface = ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT;
+        let constant = ACC_PUBLIC | ACC_STATIC | ACC_FINAL;
+        assert!(parse_field(interface, constant, 69).is_ok());
+        assert!(is_malformed(parse_field(interface, ACC_PUBLIC | ACC_STATIC, 69)));
+        assert!(is_malformed(parse_field(interface, ACC_PRIVATE | ACC_STATIC | ACC_FINAL, 69)));
+        // Enum wasn't a flag before Java 5, so older class files could have the bit set
+        assert!(is_malformed(parse_field(interface, constant | ACC_ENUM, 49)));
+        assert!(parse_field(interface, constant | ACC_ENUM, 48).is_ok());
+    }
+}
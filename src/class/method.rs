@@ -1,90 +1,278 @@
-use crate::{buf_read_named_type_arr, buf_read_u8_arr_lensize};
 use crate::class::Class;
-use crate::class::constantpool::Pool;
-use crate::class::parse::{BinaryReader, ParseError};
-use crate::types::{AccessFlags, Array};
+use crate::class::constantpool::{Index, Pool};
+use crate::class::constantpool::Tag;
+use crate::class::parse::{BinaryReader, ErrorKind, ParseError};
+use crate::loader::classfile::attribute::{parse_attributes, serialize_attributes, MethodAttribute, WriteContext};
+use crate::loader::BinaryWriter;
+use crate::types::descriptor::{MethodDescriptor, MAX_PARAMETER_SLOTS};
+use crate::types::{Array, ClassAccessFlags, ClassFileVersion, MethodAccessFlags, Version, Symbol};
+
+pub use crate::loader::classfile::attribute::code::{Code, Exception as ExceptionHandler};
 
 pub struct Method {
-    name: String,
-    descriptor: String,
-    access_flags: AccessFlags,
-    code: Code,
+    name: Symbol,
+    name_index: Index,
+    descriptor: Symbol,
+    descriptor_index: Index,
+    method_descriptor: MethodDescriptor,
+    access_flags: MethodAccessFlags,
+    attributes: Array<MethodAttribute>,
 }
 
-pub struct Code {
-    max_stack: u16,
-    max_locals: u16,
-    code: Array<u8>,
-    exception_handlers: Array<ExceptionHandler>,
-}
+impl Method {
+    pub fn name(&self) -> Symbol {
+        self.name
+    }
+
+    pub fn name_index(&self) -> Index {
+        self.name_index
+    }
+
+    pub fn descriptor(&self) -> Symbol {
+        self.descriptor
+    }
+
+    pub fn descriptor_index(&self) -> Index {
+        self.descriptor_index
+    }
+
+    pub fn method_descriptor(&self) -> &MethodDescriptor {
+        &self.method_descriptor
+    }
 
-pub struct ExceptionHandler {
-    start_pc: u16,
-    end_pc: u16,
-    handler_pc: u16,
-    catch_type: u16,
+    pub fn access_flags(&self) -> MethodAccessFlags {
+        self.access_flags
+    }
+
+    pub fn attributes(&self) -> &[MethodAttribute] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.attributes.as_slice() }
+    }
+
+    /// The method's code, or `None` if the method is abstract or native and so has no code.
+    pub fn code(&self) -> Option<&Code> {
+        self.attributes().iter().find_map(|attr| match attr {
+            MethodAttribute::Code(code) => Some(code.attribute()),
+            _ => None,
+        })
+    }
 }
 
-pub(super) fn parse_method(pool: &Pool, buf: &mut BinaryReader) -> Result<Method, ParseError> {
+pub(super) fn parse_method(pool: &Pool, version: Version, class_flags: ClassAccessFlags,
+                           buf: &mut BinaryReader) -> Result<Method, ParseError> {
     // 2 access flags, 2 name index, 2 descriptor index
     buf.check_bytes(2 + 2 + 2, "access flags, name index, descriptor index")?;
 
     // SAFETY: Guaranteed by check_bytes
-    let flags = unsafe { buf.unsafe_read_u16() };
+    let flags = MethodAccessFlags::new(unsafe { buf.unsafe_read_u16() });
     let name_index = unsafe { buf.unsafe_read_u16() };
     let descriptor_index = unsafe { buf.unsafe_read_u16() };
 
-    // TODO: Handle these errors properly
+    // Offset of the name index, as we've read past it by now
+    let offset = buf.position() - 4;
     let name = pool.resolve_utf8(name_index)
-        .expect("cannot resolve name").as_string();
+        .ok_or_else(|| ParseError::bad_reference(name_index, Tag::Utf8, offset))?.symbol();
     let descriptor = pool.resolve_utf8(descriptor_index)
-        .expect("cannot resolve descriptor").as_string();
+        .ok_or_else(|| ParseError::bad_reference(descriptor_index, Tag::Utf8, offset + 2))?.symbol();
+    check_flags(flags, name, class_flags, version)
+        .map_err(|msg| {
+            let msg = format!("method {name}: illegal access flags {flags:?}: {msg}");
+            ParseError::new(ErrorKind::Malformed(msg), offset - 2)
+        })?;
+    let bad_descriptor = |msg: String| {
+        ParseError::new(ErrorKind::Malformed(format!("method descriptor {descriptor}: {msg}")), offset + 2)
+    };
+    let method_descriptor = MethodDescriptor::parse(descriptor.as_str())
+        .map_err(|err| bad_descriptor(err.to_string()))?;
+    check_descriptor(&method_descriptor, name, flags)
+        .map_err(|msg| bad_descriptor(msg.to_string()))?;
 
-    // TODO: Read this as an attribute - this won't work
-    let code = Code::parse(buf)?;
+    let attributes = parse_attributes(pool, version, buf)
+        .map_err(ParseError::wrap("method - attributes"))?;
 
     Ok(Method {
         name,
+        name_index,
         descriptor,
-        access_flags: AccessFlags::new(flags),
-        code
+        descriptor_index,
+        method_descriptor,
+        access_flags: flags,
+        attributes,
     })
 }
 
-impl Code {
-    fn parse(buf: &mut BinaryReader) -> Result<Code, ParseError> {
-        // 2 max stack, 2 max locals, 4 code length
-        buf.check_bytes(2 + 2, "code - max stack, max locals, code length")?;
+pub(super) fn write_method(method: &Method, ctx: &WriteContext, buf: &mut BinaryWriter) {
+    buf.write_u16(method.access_flags.flags());
+    buf.write_u16(method.name_index);
+    buf.write_u16(method.descriptor_index);
+    serialize_attributes(method.attributes(), ctx, buf);
+}
 
-        // Safety: Guaranteed by check_bytes
-        let max_stack = unsafe { buf.unsafe_read_u16() };
-        let max_locals = unsafe { buf.unsafe_read_u16() };
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.3.3
+fn check_descriptor(descriptor: &MethodDescriptor, name: Symbol, flags: MethodAccessFlags) -> Result<(), &'static str> {
+    // Instance methods also get `this` passed in
+    let this_slots = if flags.is_static() { 0 } else { 1 };
+    if descriptor.parameter_slots() + this_slots > MAX_PARAMETER_SLOTS {
+        return Err("parameters take up more than 255 slots");
+    }
 
-        buf_read_u8_arr_lensize!(code, buf, unsafe_read_u32, "code - code array");
-        buf_read_named_type_arr!(ExceptionHandler, exception_handlers, buf,
-                "code - exception handlers", "code - exception handlers - idx {}");
+    // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-2.html#jvms-2.9.1
+    if name == "<init>" && descriptor.return_type().is_some() {
+        return Err("instance initialization method must return void");
+    }
+    Ok(())
+}
 
-        // TODO: Parse stack map table and verify here
+// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.6-200-A.2
+fn check_flags(flags: MethodAccessFlags, name: Symbol, class_flags: ClassAccessFlags,
+               version: Version) -> Result<(), &'static str> {
+    let major = version.major();
 
-        Ok(Code {
-            max_stack,
-            max_locals,
-            code,
-            exception_handlers
-        })
+    // Class initialization methods have all their flags bar static ignored, but from Java 7 on
+    // must be static to be one at all
+    // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-2.html#jvms-2.9.2
+    if name == "<clinit>" {
+        if major >= ClassFileVersion::Java7.major() && !flags.is_static() {
+            return Err("class initialization methods must be static");
+        }
+        return Ok(());
     }
+
+    let since_java5 = major >= ClassFileVersion::Java5.major();
+    // Strict is implied from Java 17 on, so abstract methods are allowed to have it
+    let strict_matters = major < ClassFileVersion::Java17.major() && flags.is_strict();
+
+    if class_flags.is_interface() {
+        if major >= ClassFileVersion::Java8.major() {
+            if flags.is_public() == flags.is_private() {
+                return Err("interface methods must be exactly one of public and private");
+            }
+            if flags.is_protected() || flags.is_final() || flags.is_synchronized() || flags.is_native() {
+                return Err("interface methods can't be protected, final, synchronized or native");
+            }
+            if flags.is_abstract() && (flags.is_private() || flags.is_static() || strict_matters) {
+                return Err("abstract interface methods can't be private, static or strict");
+            }
+        } else if since_java5 {
+            const FORBIDDEN: u16 = MethodAccessFlags::PRIVATE | MethodAccessFlags::PROTECTED | MethodAccessFlags::STATIC
+                | MethodAccessFlags::FINAL | MethodAccessFlags::SYNCHRONIZED | MethodAccessFlags::NATIVE
+                | MethodAccessFlags::STRICT;
+            if !flags.contains(MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT) || flags.intersects(FORBIDDEN) {
+                return Err("interface methods must be public and abstract");
+            }
+        } else {
+            const FORBIDDEN: u16 = MethodAccessFlags::STATIC | MethodAccessFlags::FINAL | MethodAccessFlags::NATIVE;
+            if !flags.contains(MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT) || flags.intersects(FORBIDDEN) {
+                return Err("interface methods must be public and abstract");
+            }
+        }
+        return Ok(());
+    }
+
+    const VISIBILITY: u16 = MethodAccessFlags::PUBLIC | MethodAccessFlags::PRIVATE | MethodAccessFlags::PROTECTED;
+    if (flags.flags() & VISIBILITY).count_ones() > 1 {
+        return Err("more than one of public, private and protected");
+    }
+
+    if name == "<init>" {
+        const FORBIDDEN: u16 = MethodAccessFlags::STATIC | MethodAccessFlags::FINAL | MethodAccessFlags::SYNCHRONIZED
+            | MethodAccessFlags::NATIVE | MethodAccessFlags::ABSTRACT;
+        if flags.intersects(FORBIDDEN) || since_java5 && flags.is_bridge() {
+            return Err("instance initialization methods can't be static, final, synchronized, native, abstract or bridge");
+        }
+    } else if flags.is_abstract() && (flags.is_private() || flags.is_static() || flags.is_final()
+        || flags.is_native() || since_java5 && (flags.is_synchronized() || strict_matters)) {
+        return Err("abstract methods can't be private, static, final, synchronized, native or strict");
+    }
+    Ok(())
 }
 
-impl ExceptionHandler {
-    fn parse(buf: &mut BinaryReader) -> Result<ExceptionHandler, ParseError> {
-        buf.check_bytes(2 + 2 + 2 + 2, "exception")?;
+#[cfg(test)]
+mod tests {
+    use crate::class::parse::{ErrorKind, ParseError};
+    use crate::class::testing::*;
+
+    const ACC_PROTECTED: u16 = 0x0004;
+    const ACC_NATIVE: u16 = 0x0100;
+    const ACC_STRICT: u16 = 0x0800;
+
+    fn parse_method_in(class_flags: u16, flags: u16, name: &str, descriptor: &str, major: u16) -> Result<(), ParseError> {
+        let mut builder = ClassBuilder::new("Test", class_flags);
+        // Only abstract and native methods go without code
+        let attributes = match flags & (ACC_ABSTRACT | ACC_NATIVE) {
+            0 => vec![builder.code(0, 255, &[0xB1], &[])],
+            _ => Vec::new(),
+        };
+        let method = builder.member(flags, name, descriptor, &attributes);
+        builder.methods.push(method);
+        builder.parse(major).map(|_| ())
+    }
+
+    fn parse_method(flags: u16, name: &str, descriptor: &str) -> Result<(), ParseError> {
+        parse_method_in(ACC_PUBLIC | ACC_SUPER | ACC_ABSTRACT, flags, name, descriptor, 69)
+    }
+
+    fn is_malformed(result: Result<(), ParseError>) -> bool {
+        result.is_err_and(|err| matches!(err.kind(), ErrorKind::Malformed(_)))
+    }
 
-        // SAFETY: Guaranteed by check_bytes
-        let start_pc = unsafe { buf.unsafe_read_u16() };
-        let end_pc = unsafe { buf.unsafe_read_u16() };
-        let handler_pc = unsafe { buf.unsafe_read_u16() };
-        let catch_type = unsafe { buf.unsafe_read_u16() };
+    #[test]
+    fn limits_parameter_slots() {
+        let ints = |count| format!("({})V", "I".repeat(count));
+        // Instance methods also take `this`
+        assert!(parse_method(ACC_PUBLIC, "run", &ints(254)).is_ok());
+        assert!(is_malformed(parse_method(ACC_PUBLIC, "run", &ints(255))));
+        assert!(parse_method(ACC_PUBLIC | ACC_STATIC, "run", &ints(255)).is_ok());
+        // Longs and doubles take two slots each
+        assert!(is_malformed(parse_method(ACC_PUBLIC, "run", &format!("({}I)V", "J".repeat(127)))));
+        assert!(parse_method(ACC_PUBLIC, "run", &format!("({})V", "J".repeat(127))).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_descriptors() {
+        assert!(is_malformed(parse_method(ACC_PUBLIC, "run", "(I")));
+        assert!(is_malformed(parse_method(ACC_PUBLIC, "run", "I")));
+        assert!(is_malformed(parse_method(ACC_PUBLIC, "<init>", "()I")));
+    }
+
+    #[test]
+    fn class_method_flags() {
+        assert!(is_malformed(parse_method(ACC_PUBLIC | ACC_PRIVATE, "run", "()V")));
+        assert!(is_malformed(parse_method(ACC_PUBLIC | ACC_ABSTRACT | ACC_FINAL, "run", "()V")));
+        assert!(is_malformed(parse_method(ACC_PRIVATE | ACC_ABSTRACT, "run", "()V")));
+        assert!(is_malformed(parse_method(ACC_PUBLIC | ACC_STATIC, "<init>", "()V")));
+        assert!(parse_method(ACC_PROTECTED | ACC_FINAL, "run", "()V").is_ok());
+        assert!(parse_method(ACC_PUBLIC | ACC_NATIVE, "run", "()V").is_ok());
+
+        // Abstract methods can only be strict before strict became the default in Java 17
+        let class_flags = ACC_PUBLIC | ACC_SUPER | ACC_ABSTRACT;
+        assert!(is_malformed(parse_method_in(class_flags, ACC_PUBLIC | ACC_ABSTRACT | ACC_STRICT, "run", "()V", 60)));
+        assert!(parse_method_in(class_flags, ACC_PUBLIC | ACC_ABSTRACT | ACC_STRICT, "run", "()V", 61).is_ok());
+    }
+
+    #[test]
+    fn interface_method_flags() {
+        let interface = ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT;
+        // Java 8 added private, static and default methods
+        assert!(parse_method_in(interface, ACC_PRIVATE, "run", "()V", 52).is_ok());
+        assert!(parse_method_in(interface, ACC_PUBLIC | ACC_STATIC, "run", "()V", 52).is_ok());
+        assert!(is_malformed(parse_method_in(interface, ACC_PUBLIC | ACC_STATIC, "run", "()V", 51)));
+        assert!(is_malformed(parse_method_in(interface, ACC_PUBLIC, "run", "()V", 51)));
+        assert!(parse_method_in(interface, ACC_PUBLIC | ACC_ABSTRACT, "run", "()V", 51).is_ok());
+        assert!(is_malformed(parse_method_in(interface, ACC_ABSTRACT, "run", "()V", 52)));
+        assert!(is_malformed(parse_method_in(interface, ACC_PUBLIC | ACC_FINAL, "run", "()V", 52)));
+    }
 
-        Ok(ExceptionHandler { start_pc, end_pc, handler_pc, catch_type })
+    #[test]
+    fn class_initializer_flags() {
+        // Everything but static is ignored
+        assert!(parse_method(ACC_STATIC | ACC_PUBLIC | ACC_PRIVATE, "<clinit>", "()V").is_ok());
+        // From Java 7 on, it has to be static, even in interfaces
+        let class_flags = ACC_PUBLIC | ACC_SUPER;
+        assert!(is_malformed(parse_method_in(class_flags, 0, "<clinit>", "()V", 51)));
+        assert!(parse_method_in(class_flags, 0, "<clinit>", "()V", 50).is_ok());
+        let interface = ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT;
+        assert!(is_malformed(parse_method_in(interface, 0, "<clinit>", "()V", 52)));
+        assert!(parse_method_in(interface, ACC_STATIC, "<clinit>", "()V", 52).is_ok());
     }
 }
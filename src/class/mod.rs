@@ -1,104 +1,257 @@
-mod constantpool;
+pub mod asm;
+pub mod bytecode;
+pub mod constantpool;
 mod field;
 mod method;
-mod parse;
+pub mod parse;
+mod record;
+#[cfg(test)]
+pub mod testing;
+pub mod write;
+
+pub use _parse::{parse_class, parse_class_with};
+pub use _write::write_class;
+pub use field::Field;
+pub use method::Method;
+pub use record::RecordComponentInfo;
 
 use std::cell::{Ref, RefCell};
-use crate::types::{AccessFlags, Array};
+use crate::loader::classfile::attribute::ClassFileAttribute;
+use crate::types::signature::{ClassSignature, SignatureError};
+use crate::types::{Array, ClassAccessFlags, Symbol, Version};
+
+const CLASS_FILE_MAGIC_NUMBER: u32 = 0xCAFEBABE;
 
 pub struct Class {
     info: ClassInfo,
     constant_pool: constantpool::Pool,
     fields: Array<field::Field>,
     methods: Array<method::Method>,
+    attributes: Array<ClassFileAttribute>,
+}
+
+impl Class {
+    pub fn version(&self) -> Version {
+        self.info.version
+    }
+
+    /// The flags the class is treated as having, which for interfaces from before Java 6
+    /// includes `ABSTRACT` even if the class file doesn't set it.
+    pub fn access_flags(&self) -> ClassAccessFlags {
+        self.info.access_flags
+    }
+
+    /// The flags exactly as they're given in the class file.
+    pub fn declared_access_flags(&self) -> ClassAccessFlags {
+        self.info.declared_flags
+    }
+
+    pub fn this_class_index(&self) -> constantpool::Index {
+        self.info.this_class
+    }
+
+    /// The index of the superclass, which is 0 if there isn't one.
+    pub fn super_class_index(&self) -> constantpool::Index {
+        self.info.super_class
+    }
+
+    /// The binary name of the class, such as `java/lang/String`.
+    pub fn name(&self) -> Symbol {
+        self.info.descriptor.name
+    }
+
+    /// The binary name of the superclass, which only `java/lang/Object` and modules don't have.
+    pub fn super_class(&self) -> Option<Symbol> {
+        self.info.descriptor.super_class
+    }
+
+    /// The binary names of the interfaces the class directly implements, or that an
+    /// interface directly extends, in the order they're declared.
+    pub fn interfaces(&self) -> &[Symbol] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.info.descriptor.interfaces.as_slice() }
+    }
+
+    /// The class's generic signature, from its `Signature` attribute.
+    pub fn signature(&self) -> Option<Symbol> {
+        self.info.descriptor.signature
+    }
+
+    /// Parses the class's generic signature. Signatures aren't checked when the class is
+    /// loaded, so this can fail, which reflection reports as `GenericSignatureFormatError`.
+    pub fn generic_signature(&self) -> Option<Result<ClassSignature, SignatureError>> {
+        self.signature().map(|signature| ClassSignature::parse(signature.as_str()))
+    }
+
+    pub fn constant_pool(&self) -> &constantpool::Pool {
+        &self.constant_pool
+    }
+
+    pub fn fields(&self) -> &[field::Field] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.fields.as_slice() }
+    }
+
+    pub fn methods(&self) -> &[method::Method] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.methods.as_slice() }
+    }
+
+    pub fn attributes(&self) -> &[ClassFileAttribute] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.attributes.as_slice() }
+    }
 }
 
 pub struct ClassInfo {
-    minor_version: u16,
-    major_version: u16,
-    access_flags: AccessFlags,
+    version: Version,
+    access_flags: ClassAccessFlags,
+    // The flags as they are in the class file, before any are implied
+    declared_flags: ClassAccessFlags,
     descriptor: ClassDescriptor,
+    this_class: constantpool::Index,
     super_class: constantpool::Index,
     interfaces: Array<constantpool::Index>,
 }
 
+/// Options that change which class files are accepted.
+#[derive(Debug, Default, Clone)]
+pub struct ParseOptions {
+    /// Accept class files that depend on the preview features of the current version,
+    /// like `--enable-preview`.
+    pub enable_preview: bool,
+}
+
 pub struct ClassDescriptor {
-    name: String,
-    signature: String
+    name: Symbol,
+    super_class: Option<Symbol>,
+    interfaces: Array<Symbol>,
+    signature: Option<Symbol>,
 }
 
 mod _parse {
-    use crate::{buf_read_u16_arr, types};
-    use crate::types::{AccessFlags, ClassFileVersion};
+    use crate::buf_read_u16_arr;
+    use crate::types::{ClassFileVersion, Feature, CURRENT_VIRTUAL_MACHINE_VERSION, PREVIEW_MINOR_VERSION};
     use super::*;
-    use super::parse::{BinaryReader, ParseError};
+    use super::parse::{BinaryReader, ErrorKind, ParseError};
     use super::field::{Field, parse_field};
     use super::method::{Method, parse_method};
+    use super::constantpool::Tag;
+    use crate::loader::classfile::attribute::parse_attributes;
 
     macro_rules! parse_field_method {
-        ($typ: ident, $func: ident, $var_name: ident, $cp: expr, $buf: expr, $error: expr, $error_idx: expr) => {
+        ($typ: ident, $func: ident, $var_name: ident, $cp: expr, $version: expr, $flags: expr, $buf: expr,
+     $error: expr, $error_idx: expr) => {
             $buf.check_bytes(2, $error)?;
 
-            let mut $var_name: Array<$typ>;
+            let $var_name: Array<$typ>;
             {
                 // SAFETY: Guaranteed by check_bytes at top
                 let len = unsafe { $buf.unsafe_read_u16() } as usize;
 
-                // TODO: We shouldn't wrap this. When we have proper error handling,
-                //  propagate it.
-                $var_name = Array::new(len)
-                    .map_err(|_| ParseError::new("cannot allocate array"))?;
-
+                // See buf_read_named_type_arr for why this goes through a Vec
+                let mut values = Vec::with_capacity(len);
                 for i in 0..len {
-                    let v = $func($cp, $buf).map_err(ParseError::wrap(format!($error_idx, i)))?;
-                    $var_name.set(i, v).expect("array set was somehow out of bounds");
+                    let v = $func($cp, $version, $flags, $buf).map_err(ParseError::wrap(format!($error_idx, i)))?;
+                    values.push(v);
                 }
+                $var_name = Array::from_vec(values)
+                    .map_err(|_| $buf.error(ErrorKind::OutOfMemory))?;
             }
         };
     }
 
     pub fn parse_class(buf: &mut BinaryReader) -> Result<Class, ParseError> {
+        parse_class_with(buf, &ParseOptions::default())
+    }
+
+    pub fn parse_class_with(buf: &mut BinaryReader, options: &ParseOptions) -> Result<Class, ParseError> {
         read_and_check_magic(buf)?;
 
-        let (minor_version, major_version): (u16, u16);
+        let version: Version;
         {
             buf.check_bytes(2 + 2, "minor and major version")?;
+            let offset = buf.position();
 
             // SAFETY: Both guaranteed by check_bytes
-            minor_version = unsafe { buf.unsafe_read_u16() };
-            major_version = unsafe { buf.unsafe_read_u16() };
-            check_major_minor(major_version, minor_version)?;
+            let minor = unsafe { buf.unsafe_read_u16() };
+            let major = unsafe { buf.unsafe_read_u16() };
+            version = Version::new(major, minor);
+            check_major_minor(version, options)
+                .map_err(|kind| ParseError::new(kind, offset))?;
         }
 
-        let constant_pool = constantpool::parse_pool(buf)
-            .map_err(|err| ParseError::new(format!("bad constant pool: {err}")))?;
+        let pool_start = buf.position();
+        let constant_pool = constantpool::parse_pool(buf, version)
+            .map_err(ParseError::wrap("constant pool"))?;
 
         // 2 for access flags, 2 for this class, 2 for super class
         buf.check_bytes(2 + 2 + 2, "access flags, this class, super class")?;
 
         // SAFETY: Next 3 reads guaranteed by above check_bytes
-        let flags = unsafe { buf.unsafe_read_u16() };
+        let declared_flags = ClassAccessFlags::new(unsafe { buf.unsafe_read_u16() });
+        let flags = check_class_flags(declared_flags, version)
+            .map_err(|msg| {
+                let msg = format!("illegal class access flags {declared_flags:?}: {msg}");
+                ParseError::new(ErrorKind::Malformed(msg), buf.position() - 2)
+            })?;
         let this_class = unsafe { buf.unsafe_read_u16() };
-        if !constant_pool.is_valid_index(this_class) {
-            return ParseError::new("this class not in constant pool").into();
-        }
+        let name = resolve_class_name(&constant_pool, this_class, buf.position() - 2)?;
         let super_class = unsafe { buf.unsafe_read_u16() };
-        if super_class != 0 && !constant_pool.is_valid_index(super_class) {
-            return ParseError::new("super class not in constant pool").into();
-        }
+        let super_name = match super_class {
+            constantpool::INDEX_INVALID => None,
+            _ => Some(resolve_class_name(&constant_pool, super_class, buf.position() - 2)?),
+        };
+        check_super_class(name, super_name, flags)
+            .map_err(|msg| ParseError::new(ErrorKind::Malformed(msg.into()), buf.position() - 2))?;
 
+        // Skip the count, which buf_read_u16_arr checks for us
+        let interfaces_start = buf.position() + 2;
         buf_read_u16_arr!(interfaces, buf, "interfaces");
+        let interface_names = {
+            // SAFETY: buf_read_u16_arr fully initializes the array
+            let names = unsafe { interfaces.as_slice() }.iter().enumerate()
+                .map(|(i, &index)| resolve_class_name(&constant_pool, index, interfaces_start + i * 2))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(ParseError::wrap("interfaces"))?;
+            Array::from_vec(names).map_err(|_| buf.error(ErrorKind::OutOfMemory))?
+        };
 
-        parse_field_method!(Field, parse_field, fields, &constant_pool, buf,
+        parse_field_method!(Field, parse_field, fields, &constant_pool, version, flags, buf,
             "fields", "fields - idx {}");
-        parse_field_method!(Method, parse_method, methods, &constant_pool, buf,
+        parse_field_method!(Method, parse_method, methods, &constant_pool, version, flags, buf,
             "methods", "methods - idx {}");
 
+        let attributes = parse_attributes(&constant_pool, version, buf)
+            .map_err(ParseError::wrap("attributes"))?;
+
+        // Dynamic constants can only be checked against the bootstrap methods now that we've got them
+        // SAFETY: parse_attributes fully initializes the array
+        let bootstrap_methods = unsafe { attributes.as_slice() }.iter()
+            .find_map(|attr| match attr {
+                ClassFileAttribute::BootstrapMethods(attr) => Some(attr.methods().len()),
+                _ => None,
+            })
+            .unwrap_or(0);
+        constantpool::validate_bootstrap_indexes(&constant_pool, bootstrap_methods, pool_start)
+            .map_err(ParseError::wrap("constant pool"))?;
+        if buf.try_has_bytes(1)? {
+            return buf.error(ErrorKind::Malformed("extra bytes after end of class file".into())).into();
+        }
+
+        // SAFETY: parse_attributes fully initializes the array
+        let signature = unsafe { attributes.as_slice() }.iter()
+            .find_map(|attr| match attr {
+                ClassFileAttribute::Signature(attr) => attr.signature(&constant_pool),
+                _ => None,
+            });
+
         let info = ClassInfo {
-            minor_version,
-            major_version,
-            access_flags: AccessFlags::new(flags),
-            descriptor: ClassDescriptor { name: String::new(), signature: String::new() },
+            version,
+            access_flags: flags,
+            declared_flags,
+            descriptor: ClassDescriptor { name, super_class: super_name, interfaces: interface_names, signature },
+            this_class,
             super_class,
             interfaces
         };
@@ -108,49 +261,239 @@ mod _parse {
             constant_pool,
             fields,
             methods,
+            attributes,
         })
     }
 
-    fn read_and_check_magic(buf: &mut BinaryReader) -> Result<(), ParseError> {
-        const CLASS_FILE_MAGIC_NUMBER: u32 = 0xCAFEBABE;
+    fn resolve_class_name(pool: &constantpool::Pool, index: constantpool::Index,
+                          offset: usize) -> Result<Symbol, ParseError> {
+        pool.resolve_class(index)
+            .map(|class| class.name())
+            .ok_or_else(|| ParseError::bad_reference(index, Tag::Class, offset))
+    }
+
+    // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.1-200-E.1
+    fn check_super_class(name: Symbol, super_name: Option<Symbol>, flags: ClassAccessFlags) -> Result<(), &'static str> {
+        match super_name {
+            // Modules have no superclass either, but aren't classes to begin with
+            None if name != "java/lang/Object" && !flags.is_module() => {
+                Err("only java/lang/Object can have no superclass")
+            },
+            Some(super_name) if flags.is_interface() && super_name != "java/lang/Object" => {
+                Err("the superclass of an interface must be java/lang/Object")
+            },
+            _ => Ok(()),
+        }
+    }
 
+    // Gives back the flags the class should be treated as having
+    // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.1-200-E.2
+    fn check_class_flags(flags: ClassAccessFlags, version: Version) -> Result<ClassAccessFlags, &'static str> {
+        // Interfaces weren't always marked abstract before Java 6
+        let flags = if flags.is_interface() && version.major() < ClassFileVersion::Java6.major() {
+            ClassAccessFlags::new(flags.flags() | ClassAccessFlags::ABSTRACT)
+        } else {
+            flags
+        };
+
+        if flags.is_module() && version.supports(Feature::Modules) {
+            return match flags.flags() {
+                ClassAccessFlags::MODULE => Ok(flags),
+                _ => Err("modules can't have any other flags"),
+            };
+        }
+
+        let since_java5 = version.major() >= ClassFileVersion::Java5.major();
+        if flags.is_abstract() && flags.is_final() {
+            return Err("both abstract and final");
+        }
+        if flags.is_interface() && !flags.is_abstract() {
+            return Err("interfaces must be abstract");
+        }
+        if flags.is_interface() && since_java5 && (flags.is_super() || flags.is_enum()) {
+            return Err("interfaces can't be super or enum");
+        }
+        if !flags.is_interface() && since_java5 && flags.is_annotation() {
+            return Err("annotation interfaces must be interfaces");
+        }
+        Ok(flags)
+    }
+
+    fn read_and_check_magic(buf: &mut BinaryReader) -> Result<(), ParseError> {
         buf.check_bytes(4, "classfile magic")?;
+        let offset = buf.position();
 
         // SAFETY: Guaranteed by check_bytes
         let magic = unsafe { buf.unsafe_read_u32() };
         match magic {
             CLASS_FILE_MAGIC_NUMBER => Ok(()),
-            _ => ParseError::new(format!("invalid magic {magic} (not a classfile)")).into()
+            _ => ParseError::new(ErrorKind::BadMagic(magic), offset).into()
         }
     }
 
-    fn check_major_minor(major: u16, minor: u16) -> Result<(), ParseError> {
+    // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.1-200-B.2
+    fn check_major_minor(version: Version, options: &ParseOptions) -> Result<(), ErrorKind> {
         const MIN_SUPPORTED: ClassFileVersion = ClassFileVersion::Java1_1;
-        const MAX_SUPPORTED: ClassFileVersion = ClassFileVersion::Java1_2;
-        const SUPPORT_PREVIEW: bool = false; // TODO: Will we ever support preview features?
+        const MAX_SUPPORTED: ClassFileVersion = CURRENT_VIRTUAL_MACHINE_VERSION;
+
+        let (major, minor) = (version.major(), version.minor());
+        let unsupported = |reason: String| ErrorKind::UnsupportedVersion { major, minor, reason };
 
-        if major < MIN_SUPPORTED as u16 {
-            let msg = format!("major version {major} not supported (min is {MIN_SUPPORTED})");
-            return ParseError::new(msg).into();
+        if major < MIN_SUPPORTED.major() {
+            return Err(unsupported(format!("major version not supported (min is {MIN_SUPPORTED})")));
         }
-        if major > MAX_SUPPORTED as u16 {
-            let msg = format!("major version {major} not supported (max is {MAX_SUPPORTED})");
-            return ParseError::new(msg).into();
+        if major > MAX_SUPPORTED.major() {
+            return Err(unsupported(format!("major version not supported (max is {MAX_SUPPORTED})")));
         }
 
-        if major > ClassFileVersion::Java12 as u16 {
-            if minor != 0 && minor != 65535 {
-                let msg = format!("minor version must be 0 or 65535 for classfiles major {major}, was {minor}");
-                return ParseError::new(msg).into();
-            }
+        // Before Java 12 any minor version is allowed, including all of 45.0 to 45.65535,
+        // which are all treated as Java 1.1 class files
+        if major < ClassFileVersion::Java12.major() {
+            return Ok(());
+        }
 
-            if minor == 65535 {
-                if major != types::CURRENT_VIRTUAL_MACHINE_VERSION as u16 {
-                    return ParseError::new("Astatine only supports preview features for its current version").into();
-                }
+        if minor != 0 && minor != PREVIEW_MINOR_VERSION {
+            return Err(unsupported(format!("minor version must be 0 or {PREVIEW_MINOR_VERSION} for classfiles major {major}")));
+        }
+
+        if version.is_preview() {
+            if major != CURRENT_VIRTUAL_MACHINE_VERSION.major() {
+                return Err(unsupported("Astatine only supports preview features for its current version".into()));
+            }
+            if !options.enable_preview {
+                return Err(unsupported("preview features are not enabled".into()));
             }
         }
 
         Ok(())
     }
 }
+
+mod _write {
+    use super::*;
+    use super::field::write_field;
+    use super::method::write_method;
+    use super::write::BinaryWriter;
+    use crate::loader::Serialize;
+    use crate::loader::classfile::attribute::{serialize_attributes, WriteContext};
+
+    /// Writes the class back out as a class file. Nothing is normalised, so a class that was
+    /// parsed from a class file is written out as exactly the same bytes.
+    pub fn write_class(class: &Class) -> Vec<u8> {
+        let mut buf = BinaryWriter::new();
+        let ctx = WriteContext::new(&class.constant_pool);
+
+        buf.write_u32(CLASS_FILE_MAGIC_NUMBER);
+        buf.write_u16(class.info.version.minor());
+        buf.write_u16(class.info.version.major());
+        class.constant_pool.serialize(&mut buf);
+
+        buf.write_u16(class.info.declared_flags.flags());
+        buf.write_u16(class.info.this_class);
+        buf.write_u16(class.info.super_class);
+        // SAFETY: We know this array is fully initialized
+        buf.write_u16_arr(unsafe { class.info.interfaces.as_slice() });
+
+        buf.write_u16(class.fields().len() as u16);
+        for field in class.fields() {
+            write_field(field, &ctx, &mut buf);
+        }
+        buf.write_u16(class.methods().len() as u16);
+        for method in class.methods() {
+            write_method(method, &ctx, &mut buf);
+        }
+        serialize_attributes(class.attributes(), &ctx, &mut buf);
+
+        buf.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::constantpool::Tag;
+    use crate::class::parse::{ErrorKind, ParseError};
+    use crate::class::testing::*;
+
+    #[test]
+    fn resolves_names() {
+        let mut builder = ClassBuilder::new("com/example/Names", ACC_PUBLIC | ACC_SUPER);
+        builder.interfaces = vec![builder.class("java/lang/Runnable"), builder.class("java/io/Serializable")];
+        let signature = builder.utf8("<T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Runnable;Ljava/io/Serializable;");
+        builder.attributes = vec![builder.attribute("Signature", &u16_bytes(signature))];
+
+        let class = builder.parse(69).expect("class should parse");
+        assert_eq!(class.name().as_str(), "com/example/Names");
+        assert_eq!(class.super_class().map(|name| name.as_str()), Some("java/lang/Object"));
+        assert_eq!(class.interfaces().iter().map(|name| name.as_str()).collect::<Vec<_>>(),
+            ["java/lang/Runnable", "java/io/Serializable"]);
+        let signature = class.generic_signature().and_then(Result::ok).expect("signature should parse");
+        assert_eq!(signature.type_parameters().len(), 1);
+    }
+
+    #[test]
+    fn only_object_has_no_superclass() {
+        let mut builder = ClassBuilder::new("java/lang/Object", ACC_PUBLIC | ACC_SUPER);
+        builder.super_class = 0;
+        let class = builder.parse(69).expect("class should parse");
+        assert!(class.super_class().is_none());
+        assert_eq!(class.super_class_index(), 0);
+
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        builder.super_class = 0;
+        let err = builder.parse(69).err().expect("class should need a superclass");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+
+        // Modules aren't classes, so don't have one either
+        let mut builder = ClassBuilder::new("module-info", ACC_MODULE);
+        builder.super_class = 0;
+        assert!(builder.parse(69).is_ok_and(|class| class.super_class().is_none()));
+    }
+
+    #[test]
+    fn interface_superclass_must_be_object() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT);
+        builder.super_class = builder.class("java/lang/Number");
+        let err = builder.parse(69).err().expect("superclass should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+    }
+
+    #[test]
+    fn names_must_be_classes() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let not_a_class = builder.utf8("java/lang/Runnable");
+        builder.interfaces = vec![builder.class("java/io/Serializable"), not_a_class];
+        let bytes = builder.build(69, 0);
+        // The second interface index, just before the empty field, method and attribute tables
+        let offset = bytes.len() - 2 * 3 - 2;
+
+        let err = builder.parse(69).err().expect("interface should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { index, expected: Tag::Class }
+            if *index == not_a_class));
+        assert_eq!(err.offset(), offset);
+        assert_eq!(err.context(), ["interfaces"]);
+
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        builder.this_class = builder.utf8("Test");
+        let err = builder.parse(69).err().expect("this class should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { expected: Tag::Class, .. }));
+    }
+
+    #[test]
+    fn class_flags() {
+        let parse = |flags, major| ClassBuilder::new("Test", flags).parse(major);
+        let is_malformed = |result: Result<_, ParseError>| result.is_err_and(|err| matches!(err.kind(), ErrorKind::Malformed(_)));
+
+        assert!(is_malformed(parse(ACC_PUBLIC | ACC_ABSTRACT | ACC_FINAL, 69)));
+        assert!(is_malformed(parse(ACC_PUBLIC | ACC_INTERFACE, 69)));
+        assert!(is_malformed(parse(ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT | ACC_SUPER, 69)));
+        // Before Java 5, interfaces could be super, and before Java 6 they needn't be abstract
+        assert!(parse(ACC_PUBLIC | ACC_INTERFACE | ACC_ABSTRACT | ACC_SUPER, 48).is_ok());
+        let class = parse(ACC_PUBLIC | ACC_INTERFACE, 49).expect("class should parse");
+        assert!(class.access_flags().is_abstract());
+        assert!(!class.declared_access_flags().is_abstract());
+
+        // Modules can't have anything else, but before Java 9 the flag means nothing
+        assert!(is_malformed(parse(ACC_MODULE | ACC_PUBLIC, 69)));
+        assert!(parse(ACC_MODULE | ACC_PUBLIC | ACC_SUPER, 52).is_ok());
+    }
+}
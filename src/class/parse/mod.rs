@@ -4,30 +4,84 @@ pub use reader::BinaryReader;
 
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::io;
+use crate::class::constantpool::{Index, Tag};
 
+/// An error encountered while reading a class file.
+///
+/// Every error carries the offset into the class file at which it was found, and a chain
+/// of context describing which structure was being read at the time, outermost first.
 #[derive(Debug)]
 pub struct ParseError {
-    msg: String
+    kind: ErrorKind,
+    offset: usize,
+    context: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// The input ended before the named structure could be read in full
+    Truncated { what: String },
+    /// The class file doesn't start with 0xCAFEBABE
+    BadMagic(u32),
+    /// The class file's version is outside of what we can load
+    UnsupportedVersion { major: u16, minor: u16, reason: String },
+    /// A constant pool entry has a tag that isn't defined by the JVMS
+    BadConstantPoolTag(u8),
+    /// A constant pool index is out of range, or points at an entry with the wrong tag
+    BadConstantPoolReference { index: Index, expected: Tag },
+    /// An attribute's contents don't match its declared length or its format
+    MalformedAttribute { name: String, reason: String },
+    /// Any other structural problem with the class file
+    Malformed(String),
+    /// We couldn't allocate memory for something in the class file
+    OutOfMemory,
+    /// The class file was being read from a stream, which gave an error
+    Io(io::Error),
 }
 
 impl ParseError {
-    pub fn new(msg: impl Into<String>) -> ParseError {
-        Self { msg: msg.into() }
+    pub fn new(kind: ErrorKind, offset: usize) -> ParseError {
+        Self { kind, offset, context: Vec::new() }
+    }
+
+    pub fn bad_reference(index: Index, expected: Tag, offset: usize) -> ParseError {
+        Self::new(ErrorKind::BadConstantPoolReference { index, expected }, offset)
+    }
+
+    /// Returns a function that adds the given context to an error, for use with `map_err`.
+    pub fn wrap(context: impl Into<String>) -> impl Fn(ParseError) -> ParseError {
+        let context = context.into();
+        move |mut err| {
+            err.context.insert(0, context.clone());
+            err
+        }
+    }
+
+    /// Replaces what went wrong, keeping where it was found.
+    pub fn with_kind(self, kind: ErrorKind) -> ParseError {
+        Self { kind, ..self }
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
     }
 
-    pub fn wrap(wrapping_msg: impl Into<String>) -> impl Fn(ParseError) -> ParseError {
-        let msg = wrapping_msg.into();
-        move |err| ParseError::new(format!("{msg}: {err:?}"))
+    /// The offset in to the class file, in bytes, at which the error was found.
+    pub fn offset(&self) -> usize {
+        self.offset
     }
 
-    pub fn not_enough_bytes<T>(msg: impl Into<String>) -> Result<T, ParseError> {
-        let msg: String = msg.into();
-        let full_msg = format!("Not enough bytes for {msg}");
-        Err(Self { msg: full_msg })
+    /// What was being parsed when the error was found, outermost first.
+    pub fn context(&self) -> &[String] {
+        &self.context
     }
 
-    pub fn msg(&self) -> &str {
-        &self.msg
+    /// Whether the error is caused by the class file's version, rather than its format.
+    /// The JVM reports the former as `UnsupportedClassVersionError`, and everything else
+    /// as `ClassFormatError`.
+    pub fn is_unsupported_version(&self) -> bool {
+        matches!(self.kind, ErrorKind::UnsupportedVersion { .. })
     }
 }
 
@@ -35,12 +89,120 @@ impl Error for ParseError {}
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.msg.as_str())
+        for context in &self.context {
+            write!(f, "{context}: ")?;
+        }
+        write!(f, "{} (at offset {})", self.kind, self.offset)
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Truncated { what } => write!(f, "not enough bytes for {what}"),
+            ErrorKind::BadMagic(magic) => write!(f, "invalid magic {magic:#X} (not a classfile)"),
+            ErrorKind::UnsupportedVersion { major, minor, reason } =>
+                write!(f, "unsupported class file version {major}.{minor}: {reason}"),
+            ErrorKind::BadConstantPoolTag(tag) => write!(f, "invalid constant pool tag {tag}"),
+            ErrorKind::BadConstantPoolReference { index, expected } =>
+                write!(f, "constant pool index {index} is not a valid {expected:?} entry"),
+            ErrorKind::MalformedAttribute { name, reason } => write!(f, "malformed {name} attribute: {reason}"),
+            ErrorKind::Malformed(msg) => f.write_str(msg),
+            ErrorKind::OutOfMemory => f.write_str("out of memory"),
+            ErrorKind::Io(err) => write!(f, "error reading class file: {err}"),
+        }
     }
 }
 
-impl<T> Into<Result<T, ParseError>> for ParseError {
-    fn into(self) -> Result<T, ParseError> {
-        Err(self)
+impl<T> From<ParseError> for Result<T, ParseError> {
+    fn from(err: ParseError) -> Result<T, ParseError> {
+        Err(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::{parse_class, parse_class_with, ParseOptions};
+    use crate::class::testing::*;
+    use super::*;
+
+    #[test]
+    fn bad_magic() {
+        let err = parse_class(&mut BinaryReader::new(vec![0xCA, 0xFE, 0xD0, 0x0D])).err().expect("should fail");
+        assert!(matches!(err.kind(), ErrorKind::BadMagic(0xCAFED00D)));
+        assert_eq!(err.offset(), 0);
+        assert_eq!(err.to_string(), "invalid magic 0xCAFED00D (not a classfile) (at offset 0)");
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let err = builder.parse(44).err().expect("should fail");
+        assert!(matches!(err.kind(), ErrorKind::UnsupportedVersion { major: 44, minor: 0, .. }));
+        assert!(err.is_unsupported_version());
+        // The offset of the minor version, which comes first
+        assert_eq!(err.offset(), 4);
+    }
+
+    #[test]
+    fn version_edges() {
+        let builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let parse = |major, minor, enable_preview| {
+            let options = ParseOptions { enable_preview };
+            parse_class_with(&mut BinaryReader::new(builder.build(major, minor)), &options)
+        };
+        let rejected = |result: Result<_, ParseError>| result.err().is_some_and(|err| err.is_unsupported_version());
+
+        // Every minor version is accepted before Java 12
+        assert!(parse(45, 65535, false).is_ok());
+        assert!(parse(55, 1, false).is_ok());
+        // After that it must be 0, or the preview version of the current release
+        assert!(parse(56, 0, false).is_ok());
+        assert!(rejected(parse(56, 1, false)));
+        assert!(rejected(parse(56, 65535, true)));
+        assert!(parse(69, 0, false).is_ok());
+        assert!(rejected(parse(69, 65535, false)));
+        assert!(parse(69, 65535, true).is_ok());
+        assert!(rejected(parse(70, 0, false)));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bytes = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER).build(69, 0);
+        bytes.truncate(bytes.len() - 1);
+        let err = parse_class(&mut BinaryReader::new(bytes)).err().expect("should fail");
+        assert!(matches!(err.kind(), ErrorKind::Truncated { .. }));
+        assert!(!err.is_unsupported_version());
+        assert_eq!(err.context(), ["attributes"]);
+    }
+
+    #[test]
+    fn bad_reference_has_context() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let integer = builder.entry(INTEGER, &5_i32.to_be_bytes());
+        let mut field = builder.member(0, "x", "I", &[]);
+        // Point the name at an integer rather than a utf8
+        field[2..4].copy_from_slice(&integer.to_be_bytes());
+        builder.fields.push(field);
+        let bytes = builder.build(69, 0);
+        let name_offset = bytes.len() - 2 - 2 - 2 - 4;
+
+        let err = parse_class(&mut BinaryReader::new(bytes)).err().expect("should fail");
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { index, expected: Tag::Utf8 }
+            if *index == integer));
+        assert_eq!(err.offset(), name_offset);
+        assert_eq!(err.context(), ["fields - idx 0"]);
+        assert_eq!(err.to_string(), format!(
+            "fields - idx 0: constant pool index {integer} is not a valid Utf8 entry (at offset {name_offset})"));
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let mut bytes = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER).build(69, 0);
+        let end = bytes.len();
+        bytes.push(0);
+        let err = parse_class(&mut BinaryReader::new(bytes)).err().expect("should fail");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+        assert_eq!(err.offset(), end);
     }
 }
@@ -1,32 +1,97 @@
-use std::cmp::min;
+use std::cmp::{max, min};
+use std::io::{self, Read};
 use std::ptr;
-use crate::class::parse::ParseError;
-use crate::types::Array;
-
-pub struct BinaryReader {
-    buf: Vec<u8>,
+use crate::class::parse::{ErrorKind, ParseError};
+use crate::types::{Array, Bytes};
+
+/// How much is read from a stream at once, when less than this is needed.
+const STREAM_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads big endian values from a class file. Reading arrays of bytes with [Self::read_bytes]
+/// doesn't copy them, but gives back a view that shares the reader's buffer.
+///
+/// The class file can either be entirely in memory, or be read from a stream as it's needed.
+/// When it's read from a stream, views that are much smaller than the window of the stream
+/// they're in are copied instead, so that they don't keep all of the window alive.
+/// Either way, everything is read through [Self::check_bytes] (or the methods built on it),
+/// which makes sure enough is buffered, so parsing works the same for both.
+pub struct BinaryReader<'a> {
+    // Everything, for a reader over memory, or the window of the stream being read
+    buf: Bytes,
+    // The position in the class file of the start of the buffer
+    start: usize,
+    // The offset in to the buffer
     off: usize,
+    // Where more of the class file comes from, until the end of it is reached
+    source: Option<Box<dyn Read + 'a>>,
+    // Whether the buffer is a window of a stream, rather than the whole class file
+    streamed: bool,
 }
 
-impl BinaryReader {
-    pub fn new(buf: Vec<u8>) -> Self {
-        Self { buf, off: 0 }
+impl<'a> BinaryReader<'a> {
+    /// Creates a reader over the given buffer, which can be anything that can be turned in to
+    /// [Bytes], like a `Vec<u8>`, without copying it.
+    pub fn new(buf: impl Into<Bytes>) -> Self {
+        Self { buf: buf.into(), start: 0, off: 0, source: None, streamed: false }
+    }
+
+    /// Creates a reader over part of the class file that was read with this reader, which is
+    /// found at `position` in it, so that errors give offsets in to the whole class file. The
+    /// new reader can't read past the end of the part, which is how structures are kept to
+    /// their declared length, and copies small views of it like this one does.
+    pub fn bounded(&self, buf: Bytes, position: usize) -> BinaryReader<'static> {
+        BinaryReader { buf, start: position, off: 0, source: None, streamed: self.streamed }
+    }
+
+    /// Creates a reader over a copy of the given bytes. Only the one copy is made, rather than
+    /// one for every array in the class file, but it's still a copy of the whole class file,
+    /// as parsed classes keep views of it that can outlive the slice. To avoid copying, give
+    /// [Self::new] a buffer that can be shared, like a `Vec<u8>` or [Bytes::map_file].
+    pub fn from_slice(buf: &[u8]) -> Self {
+        Self::new(Bytes::copy_from_slice(buf))
+    }
+
+    /// Creates a reader that reads the class file from the given stream as it's needed, so
+    /// only what's being parsed is buffered, which is at most the largest attribute. The
+    /// stream is read until it ends, as a class file must have nothing after it.
+    ///
+    /// Errors reading from the stream are reported as [ErrorKind::Io], at the position in
+    /// the class file they happened at. Parsing is otherwise the same as for a reader over
+    /// memory, and gives the same errors.
+    pub fn from_read(source: impl Read + 'a) -> Self {
+        let source: Box<dyn Read + 'a> = Box::new(source);
+        Self { buf: Bytes::from(Vec::new()), start: 0, off: 0, source: Some(source), streamed: true }
     }
 
     // Doesn't error with EOF as this function just reads as many bytes as it can from the buffer
     pub fn read(&mut self, out: &mut [u8]) -> usize {
+        // Any error will come up again the next time something is read
+        let _ = self.fill(out.len());
         let read_len = min(self.buf.len() - self.off, out.len());
 
         // SAFETY: read_len ensures that we will only read the minimum of how many bytes are left
         // in the buffer and the size of the output. Slices are properly aligned.
         unsafe {
-            ptr::copy(self.buf.as_ptr().offset(self.off as isize), out.as_mut_ptr(), read_len);
+            ptr::copy(self.buf.as_ptr().add(self.off), out.as_mut_ptr(), read_len);
         }
 
         self.off += read_len;
         read_len
     }
 
+    /// Gives back a view of the next `len` bytes, without copying them unless they're less than
+    /// half of a stream's window, or an error naming what they are if there aren't enough left.
+    pub fn read_bytes(&mut self, len: usize, msg: impl Into<String>) -> Result<Bytes, ParseError> {
+        self.check_bytes(len, msg)?;
+        let range = self.off..self.off + len;
+        let r = match self.streamed && len < self.buf.len() / 2 {
+            true => Bytes::copy_from_slice(&self.buf[range]),
+            false => self.buf.slice(range),
+        };
+        self.off += len;
+        Ok(r)
+    }
+
     pub fn read_u8(&mut self) -> Result<u8, EndOfBufferError> {
         self.check_eof(1)?;
 
@@ -38,8 +103,9 @@ impl BinaryReader {
     pub unsafe fn unsafe_read_u8(&mut self) -> u8 {
         // SAFETY: Caller must guarantee that buffer has remaining bytes with has_bytes call
         // else behaviour is undefined
+        let r = unsafe { *self.buf.get_unchecked(self.off) };
         self.off += 1;
-        unsafe { *self.buf.get_unchecked(self.off) }
+        r
     }
 
     pub fn read_u16(&mut self) -> Result<u16, EndOfBufferError> {
@@ -99,8 +165,7 @@ impl BinaryReader {
 
         // TODO: It's probably possible to do it faster than this, but
         //  as it stands at the moment, it's not worth it.
-        let mut j = 0;
-        for i in (0..byte_len).step_by(2) {
+        for (j, i) in (0..byte_len).step_by(2).enumerate() {
             let (a, b): (u16, u16);
 
             // SAFETY: Caller must guarantee that buffer has remaining bytes
@@ -111,56 +176,118 @@ impl BinaryReader {
             }
 
             out[j] = (a << 8) | b;
-            j += 1;
         }
 
         self.off += byte_len
     }
 
-    pub const fn has_bytes(&self, num: usize) -> bool {
-        self.buf.len() < self.off + num
+    /// Whether there are at least `num` more bytes, reading them in from the stream if needed.
+    /// Errors reading from the stream are treated as having reached the end of it.
+    pub fn has_bytes(&mut self, num: usize) -> bool {
+        self.fill(num).unwrap_or(false)
     }
 
-    fn check_eof(&self, bytes: usize) -> Result<(), EndOfBufferError> {
+    /// Whether there are at least `num` more bytes, like [Self::has_bytes], but errors reading
+    /// from the stream are given back as [ErrorKind::Io], like [Self::check_bytes] does.
+    pub fn try_has_bytes(&mut self, num: usize) -> Result<bool, ParseError> {
+        self.fill(num).map_err(|err| self.error(ErrorKind::Io(err)))
+    }
+
+    /// The current offset into the class file, i.e. how many bytes have been read so far.
+    pub const fn position(&self) -> usize {
+        self.start + self.off
+    }
+
+    const fn available(&self) -> usize {
+        self.buf.len() - self.off
+    }
+
+    // Makes sure at least `num` bytes are buffered, if there are that many left, and gives back
+    // whether there are. Only what's left of the buffer is kept, along with what's read in.
+    #[inline]
+    fn fill(&mut self, num: usize) -> io::Result<bool> {
+        if self.available() >= num {
+            return Ok(true);
+        }
+        self.refill(num)
+    }
+
+    #[cold]
+    fn refill(&mut self, num: usize) -> io::Result<bool> {
+        // Put back afterwards, unless the end of it is reached
+        let Some(mut source) = self.source.take() else {
+            return Ok(false);
+        };
+
+        let mut window = Vec::with_capacity(max(self.available(), STREAM_CHUNK_SIZE));
+        window.extend_from_slice(&self.buf[self.off..]);
+
+        // Read as much as the stream has ready, but don't wait for more than is needed
+        let mut result = Ok(true);
+        while window.len() < num {
+            if window.len() == window.capacity() {
+                // Grow with what's actually read, as a bad length could be anything up to 4GB
+                window.reserve(window.len());
+            }
+            let len = window.len();
+            window.resize(window.capacity(), 0);
+
+            match source.read(&mut window[len..]) {
+                Ok(0) => {
+                    window.truncate(len);
+                    result = Ok(false);
+                    break;
+                },
+                Ok(read) => window.truncate(len + read),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => window.truncate(len),
+                Err(err) => {
+                    window.truncate(len);
+                    result = Err(err);
+                    break;
+                },
+            }
+        }
+
+        if !matches!(result, Ok(false)) {
+            self.source = Some(source);
+        }
+        self.start += self.off;
+        self.off = 0;
+        // Only keep what was read, as views of the window keep all of it alive
+        self.buf = Bytes::from_owner(window.into_boxed_slice());
+        result
+    }
+
+    pub fn skip(&mut self, num: usize) -> Result<(), EndOfBufferError> {
+        self.check_eof(num)?;
+        self.off += num;
+        Ok(())
+    }
+
+    fn check_eof(&mut self, bytes: usize) -> Result<(), EndOfBufferError> {
         match self.has_bytes(bytes) {
             true => Ok(()),
             false => Err(END_OF_BUFFER),
         }
     }
 
-    pub fn check_bytes(&self, num: usize, msg: impl Into<String>) -> Result<(), ParseError> {
-        match self.has_bytes(num) {
-            true => Ok(()),
-            false => ParseError::not_enough_bytes(msg.into())
+    #[inline]
+    pub fn check_bytes(&mut self, num: usize, msg: impl Into<String>) -> Result<(), ParseError> {
+        match self.fill(num) {
+            Ok(true) => Ok(()),
+            Ok(false) => self.error(ErrorKind::Truncated { what: msg.into() }).into(),
+            Err(err) => self.error(ErrorKind::Io(err)).into(),
         }
     }
+
+    /// Creates an error of the given kind at the current offset.
+    pub fn error(&self, kind: ErrorKind) -> ParseError {
+        ParseError::new(kind, self.position())
+    }
 }
 
 /// Allows arbitrary reader function for len to allow different u-sized lengths
 /// This is useful in code array as the size of the code array is a u32 but the array is Vec<u8>
-#[macro_export]
-macro_rules! buf_read_u8_arr_lensize {
-    ($var_name: ident, $buf: expr, $read_len: ident, $error: expr) => {
-        $buf.check_bytes(2, $error)?;
-
-        let mut $var_name: Array<u8>;
-        {
-            // SAFETY: Guaranteed by check_bytes at top
-            let len = unsafe { $buf.$read_len() };
-            $buf.check_bytes(len as usize, $error)?;
-
-            // TODO: We shouldn't wrap this. When we have proper error handling,
-            //  propagate it.
-            $var_name = Array::new(len as usize)
-                .map_err(|_| ParseError::new("cannot allocate array"))?;
-            // SAFETY: We know the method we pass it to does not perform get operations,
-            // therefore no chance of dereferencing a pointer to uninitialized memory
-            let slice = unsafe { $var_name.as_slice_mut() };
-            $buf.read(slice);
-        }
-    };
-}
-
 #[macro_export]
 macro_rules! buf_read_u16_arr {
     ($var_name: ident, $buf: expr, $error: expr) => {
@@ -172,10 +299,8 @@ macro_rules! buf_read_u16_arr {
             let len = unsafe { $buf.unsafe_read_u16() } as usize;
             $buf.check_bytes(len * 2, $error)?;
 
-            // TODO: We shouldn't wrap this. When we have proper error handling,
-            //  propagate it.
             $var_name = Array::new(len as usize)
-                .map_err(|_| ParseError::new("cannot allocate array"))?;
+                .map_err(|_| $buf.error($crate::class::parse::ErrorKind::OutOfMemory))?;
             // SAFETY: We know the method we pass it to does not perform get operations,
             // therefore no chance of dereferencing a pointer to uninitialized memory
             let slice = unsafe { $var_name.as_slice_mut() };
@@ -190,23 +315,111 @@ macro_rules! buf_read_named_type_arr {
     ($typ: ident, $var_name: ident, $buf: expr, $error: expr, $error_idx: expr) => {
         $buf.check_bytes(2, $error)?;
 
-        let mut $var_name: Array<$typ>;
+        let $var_name: Array<$typ>;
         {
             // SAFETY: Guaranteed by check_bytes at top
             let len = unsafe { $buf.unsafe_read_u16() } as usize;
 
-            // TODO: We shouldn't wrap this. When we have proper error handling,
-            //  propagate it.
-            $var_name = Array::new(len)
-                .map_err(|_| ParseError::new("cannot allocate array"))?;
-
+            // Collected in to a Vec first so that, if parsing an element fails, only the
+            // elements parsed so far are dropped, rather than the whole uninitialized array
+            let mut values = Vec::with_capacity(len);
             for i in 0..len {
                 let v = $typ::parse($buf).map_err(ParseError::wrap(format!($error_idx, i)))?;
-                $var_name.set(i, v).expect("array set was somehow out of bounds");
+                values.push(v);
             }
+            $var_name = Array::from_vec(values)
+                .map_err(|_| $buf.error($crate::class::parse::ErrorKind::OutOfMemory))?;
         }
     };
 }
 
 pub struct EndOfBufferError;
 const END_OF_BUFFER: EndOfBufferError = EndOfBufferError{};
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+    use crate::class::parse_class;
+    use crate::class::testing::*;
+    use super::*;
+
+    // A stream that fails after giving back the bytes it has
+    struct Failing<'a>(&'a [u8]);
+
+    impl Read for Failing<'_> {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("connection reset"));
+            }
+            let len = out.len().min(self.0.len());
+            out[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn stream_errors_match_memory() {
+        // A magic and version, then a constant pool that's cut off part way through
+        let bytes = [0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52, 0, 3, 1, 0, 4, b'a'];
+        let memory = parse_class(&mut BinaryReader::new(bytes.to_vec())).err().expect("should be truncated");
+        let stream = parse_class(&mut BinaryReader::from_read(&bytes[..])).err().expect("should be truncated");
+        assert!(matches!(stream.kind(), ErrorKind::Truncated { .. }));
+        assert_eq!(stream.offset(), memory.offset());
+        assert_eq!(stream.to_string(), memory.to_string());
+    }
+
+    #[test]
+    fn stream_reads_across_chunks() {
+        let bytes: Vec<u8> = (0..STREAM_CHUNK_SIZE * 3).map(|i| i as u8).collect();
+        let mut reader = BinaryReader::from_read(&bytes[..]);
+        reader.skip(STREAM_CHUNK_SIZE - 1).ok().expect("should skip");
+
+        // Straddles the end of the first chunk, and is longer than a chunk
+        let read = reader.read_bytes(STREAM_CHUNK_SIZE + 2, "bytes").expect("should read");
+        let next = STREAM_CHUNK_SIZE * 2 + 1;
+        assert_eq!(read.as_slice(), &bytes[STREAM_CHUNK_SIZE - 1..next]);
+        assert_eq!(reader.position(), next);
+        assert_eq!(reader.read_u16().ok(), Some(u16::from_be_bytes([bytes[next], bytes[next + 1]])));
+        assert!(!reader.has_bytes(STREAM_CHUNK_SIZE));
+        assert!(reader.has_bytes(STREAM_CHUNK_SIZE - 3));
+    }
+
+    #[test]
+    fn stream_views_only_keep_what_they_need() {
+        let bytes: Vec<u8> = (0..STREAM_CHUNK_SIZE * 3).map(|i| i as u8).collect();
+        let mut reader = BinaryReader::from_read(&bytes[..]);
+        let small = reader.read_bytes(4, "small").expect("should read");
+        assert_eq!(small.as_slice(), &bytes[..4]);
+        assert_eq!(small.buffer_len(), 4);
+
+        // Most of the window it's in, which it shares instead
+        let large = reader.read_bytes(STREAM_CHUNK_SIZE * 2, "large").expect("should read");
+        assert_eq!(large.as_slice(), &bytes[4..STREAM_CHUNK_SIZE * 2 + 4]);
+        assert!(large.buffer_len() < large.len() * 2);
+
+        // Views of memory are never copied
+        let mut reader = BinaryReader::new(bytes.clone());
+        assert_eq!(reader.read_bytes(4, "small").expect("should read").buffer_len(), bytes.len());
+    }
+
+    #[test]
+    fn stream_failure_is_an_io_error() {
+        let mut reader = BinaryReader::from_read(Failing(&[1, 2, 3]));
+        reader.check_bytes(2, "first").expect("should have the first bytes");
+        assert_eq!(reader.read_u16().ok(), Some(0x0102));
+
+        let err = reader.check_bytes(2, "second").expect_err("should fail");
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+        assert_eq!(err.offset(), 2);
+    }
+
+    #[test]
+    fn stream_failure_after_the_class_is_an_io_error() {
+        let bytes = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER).build(69, 0);
+        let err = parse_class(&mut BinaryReader::from_read(Failing(&bytes))).err()
+            .expect("should fail checking for extra bytes");
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+        assert_eq!(err.offset(), bytes.len());
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use crate::class::Class;
+use crate::class::method::Method;
+use crate::loader::classfile::attribute::{annotations, record, type_annotations, ClassFileAttribute, RecordAttribute};
+use crate::types::Symbol;
+
+/// The reflection data for a single component of a record class, with everything it refers
+/// to resolved, as `Class.getRecordComponents()` needs.
+pub struct RecordComponentInfo<'a> {
+    component: &'a record::Component,
+    name: Symbol,
+    descriptor: Symbol,
+    signature: Option<Symbol>,
+    accessor: Option<&'a Method>,
+}
+
+impl<'a> RecordComponentInfo<'a> {
+    pub fn name(&self) -> Symbol {
+        self.name
+    }
+
+    pub fn descriptor(&self) -> Symbol {
+        self.descriptor
+    }
+
+    /// The component's generic signature, if it has one.
+    pub fn signature(&self) -> Option<Symbol> {
+        self.signature
+    }
+
+    /// The method that returns the value of the component, which is the public instance method
+    /// with the same name as the component, no parameters, and the component's type as its
+    /// return type. Only `None` if the class file is inconsistent.
+    pub fn accessor(&self) -> Option<&'a Method> {
+        self.accessor
+    }
+
+    pub fn annotations(&self) -> Option<&'a annotations::RuntimeVisible> {
+        self.component.attributes().iter().find_map(|attr| match attr {
+            RecordAttribute::RuntimeVisibleAnnotations(annotations) => Some(annotations.attribute()),
+            _ => None,
+        })
+    }
+
+    pub fn invisible_annotations(&self) -> Option<&'a annotations::RuntimeInvisible> {
+        self.component.attributes().iter().find_map(|attr| match attr {
+            RecordAttribute::RuntimeInvisibleAnnotations(annotations) => Some(annotations.attribute()),
+            _ => None,
+        })
+    }
+
+    pub fn type_annotations(&self) -> Option<&'a type_annotations::RuntimeVisible> {
+        self.component.attributes().iter().find_map(|attr| match attr {
+            RecordAttribute::RuntimeVisibleTypeAnnotations(annotations) => Some(annotations.attribute()),
+            _ => None,
+        })
+    }
+
+    pub fn invisible_type_annotations(&self) -> Option<&'a type_annotations::RuntimeInvisible> {
+        self.component.attributes().iter().find_map(|attr| match attr {
+            RecordAttribute::RuntimeInvisibleTypeAnnotations(annotations) => Some(annotations.attribute()),
+            _ => None,
+        })
+    }
+
+    pub fn component(&self) -> &'a record::Component {
+        self.component
+    }
+}
+
+impl Class {
+    /// The components of this class, in declaration order, or `None` if it isn't a record
+    /// class.
+    pub fn record_components(&self) -> Option<impl Iterator<Item = RecordComponentInfo<'_>>> {
+        let record = self.attributes().iter().find_map(|attr| match attr {
+            ClassFileAttribute::Record(record) => Some(record),
+            _ => None,
+        })?;
+        Some(record.components().iter().map(|component| self.record_component_info(component)))
+    }
+
+    fn record_component_info<'a>(&'a self, component: &'a record::Component) -> RecordComponentInfo<'a> {
+        let pool = self.constant_pool();
+        // Resolving a Utf8 entry can't fail, and the components are only parsed if these are
+        // Utf8 entries, so these can't panic
+        let name = pool.resolve_utf8(component.name_index())
+            .expect("record component name is checked when parsed").symbol();
+        let descriptor = pool.resolve_utf8(component.descriptor_index())
+            .expect("record component descriptor is checked when parsed").symbol();
+
+        let signature = component.attributes().iter()
+            .find_map(|attr| match attr {
+                RecordAttribute::Signature(signature) => Some(signature.signature_index()),
+                _ => None,
+            })
+            .and_then(|index| pool.resolve_utf8(index))
+            .map(|signature| signature.symbol());
+
+        let accessor = self.methods().iter().find(|method| {
+            let flags = method.access_flags();
+            flags.is_public() && !flags.is_static() && method.name() == name &&
+                method.descriptor().as_str().strip_prefix("()") == Some(descriptor.as_str())
+        });
+
+        RecordComponentInfo { component, name, descriptor, signature, accessor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::Class;
+    use crate::class::constantpool::Tag;
+    use crate::class::parse::ErrorKind;
+    use crate::class::testing::*;
+
+    // A record with the components `int x` and `List<String> names`, and the given methods
+    fn record(methods: &[(u16, &str, &str)]) -> Class {
+        let mut builder = ClassBuilder::new("Point", ACC_PUBLIC | ACC_FINAL | ACC_SUPER);
+        let signature = builder.utf8("Ljava/util/List<Ljava/lang/String;>;");
+        let signature = builder.attribute("Signature", &u16_bytes(signature));
+        let components = [
+            [u16_bytes(builder.utf8("x")), u16_bytes(builder.utf8("I")), table(&[])].concat(),
+            [u16_bytes(builder.utf8("names")), u16_bytes(builder.utf8("Ljava/util/List;")), table(&[signature])].concat(),
+        ];
+        builder.attributes = vec![builder.attribute("Record", &table(&components))];
+        for &(flags, name, descriptor) in methods {
+            let code = builder.code(1, 1, &[0x03, 0xAC], &[]);
+            let method = builder.member(flags, name, descriptor, &[code]);
+            builder.methods.push(method);
+        }
+        builder.parse(69).expect("class should parse")
+    }
+
+    #[test]
+    fn resolves_components() {
+        let class = record(&[(ACC_PUBLIC, "x", "()I")]);
+        let components: Vec<_> = class.record_components().expect("class should be a record").collect();
+        let [x, names] = &components[..] else {
+            panic!("expected two components");
+        };
+
+        assert_eq!((x.name().as_str(), x.descriptor().as_str()), ("x", "I"));
+        assert!(x.signature().is_none());
+        assert_eq!(x.accessor().map(|method| method.descriptor().as_str()), Some("()I"));
+        assert_eq!(names.signature().map(|signature| signature.as_str()), Some("Ljava/util/List<Ljava/lang/String;>;"));
+        assert!(names.accessor().is_none());
+    }
+
+    #[test]
+    fn accessor_must_be_public_instance_method() {
+        let class = record(&[
+            (ACC_PUBLIC | ACC_STATIC, "x", "()I"),
+            (ACC_PRIVATE, "x", "()I"),
+            (ACC_PUBLIC, "x", "(I)I"),
+        ]);
+        let x = class.record_components().and_then(|mut components| components.next()).expect("class should be a record");
+        assert!(x.accessor().is_none());
+
+        let class = record(&[(ACC_PRIVATE, "x", "()I"), (ACC_PUBLIC, "x", "()I")]);
+        let x = class.record_components().and_then(|mut components| components.next()).expect("class should be a record");
+        assert!(x.accessor().is_some_and(|method| method.access_flags().is_public()));
+    }
+
+    #[test]
+    fn component_annotations() {
+        let mut builder = ClassBuilder::new("Point", ACC_PUBLIC | ACC_FINAL | ACC_SUPER);
+        let names = ["LVisible;", "LInvisible;", "LVisibleType;", "LInvisibleType;"].map(|name| builder.utf8(name));
+        // One annotation with no elements, and a type annotation on the component's own type
+        let annotation = |index| [u16_bytes(1), u16_bytes(index), u16_bytes(0)].concat();
+        let type_annotation = |index| [vec![0, 1, 0x13, 0], u16_bytes(index), u16_bytes(0)].concat();
+        let attributes = [
+            builder.attribute("RuntimeVisibleAnnotations", &annotation(names[0])),
+            builder.attribute("RuntimeInvisibleAnnotations", &annotation(names[1])),
+            builder.attribute("RuntimeVisibleTypeAnnotations", &type_annotation(names[2])),
+            builder.attribute("RuntimeInvisibleTypeAnnotations", &type_annotation(names[3])),
+        ];
+        let component = [u16_bytes(builder.utf8("x")), u16_bytes(builder.utf8("I")), table(&attributes)].concat();
+        builder.attributes = vec![builder.attribute("Record", &table(&[component]))];
+        let class = builder.parse(69).expect("class should parse");
+        let x = class.record_components().and_then(|mut components| components.next()).expect("class should be a record");
+
+        let visible = x.annotations().expect("should have annotations").annotations();
+        let invisible = x.invisible_annotations().expect("should have invisible annotations").annotations();
+        let visible_type = x.type_annotations().expect("should have type annotations").annotations();
+        let invisible_type = x.invisible_type_annotations().expect("should have invisible type annotations").annotations();
+        let types = [visible[0].type_index(), invisible[0].type_index(),
+            visible_type[0].annotation().type_index(), invisible_type[0].annotation().type_index()];
+        assert_eq!(types, names);
+
+        let class = record(&[]);
+        let x = class.record_components().and_then(|mut components| components.next()).expect("class should be a record");
+        assert!(x.invisible_type_annotations().is_none());
+    }
+
+    #[test]
+    fn not_a_record() {
+        let class = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER).parse(69).expect("class should parse");
+        assert!(class.record_components().is_none());
+    }
+
+    #[test]
+    fn component_names_must_be_utf8() {
+        let mut builder = ClassBuilder::new("Point", ACC_PUBLIC | ACC_FINAL | ACC_SUPER);
+        // The reflection data relies on this being checked when parsed
+        let component = [u16_bytes(builder.this_class), u16_bytes(builder.utf8("I")), table(&[])].concat();
+        builder.attributes = vec![builder.attribute("Record", &table(&[component]))];
+        let err = builder.parse(69).err().expect("component name should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { expected: Tag::Utf8, .. }));
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers for building class files by hand in tests, so that every kind of structure can be
+//! covered without needing a compiler.
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+use crate::class::parse::{BinaryReader, ParseError};
+use crate::class::{parse_class, Class};
+
+pub const UTF8: u8 = 1;
+pub const INTEGER: u8 = 3;
+pub const FLOAT: u8 = 4;
+pub const LONG: u8 = 5;
+pub const DOUBLE: u8 = 6;
+pub const CLASS: u8 = 7;
+pub const STRING: u8 = 8;
+pub const FIELD_REF: u8 = 9;
+pub const METHOD_REF: u8 = 10;
+pub const INTERFACE_METHOD_REF: u8 = 11;
+pub const NAME_AND_TYPE: u8 = 12;
+pub const METHOD_HANDLE: u8 = 15;
+pub const METHOD_TYPE: u8 = 16;
+pub const DYNAMIC: u8 = 17;
+pub const INVOKE_DYNAMIC: u8 = 18;
+pub const MODULE: u8 = 19;
+pub const PACKAGE: u8 = 20;
+
+pub const ACC_PUBLIC: u16 = 0x0001;
+pub const ACC_PRIVATE: u16 = 0x0002;
+pub const ACC_STATIC: u16 = 0x0008;
+pub const ACC_FINAL: u16 = 0x0010;
+pub const ACC_SUPER: u16 = 0x0020;
+pub const ACC_INTERFACE: u16 = 0x0200;
+pub const ACC_ABSTRACT: u16 = 0x0400;
+pub const ACC_MODULE: u16 = 0x8000;
+
+pub struct ClassBuilder {
+    pool: Vec<u8>,
+    pool_count: u16,
+    utf8s: HashMap<String, u16>,
+    pub flags: u16,
+    pub this_class: u16,
+    pub super_class: u16,
+    pub interfaces: Vec<u16>,
+    pub fields: Vec<Vec<u8>>,
+    pub methods: Vec<Vec<u8>>,
+    pub attributes: Vec<Vec<u8>>,
+}
+
+impl ClassBuilder {
+    pub fn new(name: &str, flags: u16) -> Self {
+        let mut builder = ClassBuilder {
+            pool: Vec::new(),
+            pool_count: 1,
+            utf8s: HashMap::new(),
+            flags,
+            this_class: 0,
+            super_class: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: Vec::new(),
+        };
+        builder.this_class = builder.class(name);
+        builder.super_class = builder.class("java/lang/Object");
+        builder
+    }
+
+    pub fn entry(&mut self, tag: u8, body: &[u8]) -> u16 {
+        let index = self.pool_count;
+        self.pool.push(tag);
+        self.pool.extend_from_slice(body);
+        // Longs and doubles take up two entries
+        self.pool_count += if tag == LONG || tag == DOUBLE { 2 } else { 1 };
+        index
+    }
+
+    /// Adds a utf8 entry, or reuses the one that's already been added for the same value.
+    pub fn utf8(&mut self, value: &str) -> u16 {
+        if let Some(&index) = self.utf8s.get(value) {
+            return index;
+        }
+        let index = self.raw_utf8(value.as_bytes());
+        self.utf8s.insert(value.into(), index);
+        index
+    }
+
+    /// Always adds a new utf8 entry with exactly the given bytes, which don't have to be valid.
+    pub fn raw_utf8(&mut self, bytes: &[u8]) -> u16 {
+        let mut body = u16_bytes(bytes.len() as u16);
+        body.extend_from_slice(bytes);
+        self.entry(UTF8, &body)
+    }
+
+    pub fn class(&mut self, name: &str) -> u16 {
+        let name = self.utf8(name);
+        self.entry(CLASS, &u16_bytes(name))
+    }
+
+    pub fn name_and_type(&mut self, name: &str, descriptor: &str) -> u16 {
+        let body = [u16_bytes(self.utf8(name)), u16_bytes(self.utf8(descriptor))].concat();
+        self.entry(NAME_AND_TYPE, &body)
+    }
+
+    /// Adds a field, method or interface method ref, depending on the tag.
+    pub fn member_ref(&mut self, tag: u8, class: u16, name: &str, descriptor: &str) -> u16 {
+        let name_and_type = self.name_and_type(name, descriptor);
+        self.entry(tag, &[u16_bytes(class), u16_bytes(name_and_type)].concat())
+    }
+
+    pub fn attribute(&mut self, name: &str, body: &[u8]) -> Vec<u8> {
+        let mut bytes = u16_bytes(self.utf8(name));
+        bytes.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// A `Code` attribute with no exception handlers.
+    pub fn code(&mut self, max_stack: u16, max_locals: u16, code: &[u8], attributes: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = [u16_bytes(max_stack), u16_bytes(max_locals)].concat();
+        body.extend_from_slice(&(code.len() as u32).to_be_bytes());
+        body.extend_from_slice(code);
+        body.extend(table(&[]));
+        body.extend(table(attributes));
+        self.attribute("Code", &body)
+    }
+
+    pub fn member(&mut self, flags: u16, name: &str, descriptor: &str, attributes: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = u16_bytes(flags);
+        bytes.extend(u16_bytes(self.utf8(name)));
+        bytes.extend(u16_bytes(self.utf8(descriptor)));
+        bytes.extend(table(attributes));
+        bytes
+    }
+
+    pub fn build(&self, major: u16, minor: u16) -> Vec<u8> {
+        let mut bytes = 0xCAFEBABE_u32.to_be_bytes().to_vec();
+        bytes.extend(u16_bytes(minor));
+        bytes.extend(u16_bytes(major));
+        bytes.extend(u16_bytes(self.pool_count));
+        bytes.extend_from_slice(&self.pool);
+        bytes.extend(u16_bytes(self.flags));
+        bytes.extend(u16_bytes(self.this_class));
+        bytes.extend(u16_bytes(self.super_class));
+        bytes.extend(u16_bytes(self.interfaces.len() as u16));
+        for &interface in &self.interfaces {
+            bytes.extend(u16_bytes(interface));
+        }
+        bytes.extend(table(&self.fields));
+        bytes.extend(table(&self.methods));
+        bytes.extend(table(&self.attributes));
+        bytes
+    }
+
+    pub fn parse(&self, major: u16) -> Result<Class, ParseError> {
+        parse_class(&mut BinaryReader::new(self.build(major, 0)))
+    }
+}
+
+pub fn u16_bytes(v: u16) -> Vec<u8> {
+    v.to_be_bytes().to_vec()
+}
+
+/// A u2 count followed by the already serialized structures.
+pub fn table(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = u16_bytes(items.len() as u16);
+    for item in items {
+        bytes.extend_from_slice(item);
+    }
+    bytes
+}
+
+/// Gives back a few bytes at a time, to make sure nothing depends on reads lining up.
+pub struct Trickle<'a>(pub &'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let len = out.len().min(self.0.len()).min(3);
+        out[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Ok(len)
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+mod writer;
+
+pub use writer::BinaryWriter;
+
+#[cfg(test)]
+mod tests {
+    use crate::class::parse::BinaryReader;
+    use crate::class::testing::*;
+    use crate::class::{parse_class, write_class};
+
+    fn assert_round_trips(bytes: Vec<u8>) {
+        let class = parse_class(&mut BinaryReader::new(bytes.clone())).expect("class should parse");
+        assert_eq!(write_class(&class), bytes);
+
+        let class = parse_class(&mut BinaryReader::from_read(Trickle(&bytes))).expect("class should parse from a stream");
+        assert_eq!(write_class(&class), bytes);
+    }
+
+    #[test]
+    fn empty_class() {
+        let builder = ClassBuilder::new("Empty", ACC_PUBLIC | ACC_SUPER);
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn interfaces_and_wide_constants() {
+        let mut builder = ClassBuilder::new("Constants", ACC_PUBLIC | ACC_SUPER);
+        let runnable = builder.class("java/lang/Runnable");
+        let cloneable = builder.class("java/lang/Cloneable");
+        builder.interfaces = vec![runnable, cloneable];
+
+        builder.entry(INTEGER, &(-5_i32).to_be_bytes());
+        builder.entry(FLOAT, &1.5_f32.to_be_bytes());
+        let long = builder.entry(LONG, &i64::MAX.to_be_bytes());
+        builder.entry(DOUBLE, &f64::NAN.to_bits().to_be_bytes());
+        let string = builder.utf8("hello");
+        builder.entry(STRING, &u16_bytes(string));
+
+        let constant = builder.attribute("ConstantValue", &u16_bytes(long));
+        let field = builder.member(ACC_STATIC | ACC_FINAL, "MAX", "J", &[constant]);
+        builder.fields.push(field);
+        assert_round_trips(builder.build(52, 0));
+    }
+
+    #[test]
+    fn unknown_attributes() {
+        let mut builder = ClassBuilder::new("Unknown", ACC_SUPER);
+        let field_attr = builder.attribute("com.example.Custom", &[1, 2, 3]);
+        let field = builder.member(0, "value", "I", &[field_attr]);
+        builder.fields.push(field);
+
+        // An empty unknown attribute, and one that's only known in later versions
+        let empty = builder.attribute("Empty", &[]);
+        let class = builder.class("Nest");
+        let nest_host = builder.attribute("NestHost", &u16_bytes(class));
+        builder.attributes = vec![empty, nest_host];
+        assert_round_trips(builder.build(50, 0));
+    }
+
+    #[test]
+    fn duplicate_attribute_names() {
+        let mut builder = ClassBuilder::new("Duplicates", ACC_PUBLIC | ACC_SUPER);
+        let code = builder.code(0, 0, &[0xB1], &[]);
+        let method = builder.member(ACC_STATIC, "first", "()V", &[code]);
+        builder.methods.push(method);
+
+        // The second method's Code attribute is named by a second Utf8 entry with the same value
+        let second_name = builder.raw_utf8(b"Code");
+        let mut code = builder.code(0, 0, &[0xB1], &[]);
+        code[0..2].copy_from_slice(&u16_bytes(second_name));
+        let method = builder.member(ACC_STATIC, "second", "()V", &[code]);
+        builder.methods.push(method);
+
+        let source = builder.utf8("Duplicates.java");
+        let source_file = builder.attribute("SourceFile", &u16_bytes(source));
+        builder.attributes = vec![source_file];
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn annotations() {
+        let mut builder = ClassBuilder::new("Annotated", ACC_PUBLIC | ACC_SUPER);
+        let annotation_type = builder.utf8("LAnno;");
+        let int = builder.entry(INTEGER, &7_i32.to_be_bytes());
+        let string = builder.utf8("text");
+        let enum_type = builder.utf8("LColour;");
+        let enum_name = builder.utf8("RED");
+        let class_name = builder.utf8("Ljava/lang/String;");
+
+        // One element for each kind of value, with an array holding a nested annotation
+        let mut values = Vec::new();
+        for tag in [b'B', b'C', b'I', b'S', b'Z'] {
+            values.push([vec![tag], u16_bytes(int)].concat());
+        }
+        values.push([vec![b's'], u16_bytes(string)].concat());
+        values.push([vec![b'e'], u16_bytes(enum_type), u16_bytes(enum_name)].concat());
+        values.push([vec![b'c'], u16_bytes(class_name)].concat());
+        let nested = [vec![b'@'], u16_bytes(annotation_type), u16_bytes(0)].concat();
+        values.push([vec![b'['], u16_bytes(2), nested.clone(), nested].concat());
+
+        let mut elements = Vec::new();
+        for (i, value) in values.into_iter().enumerate() {
+            let name = builder.utf8(&format!("e{i}"));
+            elements.push([u16_bytes(name), value].concat());
+        }
+        let annotation = [u16_bytes(annotation_type), table(&elements)].concat();
+
+        let visible = builder.attribute("RuntimeVisibleAnnotations", &table(std::slice::from_ref(&annotation)));
+        let invisible = builder.attribute("RuntimeInvisibleAnnotations", &table(&[]));
+        builder.attributes = vec![visible, invisible];
+
+        // Parameter annotations have a u1 count of parameters
+        let parameters = [vec![2], table(std::slice::from_ref(&annotation)), table(&[])].concat();
+        let parameter_annotations = builder.attribute("RuntimeVisibleParameterAnnotations", &parameters);
+        let default = builder.attribute("AnnotationDefault", &[vec![b'I'], u16_bytes(int)].concat());
+        let method = builder.member(ACC_PUBLIC | ACC_ABSTRACT, "run", "(II)V", &[parameter_annotations, default]);
+        builder.methods.push(method);
+        builder.flags |= ACC_ABSTRACT;
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn code() {
+        let mut builder = ClassBuilder::new("Code", ACC_PUBLIC | ACC_SUPER);
+        let exception = builder.class("java/lang/Exception");
+        let name = builder.utf8("x");
+        let descriptor = builder.utf8("Ljava/util/List;");
+        let signature = builder.utf8("Ljava/util/List<Ljava/lang/String;>;");
+
+        let line_numbers = builder.attribute("LineNumberTable",
+            &table(&[[u16_bytes(0), u16_bytes(10)].concat(), [u16_bytes(4), u16_bytes(11)].concat()]));
+        let local_variables = builder.attribute("LocalVariableTable",
+            &table(&[[u16_bytes(0), u16_bytes(8), u16_bytes(name), u16_bytes(descriptor), u16_bytes(0)].concat()]));
+        let local_variable_types = builder.attribute("LocalVariableTypeTable",
+            &table(&[[u16_bytes(0), u16_bytes(8), u16_bytes(name), u16_bytes(signature), u16_bytes(0)].concat()]));
+
+        // One of each kind of frame, along with every verification type
+        let object = [vec![7], u16_bytes(exception)].concat();
+        let frames = vec![
+            vec![3],
+            [vec![64], object.clone()].concat(),
+            [vec![247], u16_bytes(1), vec![8], u16_bytes(0)].concat(),
+            [vec![249], u16_bytes(1)].concat(),
+            [vec![251], u16_bytes(1)].concat(),
+            [vec![253], u16_bytes(1), vec![1, 2]].concat(),
+            [vec![255], u16_bytes(1), table(&[vec![0], vec![3], vec![4], object]), table(&[vec![5], vec![6]])].concat(),
+        ];
+        let stack_map = builder.attribute("StackMapTable", &table(&frames));
+
+        // Type annotations with targets that have differently shaped info
+        let annotation_type = builder.utf8("LAnno;");
+        let annotation = [u16_bytes(annotation_type), u16_bytes(0)].concat();
+        let path = vec![2, 3, 0, 0, 1];
+        let type_annotations = vec![
+            [vec![0x40], table(&[[u16_bytes(0), u16_bytes(8), u16_bytes(0)].concat()]), path, annotation.clone()].concat(),
+            [vec![0x42], u16_bytes(0), vec![0], annotation.clone()].concat(),
+            [vec![0x43], u16_bytes(2), vec![0], annotation.clone()].concat(),
+            [vec![0x47], u16_bytes(2), vec![1], vec![0], annotation].concat(),
+        ];
+        let type_annotations = builder.attribute("RuntimeVisibleTypeAnnotations", &table(&type_annotations));
+
+        let code = [0x2A, 0x4C, 0x00, 0x00, 0x2B, 0x57, 0x00, 0xB1];
+        let exception_table = [u16_bytes(0), u16_bytes(4), u16_bytes(4), u16_bytes(exception)].concat();
+        let mut body = [u16_bytes(2), u16_bytes(2), (code.len() as u32).to_be_bytes().to_vec(), code.to_vec()].concat();
+        body.extend(table(&[exception_table]));
+        body.extend(table(&[line_numbers, local_variables, local_variable_types, stack_map, type_annotations]));
+        let code = builder.attribute("Code", &body);
+
+        let exceptions = builder.attribute("Exceptions", &table(&[u16_bytes(exception)]));
+        let parameter_name = builder.utf8("arg");
+        let parameters = builder.attribute("MethodParameters", &[vec![1], u16_bytes(parameter_name), u16_bytes(ACC_FINAL)].concat());
+        let method = builder.member(ACC_PUBLIC, "run", "(I)V", &[code, exceptions, parameters]);
+        builder.methods.push(method);
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn class_attributes() {
+        let mut builder = ClassBuilder::new("Outer", ACC_PUBLIC | ACC_FINAL | ACC_SUPER);
+        let source = builder.utf8("Outer.java");
+        let source_file = builder.attribute("SourceFile", &u16_bytes(source));
+        let debug_extension = builder.attribute("SourceDebugExtension", b"SMAP\nOuter.java\n");
+        let signature = builder.utf8("<T:Ljava/lang/Object;>Ljava/lang/Object;");
+        let signature = builder.attribute("Signature", &u16_bytes(signature));
+        let deprecated = builder.attribute("Deprecated", &[]);
+        let synthetic = builder.attribute("Synthetic", &[]);
+
+        let inner = builder.class("Outer$Inner");
+        let inner_name = builder.utf8("Inner");
+        let this_class = builder.this_class;
+        let inner_classes = builder.attribute("InnerClasses",
+            &table(&[[u16_bytes(inner), u16_bytes(this_class), u16_bytes(inner_name), u16_bytes(ACC_STATIC)].concat()]));
+        let nest_members = builder.attribute("NestMembers", &table(&[u16_bytes(inner)]));
+        let permitted = builder.attribute("PermittedSubclasses", &table(&[u16_bytes(inner)]));
+
+        // A bootstrap method for an invokedynamic, with a method type as its static argument
+        let object = builder.super_class;
+        let bootstrap_type = builder.name_and_type("bootstrap", "()V");
+        let bootstrap_ref = builder.entry(METHOD_REF, &[u16_bytes(object), u16_bytes(bootstrap_type)].concat());
+        let handle = builder.entry(METHOD_HANDLE, &[vec![6], u16_bytes(bootstrap_ref)].concat());
+        let method_descriptor = builder.utf8("()V");
+        let method_type = builder.entry(METHOD_TYPE, &u16_bytes(method_descriptor));
+        let call_site = builder.name_and_type("run", "()Ljava/lang/Runnable;");
+        builder.entry(INVOKE_DYNAMIC, &[u16_bytes(0), u16_bytes(call_site)].concat());
+        let bootstrap_methods = builder.attribute("BootstrapMethods",
+            &table(&[[u16_bytes(handle), table(&[u16_bytes(method_type)])].concat()]));
+
+        builder.attributes = vec![source_file, debug_extension, signature, deprecated, synthetic, inner_classes,
+                                  nest_members, permitted, bootstrap_methods];
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn enclosing_method_and_record() {
+        let mut builder = ClassBuilder::new("Outer$1Point", ACC_FINAL | ACC_SUPER);
+        builder.super_class = builder.class("java/lang/Record");
+        let outer = builder.class("Outer");
+        let method = builder.name_and_type("run", "()V");
+        let enclosing_method = builder.attribute("EnclosingMethod", &[u16_bytes(outer), u16_bytes(method)].concat());
+
+        let signature = builder.utf8("TT;");
+        let component_signature = builder.attribute("Signature", &u16_bytes(signature));
+        let x = builder.utf8("x");
+        let int = builder.utf8("I");
+        let y = builder.utf8("y");
+        let object = builder.utf8("Ljava/lang/Object;");
+        let components = [
+            [u16_bytes(x), u16_bytes(int), table(&[])].concat(),
+            [u16_bytes(y), u16_bytes(object), table(&[component_signature])].concat(),
+        ];
+        let record = builder.attribute("Record", &table(&components));
+        builder.attributes = vec![enclosing_method, record];
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn module() {
+        let mut builder = ClassBuilder::new("module-info", ACC_MODULE);
+        builder.super_class = 0;
+
+        let module_name = builder.utf8("com.example");
+        let module = builder.entry(MODULE, &u16_bytes(module_name));
+        let base_name = builder.utf8("java.base");
+        let base = builder.entry(MODULE, &u16_bytes(base_name));
+        let version = builder.utf8("25");
+        let package_name = builder.utf8("com/example/api");
+        let package = builder.entry(PACKAGE, &u16_bytes(package_name));
+        let service = builder.class("com/example/api/Service");
+        let provider = builder.class("com/example/impl/ServiceImpl");
+
+        let mut body = [u16_bytes(module), u16_bytes(0x0020), u16_bytes(0)].concat();
+        body.extend(table(&[[u16_bytes(base), u16_bytes(0x8000), u16_bytes(version)].concat()]));
+        body.extend(table(&[[u16_bytes(package), u16_bytes(0), table(&[u16_bytes(base)])].concat()]));
+        body.extend(table(&[[u16_bytes(package), u16_bytes(0x1000), table(&[])].concat()]));
+        body.extend(table(&[u16_bytes(service)]));
+        body.extend(table(&[[u16_bytes(service), table(&[u16_bytes(provider)])].concat()]));
+        let module = builder.attribute("Module", &body);
+        let packages = builder.attribute("ModulePackages", &table(&[u16_bytes(package)]));
+        let main_class = builder.attribute("ModuleMainClass", &u16_bytes(provider));
+        builder.attributes = vec![module, packages, main_class];
+        assert_round_trips(builder.build(69, 0));
+    }
+
+    #[test]
+    fn old_interface_keeps_its_flags() {
+        // Interfaces before Java 6 are treated as abstract, but it mustn't be written out
+        let builder = ClassBuilder::new("Old", ACC_PUBLIC | ACC_INTERFACE);
+        assert_round_trips(builder.build(49, 0));
+    }
+}
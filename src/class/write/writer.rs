@@ -0,0 +1,90 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use crate::loader::Serialize;
+
+/// Builds up a class file in memory, the counterpart to
+/// [BinaryReader](crate::class::parse::BinaryReader). Everything is written big-endian.
+pub struct BinaryWriter {
+    buf: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { buf: Vec::with_capacity(capacity) }
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn write_u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_be_bytes());
+    }
+
+    pub fn write_u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_be_bytes());
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Writes a u2 count, followed by each of the values.
+    pub fn write_u16_arr(&mut self, values: &[u16]) {
+        self.write_u16(values.len() as u16);
+        for &v in values {
+            self.write_u16(v);
+        }
+    }
+
+    /// Writes a u2 count, followed by each of the structures.
+    pub fn write_arr<T: Serialize>(&mut self, values: &[T]) {
+        self.write_u16(values.len() as u16);
+        for v in values {
+            v.serialize(self);
+        }
+    }
+
+    /// Writes a u4 length, followed by whatever `body` writes, which is what the length is
+    /// of. The length isn't known until the body has been written, so it's filled in after.
+    pub fn write_u32_len_prefixed(&mut self, body: impl FnOnce(&mut Self)) {
+        let start = self.buf.len();
+        self.write_u32(0);
+        body(self);
+
+        let len = (self.buf.len() - start - 4) as u32;
+        self.buf[start..start + 4].copy_from_slice(&len.to_be_bytes());
+    }
+
+    /// How many bytes have been written so far.
+    pub fn position(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Default for BinaryWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,425 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Describing the parts of a class the same way in both the text and JSON output.
+
+use crate::class::bytecode::{ArrayType, Instruction, LookupSwitch, TableSwitch, WideInstruction};
+use crate::class::constantpool::{Index, Pool, Tag, Utf8Info};
+use crate::loader::classfile::attribute::annotations::{Annotation, ElementValue, ResolvedValue};
+use crate::loader::classfile::attribute::stackmap::{Frame, VerificationType};
+use crate::loader::classfile::attribute::type_annotations::{TargetInfo, TypeAnnotation};
+use crate::types::methodhandle;
+
+/// Resolves constant pool entries in to the text that's shown for them. Nothing here fails on
+/// an index that's out of range or of the wrong type, as showing broken classes is half the
+/// point, so they're shown as `<invalid #n>` instead.
+#[derive(Copy, Clone)]
+pub struct Constants<'a> {
+    pool: &'a Pool,
+}
+
+impl<'a> Constants<'a> {
+    pub fn new(pool: &'a Pool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &'a Pool {
+        self.pool
+    }
+
+    /// The indexes of all the usable entries, skipping the slots after longs and doubles.
+    pub fn indexes(&self) -> impl Iterator<Item = Index> + 'a {
+        let pool = self.pool;
+        (1..pool.size()).filter(move |&index| pool.tag(index).is_some())
+    }
+
+    pub fn utf8(&self, index: Index) -> Option<&'a str> {
+        self.pool.resolve_utf8(index).map(Utf8Info::as_str)
+    }
+
+    pub fn class_name(&self, index: Index) -> Option<&'a str> {
+        self.utf8(self.pool.get_unresolved_class(index)?.name_index())
+    }
+
+    fn name_and_type(&self, index: Index) -> Option<(&'a str, &'a str)> {
+        let nat = self.pool.get_unresolved_name_and_type(index)?;
+        Some((self.utf8(nat.name_index())?, self.utf8(nat.descriptor_index())?))
+    }
+
+    fn member(&self, class_index: Index, nat_index: Index) -> Option<String> {
+        let class = self.class_name(class_index)?;
+        let (name, descriptor) = self.name_and_type(nat_index)?;
+        Some(format!("{class}.{}:{descriptor}", quote_special(name)))
+    }
+
+    /// Like [Constants::utf8], but for showing, so it's never missing and is escaped.
+    pub fn utf8_text(&self, index: Index) -> String {
+        self.utf8(index).map(escape).unwrap_or_else(|| invalid(index))
+    }
+
+    pub fn class_text(&self, index: Index) -> String {
+        self.class_name(index).map(str::to_string).unwrap_or_else(|| invalid(index))
+    }
+
+    /// The indexes the entry refers to, such as `#2.#3` for a method reference, or its value
+    /// for entries that don't refer to any.
+    pub fn references(&self, index: Index) -> String {
+        let pool = self.pool;
+        let references = match pool.tag(index) {
+            Some(Tag::Class) => pool.get_unresolved_class(index).map(|info| format!("#{}", info.name_index())),
+            Some(Tag::String) => pool.get_unresolved_string(index).map(|info| format!("#{}", info.string_index())),
+            Some(Tag::Fieldref) => pool.get_field_ref(index)
+                .map(|info| format!("#{}.#{}", info.class_index(), info.name_and_type_index())),
+            Some(Tag::Methodref) => pool.get_method_ref(index)
+                .map(|info| format!("#{}.#{}", info.class_index(), info.name_and_type_index())),
+            Some(Tag::InterfaceMethodref) => pool.get_interface_method_ref(index)
+                .map(|info| format!("#{}.#{}", info.class_index(), info.name_and_type_index())),
+            Some(Tag::NameAndType) => pool.get_unresolved_name_and_type(index)
+                .map(|info| format!("#{}:#{}", info.name_index(), info.descriptor_index())),
+            Some(Tag::MethodHandle) => pool.get_method_handle(index)
+                .map(|info| format!("{}:#{}", info.reference_kind() as u8, info.reference_index())),
+            Some(Tag::MethodType) => pool.get_method_type(index).map(|info| format!("#{}", info.descriptor_index())),
+            Some(Tag::Dynamic) => pool.get_dynamic(index)
+                .map(|info| format!("#{}:#{}", info.bootstrap_method_attr_index(), info.name_and_type_index())),
+            Some(Tag::InvokeDynamic) => pool.get_invoke_dynamic(index)
+                .map(|info| format!("#{}:#{}", info.bootstrap_method_attr_index(), info.name_and_type_index())),
+            Some(Tag::Module) => pool.get_module(index).map(|info| format!("#{}", info.name_index())),
+            Some(Tag::Package) => pool.get_package(index).map(|info| format!("#{}", info.name_index())),
+            Some(_) => self.describe(index),
+            None => None,
+        };
+        references.unwrap_or_else(|| invalid(index))
+    }
+
+    /// What the entry resolves to, such as `java/lang/Object."<init>":()V` for a method
+    /// reference, or `None` if it refers to an entry it can't.
+    pub fn describe(&self, index: Index) -> Option<String> {
+        let pool = self.pool;
+        Some(match pool.tag(index)? {
+            Tag::Utf8 => escape(self.utf8(index)?),
+            Tag::Integer => pool.get_integer(index)?.value().to_string(),
+            Tag::Float => format!("{}f", java_float(pool.get_float(index)?.value() as f64)),
+            Tag::Long => format!("{}l", pool.get_long(index)?.value()),
+            Tag::Double => format!("{}d", java_float(pool.get_double(index)?.value())),
+            Tag::Class => self.class_name(index)?.to_string(),
+            Tag::String => escape(self.utf8(pool.get_unresolved_string(index)?.string_index())?),
+            Tag::Fieldref => {
+                let info = pool.get_field_ref(index)?;
+                self.member(info.class_index(), info.name_and_type_index())?
+            }
+            Tag::Methodref => {
+                let info = pool.get_method_ref(index)?;
+                self.member(info.class_index(), info.name_and_type_index())?
+            }
+            Tag::InterfaceMethodref => {
+                let info = pool.get_interface_method_ref(index)?;
+                self.member(info.class_index(), info.name_and_type_index())?
+            }
+            Tag::NameAndType => {
+                let (name, descriptor) = self.name_and_type(index)?;
+                format!("{}:{descriptor}", quote_special(name))
+            }
+            Tag::MethodHandle => {
+                let info = pool.get_method_handle(index)?;
+                format!("{} {}", reference_kind_name(info.reference_kind()), self.describe(info.reference_index())?)
+            }
+            Tag::MethodType => self.utf8(pool.get_method_type(index)?.descriptor_index())?.to_string(),
+            Tag::Dynamic => {
+                let info = pool.get_dynamic(index)?;
+                let (name, descriptor) = self.name_and_type(info.name_and_type_index())?;
+                format!("#{}:{name}:{descriptor}", info.bootstrap_method_attr_index())
+            }
+            Tag::InvokeDynamic => {
+                let info = pool.get_invoke_dynamic(index)?;
+                let (name, descriptor) = self.name_and_type(info.name_and_type_index())?;
+                format!("#{}:{name}:{descriptor}", info.bootstrap_method_attr_index())
+            }
+            Tag::Module => self.utf8(pool.get_module(index)?.name_index())?.to_string(),
+            Tag::Package => self.utf8(pool.get_package(index)?.name_index())?.to_string(),
+        })
+    }
+
+    /// The entry's value prefixed with what kind of entry it is, as shown next to the
+    /// instructions that use it, such as `Method java/lang/Object."<init>":()V`.
+    pub fn comment(&self, index: Index) -> String {
+        let (Some(tag), Some(description)) = (self.pool.tag(index), self.describe(index)) else {
+            return invalid(index);
+        };
+        let kind = match tag {
+            Tag::Fieldref => "Field",
+            Tag::Methodref => "Method",
+            Tag::InterfaceMethodref => "InterfaceMethod",
+            Tag::Class => "class",
+            Tag::Integer => "int",
+            Tag::Float => "float",
+            Tag::Long => "long",
+            Tag::Double => "double",
+            Tag::Utf8 => "Utf8",
+            Tag::String => "String",
+            Tag::NameAndType => "NameAndType",
+            Tag::MethodHandle => "MethodHandle",
+            Tag::MethodType => "MethodType",
+            Tag::Dynamic => "Dynamic",
+            Tag::InvokeDynamic => "InvokeDynamic",
+            Tag::Module => "Module",
+            Tag::Package => "Package",
+        };
+        format!("{kind} {description}")
+    }
+}
+
+pub fn invalid(index: Index) -> String {
+    format!("<invalid #{index}>")
+}
+
+// Escapes control characters, so that every entry stays on one line
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Special method names are quoted, as javap does
+fn quote_special(name: &str) -> String {
+    if name.starts_with('<') {
+        format!("\"{name}\"")
+    } else {
+        name.to_string()
+    }
+}
+
+// Formats a float the way Java does for the values Rust formats differently
+fn java_float(value: f64) -> String {
+    if value.is_infinite() {
+        if value > 0.0 { "Infinity".into() } else { "-Infinity".into() }
+    } else {
+        format!("{value:?}")
+    }
+}
+
+pub fn reference_kind_name(kind: methodhandle::Ref) -> &'static str {
+    match kind {
+        methodhandle::Ref::GetField => "REF_getField",
+        methodhandle::Ref::GetStatic => "REF_getStatic",
+        methodhandle::Ref::PutField => "REF_putField",
+        methodhandle::Ref::PutStatic => "REF_putStatic",
+        methodhandle::Ref::InvokeVirtual => "REF_invokeVirtual",
+        methodhandle::Ref::InvokeStatic => "REF_invokeStatic",
+        methodhandle::Ref::InvokeSpecial => "REF_invokeSpecial",
+        methodhandle::Ref::NewInvokeSpecial => "REF_newInvokeSpecial",
+        methodhandle::Ref::InvokeInterface => "REF_invokeInterface",
+    }
+}
+
+/// The names of the given flags as javap shows them, such as `ACC_PUBLIC`.
+pub fn flag_names(names: impl Iterator<Item = &'static str>) -> Vec<String> {
+    names.map(|name| format!("ACC_{name}")).collect()
+}
+
+/// The operands of an instruction, grouped by how they're shown.
+pub enum Operands<'a> {
+    None,
+    /// The value pushed by `bipush` and `sipush`
+    Value(i32),
+    Local(u16),
+    Iinc { local: u16, increment: i32 },
+    Constant(Index),
+    /// `invokeinterface` and `multianewarray`, which take a count or number of dimensions
+    /// after the constant
+    ConstantAndCount(Index, u8),
+    /// A branch, relative to the instruction
+    Branch(i32),
+    ArrayType(ArrayType),
+    TableSwitch(&'a TableSwitch),
+    LookupSwitch(&'a LookupSwitch),
+    Wide(&'a WideInstruction),
+}
+
+pub fn operands(instruction: &Instruction) -> Operands<'_> {
+    use Instruction::*;
+    match instruction {
+        Bipush(value) => Operands::Value(*value as i32),
+        Sipush(value) => Operands::Value(*value as i32),
+        Iload(local) | Lload(local) | Fload(local) | Dload(local) | Aload(local) | Istore(local) | Lstore(local)
+        | Fstore(local) | Dstore(local) | Astore(local) | Ret(local) => Operands::Local(*local as u16),
+        Iinc(local, increment) => Operands::Iinc { local: *local as u16, increment: *increment as i32 },
+        Ldc(index) => Operands::Constant(*index as Index),
+        LdcW(index) | Ldc2W(index) | Getstatic(index) | Putstatic(index) | Getfield(index) | Putfield(index)
+        | Invokevirtual(index) | Invokespecial(index) | Invokestatic(index) | Invokedynamic(index) | New(index)
+        | Anewarray(index) | Checkcast(index) | Instanceof(index) => Operands::Constant(*index),
+        Invokeinterface(index, count) | Multianewarray(index, count) => Operands::ConstantAndCount(*index, *count),
+        Ifeq(offset) | Ifne(offset) | Iflt(offset) | Ifge(offset) | Ifgt(offset) | Ifle(offset) | IfIcmpeq(offset)
+        | IfIcmpne(offset) | IfIcmplt(offset) | IfIcmpge(offset) | IfIcmpgt(offset) | IfIcmple(offset)
+        | IfAcmpeq(offset) | IfAcmpne(offset) | Goto(offset) | Jsr(offset) | Ifnull(offset)
+        | Ifnonnull(offset) => Operands::Branch(*offset as i32),
+        GotoW(offset) | JsrW(offset) => Operands::Branch(*offset),
+        Newarray(ty) => Operands::ArrayType(*ty),
+        Tableswitch(switch) => Operands::TableSwitch(switch),
+        Lookupswitch(switch) => Operands::LookupSwitch(switch),
+        Wide(wide) => Operands::Wide(wide),
+        _ => Operands::None,
+    }
+}
+
+/// The absolute address a branch goes to.
+pub fn branch_target(pc: u32, offset: i32) -> i64 {
+    pc as i64 + offset as i64
+}
+
+pub fn frame_kind(frame: &Frame) -> &'static str {
+    match frame {
+        Frame::Same { .. } => "same",
+        Frame::SameLocalsOneStackItem { .. } => "same_locals_1_stack_item",
+        Frame::SameLocalsOneStackItemExtended { .. } => "same_locals_1_stack_item_extended",
+        Frame::Chop { .. } => "chop",
+        Frame::SameExtended { .. } => "same_extended",
+        Frame::Append { .. } => "append",
+        Frame::Full { .. } => "full",
+    }
+}
+
+pub fn offset_delta(frame: &Frame) -> u16 {
+    match *frame {
+        Frame::Same { frame_type } => frame_type as u16,
+        Frame::SameLocalsOneStackItem { frame_type, .. } => frame_type as u16 - 64,
+        Frame::SameLocalsOneStackItemExtended { offset_delta, .. } | Frame::Chop { offset_delta, .. }
+        | Frame::SameExtended { offset_delta } | Frame::Append { offset_delta, .. }
+        | Frame::Full { offset_delta, .. } => offset_delta,
+    }
+}
+
+/// The addresses each frame applies to. Each frame's offset is relative to the one before it,
+/// plus one, except for the first, which is relative to the start of the code.
+pub fn frame_addresses(frames: &[Frame]) -> Vec<u32> {
+    let mut addresses = Vec::with_capacity(frames.len());
+    let mut previous: Option<u32> = None;
+    for frame in frames {
+        let delta = offset_delta(frame) as u32;
+        let pc = previous.map_or(delta, |previous| previous + delta + 1);
+        addresses.push(pc);
+        previous = Some(pc);
+    }
+    addresses
+}
+
+/// The locals and stack a frame gives, where they're given explicitly.
+pub fn frame_types(frame: &Frame) -> (&[VerificationType], &[VerificationType]) {
+    match frame {
+        Frame::SameLocalsOneStackItem { stack, .. } | Frame::SameLocalsOneStackItemExtended { stack, .. } =>
+            (&[], std::slice::from_ref(stack)),
+        // SAFETY: Arrays in a parsed class are fully initialized
+        Frame::Append { locals, .. } => (unsafe { locals.as_slice() }, &[]),
+        Frame::Full { locals, stack, .. } => unsafe { (locals.as_slice(), stack.as_slice()) },
+        _ => (&[], &[]),
+    }
+}
+
+pub fn verification_type(ty: &VerificationType, constants: Constants) -> String {
+    match ty {
+        VerificationType::Top => "top".into(),
+        VerificationType::Integer => "int".into(),
+        VerificationType::Float => "float".into(),
+        VerificationType::Double => "double".into(),
+        VerificationType::Long => "long".into(),
+        VerificationType::Null => "null".into(),
+        VerificationType::UninitializedThis => "this".into(),
+        VerificationType::Object { pool_index } => format!("class {}", constants.class_text(*pool_index)),
+        VerificationType::Uninitialized { offset } => format!("uninitialized {offset}"),
+    }
+}
+
+/// An annotation in the same syntax as the source, but with the type as a descriptor, such
+/// as `Ljava/lang/Deprecated;(since="9")`.
+pub fn annotation(annotation: &Annotation, constants: Constants) -> String {
+    let ty = constants.utf8_text(annotation.type_index());
+    let elements: Vec<String> = annotation.elements().iter()
+        .map(|element| {
+            let name = constants.utf8_text(element.name_index());
+            format!("{name}={}", element_value(element.value(), constants))
+        })
+        .collect();
+    format!("{ty}({})", elements.join(","))
+}
+
+pub fn element_value(value: &ElementValue, constants: Constants) -> String {
+    let Some(resolved) = value.resolve(constants.pool()) else {
+        return "<invalid>".into();
+    };
+    match resolved {
+        ResolvedValue::Byte(value) => format!("(byte){value}"),
+        ResolvedValue::Char(value) => match char::from_u32(value as u32) {
+            Some(c) => format!("'{}'", escape(&c.to_string())),
+            None => format!("'\\u{value:04x}'"),
+        },
+        ResolvedValue::Double(value) => format!("{}d", java_float(value)),
+        ResolvedValue::Float(value) => format!("{}f", java_float(value as f64)),
+        ResolvedValue::Int(value) => value.to_string(),
+        ResolvedValue::Long(value) => format!("{value}l"),
+        ResolvedValue::Short(value) => format!("(short){value}"),
+        ResolvedValue::Boolean(value) => value.to_string(),
+        ResolvedValue::String(value) => format!("\"{}\"", escape(value.as_str())),
+        ResolvedValue::Enum { type_descriptor, name } => format!("{type_descriptor}.{name}"),
+        ResolvedValue::Class(descriptor) => format!("class {descriptor}"),
+        ResolvedValue::Annotation(nested) => format!("@{}", annotation(nested, constants)),
+        ResolvedValue::Array(values) => {
+            let values: Vec<String> = values.iter().map(|value| element_value(value, constants)).collect();
+            format!("[{}]", values.join(","))
+        }
+    }
+}
+
+/// What a type annotation is on, such as `CAST, offset=5, type_index=0`.
+pub fn type_annotation_target(annotation: &TypeAnnotation) -> String {
+    let mut target = format!("{:?}", annotation.target_type());
+    match annotation.target_info() {
+        TargetInfo::TypeParameter { index } => target += &format!(", param_index={index}"),
+        TargetInfo::Supertype { index } => target += &format!(", type_index={index}"),
+        TargetInfo::ParameterBound { type_parameter_index, bound_index } =>
+            target += &format!(", param_index={type_parameter_index}, bound_index={bound_index}"),
+        TargetInfo::Empty => {}
+        TargetInfo::FormalParameter { index } => target += &format!(", param_index={index}"),
+        TargetInfo::Throws { type_index } => target += &format!(", throws_index={type_index}"),
+        TargetInfo::LocalVar { table } => {
+            // SAFETY: Arrays in a parsed class are fully initialized
+            let ranges: Vec<String> = unsafe { table.as_slice() }.iter()
+                .map(|entry| {
+                    format!("start_pc={}, length={}, index={}", entry.start_pc(), entry.length(), entry.index())
+                })
+                .collect();
+            target += &format!(", {{{}}}", ranges.join("; "));
+        }
+        TargetInfo::Catch { exception_table_index } => target += &format!(", exception_index={exception_table_index}"),
+        TargetInfo::Offset(offset) => target += &format!(", offset={offset}"),
+        TargetInfo::TypeArgument { offset, index } => target += &format!(", offset={offset}, type_index={index}"),
+    }
+
+    let path = annotation.target_path();
+    if !path.is_empty() {
+        let parts: Vec<String> = path.iter()
+            .map(|part| format!("({},{})", part.type_path_kind(), part.type_argument_index()))
+            .collect();
+        target += &format!(", location={}", parts.join(""));
+    }
+    target
+}
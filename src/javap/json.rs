@@ -0,0 +1,615 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Output as JSON, which always has everything in it, for scripts to pick through.
+
+use std::fmt;
+use crate::class::{Class, Field, Method};
+use crate::class::bytecode::{Instructions, WideInstruction};
+use crate::class::constantpool::{Index, Tag};
+use crate::loader::classfile::attribute::{
+    annotations, type_annotations, AttributeSet, ClassFileAttribute, CodeAttribute, FieldAttribute,
+    MethodAttribute, RecordAttribute, UnknownAttribute,
+};
+use crate::loader::classfile::attribute::annotations::ResolvedValue;
+use crate::loader::classfile::attribute::code::Code;
+use crate::loader::classfile::attribute::module::Module;
+use crate::loader::classfile::attribute::stackmap::VerificationType;
+use super::format::{self, Constants, Operands};
+
+/// A JSON value. Objects keep their keys in the order they're added.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn object() -> Json {
+        Json::Object(Vec::new())
+    }
+
+    fn with(mut self, key: &'static str, value: impl Into<Json>) -> Json {
+        if let Json::Object(entries) = &mut self {
+            entries.push((key, value.into()));
+        }
+        self
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{value}"),
+            Json::Int(value) => write!(f, "{value}"),
+            // JSON has no NaN or infinities, so they're given as strings
+            Json::Float(value) if !value.is_finite() => write_string(f, &value.to_string()),
+            Json::Float(value) => write!(f, "{value:?}"),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) if values.is_empty() => f.write_str("[]"),
+            Json::Array(values) => {
+                f.write_str("[\n")?;
+                for (i, value) in values.iter().enumerate() {
+                    write!(f, "{:width$}", "", width = indent + 2)?;
+                    value.write(f, indent + 2)?;
+                    f.write_str(if i + 1 < values.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{:indent$}]", "")
+            }
+            Json::Object(entries) if entries.is_empty() => f.write_str("{}"),
+            Json::Object(entries) => {
+                f.write_str("{\n")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    write!(f, "{:width$}", "", width = indent + 2)?;
+                    write_string(f, key)?;
+                    f.write_str(": ")?;
+                    value.write(f, indent + 2)?;
+                    f.write_str(if i + 1 < entries.len() { ",\n" } else { "\n" })?;
+                }
+                write!(f, "{:indent$}}}", "")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Json {
+        Json::Bool(value)
+    }
+}
+
+macro_rules! json_from_int {
+    ($($ty: ty),*) => {
+        $(
+            impl From<$ty> for Json {
+                fn from(value: $ty) -> Json {
+                    Json::Int(value as i64)
+                }
+            }
+        )*
+    };
+}
+
+json_from_int!(i8, u8, i16, u16, i32, u32, i64, usize);
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Json {
+        Json::Float(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Json {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Json {
+        Json::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Json {
+        Json::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+pub fn class_to_json(class: &Class, file: &str) -> Json {
+    let constants = Constants::new(class.constant_pool());
+    let version = class.version();
+    let super_class = class.super_class().map(|name| {
+        Json::object().with("index", class.super_class_index()).with("name", name.as_str())
+    });
+
+    Json::object()
+        .with("file", file)
+        .with("version", Json::object().with("major", version.major()).with("minor", version.minor()))
+        .with("access_flags", flags(class.declared_access_flags().flags(), class.declared_access_flags().names()))
+        .with("effective_access_flags", flags(class.access_flags().flags(), class.access_flags().names()))
+        .with("this_class", Json::object().with("index", class.this_class_index()).with("name", class.name().as_str()))
+        .with("super_class", super_class)
+        .with("interfaces", class.interfaces().iter().map(|name| name.as_str()).collect::<Vec<_>>())
+        .with("constant_pool", constant_pool(constants))
+        .with("fields", class.fields().iter().map(|field| field_json(field, constants)).collect::<Vec<_>>())
+        .with("methods", class.methods().iter().map(|method| method_json(method, constants)).collect::<Vec<_>>())
+        .with("attributes", class.attributes().iter()
+            .map(|attr| class_attribute(attr, constants)).collect::<Vec<_>>())
+}
+
+fn flags(value: u16, names: impl Iterator<Item = &'static str>) -> Json {
+    Json::object().with("value", value).with("names", format::flag_names(names))
+}
+
+fn constant_pool(constants: Constants) -> Json {
+    let pool = constants.pool();
+    let entries: Vec<Json> = constants.indexes().filter_map(|index| {
+        let tag = pool.tag(index)?;
+        let value: Json = match tag {
+            Tag::Utf8 => constants.utf8(index).into(),
+            Tag::Integer => pool.get_integer(index).map(|info| info.value()).into(),
+            Tag::Long => pool.get_long(index).map(|info| info.value()).into(),
+            Tag::Float => pool.get_float(index).map(|info| info.value() as f64).into(),
+            Tag::Double => pool.get_double(index).map(|info| info.value()).into(),
+            _ => constants.describe(index).into(),
+        };
+        let entry = Json::object().with("index", index).with("tag", format!("{tag:?}"));
+        let entry = match tag {
+            Tag::Utf8 | Tag::Integer | Tag::Long | Tag::Float | Tag::Double => entry,
+            _ => entry.with("references", constants.references(index)),
+        };
+        Some(entry.with("value", value))
+    }).collect();
+    entries.into()
+}
+
+fn constant(index: Index, constants: Constants) -> Json {
+    Json::object()
+        .with("index", index)
+        .with("tag", constants.pool().tag(index).map(|tag| format!("{tag:?}")))
+        .with("value", constants.describe(index))
+}
+
+fn utf8(index: Index, constants: Constants) -> Json {
+    constants.utf8(index).into()
+}
+
+fn class_name(index: Index, constants: Constants) -> Json {
+    match index {
+        0 => Json::Null,
+        index => constants.class_name(index).into(),
+    }
+}
+
+fn field_json(field: &Field, constants: Constants) -> Json {
+    let attributes: Vec<Json> = field.attributes().iter().map(|attr| {
+        let json = named(attr, constants);
+        match attr {
+            FieldAttribute::ConstantValue(value) => json.with("value", constant(value.value_index(), constants)),
+            FieldAttribute::Signature(signature) =>
+                json.with("signature", utf8(signature.signature_index(), constants)),
+            FieldAttribute::RuntimeVisibleAnnotations(attr) => with_annotations(json, attr.annotations(), constants),
+            FieldAttribute::RuntimeInvisibleAnnotations(attr) => with_annotations(json, attr.annotations(), constants),
+            FieldAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                with_type_annotations(json, attr.annotations(), constants),
+            FieldAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                with_type_annotations(json, attr.annotations(), constants),
+            FieldAttribute::Synthetic(_) | FieldAttribute::Deprecated(_) => json,
+            FieldAttribute::Unknown(unknown) => unknown_json(json, unknown),
+        }
+    }).collect();
+
+    Json::object()
+        .with("name", field.name().as_str())
+        .with("descriptor", field.descriptor().as_str())
+        .with("access_flags", flags(field.access_flags().flags(), field.access_flags().names()))
+        .with("attributes", attributes)
+}
+
+fn method_json(method: &Method, constants: Constants) -> Json {
+    let attributes: Vec<Json> = method.attributes().iter().map(|attr| {
+        let json = named(attr, constants);
+        match attr {
+            MethodAttribute::Code(code) => code_json(json, code, constants),
+            MethodAttribute::Exceptions(exceptions) => json.with("exceptions", exceptions.indexes().iter()
+                .map(|&index| class_name(index, constants)).collect::<Vec<_>>()),
+            MethodAttribute::Signature(signature) =>
+                json.with("signature", utf8(signature.signature_index(), constants)),
+            MethodAttribute::RuntimeVisibleAnnotations(attr) => with_annotations(json, attr.annotations(), constants),
+            MethodAttribute::RuntimeInvisibleAnnotations(attr) => with_annotations(json, attr.annotations(), constants),
+            MethodAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                with_type_annotations(json, attr.annotations(), constants),
+            MethodAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                with_type_annotations(json, attr.annotations(), constants),
+            MethodAttribute::RuntimeVisibleParameterAnnotations(attr) => {
+                let parameters = (0..attr.num_parameters()).filter_map(|i| attr.parameter(i));
+                json.with("parameters", parameter_annotations(parameters, constants))
+            }
+            MethodAttribute::RuntimeInvisibleParameterAnnotations(attr) => {
+                let parameters = (0..attr.num_parameters()).filter_map(|i| attr.parameter(i));
+                json.with("parameters", parameter_annotations(parameters, constants))
+            }
+            MethodAttribute::AnnotationDefault(default) =>
+                json.with("value", element_value(default.value(), constants)),
+            MethodAttribute::MethodParameters(parameters) => json.with("parameters", parameters.parameters().iter()
+                .map(|parameter| {
+                    let name = match parameter.name_index() {
+                        0 => Json::Null,
+                        index => utf8(index, constants),
+                    };
+                    let flags = flags(parameter.access_flags().flags(), parameter.access_flags().names());
+                    Json::object().with("name", name).with("access_flags", flags)
+                })
+                .collect::<Vec<_>>()),
+            MethodAttribute::Synthetic(_) | MethodAttribute::Deprecated(_) => json,
+            MethodAttribute::Unknown(unknown) => unknown_json(json, unknown),
+        }
+    }).collect();
+
+    Json::object()
+        .with("name", method.name().as_str())
+        .with("descriptor", method.descriptor().as_str())
+        .with("access_flags", flags(method.access_flags().flags(), method.access_flags().names()))
+        .with("attributes", attributes)
+}
+
+// Starts the object for an attribute with its name
+fn named<A: AttributeSet>(attribute: &A, constants: Constants) -> Json {
+    Json::object().with("name", attribute.name(constants.pool()))
+}
+
+fn unknown_json(json: Json, unknown: &UnknownAttribute) -> Json {
+    let info: String = unknown.info().iter().map(|byte| format!("{byte:02x}")).collect();
+    json.with("length", unknown.info().len()).with("info", info)
+}
+
+fn code_json(json: Json, code: &Code, constants: Constants) -> Json {
+    let mut instructions = Vec::new();
+    for result in Instructions::new(code.code()) {
+        match result {
+            Ok((pc, instruction)) => {
+                let json = Json::object().with("pc", pc).with("opcode", instruction.opcode().mnemonic());
+                instructions.push(operands_json(json, pc, format::operands(&instruction), constants));
+            }
+            Err(err) => instructions.push(Json::object().with("pc", err.pc).with("error", err.to_string())),
+        }
+    }
+
+    let exceptions: Vec<Json> = code.exceptions().iter().map(|handler| {
+        Json::object()
+            .with("start_pc", handler.start_pc())
+            .with("end_pc", handler.end_pc())
+            .with("handler_pc", handler.handler_pc())
+            .with("catch_type", class_name(handler.catch_type(), constants))
+    }).collect();
+
+    let attributes: Vec<Json> = code.attributes().iter().map(|attr| {
+        let json = named(attr, constants);
+        match attr {
+            CodeAttribute::LineNumberTable(table) => json.with("entries", table.entries().iter()
+                .map(|entry| Json::object().with("start_pc", entry.start_pc()).with("line", entry.line_number()))
+                .collect::<Vec<_>>()),
+            CodeAttribute::LocalVariableTable(table) => json.with("entries", table.entries().iter()
+                .map(|var| Json::object()
+                    .with("start_pc", var.start_pc())
+                    .with("length", var.length())
+                    .with("index", var.index())
+                    .with("name", utf8(var.name_index(), constants))
+                    .with("descriptor", utf8(var.descriptor_index(), constants)))
+                .collect::<Vec<_>>()),
+            CodeAttribute::LocalVariableTypeTable(table) => json.with("entries", table.entries().iter()
+                .map(|var| Json::object()
+                    .with("start_pc", var.start_pc())
+                    .with("length", var.length())
+                    .with("index", var.index())
+                    .with("name", utf8(var.name_index(), constants))
+                    .with("signature", utf8(var.signature_index(), constants)))
+                .collect::<Vec<_>>()),
+            CodeAttribute::StackMapTable(table) => {
+                let frames = table.entries();
+                json.with("frames", frames.iter().zip(format::frame_addresses(frames)).map(|(frame, pc)| {
+                    let (locals, stack) = format::frame_types(frame);
+                    Json::object()
+                        .with("pc", pc)
+                        .with("frame_type", frame.frame_type())
+                        .with("kind", format::frame_kind(frame))
+                        .with("offset_delta", format::offset_delta(frame))
+                        .with("locals", verification_types(locals, constants))
+                        .with("stack", verification_types(stack, constants))
+                }).collect::<Vec<_>>())
+            }
+            CodeAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                with_type_annotations(json, attr.annotations(), constants),
+            CodeAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                with_type_annotations(json, attr.annotations(), constants),
+            CodeAttribute::Unknown(unknown) => unknown_json(json, unknown),
+        }
+    }).collect();
+
+    json.with("max_stack", code.max_stack())
+        .with("max_locals", code.max_locals())
+        .with("instructions", instructions)
+        .with("exception_table", exceptions)
+        .with("attributes", attributes)
+}
+
+fn operands_json(json: Json, pc: u32, operands: Operands, constants: Constants) -> Json {
+    let target = |offset: i32| format::branch_target(pc, offset);
+    match operands {
+        Operands::None => json,
+        Operands::Value(value) => json.with("value", value),
+        Operands::Local(local) => json.with("local", local),
+        Operands::Iinc { local, increment } => json.with("local", local).with("increment", increment),
+        Operands::Constant(index) => json.with("constant", constant(index, constants)),
+        Operands::ConstantAndCount(index, count) =>
+            json.with("constant", constant(index, constants)).with("count", count),
+        Operands::Branch(offset) => json.with("target", target(offset)),
+        Operands::ArrayType(ty) => json.with("type", ty.base_type().java_name()),
+        Operands::Wide(wide) => {
+            let json = json.with("modifies", wide.opcode().mnemonic()).with("local", wide.index());
+            match wide {
+                WideInstruction::Iinc(_, increment) => json.with("increment", *increment),
+                _ => json,
+            }
+        }
+        Operands::TableSwitch(switch) => json
+            .with("low", switch.low)
+            .with("high", switch.high)
+            .with("default", target(switch.default))
+            .with("targets", switch.offsets.iter().map(|&offset| target(offset)).collect::<Vec<_>>()),
+        Operands::LookupSwitch(switch) => json
+            .with("default", target(switch.default))
+            .with("pairs", switch.pairs.iter()
+                .map(|&(value, offset)| Json::object().with("match", value).with("target", target(offset)))
+                .collect::<Vec<_>>()),
+    }
+}
+
+fn verification_types(types: &[VerificationType], constants: Constants) -> Json {
+    types.iter().map(|ty| format::verification_type(ty, constants)).collect::<Vec<_>>().into()
+}
+
+fn with_annotations(json: Json, annotations: &[annotations::Annotation], constants: Constants) -> Json {
+    json.with("annotations", annotation_list(annotations, constants))
+}
+
+fn with_type_annotations(json: Json, annotations: &[type_annotations::TypeAnnotation], constants: Constants) -> Json {
+    json.with("annotations", type_annotation_list(annotations, constants))
+}
+
+fn parameter_annotations<'a>(
+    parameters: impl Iterator<Item = &'a [annotations::Annotation]>,
+    constants: Constants,
+) -> Json {
+    Json::Array(parameters.map(|annotations| annotation_list(annotations, constants)).collect())
+}
+
+fn annotation_list(annotations: &[annotations::Annotation], constants: Constants) -> Json {
+    annotations.iter().map(|annotation| annotation_json(annotation, constants)).collect::<Vec<_>>().into()
+}
+
+fn type_annotation_list(annotations: &[type_annotations::TypeAnnotation], constants: Constants) -> Json {
+    annotations.iter()
+        .map(|annotation| Json::object()
+            .with("target", format::type_annotation_target(annotation))
+            .with("annotation", annotation_json(annotation.annotation(), constants)))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn annotation_json(annotation: &annotations::Annotation, constants: Constants) -> Json {
+    let elements: Vec<Json> = annotation.elements().iter()
+        .map(|element| Json::object()
+            .with("name", utf8(element.name_index(), constants))
+            .with("value", element_value(element.value(), constants)))
+        .collect();
+    Json::object().with("type", utf8(annotation.type_index(), constants)).with("elements", elements)
+}
+
+fn element_value(value: &annotations::ElementValue, constants: Constants) -> Json {
+    let Some(resolved) = value.resolve(constants.pool()) else {
+        return Json::Null;
+    };
+    match resolved {
+        ResolvedValue::Byte(value) => value.into(),
+        ResolvedValue::Char(value) => char::from_u32(value as u32).map(String::from).into(),
+        ResolvedValue::Double(value) => value.into(),
+        ResolvedValue::Float(value) => (value as f64).into(),
+        ResolvedValue::Int(value) => value.into(),
+        ResolvedValue::Long(value) => value.into(),
+        ResolvedValue::Short(value) => value.into(),
+        ResolvedValue::Boolean(value) => value.into(),
+        ResolvedValue::String(value) => value.as_str().into(),
+        ResolvedValue::Enum { type_descriptor, name } =>
+            Json::object().with("enum", type_descriptor.as_str()).with("name", name.as_str()),
+        ResolvedValue::Class(descriptor) => Json::object().with("class", descriptor.as_str()),
+        ResolvedValue::Annotation(nested) => annotation_json(nested, constants),
+        ResolvedValue::Array(values) =>
+            values.iter().map(|value| element_value(value, constants)).collect::<Vec<_>>().into(),
+    }
+}
+
+fn class_attribute(attribute: &ClassFileAttribute, constants: Constants) -> Json {
+    let json = named(attribute, constants);
+    let classes = |indexes: &[Index]| indexes.iter().map(|&index| class_name(index, constants)).collect::<Vec<_>>();
+    match attribute {
+        ClassFileAttribute::SourceFile(source) => json.with("source_file", utf8(source.source_file_index(), constants)),
+        ClassFileAttribute::Signature(signature) =>
+            json.with("signature", utf8(signature.signature_index(), constants)),
+        ClassFileAttribute::SourceDebugExtension(extension) =>
+            json.with("debug_extension", String::from_utf8_lossy(extension.debug_extension()).into_owned()),
+        ClassFileAttribute::InnerClasses(inner) => json.with("classes", inner.classes().iter().map(|class| {
+            let name = match class.inner_name_index() {
+                0 => Json::Null,
+                index => utf8(index, constants),
+            };
+            let flags = flags(class.inner_class_access_flags().flags(), class.inner_class_access_flags().names());
+            Json::object()
+                .with("inner_class", class_name(class.inner_class_index(), constants))
+                .with("outer_class", class_name(class.outer_class_index(), constants))
+                .with("inner_name", name)
+                .with("access_flags", flags)
+        }).collect::<Vec<_>>()),
+        ClassFileAttribute::EnclosingMethod(enclosing) => {
+            let method = match enclosing.method_index() {
+                0 => Json::Null,
+                index => constant(index, constants),
+            };
+            json.with("class", class_name(enclosing.class_index(), constants)).with("method", method)
+        }
+        ClassFileAttribute::BootstrapMethods(methods) => json.with("methods", methods.methods().iter().map(|method| {
+            let arguments: Vec<Json> = method.bootstrap_arguments().iter()
+                .map(|&index| constant(index, constants))
+                .collect();
+            Json::object().with("method_handle", constant(method.method_ref(), constants)).with("arguments", arguments)
+        }).collect::<Vec<_>>()),
+        ClassFileAttribute::NestHost(host) => json.with("host", class_name(host.host_class_index(), constants)),
+        ClassFileAttribute::NestMembers(members) => json.with("classes", classes(members.classes())),
+        ClassFileAttribute::PermittedSubclasses(subclasses) => json.with("classes", classes(subclasses.classes())),
+        ClassFileAttribute::Record(record) => json.with("components", record.components().iter().map(|component| {
+            let attributes: Vec<Json> = component.attributes().iter().map(|attr| {
+                let json = named(attr, constants);
+                match attr {
+                    RecordAttribute::Signature(signature) =>
+                        json.with("signature", utf8(signature.signature_index(), constants)),
+                    RecordAttribute::RuntimeVisibleAnnotations(attr) =>
+                        with_annotations(json, attr.annotations(), constants),
+                    RecordAttribute::RuntimeInvisibleAnnotations(attr) =>
+                        with_annotations(json, attr.annotations(), constants),
+                    RecordAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                        with_type_annotations(json, attr.annotations(), constants),
+                    RecordAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                        with_type_annotations(json, attr.annotations(), constants),
+                    RecordAttribute::Unknown(unknown) => unknown_json(json, unknown),
+                }
+            }).collect();
+            Json::object()
+                .with("name", utf8(component.name_index(), constants))
+                .with("descriptor", utf8(component.descriptor_index(), constants))
+                .with("attributes", attributes)
+        }).collect::<Vec<_>>()),
+        ClassFileAttribute::Module(module) => module_json(json, module, constants),
+        ClassFileAttribute::ModulePackages(packages) => json.with("packages", packages.package_index().iter()
+            .map(|&index| package_name(index, constants)).collect::<Vec<_>>()),
+        ClassFileAttribute::ModuleMainClass(main) =>
+            json.with("main_class", class_name(main.main_class_index(), constants)),
+        ClassFileAttribute::RuntimeVisibleAnnotations(attr) => with_annotations(json, attr.annotations(), constants),
+        ClassFileAttribute::RuntimeInvisibleAnnotations(attr) => with_annotations(json, attr.annotations(), constants),
+        ClassFileAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+            with_type_annotations(json, attr.annotations(), constants),
+        ClassFileAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+            with_type_annotations(json, attr.annotations(), constants),
+        ClassFileAttribute::Synthetic(_) | ClassFileAttribute::Deprecated(_) => json,
+        ClassFileAttribute::Unknown(unknown) => unknown_json(json, unknown),
+    }
+}
+
+fn module_name(index: Index, constants: Constants) -> Json {
+    constants.pool().get_module(index).and_then(|module| constants.utf8(module.name_index())).into()
+}
+
+fn package_name(index: Index, constants: Constants) -> Json {
+    constants.pool().get_package(index).and_then(|package| constants.utf8(package.name_index())).into()
+}
+
+fn module_json(json: Json, module: &Module, constants: Constants) -> Json {
+    let version = |index: Index| match index {
+        0 => Json::Null,
+        index => utf8(index, constants),
+    };
+    let modules = |indexes: &[Index]| indexes.iter().map(|&index| module_name(index, constants)).collect::<Vec<_>>();
+
+    json.with("module_name", module_name(module.name_index(), constants))
+        .with("flags", flags(module.flags().flags(), module.flags().names()))
+        .with("version", version(module.version_index()))
+        .with("requires", module.requires().iter().map(|requires| Json::object()
+            .with("module", module_name(requires.index(), constants))
+            .with("flags", flags(requires.flags().flags(), requires.flags().names()))
+            .with("version", version(requires.version_index())))
+            .collect::<Vec<_>>())
+        .with("exports", module.exports().iter().map(|exports| Json::object()
+            .with("package", package_name(exports.index(), constants))
+            .with("flags", flags(exports.flags().flags(), exports.flags().names()))
+            .with("to", modules(exports.to_index())))
+            .collect::<Vec<_>>())
+        .with("opens", module.opens().iter().map(|opens| Json::object()
+            .with("package", package_name(opens.index(), constants))
+            .with("flags", flags(opens.flags().flags(), opens.flags().names()))
+            .with("to", modules(opens.to_index())))
+            .collect::<Vec<_>>())
+        .with("uses", module.uses().iter().map(|&index| class_name(index, constants)).collect::<Vec<_>>())
+        .with("provides", module.provides().iter().map(|provides| Json::object()
+            .with("service", class_name(provides.index(), constants))
+            .with("with", provides.with_index().iter().map(|&index| class_name(index, constants)).collect::<Vec<_>>()))
+            .collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting() {
+        let json = Json::object()
+            .with("string", "quote \" backslash \\ newline \n control \u{1}")
+            .with("numbers", vec![Json::Int(-1), Json::Float(0.5), Json::Float(f64::NAN)])
+            .with("empty", Json::Array(Vec::new()))
+            .with("nothing", None::<i32>);
+        assert_eq!(json.to_string(), "\
+{
+  \"string\": \"quote \\\" backslash \\\\ newline \\n control \\u0001\",
+  \"numbers\": [
+    -1,
+    0.5,
+    \"NaN\"
+  ],
+  \"empty\": [],
+  \"nothing\": null
+}");
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! A `javap`-like disassembler, showing classes the way Astatine parses them.
+
+mod format;
+mod json;
+mod text;
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+use crate::class::parse::BinaryReader;
+use crate::class::{parse_class_with, Class, ParseOptions};
+
+const USAGE: &str = "\
+usage: astatine-javap [options] <class file>...
+
+options:
+  -v, --verbose       show the constant pool, flags, stack maps and all attributes
+      --json          print each class as JSON, which always includes everything
+      --enable-preview  accept class files that use preview features
+  -h, --help          show this message";
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    pub verbose: bool,
+    pub json: bool,
+    pub parse: ParseOptions,
+}
+
+/// Disassembles each of the class files named in `args`, which can also contain options.
+pub fn run(args: impl Iterator<Item = String>) -> ExitCode {
+    let mut options = Options::default();
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-v" | "--verbose" => options.verbose = true,
+            "--json" => options.json = true,
+            "--enable-preview" => options.parse.enable_preview = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("unknown option: {arg}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let mut stdout = io::stdout().lock();
+    let mut status = ExitCode::SUCCESS;
+    for file in &files {
+        match disassemble(file, &options) {
+            Ok(output) => {
+                // Stop quietly if the output is closed, such as when piped in to `head`
+                if writeln!(stdout, "{}", output.trim_end()).is_err() {
+                    return status;
+                }
+            }
+            Err(err) => {
+                eprintln!("{file}: {err}");
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    status
+}
+
+/// Parses the class file at `path` and gives the output for it.
+pub fn disassemble(path: &str, options: &Options) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+    let class = parse_class_with(&mut BinaryReader::new(bytes), &options.parse).map_err(|err| err.to_string())?;
+    Ok(format_class(&class, path, options))
+}
+
+/// Gives the output for a class that's already been parsed, from the file at `path`.
+pub fn format_class(class: &Class, path: &str, options: &Options) -> String {
+    if options.json {
+        json::class_to_json(class, path).to_string()
+    } else {
+        text::print_class(class, path, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::testing::*;
+    use super::*;
+
+    // A class with a constructor, and a method that switches on its argument
+    fn test_class() -> Class {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let object = builder.super_class;
+        let [init_high, init_low] = builder.member_ref(METHOD_REF, object, "<init>", "()V").to_be_bytes();
+        let code = builder.code(1, 1, &[
+            0x2A,                       // aload_0
+            0xB7, init_high, init_low,  // invokespecial
+            0xB1,                       // return
+        ], &[]);
+        let init = builder.member(ACC_PUBLIC, "<init>", "()V", &[code]);
+        let code = builder.code(1, 1, &[
+            0x1A,                       // iload_0
+            0xAA, 0x00, 0x00,           // tableswitch, padded
+            0, 0, 0, 23,                // default
+            0, 0, 0, 0,                 // low
+            0, 0, 0, 1,                 // high
+            0, 0, 0, 23,
+            0, 0, 0, 25,
+            0x03, 0xAC,                 // iconst_0, ireturn
+            0x04, 0xAC,                 // iconst_1, ireturn
+        ], &[]);
+        let pick = builder.member(ACC_STATIC, "pick", "(I)I", &[code]);
+        builder.methods = vec![init, pick];
+        builder.parse(52).expect("test class should be valid")
+    }
+
+    #[test]
+    fn text() {
+        let class = test_class();
+        let output = format_class(&class, "Test.class", &Options::default());
+        assert!(output.starts_with("public class Test extends java.lang.Object\n{\n  public Test();\n"));
+        assert!(output.contains("1: invokespecial #8                  // Method java/lang/Object.\"<init>\":()V\n"));
+        assert!(output.contains("static int pick(int);"));
+        assert!(output.contains("1: tableswitch   { // 0 to 1\n"));
+        assert!(output.contains("                             1: 26\n"));
+        assert!(output.contains("                       default: 24\n"));
+        assert!(!output.contains("Constant pool:"));
+
+        let verbose = format_class(&class, "Test.class", &Options { verbose: true, ..Options::default() });
+        assert!(verbose.contains("  major version: 52\n"));
+        assert!(verbose.contains("  flags: (0x0021) ACC_PUBLIC, ACC_SUPER\n"));
+        assert!(verbose.contains("   #8 = Methodref          #4.#7          // java/lang/Object.\"<init>\":()V\n"));
+        assert!(verbose.contains("      stack=1, locals=1, args_size=1\n"));
+    }
+
+    #[test]
+    fn json() {
+        let class = test_class();
+        let output = format_class(&class, "Test.class", &Options { json: true, ..Options::default() });
+        assert!(output.starts_with("{\n  \"file\": \"Test.class\",\n"));
+        assert!(output.contains("\"opcode\": \"invokespecial\",\n"));
+        assert!(output.contains("\"value\": \"java/lang/Object.\\\"<init>\\\":()V\"\n"));
+        assert!(output.contains("\"targets\": [\n"));
+    }
+}
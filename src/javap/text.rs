@@ -0,0 +1,688 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Output in the same layout as `javap -c`, or `javap -v` when verbose.
+
+use crate::class::{Class, Field, Method};
+use crate::class::bytecode::{Instructions, WideInstruction};
+use crate::class::constantpool::{Index, Tag};
+use crate::loader::classfile::attribute::{
+    annotations, type_annotations, AttributeSet, ClassFileAttribute, CodeAttribute, FieldAttribute,
+    MethodAttribute, RecordAttribute, Signature, UnknownAttribute,
+};
+use crate::loader::classfile::attribute::classfile::{BootstrapMethods, InnerClasses};
+use crate::loader::classfile::attribute::module::Module;
+use crate::loader::classfile::attribute::record::Record;
+use crate::loader::classfile::attribute::code::{Code, StackMapTable};
+use crate::loader::classfile::attribute::stackmap::{Frame, VerificationType};
+use crate::loader::classfile::attribute::type_annotations::TypeAnnotation;
+use crate::types::{FieldAccessFlags, MethodAccessFlags};
+use super::format::{self, Constants, Operands};
+use super::Options;
+
+// Where comments start on lines that have them, from the start of the text after the indent
+const COMMENT_COLUMN: usize = 40;
+
+pub fn print_class(class: &Class, file: &str, options: &Options) -> String {
+    let mut printer = Printer { out: String::new(), constants: Constants::new(class.constant_pool()), options };
+    printer.class(class, file);
+    printer.out
+}
+
+struct Printer<'a> {
+    out: String,
+    constants: Constants<'a>,
+    options: &'a Options,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, indent: usize, text: impl AsRef<str>) {
+        for _ in 0..indent {
+            self.out.push(' ');
+        }
+        self.out.push_str(text.as_ref());
+        self.out.push('\n');
+    }
+
+    // A line with a comment after it, lined up with the others
+    fn commented(&mut self, indent: usize, text: impl AsRef<str>, comment: impl AsRef<str>) {
+        let text = text.as_ref();
+        let padding = COMMENT_COLUMN.saturating_sub(text.len()).max(1);
+        self.line(indent, format!("{text}{:padding$}// {}", "", comment.as_ref()));
+    }
+
+    fn flags(&mut self, indent: usize, value: u16, names: impl Iterator<Item = &'static str>) {
+        let names = format::flag_names(names).join(", ");
+        self.line(indent, format!("flags: (0x{value:04x}) {names}"));
+    }
+
+    fn class(&mut self, class: &Class, file: &str) {
+        let verbose = self.options.verbose;
+        let constants = self.constants;
+        if verbose {
+            self.line(0, format!("Classfile {file}"));
+        }
+        let source_file = class.attributes().iter().find_map(|attr| match attr {
+            ClassFileAttribute::SourceFile(source) => Some(source.source_file_index()),
+            _ => None,
+        });
+        if let Some(index) = source_file {
+            self.line(if verbose { 2 } else { 0 }, format!("Compiled from \"{}\"", constants.utf8_text(index)));
+        }
+
+        self.line(0, class_declaration(class));
+        if verbose {
+            let version = class.version();
+            self.line(2, format!("minor version: {}", version.minor()));
+            self.line(2, format!("major version: {}", version.major()));
+            let declared = class.declared_access_flags();
+            self.flags(2, declared.flags(), declared.names());
+            if class.access_flags() != declared {
+                let effective = class.access_flags();
+                let names = format::flag_names(effective.names()).join(", ");
+                self.line(2, format!("effective flags: (0x{:04x}) {names}", effective.flags()));
+            }
+            self.commented(2, format!("this_class: #{}", class.this_class_index()), class.name().as_str());
+            match class.super_class() {
+                Some(super_class) =>
+                    self.commented(2, format!("super_class: #{}", class.super_class_index()), super_class.as_str()),
+                None => self.line(2, "super_class: #0"),
+            }
+            self.line(2, format!("interfaces: {}, fields: {}, methods: {}, attributes: {}",
+                class.interfaces().len(), class.fields().len(), class.methods().len(), class.attributes().len()));
+            self.constant_pool();
+        }
+
+        self.line(0, "{");
+        let mut first = true;
+        for field in class.fields() {
+            if !first {
+                self.line(0, "");
+            }
+            first = false;
+            self.field(field);
+        }
+        for method in class.methods() {
+            if !first {
+                self.line(0, "");
+            }
+            first = false;
+            self.method(class, method);
+        }
+        self.line(0, "}");
+
+        if verbose {
+            for attribute in class.attributes() {
+                self.class_attribute(attribute);
+            }
+        }
+    }
+
+    fn constant_pool(&mut self) {
+        let constants = self.constants;
+        self.line(0, "Constant pool:");
+        let width = constants.pool().size().to_string().len() + 1;
+        for index in constants.indexes() {
+            let Some(tag) = constants.pool().tag(index) else { continue };
+            let number = format!("#{index}");
+            let text = format!("{number:>width$} = {:<18} {}", format!("{tag:?}"), constants.references(index));
+            match tag {
+                // These are shown as their value already
+                Tag::Utf8 | Tag::Integer | Tag::Float | Tag::Long | Tag::Double => self.line(2, text),
+                _ => {
+                    let description = constants.describe(index).unwrap_or_else(|| format::invalid(index));
+                    self.commented(2, text, description);
+                }
+            }
+        }
+    }
+
+    fn field(&mut self, field: &Field) {
+        self.line(2, format!("{}{} {};", field_modifiers(field.access_flags()), field.field_type(), field.name()));
+        if !self.options.verbose {
+            return;
+        }
+        self.line(4, format!("descriptor: {}", field.descriptor()));
+        let flags = field.access_flags();
+        self.flags(4, flags.flags(), flags.names());
+        for attribute in field.attributes() {
+            match attribute {
+                FieldAttribute::ConstantValue(value) => {
+                    let index = value.value_index();
+                    self.line(4, format!("ConstantValue: {}", self.constants.comment(index)));
+                }
+                FieldAttribute::Signature(signature) => self.signature(4, signature),
+                FieldAttribute::RuntimeVisibleAnnotations(attr) =>
+                    self.annotations(4, attribute.name(self.constants.pool()), attr.annotations()),
+                FieldAttribute::RuntimeInvisibleAnnotations(attr) =>
+                    self.annotations(4, attribute.name(self.constants.pool()), attr.annotations()),
+                FieldAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                    self.type_annotations(4, attribute.name(self.constants.pool()), attr.annotations()),
+                FieldAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                    self.type_annotations(4, attribute.name(self.constants.pool()), attr.annotations()),
+                FieldAttribute::Synthetic(_) => self.line(4, "Synthetic: true"),
+                FieldAttribute::Deprecated(_) => self.line(4, "Deprecated: true"),
+                FieldAttribute::Unknown(unknown) => self.unknown(4, unknown),
+            }
+        }
+    }
+
+    fn method(&mut self, class: &Class, method: &Method) {
+        self.line(2, method_declaration(class, method));
+        let verbose = self.options.verbose;
+        if verbose {
+            self.line(4, format!("descriptor: {}", method.descriptor()));
+            let flags = method.access_flags();
+            self.flags(4, flags.flags(), flags.names());
+        }
+
+        for attribute in method.attributes() {
+            let name = attribute.name(self.constants.pool());
+            match attribute {
+                MethodAttribute::Code(code) => self.code(method, code),
+                _ if !verbose => {}
+                MethodAttribute::Exceptions(exceptions) => {
+                    self.line(4, "Exceptions:");
+                    let names: Vec<String> = exceptions.indexes().iter()
+                        .map(|&index| self.constants.class_text(index).replace('/', "."))
+                        .collect();
+                    self.line(6, format!("throws {}", names.join(", ")));
+                }
+                MethodAttribute::Signature(signature) => self.signature(4, signature),
+                MethodAttribute::RuntimeVisibleAnnotations(attr) => self.annotations(4, name, attr.annotations()),
+                MethodAttribute::RuntimeInvisibleAnnotations(attr) => self.annotations(4, name, attr.annotations()),
+                MethodAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                    self.type_annotations(4, name, attr.annotations()),
+                MethodAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                    self.type_annotations(4, name, attr.annotations()),
+                MethodAttribute::RuntimeVisibleParameterAnnotations(attr) => {
+                    let parameters: Vec<_> = (0..attr.num_parameters()).filter_map(|i| attr.parameter(i)).collect();
+                    self.parameter_annotations(name, &parameters);
+                }
+                MethodAttribute::RuntimeInvisibleParameterAnnotations(attr) => {
+                    let parameters: Vec<_> = (0..attr.num_parameters()).filter_map(|i| attr.parameter(i)).collect();
+                    self.parameter_annotations(name, &parameters);
+                }
+                MethodAttribute::AnnotationDefault(default) => {
+                    self.line(4, "AnnotationDefault:");
+                    self.line(6, format!("default_value: {}", format::element_value(default.value(), self.constants)));
+                }
+                MethodAttribute::MethodParameters(parameters) => {
+                    self.line(4, "MethodParameters:");
+                    self.line(6, format!("{:<30} Flags", "Name"));
+                    for parameter in parameters.parameters() {
+                        let name = match parameter.name_index() {
+                            0 => "<no name>".to_string(),
+                            index => self.constants.utf8_text(index),
+                        };
+                        let flags = format::flag_names(parameter.access_flags().names()).join(", ");
+                        self.line(6, format!("{name:<30} {flags}").trim_end());
+                    }
+                }
+                MethodAttribute::Synthetic(_) => self.line(4, "Synthetic: true"),
+                MethodAttribute::Deprecated(_) => self.line(4, "Deprecated: true"),
+                MethodAttribute::Unknown(unknown) => self.unknown(4, unknown),
+            }
+        }
+    }
+
+    fn code(&mut self, method: &Method, code: &Code) {
+        self.line(4, "Code:");
+        if self.options.verbose {
+            let this_slot = if method.access_flags().is_static() { 0 } else { 1 };
+            let args_size = method.method_descriptor().parameter_slots() + this_slot;
+            self.line(6, format!("stack={}, locals={}, args_size={args_size}", code.max_stack(), code.max_locals()));
+        }
+        self.instructions(code);
+
+        if !code.exceptions().is_empty() {
+            self.line(6, "Exception table:");
+            self.line(9, "from    to  target type");
+            for handler in code.exceptions() {
+                let catch_type = match handler.catch_type() {
+                    0 => "any".to_string(),
+                    index => format!("Class {}", self.constants.class_text(index)),
+                };
+                self.line(8, format!("{:>5} {:>5} {:>5}   {catch_type}",
+                    handler.start_pc(), handler.end_pc(), handler.handler_pc()));
+            }
+        }
+
+        if !self.options.verbose {
+            return;
+        }
+        for attribute in code.attributes() {
+            let name = attribute.name(self.constants.pool());
+            match attribute {
+                CodeAttribute::LineNumberTable(table) => {
+                    self.line(6, "LineNumberTable:");
+                    for entry in table.entries() {
+                        self.line(8, format!("line {}: {}", entry.line_number(), entry.start_pc()));
+                    }
+                }
+                CodeAttribute::LocalVariableTable(table) => {
+                    self.line(6, "LocalVariableTable:");
+                    self.line(8, "Start  Length  Slot  Name   Signature");
+                    for var in table.entries() {
+                        let name = self.constants.utf8_text(var.name_index());
+                        let descriptor = self.constants.utf8_text(var.descriptor_index());
+                        self.line(8, format!("{:>5} {:>7} {:>5} {:>5}   {descriptor}", var.start_pc(), var.length(),
+                            var.index(), name));
+                    }
+                }
+                CodeAttribute::LocalVariableTypeTable(table) => {
+                    self.line(6, "LocalVariableTypeTable:");
+                    self.line(8, "Start  Length  Slot  Name   Signature");
+                    for var in table.entries() {
+                        let name = self.constants.utf8_text(var.name_index());
+                        let signature = self.constants.utf8_text(var.signature_index());
+                        self.line(8, format!("{:>5} {:>7} {:>5} {:>5}   {signature}", var.start_pc(), var.length(),
+                            var.index(), name));
+                    }
+                }
+                CodeAttribute::StackMapTable(table) => self.stack_map_table(table),
+                CodeAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                    self.type_annotations(6, name, attr.annotations()),
+                CodeAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                    self.type_annotations(6, name, attr.annotations()),
+                CodeAttribute::Unknown(unknown) => self.unknown(6, unknown),
+            }
+        }
+    }
+
+    fn instructions(&mut self, code: &Code) {
+        let constants = self.constants;
+        for result in Instructions::new(code.code()) {
+            let (pc, instruction) = match result {
+                Ok(decoded) => decoded,
+                Err(err) => {
+                    self.line(6, format!("** {err}"));
+                    return;
+                }
+            };
+
+            let mnemonic = instruction.opcode().mnemonic();
+            let start = format!("{pc:>4}: {mnemonic:<13}");
+            match format::operands(&instruction) {
+                Operands::None => self.line(6, start.trim_end()),
+                Operands::Value(value) => self.line(6, format!("{start} {value}")),
+                Operands::Local(local) => self.line(6, format!("{start} {local}")),
+                Operands::Iinc { local, increment } => self.line(6, format!("{start} {local}, {increment}")),
+                Operands::Constant(index) =>
+                    self.commented(6, format!("{start} #{index}"), constants.comment(index)),
+                Operands::ConstantAndCount(index, count) =>
+                    self.commented(6, format!("{start} #{index},  {count}"), constants.comment(index)),
+                Operands::Branch(offset) => self.line(6, format!("{start} {}", format::branch_target(pc, offset))),
+                Operands::ArrayType(ty) => self.line(6, format!("{start} {}", ty.base_type())),
+                Operands::Wide(wide) => {
+                    let modified = wide.opcode().mnemonic();
+                    match wide {
+                        WideInstruction::Iinc(local, increment) =>
+                            self.line(6, format!("{start} {modified} {local}, {increment}")),
+                        _ => self.line(6, format!("{start} {modified} {}", wide.index())),
+                    }
+                }
+                Operands::TableSwitch(switch) => {
+                    self.line(6, format!("{start} {{ // {} to {}", switch.low, switch.high));
+                    for (i, &offset) in switch.offsets.iter().enumerate() {
+                        let value = switch.low as i64 + i as i64;
+                        self.line(6, format!("{value:>24}: {}", format::branch_target(pc, offset)));
+                    }
+                    self.line(6, format!("{:>24}: {}", "default", format::branch_target(pc, switch.default)));
+                    self.line(10, "}");
+                }
+                Operands::LookupSwitch(switch) => {
+                    self.line(6, format!("{start} {{ // {}", switch.pairs.len()));
+                    for &(value, offset) in &switch.pairs {
+                        self.line(6, format!("{value:>24}: {}", format::branch_target(pc, offset)));
+                    }
+                    self.line(6, format!("{:>24}: {}", "default", format::branch_target(pc, switch.default)));
+                    self.line(10, "}");
+                }
+            }
+        }
+    }
+
+    fn stack_map_table(&mut self, table: &StackMapTable) {
+        let frames = table.entries();
+        self.line(6, format!("StackMapTable: number_of_entries = {}", frames.len()));
+        for (frame, pc) in frames.iter().zip(format::frame_addresses(frames)) {
+            self.line(8, format!("frame_type = {} /* {} */", frame.frame_type(), format::frame_kind(frame)));
+            self.commented(10, format!("offset_delta = {}", format::offset_delta(frame)), format!("pc {pc}"));
+            let (locals, stack) = format::frame_types(frame);
+            match frame {
+                Frame::Append { .. } => {
+                    self.line(10, format!("locals = {}", self.types(locals)));
+                }
+                Frame::Full { .. } => {
+                    self.line(10, format!("locals = {}", self.types(locals)));
+                    self.line(10, format!("stack = {}", self.types(stack)));
+                }
+                _ if !stack.is_empty() => self.line(10, format!("stack = {}", self.types(stack))),
+                _ => {}
+            }
+        }
+    }
+
+    fn types(&self, types: &[VerificationType]) -> String {
+        if types.is_empty() {
+            return "[]".into();
+        }
+        let types: Vec<String> = types.iter().map(|ty| format::verification_type(ty, self.constants)).collect();
+        format!("[ {} ]", types.join(", "))
+    }
+
+    fn signature(&mut self, indent: usize, signature: &Signature) {
+        let index = signature.signature_index();
+        self.commented(indent, format!("Signature: #{index}"), self.constants.utf8_text(index));
+    }
+
+    fn annotations(&mut self, indent: usize, name: Option<&str>, annotations: &[annotations::Annotation]) {
+        self.line(indent, format!("{}:", name.unwrap_or_default()));
+        for (i, annotation) in annotations.iter().enumerate() {
+            self.line(indent + 2, format!("{i}: {}", format::annotation(annotation, self.constants)));
+        }
+    }
+
+    fn parameter_annotations(&mut self, name: Option<&str>, parameters: &[&[annotations::Annotation]]) {
+        self.line(4, format!("{}:", name.unwrap_or_default()));
+        for (i, annotations) in parameters.iter().enumerate() {
+            self.line(6, format!("parameter {i}:"));
+            for (j, annotation) in annotations.iter().enumerate() {
+                self.line(8, format!("{j}: {}", format::annotation(annotation, self.constants)));
+            }
+        }
+    }
+
+    fn type_annotations(&mut self, indent: usize, name: Option<&str>, annotations: &[TypeAnnotation]) {
+        self.line(indent, format!("{}:", name.unwrap_or_default()));
+        for (i, annotation) in annotations.iter().enumerate() {
+            let target = format::type_annotation_target(annotation);
+            let annotation = format::annotation(annotation.annotation(), self.constants);
+            self.line(indent + 2, format!("{i}: {annotation}: {target}"));
+        }
+    }
+
+    fn unknown(&mut self, indent: usize, unknown: &UnknownAttribute) {
+        let name = self.constants.utf8_text(unknown.name_index());
+        let info = unknown.info();
+        self.line(indent, format!("{name}: length = 0x{:x} (unknown attribute)", info.len()));
+        for chunk in info.chunks(16) {
+            let bytes: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+            self.line(indent + 1, bytes.join(" "));
+        }
+    }
+
+    fn class_attribute(&mut self, attribute: &ClassFileAttribute) {
+        let constants = self.constants;
+        let name = attribute.name(constants.pool());
+        match attribute {
+            ClassFileAttribute::SourceFile(source) =>
+                self.line(0, format!("SourceFile: \"{}\"", constants.utf8_text(source.source_file_index()))),
+            ClassFileAttribute::Signature(signature) => self.signature(0, signature),
+            ClassFileAttribute::SourceDebugExtension(extension) => {
+                self.line(0, "SourceDebugExtension:");
+                let text = String::from_utf8_lossy(extension.debug_extension()).into_owned();
+                for line in text.lines() {
+                    self.line(2, format::escape(line));
+                }
+            }
+            ClassFileAttribute::InnerClasses(inner) => self.inner_classes(inner),
+            ClassFileAttribute::EnclosingMethod(enclosing) => {
+                let class = enclosing.class_index();
+                let text = format!("EnclosingMethod: #{class}.#{}", enclosing.method_index());
+                let comment = match enclosing.method_index() {
+                    0 => constants.class_text(class),
+                    method => format!("{}.{}", constants.class_text(class), constants.describe(method)
+                        .unwrap_or_else(|| format::invalid(method))),
+                };
+                self.commented(0, text, comment);
+            }
+            ClassFileAttribute::BootstrapMethods(methods) => self.bootstrap_methods(methods),
+            ClassFileAttribute::NestHost(host) =>
+                self.line(0, format!("NestHost: class {}", constants.class_text(host.host_class_index()))),
+            ClassFileAttribute::NestMembers(members) => self.class_list(name, members.classes()),
+            ClassFileAttribute::PermittedSubclasses(subclasses) => self.class_list(name, subclasses.classes()),
+            ClassFileAttribute::Record(record) => self.record(record),
+            ClassFileAttribute::Module(module) => self.module(module),
+            ClassFileAttribute::ModulePackages(packages) => {
+                self.line(0, "ModulePackages:");
+                for &index in packages.package_index() {
+                    self.line(2, package_text(constants, index));
+                }
+            }
+            ClassFileAttribute::ModuleMainClass(main) =>
+                self.line(0, format!("ModuleMainClass: {}", constants.class_text(main.main_class_index()))),
+            ClassFileAttribute::RuntimeVisibleAnnotations(attr) => self.annotations(0, name, attr.annotations()),
+            ClassFileAttribute::RuntimeInvisibleAnnotations(attr) => self.annotations(0, name, attr.annotations()),
+            ClassFileAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                self.type_annotations(0, name, attr.annotations()),
+            ClassFileAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                self.type_annotations(0, name, attr.annotations()),
+            ClassFileAttribute::Synthetic(_) => self.line(0, "Synthetic: true"),
+            ClassFileAttribute::Deprecated(_) => self.line(0, "Deprecated: true"),
+            ClassFileAttribute::Unknown(unknown) => self.unknown(0, unknown),
+        }
+    }
+
+    fn class_list(&mut self, name: Option<&str>, classes: &[Index]) {
+        self.line(0, format!("{}:", name.unwrap_or_default()));
+        for &index in classes {
+            self.line(2, self.constants.class_text(index));
+        }
+    }
+
+    fn inner_classes(&mut self, inner: &InnerClasses) {
+        let constants = self.constants;
+        self.line(0, "InnerClasses:");
+        for class in inner.classes() {
+            let flags = class.inner_class_access_flags();
+            let inner_index = class.inner_class_index();
+            // Shown as modifiers, like javap does
+            let mut text: String = flags.names().map(|name| name.to_lowercase() + " ").collect();
+            let mut comment = String::new();
+            if class.inner_name_index() != 0 {
+                text += &format!("#{}= ", class.inner_name_index());
+                comment += &format!("{}=", constants.utf8_text(class.inner_name_index()));
+            }
+            text += &format!("#{inner_index}");
+            comment += &format!("class {}", constants.class_text(inner_index));
+            if class.outer_class_index() != 0 {
+                text += &format!(" of #{}", class.outer_class_index());
+                comment += &format!(" of class {}", constants.class_text(class.outer_class_index()));
+            }
+            self.commented(2, text + ";", comment);
+        }
+    }
+
+    fn bootstrap_methods(&mut self, methods: &BootstrapMethods) {
+        let constants = self.constants;
+        self.line(0, "BootstrapMethods:");
+        for (i, method) in methods.methods().iter().enumerate() {
+            let handle = method.method_ref();
+            let description = constants.describe(handle).unwrap_or_else(|| format::invalid(handle));
+            self.line(2, format!("{i}: #{handle} {description}"));
+            self.line(4, "Method arguments:");
+            for &argument in method.bootstrap_arguments() {
+                let description = constants.describe(argument).unwrap_or_else(|| format::invalid(argument));
+                self.line(6, format!("#{argument} {description}"));
+            }
+        }
+    }
+
+    fn record(&mut self, record: &Record) {
+        let constants = self.constants;
+        self.line(0, "Record:");
+        for component in record.components() {
+            let name = constants.utf8_text(component.name_index());
+            self.line(2, format!("{name}: {}", constants.utf8_text(component.descriptor_index())));
+            for attribute in component.attributes() {
+                let attr_name = attribute.name(constants.pool());
+                match attribute {
+                    RecordAttribute::Signature(signature) => self.signature(4, signature),
+                    RecordAttribute::RuntimeVisibleAnnotations(attr) =>
+                        self.annotations(4, attr_name, attr.annotations()),
+                    RecordAttribute::RuntimeInvisibleAnnotations(attr) =>
+                        self.annotations(4, attr_name, attr.annotations()),
+                    RecordAttribute::RuntimeVisibleTypeAnnotations(attr) =>
+                        self.type_annotations(4, attr_name, attr.annotations()),
+                    RecordAttribute::RuntimeInvisibleTypeAnnotations(attr) =>
+                        self.type_annotations(4, attr_name, attr.annotations()),
+                    RecordAttribute::Unknown(unknown) => self.unknown(4, unknown),
+                }
+            }
+        }
+    }
+
+    fn module(&mut self, module: &Module) {
+        let constants = self.constants;
+        let version = |index: Index| match index {
+            0 => String::new(),
+            index => format!("@{}", constants.utf8_text(index)),
+        };
+        let flags = |names: Vec<String>| match names.is_empty() {
+            true => String::new(),
+            false => format!(" ({})", names.join(", ")),
+        };
+        let targets = |indexes: &[Index]| match indexes.is_empty() {
+            true => String::new(),
+            false => {
+                let names: Vec<String> = indexes.iter().map(|&index| module_text(constants, index)).collect();
+                format!(" to {}", names.join(", "))
+            }
+        };
+
+        self.line(0, format!("Module: {}{}{}", module_text(constants, module.name_index()),
+            version(module.version_index()), flags(format::flag_names(module.flags().names()))));
+        for requires in module.requires() {
+            self.line(2, format!("requires {}{}{}", module_text(constants, requires.index()),
+                version(requires.version_index()), flags(format::flag_names(requires.flags().names()))));
+        }
+        for exports in module.exports() {
+            self.line(2, format!("exports {}{}{}", package_text(constants, exports.index()),
+                targets(exports.to_index()), flags(format::flag_names(exports.flags().names()))));
+        }
+        for opens in module.opens() {
+            self.line(2, format!("opens {}{}{}", package_text(constants, opens.index()), targets(opens.to_index()),
+                flags(format::flag_names(opens.flags().names()))));
+        }
+        for &uses in module.uses() {
+            self.line(2, format!("uses {}", constants.class_text(uses)));
+        }
+        for provides in module.provides() {
+            let with: Vec<String> = provides.with_index().iter().map(|&index| constants.class_text(index)).collect();
+            self.line(2, format!("provides {} with {}", constants.class_text(provides.index()), with.join(", ")));
+        }
+    }
+}
+
+fn module_text(constants: Constants, index: Index) -> String {
+    match constants.pool().get_module(index) {
+        Some(module) => constants.utf8_text(module.name_index()),
+        None => format::invalid(index),
+    }
+}
+
+fn package_text(constants: Constants, index: Index) -> String {
+    match constants.pool().get_package(index) {
+        Some(package) => constants.utf8_text(package.name_index()),
+        None => format::invalid(index),
+    }
+}
+
+fn class_declaration(class: &Class) -> String {
+    let flags = class.access_flags();
+    let name = class.name().as_str().replace('/', ".");
+    if flags.is_module() {
+        return "module-info".into();
+    }
+
+    let mut declaration = String::new();
+    if flags.is_public() {
+        declaration += "public ";
+    }
+    let kind = if flags.is_annotation() {
+        "@interface"
+    } else if flags.is_interface() {
+        "interface"
+    } else {
+        if flags.is_final() {
+            declaration += "final ";
+        }
+        if flags.is_abstract() {
+            declaration += "abstract ";
+        }
+        if flags.is_enum() { "enum" } else { "class" }
+    };
+    declaration += &format!("{kind} {name}");
+
+    let interfaces: Vec<String> = class.interfaces().iter().map(|name| name.as_str().replace('/', ".")).collect();
+    if flags.is_interface() {
+        if !interfaces.is_empty() {
+            declaration += &format!(" extends {}", interfaces.join(","));
+        }
+    } else {
+        if let Some(super_class) = class.super_class() {
+            declaration += &format!(" extends {}", super_class.as_str().replace('/', "."));
+        }
+        if !interfaces.is_empty() {
+            declaration += &format!(" implements {}", interfaces.join(","));
+        }
+    }
+    declaration
+}
+
+fn field_modifiers(flags: FieldAccessFlags) -> String {
+    let modifiers = [
+        (flags.is_public(), "public "),
+        (flags.is_private(), "private "),
+        (flags.is_protected(), "protected "),
+        (flags.is_static(), "static "),
+        (flags.is_final(), "final "),
+        (flags.is_volatile(), "volatile "),
+        (flags.is_transient(), "transient "),
+    ];
+    modifiers.iter().filter(|(set, _)| *set).map(|(_, modifier)| *modifier).collect()
+}
+
+fn method_modifiers(flags: MethodAccessFlags) -> String {
+    let modifiers = [
+        (flags.is_public(), "public "),
+        (flags.is_private(), "private "),
+        (flags.is_protected(), "protected "),
+        (flags.is_static(), "static "),
+        (flags.is_final(), "final "),
+        (flags.is_synchronized(), "synchronized "),
+        (flags.is_native(), "native "),
+        (flags.is_abstract(), "abstract "),
+    ];
+    modifiers.iter().filter(|(set, _)| *set).map(|(_, modifier)| *modifier).collect()
+}
+
+fn method_declaration(class: &Class, method: &Method) -> String {
+    if method.name() == "<clinit>" {
+        return "static {};".into();
+    }
+
+    let descriptor = method.method_descriptor();
+    let parameters: Vec<String> = descriptor.parameters().iter().map(ToString::to_string).collect();
+    let modifiers = method_modifiers(method.access_flags());
+    if method.name() == "<init>" {
+        let name = class.name().as_str().replace('/', ".");
+        return format!("{modifiers}{name}({});", parameters.join(", "));
+    }
+    let return_type = descriptor.return_type().map_or_else(|| "void".to_string(), ToString::to_string);
+    format!("{modifiers}{return_type} {}({});", method.name(), parameters.join(", "))
+}
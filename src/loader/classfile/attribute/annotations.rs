@@ -13,8 +13,15 @@
 // You should have received a copy of the GNU General Public License along
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
-use crate::loader::classfile::constantpool;
-use crate::types::Array;
+//! The annotation model, shared by the annotation, parameter annotation, type annotation and
+//! annotation default attributes.
+//!
+//! This is what the attributes are parsed in to, and what reflection works with. Everything
+//! here refers to the constant pool by index, and offers a resolved view of itself given the
+//! class's constant pool.
+
+use crate::loader::classfile::constantpool::{self, Pool, Utf8Info};
+use crate::types::{Array, Jboolean, Jbyte, Jchar, Jdouble, Jfloat, Jint, Jlong, Jshort, Symbol};
 
 pub struct RuntimeVisible {
     annotations: Array<Annotation>,
@@ -32,6 +39,40 @@ pub struct ParameterRuntimeInvisible {
     annotations: Array<Array<Annotation>>, // Annotations by parameter
 }
 
+macro_rules! impl_annotations {
+    ($name: ident) => {
+        impl $name {
+            pub fn annotations(&self) -> &[Annotation] {
+                // SAFETY: We know this array is fully initialized
+                unsafe { self.annotations.as_slice() }
+            }
+        }
+    };
+}
+impl_annotations!(RuntimeVisible);
+impl_annotations!(RuntimeInvisible);
+
+macro_rules! impl_parameter_annotations {
+    ($name: ident) => {
+        impl $name {
+            /// The number of parameters that have annotations given for them. This may be less
+            /// than the number of parameters in the method's descriptor.
+            pub fn num_parameters(&self) -> usize {
+                self.annotations.len()
+            }
+
+            /// The annotations on the parameter with the given index, or `None` if the index is
+            /// past the end of the parameters that have annotations given for them.
+            pub fn parameter(&self, index: usize) -> Option<&[Annotation]> {
+                // SAFETY: We know this array is fully initialized
+                self.annotations.get(index).map(|annotations| unsafe { annotations.as_slice() })
+            }
+        }
+    };
+}
+impl_parameter_annotations!(ParameterRuntimeVisible);
+impl_parameter_annotations!(ParameterRuntimeInvisible);
+
 mod _attr_name {
     use super::*;
     use crate::loader::classfile::attribute::names::{Nameable, Names, impl_attr_name};
@@ -47,11 +88,49 @@ pub struct Annotation {
     elements: Array<Element>,
 }
 
+impl Annotation {
+    pub fn type_index(&self) -> constantpool::Index {
+        self.type_index
+    }
+
+    /// The field descriptor of the annotation interface, such as `Ljava/lang/Deprecated;`.
+    pub fn type_descriptor(&self, pool: &Pool) -> Option<Symbol> {
+        pool.resolve_utf8(self.type_index).map(Utf8Info::symbol)
+    }
+
+    /// Gets the value of the element with the given name, if it was given a value. Elements
+    /// that take their default value don't appear in the annotation.
+    pub fn element(&self, pool: &Pool, name: &str) -> Option<&ElementValue> {
+        self.elements().iter()
+            .find(|element| element.name(pool).is_some_and(|element_name| element_name == name))
+            .map(Element::value)
+    }
+
+    pub fn elements(&self) -> &[Element] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.elements.as_slice() }
+    }
+}
+
 pub struct Element {
     name_index: constantpool::Index,
     value: ElementValue,
 }
 
+impl Element {
+    pub fn name_index(&self) -> constantpool::Index {
+        self.name_index
+    }
+
+    pub fn name(&self, pool: &Pool) -> Option<Symbol> {
+        pool.resolve_utf8(self.name_index).map(Utf8Info::symbol)
+    }
+
+    pub fn value(&self) -> &ElementValue {
+        &self.value
+    }
+}
+
 pub enum ElementValue {
     Byte(ConstValue),
     Char(ConstValue),
@@ -69,7 +148,7 @@ pub enum ElementValue {
 }
 
 impl ElementValue {
-    pub fn tag(self) -> u8 {
+    pub fn tag(&self) -> u8 {
         match self {
             ElementValue::Byte(_) => Tag::BYTE,
             ElementValue::Char(_) => Tag::CHAR,
@@ -86,192 +165,531 @@ impl ElementValue {
             ElementValue::Array(_) => Tag::ARRAY,
         }
     }
+
+    /// Resolves the value against the constant pool, or returns `None` if it refers to an
+    /// entry of the wrong type.
+    ///
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.16.1
+    pub fn resolve<'a>(&'a self, pool: &'a Pool) -> Option<ResolvedValue<'a>> {
+        // Booleans, bytes, chars and shorts are all stored as integers
+        let int = |value: &ConstValue| pool.get_integer(value.value_index).map(|info| info.value());
+
+        let resolved = match self {
+            ElementValue::Byte(value) => ResolvedValue::Byte(int(value)? as Jbyte),
+            ElementValue::Char(value) => ResolvedValue::Char(int(value)? as Jchar),
+            ElementValue::Double(value) => ResolvedValue::Double(pool.get_double(value.value_index)?.value()),
+            ElementValue::Float(value) => ResolvedValue::Float(pool.get_float(value.value_index)?.value()),
+            ElementValue::Int(value) => ResolvedValue::Int(int(value)?),
+            ElementValue::Long(value) => ResolvedValue::Long(pool.get_long(value.value_index)?.value()),
+            ElementValue::Short(value) => ResolvedValue::Short(int(value)? as Jshort),
+            ElementValue::Boolean(value) => ResolvedValue::Boolean(int(value)? != 0),
+            ElementValue::String(value) => ResolvedValue::String(pool.resolve_utf8(value.value_index)?),
+            ElementValue::EnumConst(value) => ResolvedValue::Enum {
+                type_descriptor: pool.resolve_utf8(value.type_name_index)?.symbol(),
+                name: pool.resolve_utf8(value.const_name_index)?.symbol(),
+            },
+            ElementValue::Class(value) => ResolvedValue::Class(pool.resolve_utf8(value.info_index)?.symbol()),
+            ElementValue::Annotation(value) => ResolvedValue::Annotation(&value.value),
+            ElementValue::Array(value) => ResolvedValue::Array(value.values()),
+        };
+        Some(resolved)
+    }
+}
+
+/// An [ElementValue] with its constant pool references resolved. Nested annotations and
+/// arrays are left as they are, to be resolved as they're needed.
+pub enum ResolvedValue<'a> {
+    Byte(Jbyte),
+    Char(Jchar),
+    Double(Jdouble),
+    Float(Jfloat),
+    Int(Jint),
+    Long(Jlong),
+    Short(Jshort),
+    Boolean(Jboolean),
+    // Kept as the entry itself, as strings may contain unpaired surrogates
+    String(&'a Utf8Info),
+    Enum {
+        /// The field descriptor of the enum class
+        type_descriptor: Symbol,
+        name: Symbol,
+    },
+    /// The return descriptor of the class, such as `Ljava/lang/String;`, `I` or `V`
+    Class(Symbol),
+    Annotation(&'a Annotation),
+    Array(&'a [ElementValue]),
 }
 
 pub struct ConstValue {
     value_index: constantpool::Index
 }
 
+impl ConstValue {
+    pub fn value_index(&self) -> constantpool::Index {
+        self.value_index
+    }
+}
+
 pub struct EnumConstValue {
     type_name_index: constantpool::Index,
     const_name_index: constantpool::Index,
 }
 
+impl EnumConstValue {
+    pub fn type_name_index(&self) -> constantpool::Index {
+        self.type_name_index
+    }
+
+    pub fn const_name_index(&self) -> constantpool::Index {
+        self.const_name_index
+    }
+}
+
 pub struct ClassValue {
     info_index: constantpool::Index,
 }
 
+impl ClassValue {
+    /// The index of the return descriptor of the class, such as `Ljava/lang/Object;` or `V`.
+    pub fn info_index(&self) -> constantpool::Index {
+        self.info_index
+    }
+}
+
 pub struct AnnotationValue {
     value: Annotation,
 }
 
+impl AnnotationValue {
+    pub fn value(&self) -> &Annotation {
+        &self.value
+    }
+}
+
 pub struct ArrayValue {
     values: Array<ElementValue>,
 }
 
+impl ArrayValue {
+    pub fn values(&self) -> &[ElementValue] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.values.as_slice() }
+    }
+}
+
 pub struct Tag;
 
 impl Tag {
-    pub const BYTE: u8 = 'B' as u8;
-    pub const CHAR: u8 = 'C' as u8;
-    pub const DOUBLE: u8 = 'D' as u8;
-    pub const FLOAT: u8 = 'F' as u8;
-    pub const INT: u8 = 'I' as u8;
-    pub const LONG: u8 = 'J' as u8;
-    pub const SHORT: u8 = 'S' as u8;
-    pub const BOOLEAN: u8 = 'Z' as u8;
-    pub const STRING: u8 = 's' as u8;
-    pub const ENUM: u8 = 'e' as u8;
-    pub const CLASS: u8 = 'c' as u8;
-    pub const ANNOTATION: u8 = '@' as u8;
-    pub const ARRAY: u8 = '[' as u8;
+    pub const BYTE: u8 = b'B';
+    pub const CHAR: u8 = b'C';
+    pub const DOUBLE: u8 = b'D';
+    pub const FLOAT: u8 = b'F';
+    pub const INT: u8 = b'I';
+    pub const LONG: u8 = b'J';
+    pub const SHORT: u8 = b'S';
+    pub const BOOLEAN: u8 = b'Z';
+    pub const STRING: u8 = b's';
+    pub const ENUM: u8 = b'e';
+    pub const CLASS: u8 = b'c';
+    pub const ANNOTATION: u8 = b'@';
+    pub const ARRAY: u8 = b'[';
 }
 
-// mod _parse {
-//     use crate::buf_read_named_type_arr;
-//     use crate::loader::{BinaryReader, Parse, ParseError};
-//     use crate::types::Array;
-//     use super::*;
-//
-//     macro_rules! impl_annotation_attr {
-//         ($name: ident, $err_msg: expr, $err_msg_idx: expr) => {
-//             impl Parse<$name> for $name {
-//                 fn parse(buf: &mut BinaryReader) -> Result<$name, ParseError> {
-//                     buf_read_named_type_arr!(Annotation, annotations, buf, $err_msg, $err_msg_idx);
-//                     Ok($name { annotations })
-//                 }
-//             }
-//         };
-//     }
-//     impl_annotation_attr!(RuntimeVisible,
-//         "runtime visible annotations",
-//         "runtime visible annotations - idx {}");
-//     impl_annotation_attr!(RuntimeInvisible,
-//         "runtime invisible annotations",
-//         "runtime invisible annotations - idx {}");
-//
-//     macro_rules! impl_annotation_param_attr {
-//         ($name: ident, $err_msg: expr, $err_msg_idx: expr) => {
-//             impl Parse<$name> for $name {
-//                 fn parse(buf: &mut BinaryReader) -> Result<$name, ParseError> {
-//                     // copy and paste of buf_read_named_type_arr for type Array<Array<Annotation>>
-//                     // as that macro does not support this properly
-//                     buf.check_bytes(2, $err_msg)?;
-//
-//                     let mut annotations: Array<Array<Annotation>>;
-//                     {
-//                         // SAFETY: Guaranteed by check_bytes
-//                         let len = unsafe { buf.unsafe_read_u16() } as usize;
-//                         // TODO: We shouldn't wrap this. When we have proper error handling,
-//                         //  propagate it.
-//                         annotations = Array::new(len)
-//                             .map_err(|_| ParseError::new("cannot allocate array"))?;
-//
-//                         for i in 0..len {
-//                             let v = parse_param_arr(buf).map_err(ParseError::wrap(format!($err_msg_idx, i)))?;
-//                             annotations.set(i, v).expect("array set was somehow out of bounds");
-//                         }
-//                     }
-//                     Ok($name { annotations })
-//                 }
-//             }
-//         };
-//     }
-//     impl_annotation_param_attr!(ParameterRuntimeVisible,
-//         "runtime visible parameter annotations - parameters",
-//         "runtime visible parameter annotations - parameter idx {}");
-//     impl_annotation_param_attr!(ParameterRuntimeInvisible,
-//         "runtime invisible parameter annotations - parameters",
-//         "runtime invisible parameter annotations - parameter idx {}");
-//
-//     fn parse_param_arr(buf: &mut BinaryReader) -> Result<Array<Annotation>, ParseError> {
-//         buf_read_named_type_arr!(Annotation, result, buf, "annotations", "annotations - idx {}");
-//         Ok(result)
-//     }
-//
-//     impl Parse<Annotation> for Annotation {
-//         fn parse(buf: &mut BinaryReader) -> Result<Annotation, ParseError> {
-//             buf.check_bytes(2, "type index")?;
-//
-//             // SAFETY: Guaranteed by check_bytes
-//             let type_index = unsafe { buf.unsafe_read_u16() };
-//             buf_read_named_type_arr!(Element, elements, buf, "element", "element - idx {}");
-//
-//             Ok(Annotation { type_index, elements })
-//         }
-//     }
-//
-//     impl Parse<Element> for Element {
-//         fn parse(buf: &mut BinaryReader) -> Result<Element, ParseError> {
-//             buf.check_bytes(2, "name index")?;
-//
-//             // SAFETY: Guaranteed by check_bytes
-//             let name_index = unsafe { buf.unsafe_read_u16() };
-//             let value = ElementValue::parse(buf)?;
-//
-//             Ok(Element { name_index, value })
-//         }
-//     }
-//
-//     impl Parse<ElementValue> for ElementValue {
-//         fn parse(buf: &mut BinaryReader) -> Result<ElementValue, ParseError> {
-//             buf.check_bytes(1, "value - tag")?;
-//
-//             let tag = unsafe { buf.unsafe_read_u8() };
-//             match tag {
-//                 Tag::BYTE => Ok(ElementValue::Byte(parse_const(buf)?)),
-//                 Tag::CHAR => Ok(ElementValue::Char(parse_const(buf)?)),
-//                 Tag::DOUBLE => Ok(ElementValue::Double(parse_const(buf)?)),
-//                 Tag::FLOAT => Ok(ElementValue::Float(parse_const(buf)?)),
-//                 Tag::INT => Ok(ElementValue::Int(parse_const(buf)?)),
-//                 Tag::LONG => Ok(ElementValue::Long(parse_const(buf)?)),
-//                 Tag::SHORT => Ok(ElementValue::Short(parse_const(buf)?)),
-//                 Tag::BOOLEAN => Ok(ElementValue::Boolean(parse_const(buf)?)),
-//                 Tag::STRING => Ok(ElementValue::String(parse_const(buf)?)),
-//                 Tag::ENUM => Ok(ElementValue::EnumConst(parse_enum_const(buf)?)),
-//                 Tag::CLASS => Ok(ElementValue::Class(parse_class(buf)?)),
-//                 Tag::ANNOTATION => Ok(ElementValue::Annotation(parse_annotation(buf)?)),
-//                 Tag::ARRAY => Ok(ElementValue::Array(parse_array(buf)?)),
-//                 _ => ParseError::new(format!("value - invalid tag {tag}")).into()
-//             }
-//         }
-//     }
-//
-//     fn parse_const(buf: &mut BinaryReader) -> Result<ConstValue, ParseError> {
-//         buf.check_bytes(2, "value - const - value index")?;
-//
-//         let value_index = unsafe { buf.unsafe_read_u16() };
-//         Ok(ConstValue { value_index })
-//     }
-//
-//     fn parse_enum_const(buf: &mut BinaryReader) -> Result<EnumConstValue, ParseError> {
-//         buf.check_bytes(2 + 2, "value - enum - type name index, const name index")?;
-//
-//         let type_name_index = unsafe { buf.unsafe_read_u16() };
-//         let const_name_index = unsafe { buf.unsafe_read_u16() };
-//
-//         Ok(EnumConstValue { type_name_index, const_name_index })
-//     }
-//
-//     fn parse_class(buf: &mut BinaryReader) -> Result<ClassValue, ParseError> {
-//         buf.check_bytes(2, "value - class - info index")?;
-//
-//         let info_index = unsafe { buf.unsafe_read_u16() };
-//         Ok(ClassValue { info_index })
-//     }
-//
-//     fn parse_annotation(buf: &mut BinaryReader) -> Result<AnnotationValue, ParseError> {
-//         buf.check_bytes(2, "value - annotation")?;
-//
-//         let value = Annotation::parse(buf)
-//             .map_err(ParseError::wrap("value - annotation"))?;
-//
-//         Ok(AnnotationValue { value })
-//     }
-//
-//     fn parse_array(buf: &mut BinaryReader) -> Result<ArrayValue, ParseError> {
-//         buf_read_named_type_arr!(ElementValue, values, buf,
-//             "value - array", "value - array - idx {}");
-//         Ok(ArrayValue { values })
-//     }
-// }
+mod _parse {
+    use crate::buf_read_named_type_arr;
+    use crate::loader::{BinaryReader, ErrorKind, Parse, ParseError};
+    use crate::types::Array;
+    use super::*;
+
+    /// How deeply annotations and arrays can be nested inside element values. Element values
+    /// are parsed recursively, so without this a small class file could overflow the stack.
+    const MAX_NESTING_DEPTH: usize = 128;
+
+    macro_rules! impl_annotation_attr {
+        ($name: ident, $err_msg: expr, $err_msg_idx: expr) => {
+            impl Parse<$name> for $name {
+                fn parse(buf: &mut BinaryReader) -> Result<$name, ParseError> {
+                    buf_read_named_type_arr!(Annotation, annotations, buf, $err_msg, $err_msg_idx);
+                    Ok($name { annotations })
+                }
+            }
+        };
+    }
+    impl_annotation_attr!(RuntimeVisible,
+        "runtime visible annotations",
+        "runtime visible annotations - idx {}");
+    impl_annotation_attr!(RuntimeInvisible,
+        "runtime invisible annotations",
+        "runtime invisible annotations - idx {}");
+
+    macro_rules! impl_annotation_param_attr {
+        ($name: ident, $err_msg: expr, $err_msg_idx: expr) => {
+            impl Parse<$name> for $name {
+                fn parse(buf: &mut BinaryReader) -> Result<$name, ParseError> {
+                    // copy and paste of buf_read_named_type_arr for type Array<Array<Annotation>>
+                    // as that macro does not support this properly, and the count is a u1
+                    buf.check_bytes(1, $err_msg)?;
+
+                    let annotations: Array<Array<Annotation>>;
+                    {
+                        // SAFETY: Guaranteed by check_bytes
+                        let len = unsafe { buf.unsafe_read_u8() } as usize;
+
+                        let mut values = Vec::with_capacity(len);
+                        for i in 0..len {
+                            let v = parse_param_arr(buf).map_err(ParseError::wrap(format!($err_msg_idx, i)))?;
+                            values.push(v);
+                        }
+                        annotations = Array::from_vec(values)
+                            .map_err(|_| buf.error(ErrorKind::OutOfMemory))?;
+                    }
+                    Ok($name { annotations })
+                }
+            }
+        };
+    }
+    impl_annotation_param_attr!(ParameterRuntimeVisible,
+        "runtime visible parameter annotations - parameters",
+        "runtime visible parameter annotations - parameter idx {}");
+    impl_annotation_param_attr!(ParameterRuntimeInvisible,
+        "runtime invisible parameter annotations - parameters",
+        "runtime invisible parameter annotations - parameter idx {}");
+
+    fn parse_param_arr(buf: &mut BinaryReader) -> Result<Array<Annotation>, ParseError> {
+        buf_read_named_type_arr!(Annotation, result, buf, "annotations", "annotations - idx {}");
+        Ok(result)
+    }
+
+    // Like buf_read_named_type_arr, but passing the nesting depth through
+    fn parse_nested_arr<T>(buf: &mut BinaryReader, depth: usize, err_msg: &str,
+                           parse: fn(&mut BinaryReader, usize) -> Result<T, ParseError>) -> Result<Array<T>, ParseError> {
+        buf.check_bytes(2, err_msg)?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let len = unsafe { buf.unsafe_read_u16() } as usize;
+
+        let mut values = Vec::with_capacity(len);
+        for i in 0..len {
+            let v = parse(buf, depth).map_err(ParseError::wrap(format!("{err_msg} - idx {i}")))?;
+            values.push(v);
+        }
+        Array::from_vec(values).map_err(|_| buf.error(ErrorKind::OutOfMemory))
+    }
+
+    impl Parse<Annotation> for Annotation {
+        fn parse(buf: &mut BinaryReader) -> Result<Annotation, ParseError> {
+            parse_annotation(buf, 0)
+        }
+    }
+
+    fn parse_annotation(buf: &mut BinaryReader, depth: usize) -> Result<Annotation, ParseError> {
+        buf.check_bytes(2, "type index")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let type_index = unsafe { buf.unsafe_read_u16() };
+        let elements = parse_nested_arr(buf, depth, "element", parse_element)?;
+
+        Ok(Annotation { type_index, elements })
+    }
+
+    impl Parse<Element> for Element {
+        fn parse(buf: &mut BinaryReader) -> Result<Element, ParseError> {
+            parse_element(buf, 0)
+        }
+    }
+
+    fn parse_element(buf: &mut BinaryReader, depth: usize) -> Result<Element, ParseError> {
+        buf.check_bytes(2, "name index")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let name_index = unsafe { buf.unsafe_read_u16() };
+        let value = parse_value(buf, depth)?;
+
+        Ok(Element { name_index, value })
+    }
+
+    impl Parse<ElementValue> for ElementValue {
+        fn parse(buf: &mut BinaryReader) -> Result<ElementValue, ParseError> {
+            parse_value(buf, 0)
+        }
+    }
+
+    fn parse_value(buf: &mut BinaryReader, depth: usize) -> Result<ElementValue, ParseError> {
+        buf.check_bytes(1, "value - tag")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let tag = unsafe { buf.unsafe_read_u8() };
+        match tag {
+            Tag::BYTE => Ok(ElementValue::Byte(parse_const(buf)?)),
+            Tag::CHAR => Ok(ElementValue::Char(parse_const(buf)?)),
+            Tag::DOUBLE => Ok(ElementValue::Double(parse_const(buf)?)),
+            Tag::FLOAT => Ok(ElementValue::Float(parse_const(buf)?)),
+            Tag::INT => Ok(ElementValue::Int(parse_const(buf)?)),
+            Tag::LONG => Ok(ElementValue::Long(parse_const(buf)?)),
+            Tag::SHORT => Ok(ElementValue::Short(parse_const(buf)?)),
+            Tag::BOOLEAN => Ok(ElementValue::Boolean(parse_const(buf)?)),
+            Tag::STRING => Ok(ElementValue::String(parse_const(buf)?)),
+            Tag::ENUM => Ok(ElementValue::EnumConst(parse_enum_const(buf)?)),
+            Tag::CLASS => Ok(ElementValue::Class(parse_class(buf)?)),
+            Tag::ANNOTATION | Tag::ARRAY if depth >= MAX_NESTING_DEPTH => {
+                let msg = format!("value - nested too deeply (max depth is {MAX_NESTING_DEPTH})");
+                ParseError::new(ErrorKind::Malformed(msg), buf.position() - 1).into()
+            },
+            Tag::ANNOTATION => Ok(ElementValue::Annotation(parse_annotation_value(buf, depth + 1)?)),
+            Tag::ARRAY => Ok(ElementValue::Array(parse_array(buf, depth + 1)?)),
+            _ => {
+                let kind = ErrorKind::Malformed(format!("value - invalid tag {tag}"));
+                ParseError::new(kind, buf.position() - 1).into()
+            }
+        }
+    }
+
+    fn parse_const(buf: &mut BinaryReader) -> Result<ConstValue, ParseError> {
+        buf.check_bytes(2, "value - const - value index")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let value_index = unsafe { buf.unsafe_read_u16() };
+        Ok(ConstValue { value_index })
+    }
+
+    fn parse_enum_const(buf: &mut BinaryReader) -> Result<EnumConstValue, ParseError> {
+        buf.check_bytes(2 + 2, "value - enum - type name index, const name index")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let type_name_index = unsafe { buf.unsafe_read_u16() };
+        let const_name_index = unsafe { buf.unsafe_read_u16() };
+
+        Ok(EnumConstValue { type_name_index, const_name_index })
+    }
+
+    fn parse_class(buf: &mut BinaryReader) -> Result<ClassValue, ParseError> {
+        buf.check_bytes(2, "value - class - info index")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let info_index = unsafe { buf.unsafe_read_u16() };
+        Ok(ClassValue { info_index })
+    }
+
+    fn parse_annotation_value(buf: &mut BinaryReader, depth: usize) -> Result<AnnotationValue, ParseError> {
+        let value = parse_annotation(buf, depth)
+            .map_err(ParseError::wrap("value - annotation"))?;
+        Ok(AnnotationValue { value })
+    }
+
+    fn parse_array(buf: &mut BinaryReader, depth: usize) -> Result<ArrayValue, ParseError> {
+        let values = parse_nested_arr(buf, depth, "value - array", parse_value)?;
+        Ok(ArrayValue { values })
+    }
+}
+
+mod _write {
+    use super::*;
+    use crate::loader::{BinaryWriter, Serialize};
+
+    macro_rules! impl_annotation_attr {
+        ($name: ident) => {
+            impl Serialize for $name {
+                fn serialize(&self, buf: &mut BinaryWriter) {
+                    buf.write_arr(self.annotations());
+                }
+            }
+        };
+    }
+    impl_annotation_attr!(RuntimeVisible);
+    impl_annotation_attr!(RuntimeInvisible);
+
+    macro_rules! impl_annotation_param_attr {
+        ($name: ident) => {
+            impl Serialize for $name {
+                fn serialize(&self, buf: &mut BinaryWriter) {
+                    // The count is a u1 here, unlike most other arrays
+                    buf.write_u8(self.annotations.len() as u8);
+                    for i in 0..self.num_parameters() {
+                        buf.write_arr(self.parameter(i).expect("parameter index out of bounds"));
+                    }
+                }
+            }
+        };
+    }
+    impl_annotation_param_attr!(ParameterRuntimeVisible);
+    impl_annotation_param_attr!(ParameterRuntimeInvisible);
+
+    impl Serialize for Annotation {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.type_index);
+            buf.write_arr(self.elements());
+        }
+    }
+
+    impl Serialize for Element {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.name_index);
+            self.value.serialize(buf);
+        }
+    }
+
+    impl Serialize for ElementValue {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u8(self.tag());
+            match self {
+                ElementValue::Byte(value) | ElementValue::Char(value) | ElementValue::Double(value)
+                | ElementValue::Float(value) | ElementValue::Int(value) | ElementValue::Long(value)
+                | ElementValue::Short(value) | ElementValue::Boolean(value) | ElementValue::String(value) => {
+                    buf.write_u16(value.value_index);
+                },
+                ElementValue::EnumConst(value) => {
+                    buf.write_u16(value.type_name_index);
+                    buf.write_u16(value.const_name_index);
+                },
+                ElementValue::Class(value) => buf.write_u16(value.info_index),
+                ElementValue::Annotation(value) => value.value.serialize(buf),
+                ElementValue::Array(value) => buf.write_arr(value.values()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::testing::*;
+    use crate::loader::{BinaryReader, BinaryWriter, ErrorKind, Parse, Serialize};
+    use crate::loader::classfile::attribute::ClassFileAttribute;
+
+    // @A(x = 2, y = {E.C, Object.class}, z = @B)
+    const ANNOTATION: &[u8] = &[
+        0, 1, 0, 3,
+        0, 10, Tag::INT, 0, 2,
+        0, 11, Tag::ARRAY, 0, 2, Tag::ENUM, 0, 4, 0, 5, Tag::CLASS, 0, 6,
+        0, 12, Tag::ANNOTATION, 0, 7, 0, 0,
+    ];
+
+    fn round_trip<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut buf = BinaryWriter::new();
+        value.serialize(&mut buf);
+        buf.into_bytes()
+    }
+
+    // An element value of arrays nested `depth` deep, with the innermost one empty
+    fn nested_arrays(depth: usize) -> Vec<u8> {
+        let mut bytes = [Tag::ARRAY, 0, 1].repeat(depth);
+        let len = bytes.len();
+        bytes[len - 1] = 0;
+        bytes
+    }
+
+    #[test]
+    fn parses_annotations() {
+        let bytes = [&[0, 1], ANNOTATION].concat();
+        let attribute = RuntimeVisible::parse(&mut BinaryReader::new(bytes.clone())).expect("annotations should parse");
+
+        let [annotation] = attribute.annotations() else {
+            panic!("expected one annotation");
+        };
+        assert_eq!(annotation.type_index(), 1);
+        let [x, y, z] = annotation.elements() else {
+            panic!("expected three elements");
+        };
+        assert_eq!(x.name_index(), 10);
+        assert!(matches!(x.value(), ElementValue::Int(value) if value.value_index() == 2));
+        let ElementValue::Array(array) = y.value() else {
+            panic!("expected an array");
+        };
+        let [ElementValue::EnumConst(constant), ElementValue::Class(class)] = array.values() else {
+            panic!("expected an enum constant and a class");
+        };
+        assert_eq!((constant.type_name_index(), constant.const_name_index()), (4, 5));
+        assert_eq!(class.info_index(), 6);
+        let ElementValue::Annotation(nested) = z.value() else {
+            panic!("expected an annotation");
+        };
+        assert_eq!(nested.value().type_index(), 7);
+        assert!(nested.value().elements().is_empty());
+
+        assert_eq!(round_trip(&attribute), bytes);
+    }
+
+    #[test]
+    fn parses_parameter_annotations() {
+        // Two parameters, the first with one annotation and the second with none
+        let bytes = [&[2, 0, 1], ANNOTATION, &[0, 0]].concat();
+        let attribute = ParameterRuntimeInvisible::parse(&mut BinaryReader::new(bytes.clone()))
+            .expect("parameter annotations should parse");
+
+        assert_eq!(attribute.num_parameters(), 2);
+        assert_eq!(attribute.parameter(0).map(<[Annotation]>::len), Some(1));
+        assert_eq!(attribute.parameter(1).map(<[Annotation]>::len), Some(0));
+        assert!(attribute.parameter(2).is_none());
+        assert_eq!(round_trip(&attribute), bytes);
+    }
+
+    #[test]
+    fn limits_nesting() {
+        assert!(ElementValue::parse(&mut BinaryReader::new(nested_arrays(128))).is_ok());
+
+        let err = ElementValue::parse(&mut BinaryReader::new(nested_arrays(129))).err()
+            .expect("value should be nested too deeply");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+        // At the tag of the array that's one too deep
+        assert_eq!(err.offset(), 128 * 3);
+        assert_eq!(err.context().len(), 128);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let err = ElementValue::parse(&mut BinaryReader::new(vec![b'X', 0, 1])).err().expect("tag should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+        assert_eq!(err.offset(), 0);
+
+        let err = RuntimeVisible::parse(&mut BinaryReader::new(vec![0, 1, 0, 1, 0, 1, 0, 2, Tag::INT])).err()
+            .expect("value should be truncated");
+        assert!(matches!(err.kind(), ErrorKind::Truncated { .. }));
+        assert_eq!(err.context(), ["runtime visible annotations - idx 0", "element - idx 0"]);
+    }
+
+    #[test]
+    fn resolves_values() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let int = builder.entry(INTEGER, &200_i32.to_be_bytes());
+        let char = builder.entry(INTEGER, &65_i32.to_be_bytes());
+        let string = builder.utf8("hi");
+        let (type_name, const_name) = (builder.utf8("LE;"), builder.utf8("C"));
+        let object = builder.utf8("Ljava/lang/Object;");
+        let values = [
+            ("b", vec![Tag::BYTE, 0, int as u8]),
+            ("c", vec![Tag::CHAR, 0, char as u8]),
+            ("z", vec![Tag::BOOLEAN, 0, char as u8]),
+            ("s", vec![Tag::STRING, 0, string as u8]),
+            // Points at an integer, which is the wrong type for a double
+            ("d", vec![Tag::DOUBLE, 0, int as u8]),
+            ("e", vec![Tag::ENUM, 0, type_name as u8, 0, const_name as u8]),
+            ("k", vec![Tag::CLASS, 0, object as u8]),
+        ];
+        let elements: Vec<_> = values.into_iter()
+            .map(|(name, value)| [u16_bytes(builder.utf8(name)), value].concat())
+            .collect();
+        let annotation = [u16_bytes(builder.utf8("LA;")), table(&elements)].concat();
+        builder.attributes = vec![builder.attribute("RuntimeVisibleAnnotations", &table(&[annotation]))];
+
+        let class = builder.parse(69).expect("class should parse");
+        let pool = class.constant_pool();
+        let [ClassFileAttribute::RuntimeVisibleAnnotations(attribute)] = class.attributes() else {
+            panic!("expected annotations");
+        };
+        let annotation = &attribute.annotations()[0];
+        assert_eq!(annotation.type_descriptor(pool).map(|symbol| symbol.as_str()), Some("LA;"));
+        let resolve = |name| annotation.element(pool, name).and_then(|value| value.resolve(pool));
+
+        // Narrower types are stored as integers, and are truncated
+        assert!(matches!(resolve("b"), Some(ResolvedValue::Byte(-56))));
+        assert!(matches!(resolve("c"), Some(ResolvedValue::Char(65))));
+        assert!(matches!(resolve("z"), Some(ResolvedValue::Boolean(true))));
+        assert!(matches!(resolve("s"), Some(ResolvedValue::String(string)) if string.as_str() == "hi"));
+        assert!(matches!(resolve("e"), Some(ResolvedValue::Enum { type_descriptor, name })
+            if type_descriptor.as_str() == "LE;" && name.as_str() == "C"));
+        assert!(matches!(resolve("k"), Some(ResolvedValue::Class(class)) if class.as_str() == "Ljava/lang/Object;"));
+        assert!(resolve("d").is_none());
+        // Not given, so it takes its default value
+        assert!(annotation.element(pool, "x").is_none());
+    }
+}
@@ -18,7 +18,8 @@ use crate::loader::classfile::constantpool;
 pub use _attr_name::*;
 pub use _parse::*;
 
-use crate::types::{AccessFlags, Array};
+use crate::loader::classfile::attribute::smap::Smap;
+use crate::types::{mutf8, Array, Bytes, InnerClassAccessFlags};
 
 pub struct SourceFile {
     source_file_index: constantpool::Index,
@@ -30,6 +31,30 @@ impl SourceFile {
     }
 }
 
+/// Extra debugging information, which the VM doesn't use itself. In practice this is always
+/// a JSR-45 source map, which can be read with [SourceDebugExtension::smap].
+pub struct SourceDebugExtension {
+    debug_extension: Bytes,
+}
+
+impl SourceDebugExtension {
+    /// The raw contents of the attribute, which should be, but aren't checked to be, modified
+    /// UTF-8.
+    pub fn debug_extension(&self) -> &[u8] {
+        &self.debug_extension
+    }
+
+    pub fn as_string(&self) -> Result<String, mutf8::MalformedError> {
+        mutf8::decode(self.debug_extension())
+    }
+
+    /// Parses the contents of the attribute as a source map, or returns `None` if the
+    /// contents aren't valid modified UTF-8 or aren't a valid source map.
+    pub fn smap(&self) -> Option<Smap> {
+        Smap::parse(&self.as_string().ok()?).ok()
+    }
+}
+
 pub struct InnerClasses {
     inner_classes: Array<InnerClass>,
 }
@@ -45,7 +70,7 @@ pub struct InnerClass {
     index: constantpool::Index,
     outer_index: constantpool::Index,
     name_index: constantpool::Index,
-    access_flags: AccessFlags,
+    access_flags: InnerClassAccessFlags,
 }
 
 impl InnerClass {
@@ -61,7 +86,7 @@ impl InnerClass {
         self.name_index
     }
 
-    pub fn inner_class_access_flags(&self) -> AccessFlags {
+    pub fn inner_class_access_flags(&self) -> InnerClassAccessFlags {
         self.access_flags
     }
 }
@@ -98,6 +123,10 @@ pub struct BootstrapMethod {
 }
 
 impl BootstrapMethod {
+    pub(crate) fn new(method_ref: constantpool::Index, bootstrap_arguments: Array<constantpool::Index>) -> Self {
+        Self { method_ref, bootstrap_arguments }
+    }
+
     pub fn method_ref(&self) -> constantpool::Index {
         self.method_ref
     }
@@ -145,6 +174,7 @@ mod _attr_name {
     use crate::loader::classfile::attribute::names::{Names, Nameable, impl_attr_name};
 
     impl_attr_name!(SourceFile, SOURCE_FILE);
+    impl_attr_name!(SourceDebugExtension, SOURCE_DEBUG_EXTENSION);
     impl_attr_name!(InnerClasses, INNER_CLASSES);
     impl_attr_name!(EnclosingMethod, ENCLOSING_METHOD);
     impl_attr_name!(BootstrapMethods, BOOTSTRAP_METHODS);
@@ -156,8 +186,9 @@ mod _attr_name {
 mod _parse {
     use crate::{buf_read_named_type_arr, buf_read_u16_arr};
     use super::*;
-    use crate::loader::{Parse, ParseError, BinaryReader};
-    use crate::types::AccessFlags;
+    use crate::loader::{ErrorKind, Parse, ParseError, BinaryReader};
+    use crate::loader::classfile::attribute::{Context, ParseAttribute};
+    use crate::types::InnerClassAccessFlags;
 
     impl Parse<SourceFile> for SourceFile {
         fn parse(buf: &mut BinaryReader) -> Result<SourceFile, ParseError> {
@@ -169,6 +200,14 @@ mod _parse {
         }
     }
 
+    impl ParseAttribute for SourceDebugExtension {
+        fn parse_attribute(ctx: Context, buf: &mut BinaryReader) -> Result<SourceDebugExtension, ParseError> {
+            // The contents are the whole attribute, so the only length we have is the attribute's
+            let debug_extension = buf.read_bytes(ctx.length, "source debug extension")?;
+            Ok(SourceDebugExtension { debug_extension })
+        }
+    }
+
     impl Parse<InnerClasses> for InnerClasses {
         fn parse(buf: &mut BinaryReader) -> Result<InnerClasses, ParseError> {
             buf_read_named_type_arr!(InnerClass, inner_classes, buf,
@@ -188,7 +227,7 @@ mod _parse {
             let name_index = unsafe { buf.unsafe_read_u16() };
             let flags = unsafe { buf.unsafe_read_u16() };
 
-            Ok(InnerClass { index, outer_index, name_index, access_flags: AccessFlags::new(flags) })
+            Ok(InnerClass { index, outer_index, name_index, access_flags: InnerClassAccessFlags::new(flags) })
         }
     }
 
@@ -247,3 +286,73 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use super::*;
+    use crate::loader::{BinaryWriter, Serialize};
+
+    impl Serialize for SourceFile {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.source_file_index);
+        }
+    }
+
+    impl Serialize for SourceDebugExtension {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_bytes(self.debug_extension());
+        }
+    }
+
+    impl Serialize for InnerClasses {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_arr(self.classes());
+        }
+    }
+
+    impl Serialize for InnerClass {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.index);
+            buf.write_u16(self.outer_index);
+            buf.write_u16(self.name_index);
+            buf.write_u16(self.access_flags.flags());
+        }
+    }
+
+    impl Serialize for EnclosingMethod {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.class_index);
+            buf.write_u16(self.method_index);
+        }
+    }
+
+    impl Serialize for BootstrapMethods {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_arr(self.methods());
+        }
+    }
+
+    impl Serialize for BootstrapMethod {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.method_ref);
+            buf.write_u16_arr(self.bootstrap_arguments());
+        }
+    }
+
+    impl Serialize for NestHost {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.host_class_index);
+        }
+    }
+
+    impl Serialize for NestMembers {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16_arr(self.classes());
+        }
+    }
+
+    impl Serialize for PermittedSubclasses {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16_arr(self.classes());
+        }
+    }
+}
@@ -13,14 +13,16 @@
 // You should have received a copy of the GNU General Public License along
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
+use crate::class::bytecode::Instructions;
 use crate::loader::classfile::attribute::CodeAttribute;
-use crate::loader::classfile::attribute::stackmap;
-use crate::types::Array;
+use crate::loader::classfile::attribute::{stackmap, type_annotations};
+use crate::loader::classfile::constantpool;
+use crate::types::{Array, Bytes};
 
 pub struct Code {
     max_stack: u16,
     max_locals: u16,
-    code: Array<u8>,
+    code: Bytes,
     exceptions: Array<Exception>,
     attributes: Array<CodeAttribute>,
 }
@@ -35,14 +37,96 @@ impl Code {
     }
 
     pub fn code(&self) -> &[u8] {
-        // SAFETY: We know this array is fully initialized
-        unsafe { self.code.as_slice() }
+        &self.code
+    }
+
+    /// Decodes the instructions in the code, along with their addresses.
+    pub fn instructions(&self) -> Instructions<'_> {
+        Instructions::new(&self.code)
     }
 
     pub fn exceptions(&self) -> &[Exception] {
         // SAFETY: We know this array is fully initialized
         unsafe { self.exceptions.as_slice() }
     }
+
+    pub fn attributes(&self) -> &[CodeAttribute] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.attributes.as_slice() }
+    }
+
+    /// The stack map frames the type checking verifier checks the code against, or `None` if
+    /// there aren't any, in which case there's an implicit frame at the start of the method.
+    pub fn stack_map_table(&self) -> Option<&StackMapTable> {
+        self.attributes().iter().find_map(|attr| match attr {
+            CodeAttribute::StackMapTable(table) => Some(table.attribute()),
+            _ => None,
+        })
+    }
+
+    /// The annotations on types used in the code, such as in casts and local variable
+    /// declarations.
+    pub fn type_annotations(&self) -> Option<&type_annotations::RuntimeVisible> {
+        self.attributes().iter().find_map(|attr| match attr {
+            CodeAttribute::RuntimeVisibleTypeAnnotations(annotations) => Some(annotations.attribute()),
+            _ => None,
+        })
+    }
+
+    pub fn invisible_type_annotations(&self) -> Option<&type_annotations::RuntimeInvisible> {
+        self.attributes().iter().find_map(|attr| match attr {
+            CodeAttribute::RuntimeInvisibleTypeAnnotations(annotations) => Some(annotations.attribute()),
+            _ => None,
+        })
+    }
+
+    /// All the entries of all the `LineNumberTable` attributes, as a compiler may split the
+    /// table across as many attributes as it likes.
+    pub fn line_numbers(&self) -> impl Iterator<Item = &LineNumber> {
+        self.attributes().iter()
+            .filter_map(|attr| match attr {
+                CodeAttribute::LineNumberTable(table) => Some(table.entries()),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// All the entries of all the `LocalVariableTable` attributes.
+    pub fn local_variables(&self) -> impl Iterator<Item = &LocalVariable> {
+        self.attributes().iter()
+            .filter_map(|attr| match attr {
+                CodeAttribute::LocalVariableTable(table) => Some(table.entries()),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// All the entries of all the `LocalVariableTypeTable` attributes.
+    pub fn local_variable_types(&self) -> impl Iterator<Item = &LocalVariableType> {
+        self.attributes().iter()
+            .filter_map(|attr| match attr {
+                CodeAttribute::LocalVariableTypeTable(table) => Some(table.entries()),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Gets the source line that the instruction at `pc` was compiled from, or `None` if
+    /// there's no line number information for it.
+    ///
+    /// Entries give the line for the code starting at their `start_pc`, up until the next
+    /// entry, but they can be in any order, so the closest one at or before `pc` is the one.
+    pub fn line_number(&self, pc: u16) -> Option<u16> {
+        self.line_numbers()
+            .filter(|entry| entry.start_pc <= pc)
+            .max_by_key(|entry| entry.start_pc)
+            .map(|entry| entry.line_number)
+    }
+
+    /// Gets the local variable in the given local variable slot at `pc`, if it's known.
+    pub fn local_variable(&self, index: u16, pc: u16) -> Option<&LocalVariable> {
+        self.local_variables().find(|var| var.index == index && var.is_live_at(pc))
+    }
 }
 
 pub struct Exception {
@@ -53,6 +137,10 @@ pub struct Exception {
 }
 
 impl Exception {
+    pub(crate) fn new(start_pc: u16, end_pc: u16, handler_pc: u16, catch_type: u16) -> Self {
+        Self { start_pc, end_pc, handler_pc, catch_type }
+    }
+
     pub fn start_pc(&self) -> u16 {
         self.start_pc
     }
@@ -81,39 +169,184 @@ impl StackMapTable {
     }
 }
 
+pub struct LineNumberTable {
+    entries: Array<LineNumber>,
+}
+
+impl LineNumberTable {
+    pub fn entries(&self) -> &[LineNumber] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.entries.as_slice() }
+    }
+}
+
+pub struct LineNumber {
+    start_pc: u16,
+    line_number: u16,
+}
+
+impl LineNumber {
+    pub(crate) fn new(start_pc: u16, line_number: u16) -> Self {
+        Self { start_pc, line_number }
+    }
+
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn line_number(&self) -> u16 {
+        self.line_number
+    }
+}
+
+pub struct LocalVariableTable {
+    entries: Array<LocalVariable>,
+}
+
+impl LocalVariableTable {
+    pub fn entries(&self) -> &[LocalVariable] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.entries.as_slice() }
+    }
+}
+
+pub struct LocalVariable {
+    start_pc: u16,
+    length: u16,
+    name_index: constantpool::Index,
+    descriptor_index: constantpool::Index,
+    index: u16,
+}
+
+impl LocalVariable {
+    pub(crate) fn new(start_pc: u16, length: u16, name_index: constantpool::Index,
+                      descriptor_index: constantpool::Index, index: u16) -> Self {
+        Self { start_pc, length, name_index, descriptor_index, index }
+    }
+
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn name_index(&self) -> constantpool::Index {
+        self.name_index
+    }
+
+    pub fn descriptor_index(&self) -> constantpool::Index {
+        self.descriptor_index
+    }
+
+    /// The local variable slot the variable is stored in.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Whether the variable has a value at `pc`, which is from `start_pc` inclusive to
+    /// `start_pc + length` exclusive.
+    pub fn is_live_at(&self, pc: u16) -> bool {
+        pc >= self.start_pc && (pc as u32) < self.start_pc as u32 + self.length as u32
+    }
+}
+
+pub struct LocalVariableTypeTable {
+    entries: Array<LocalVariableType>,
+}
+
+impl LocalVariableTypeTable {
+    pub fn entries(&self) -> &[LocalVariableType] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.entries.as_slice() }
+    }
+}
+
+/// Like a [LocalVariable], but for variables with generic types, giving their signature
+/// rather than their descriptor.
+pub struct LocalVariableType {
+    start_pc: u16,
+    length: u16,
+    name_index: constantpool::Index,
+    signature_index: constantpool::Index,
+    index: u16,
+}
+
+impl LocalVariableType {
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn name_index(&self) -> constantpool::Index {
+        self.name_index
+    }
+
+    pub fn signature_index(&self) -> constantpool::Index {
+        self.signature_index
+    }
+
+    /// The local variable slot the variable is stored in.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
 mod _attr_name {
     use super::*;
     use crate::loader::classfile::attribute::names::{Names, Nameable, impl_attr_name};
 
     impl_attr_name!(Code, CODE);
     impl_attr_name!(StackMapTable, STACK_MAP_TABLE);
+    impl_attr_name!(LineNumberTable, LINE_NUMBER_TABLE);
+    impl_attr_name!(LocalVariableTable, LOCAL_VARIABLE_TABLE);
+    impl_attr_name!(LocalVariableTypeTable, LOCAL_VARIABLE_TYPE_TABLE);
 }
 
 mod _parse {
-    use crate::{buf_read_named_type_arr, buf_read_u8_arr_lensize};
+    use crate::buf_read_named_type_arr;
     use super::*;
     use super::stackmap::Frame;
-    use crate::loader::{Parse, ParseError, BinaryReader};
+    use crate::loader::classfile::attribute::{parse_attributes, Context, ParseAttribute};
+    use crate::loader::{Parse, ParseError, BinaryReader, ErrorKind};
+    use crate::loader::classfile::attribute::names::Nameable;
 
-    impl Parse<Code> for Code {
-        fn parse(buf: &mut BinaryReader) -> Result<Code, ParseError> {
+    impl ParseAttribute for Code {
+        fn parse_attribute(ctx: Context, buf: &mut BinaryReader) -> Result<Code, ParseError> {
             // 2 max stack, 2 max locals, 4 code length
-            buf.check_bytes(2 + 2, "code - max stack, max locals, code length")?;
+            buf.check_bytes(2 + 2 + 4, "code - max stack, max locals, code length")?;
 
             // Safety: Guaranteed by check_bytes
             let max_stack = unsafe { buf.unsafe_read_u16() };
             let max_locals = unsafe { buf.unsafe_read_u16() };
+            let code_length = unsafe { buf.unsafe_read_u32() } as usize;
 
-            buf_read_u8_arr_lensize!(code, buf, unsafe_read_u32, "code - code array");
+            // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.3
+            if code_length == 0 || code_length >= 65536 {
+                let kind = ErrorKind::MalformedAttribute {
+                    name: Code::name().to_string(),
+                    reason: format!("code length must be greater than 0 and less than 65536, but is {code_length}"),
+                };
+                return ParseError::new(kind, buf.position() - 4).into();
+            }
+
+            let code = buf.read_bytes(code_length, "code - code array")?;
             buf_read_named_type_arr!(Exception, exceptions, buf,
                 "code - exceptions", "code - exceptions - idx {}");
 
+            let attributes = parse_attributes(ctx.pool, ctx.version, buf)
+                .map_err(ParseError::wrap("code - attributes"))?;
+
             Ok(Code {
                 max_stack,
                 max_locals,
                 code,
                 exceptions,
-                attributes: Array::empty() // TODO: Attributes
+                attributes,
             })
         }
     }
@@ -132,6 +365,72 @@ mod _parse {
         }
     }
 
+    impl Parse<LineNumberTable> for LineNumberTable {
+        fn parse(buf: &mut BinaryReader) -> Result<LineNumberTable, ParseError> {
+            buf_read_named_type_arr!(LineNumber, entries, buf,
+                "code - line number table", "code - line number table - idx {}");
+            Ok(LineNumberTable { entries })
+        }
+    }
+
+    impl Parse<LineNumber> for LineNumber {
+        fn parse(buf: &mut BinaryReader) -> Result<LineNumber, ParseError> {
+            buf.check_bytes(2 + 2, "line number")?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let start_pc = unsafe { buf.unsafe_read_u16() };
+            let line_number = unsafe { buf.unsafe_read_u16() };
+
+            Ok(LineNumber { start_pc, line_number })
+        }
+    }
+
+    impl Parse<LocalVariableTable> for LocalVariableTable {
+        fn parse(buf: &mut BinaryReader) -> Result<LocalVariableTable, ParseError> {
+            buf_read_named_type_arr!(LocalVariable, entries, buf,
+                "code - local variable table", "code - local variable table - idx {}");
+            Ok(LocalVariableTable { entries })
+        }
+    }
+
+    impl Parse<LocalVariable> for LocalVariable {
+        fn parse(buf: &mut BinaryReader) -> Result<LocalVariable, ParseError> {
+            buf.check_bytes(2 + 2 + 2 + 2 + 2, "local variable")?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let start_pc = unsafe { buf.unsafe_read_u16() };
+            let length = unsafe { buf.unsafe_read_u16() };
+            let name_index = unsafe { buf.unsafe_read_u16() };
+            let descriptor_index = unsafe { buf.unsafe_read_u16() };
+            let index = unsafe { buf.unsafe_read_u16() };
+
+            Ok(LocalVariable { start_pc, length, name_index, descriptor_index, index })
+        }
+    }
+
+    impl Parse<LocalVariableTypeTable> for LocalVariableTypeTable {
+        fn parse(buf: &mut BinaryReader) -> Result<LocalVariableTypeTable, ParseError> {
+            buf_read_named_type_arr!(LocalVariableType, entries, buf,
+                "code - local variable type table", "code - local variable type table - idx {}");
+            Ok(LocalVariableTypeTable { entries })
+        }
+    }
+
+    impl Parse<LocalVariableType> for LocalVariableType {
+        fn parse(buf: &mut BinaryReader) -> Result<LocalVariableType, ParseError> {
+            buf.check_bytes(2 + 2 + 2 + 2 + 2, "local variable type")?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let start_pc = unsafe { buf.unsafe_read_u16() };
+            let length = unsafe { buf.unsafe_read_u16() };
+            let name_index = unsafe { buf.unsafe_read_u16() };
+            let signature_index = unsafe { buf.unsafe_read_u16() };
+            let index = unsafe { buf.unsafe_read_u16() };
+
+            Ok(LocalVariableType { start_pc, length, name_index, signature_index, index })
+        }
+    }
+
     impl Parse<StackMapTable> for StackMapTable {
         fn parse(buf: &mut BinaryReader) -> Result<StackMapTable, ParseError> {
             buf_read_named_type_arr!(Frame, entries, buf,
@@ -140,3 +439,237 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use super::*;
+    use crate::loader::classfile::attribute::{serialize_attributes, SerializeAttribute, WriteContext};
+    use crate::loader::{BinaryWriter, Serialize};
+
+    impl SerializeAttribute for Code {
+        fn serialize_attribute(&self, ctx: &WriteContext, buf: &mut BinaryWriter) {
+            buf.write_u16(self.max_stack);
+            buf.write_u16(self.max_locals);
+            buf.write_u32(self.code().len() as u32);
+            buf.write_bytes(self.code());
+            buf.write_arr(self.exceptions());
+            serialize_attributes(self.attributes(), ctx, buf);
+        }
+    }
+
+    impl Serialize for Exception {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.start_pc);
+            buf.write_u16(self.end_pc);
+            buf.write_u16(self.handler_pc);
+            buf.write_u16(self.catch_type);
+        }
+    }
+
+    impl Serialize for LineNumberTable {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_arr(self.entries());
+        }
+    }
+
+    impl Serialize for LineNumber {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.start_pc);
+            buf.write_u16(self.line_number);
+        }
+    }
+
+    impl Serialize for LocalVariableTable {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_arr(self.entries());
+        }
+    }
+
+    impl Serialize for LocalVariable {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.start_pc);
+            buf.write_u16(self.length);
+            buf.write_u16(self.name_index);
+            buf.write_u16(self.descriptor_index);
+            buf.write_u16(self.index);
+        }
+    }
+
+    impl Serialize for LocalVariableTypeTable {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_arr(self.entries());
+        }
+    }
+
+    impl Serialize for LocalVariableType {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.start_pc);
+            buf.write_u16(self.length);
+            buf.write_u16(self.name_index);
+            buf.write_u16(self.signature_index);
+            buf.write_u16(self.index);
+        }
+    }
+
+    impl Serialize for StackMapTable {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_arr(self.entries());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::parse::BinaryReader;
+    use crate::class::{parse_class, Class};
+    use crate::class::testing::*;
+    use crate::loader::{ErrorKind, ParseError};
+    use crate::loader::classfile::attribute::KnownAttribute;
+    use crate::loader::classfile::attribute::type_annotations::{TargetInfo, TargetType};
+    use super::*;
+
+    fn array<T>(values: Vec<T>) -> Array<T> {
+        Array::from_vec(values).unwrap_or_else(|_| panic!("test arrays should fit in memory"))
+    }
+
+    // Built directly, so the lookups are tested apart from parsing
+    fn code_with(attributes: Vec<CodeAttribute>) -> Code {
+        Code {
+            max_stack: 0,
+            max_locals: 2,
+            code: Bytes::from(Vec::new()),
+            exceptions: Array::empty(),
+            attributes: array(attributes),
+        }
+    }
+
+    fn known<T>(attribute: T) -> KnownAttribute<T> {
+        KnownAttribute { name_index: 0, attribute }
+    }
+
+    fn line_numbers(entries: &[(u16, u16)]) -> CodeAttribute {
+        let entries = entries.iter()
+            .map(|&(start_pc, line_number)| LineNumber { start_pc, line_number })
+            .collect();
+        CodeAttribute::LineNumberTable(known(LineNumberTable { entries: array(entries) }))
+    }
+
+    #[test]
+    fn line_number_lookup() {
+        // Split across two tables, neither of which is in order
+        let code = code_with(vec![line_numbers(&[(4, 12), (2, 11)]), line_numbers(&[(8, 14), (6, 13)])]);
+
+        assert_eq!(code.line_numbers().count(), 4);
+        // Nothing covers the code before the first entry
+        assert_eq!(code.line_number(0), None);
+        assert_eq!(code.line_number(1), None);
+        let lines = (2..10).map(|pc| code.line_number(pc)).collect::<Vec<_>>();
+        assert_eq!(lines, [11, 11, 12, 12, 13, 13, 14, 14].map(Some));
+    }
+
+    #[test]
+    fn local_variable_lookup() {
+        // x is in slot 1 from pc 2 for 3 bytes
+        let x = LocalVariable { start_pc: 2, length: 3, name_index: 1, descriptor_index: 2, index: 1 };
+        let entries = array(vec![x]);
+        let code = code_with(vec![CodeAttribute::LocalVariableTable(known(LocalVariableTable { entries }))]);
+
+        let live = (0..10).filter(|&pc| code.local_variable(1, pc).is_some()).collect::<Vec<_>>();
+        assert_eq!(live, [2, 3, 4]);
+        let var = code.local_variable(1, 2).expect("x should be live");
+        assert_eq!((var.name_index(), var.descriptor_index()), (1, 2));
+        // The end is exclusive
+        assert!(!var.is_live_at(2 + 3));
+        assert!(code.local_variable(0, 2).is_none());
+
+        // Even when the end is past the largest pc
+        let last = LocalVariable { start_pc: 65535, length: 1, name_index: 1, descriptor_index: 2, index: 0 };
+        assert!(last.is_live_at(65535));
+    }
+
+    // Parses a class with a method with the given code, and attributes nested in its Code
+    fn parse_code(mut builder: ClassBuilder, code: &[u8], attributes: &[Vec<u8>]) -> Result<Class, ParseError> {
+        let code = builder.code(1, 2, code, attributes);
+        let method = builder.member(ACC_STATIC, "run", "()V", &[code]);
+        builder.methods.push(method);
+        builder.parse(69)
+    }
+
+    #[test]
+    fn code_length_limits() {
+        let parse = |code: &[u8]| parse_code(ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER), code, &[]);
+        let malformed = |result: Result<Class, ParseError>| result.err().is_some_and(|err|
+            matches!(err.kind(), ErrorKind::MalformedAttribute { name, .. } if name == "Code"));
+
+        assert!(malformed(parse(&[])));
+        assert!(malformed(parse(&vec![0; 65536])));
+        // A return, padded out with nops in front of it
+        let mut code = vec![0; 65535];
+        code[65534] = 0xB1;
+        assert!(parse(&code).is_ok());
+    }
+
+    #[test]
+    fn nested_attribute_accessors() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        // A same frame at the return
+        let frames = builder.attribute("StackMapTable", &[0, 1, 2]);
+        // @NonNull on the local variable in slot 1, and @Nullable on the null
+        let non_null = builder.utf8("LNonNull;");
+        let local_var = [vec![0, 1, 0x40, 0, 1], [2, 1, 1].map(u16_bytes).concat(), vec![0]].concat();
+        let visible = [local_var, u16_bytes(non_null), u16_bytes(0)].concat();
+        let visible = builder.attribute("RuntimeVisibleTypeAnnotations", &visible);
+        let nullable = builder.utf8("LNullable;");
+        let invisible = [vec![0, 1, 0x47, 0, 0, 0, 0], u16_bytes(nullable), u16_bytes(0)].concat();
+        let invisible = builder.attribute("RuntimeInvisibleTypeAnnotations", &invisible);
+        let name = builder.utf8("names");
+        let signature = builder.utf8("Ljava/util/List<Ljava/lang/String;>;");
+        let variable = [2, 1, name, signature, 1].map(u16_bytes).concat();
+        let variable_types = builder.attribute("LocalVariableTypeTable", &table(&[variable]));
+
+        // aconst_null, astore_1, return
+        let attributes = [frames, visible, invisible, variable_types];
+        let class = parse_code(builder, &[0x01, 0x4C, 0xB1], &attributes).expect("class should parse");
+        let code = class.methods()[0].code().expect("method should have code");
+
+        let frames = code.stack_map_table().expect("code should have a stack map table");
+        assert!(matches!(frames.entries(), [stackmap::Frame::Same { frame_type: 2 }]));
+        let [visible] = code.type_annotations().expect("code should have type annotations").annotations() else {
+            panic!("expected one visible type annotation");
+        };
+        assert_eq!(visible.target_type(), TargetType::LocalVar);
+        assert_eq!(visible.annotation().type_index(), non_null);
+        let [invisible] = code.invisible_type_annotations().expect("code should have invisible type annotations").annotations() else {
+            panic!("expected one invisible type annotation");
+        };
+        assert!(matches!(invisible.target_info(), TargetInfo::TypeArgument { offset: 0, index: 0 }));
+        assert_eq!(invisible.annotation().type_index(), nullable);
+        let variable_types: Vec<_> = code.local_variable_types()
+            .map(|var| (var.start_pc(), var.length(), var.name_index(), var.signature_index(), var.index()))
+            .collect();
+        assert_eq!(variable_types, [(2, 1, name, signature, 1)]);
+    }
+
+    #[test]
+    fn missing_nested_attributes() {
+        let class = parse_code(ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER), &[0xB1], &[]).expect("class should parse");
+        let code = class.methods()[0].code().expect("method should have code");
+        assert!(code.stack_map_table().is_none());
+        assert!(code.type_annotations().is_none());
+        assert!(code.invisible_type_annotations().is_none());
+        assert_eq!(code.local_variable_types().count(), 0);
+    }
+
+    #[test]
+    fn trickled_code_keeps_only_itself() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let code = builder.code(0, 0, &[0xB1], &[]);
+        let method = builder.member(ACC_STATIC, "run", "()V", &[code]);
+        builder.methods.push(method);
+        let bytes = builder.build(69, 0);
+
+        let class = parse_class(&mut BinaryReader::from_read(Trickle(&bytes))).expect("class should parse");
+        let code = class.methods()[0].code().expect("should have code");
+        assert_eq!(code.code(), &[0xB1]);
+        assert_eq!(code.code.buffer_len(), 1);
+    }
+}
@@ -46,3 +46,14 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use crate::loader::{BinaryWriter, Serialize};
+    use super::*;
+
+    impl Serialize for ConstantValue {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.value_index);
+        }
+    }
+}
@@ -14,7 +14,7 @@
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
 use crate::loader::classfile::constantpool;
-use crate::types::{AccessFlags, Array};
+use crate::types::{Array, ParameterAccessFlags};
 
 pub struct Exceptions {
     exception_indexes: Array<constantpool::Index>,
@@ -40,7 +40,7 @@ impl MethodParameters {
 
 pub struct MethodParameter {
     name_index: constantpool::Index,
-    access_flags: AccessFlags,
+    access_flags: ParameterAccessFlags,
 }
 
 impl MethodParameter {
@@ -48,7 +48,7 @@ impl MethodParameter {
         self.name_index
     }
 
-    pub fn access_flags(&self) -> AccessFlags {
+    pub fn access_flags(&self) -> ParameterAccessFlags {
         self.access_flags
     }
 }
@@ -69,11 +69,12 @@ mod _attr_name {
 
     impl_attr_name!(Exceptions, EXCEPTIONS);
     impl_attr_name!(MethodParameters, METHOD_PARAMETERS);
+    impl_attr_name!(AnnotationDefault, ANNOTATION_DEFAULT);
 }
 
 mod _parse {
-    use crate::{buf_read_named_type_arr, buf_read_u16_arr};
-    use crate::loader::{BinaryReader, Parse, ParseError};
+    use crate::buf_read_u16_arr;
+    use crate::loader::{BinaryReader, ErrorKind, Parse, ParseError};
     use super::*;
     use super::super::annotations;
 
@@ -86,8 +87,19 @@ mod _parse {
 
     impl Parse<MethodParameters> for MethodParameters {
         fn parse(buf: &mut BinaryReader) -> Result<MethodParameters, ParseError> {
-            buf_read_named_type_arr!(MethodParameter, parameters, buf,
-                "method parameters", "method parameters - idx {}");
+            // Cannot use macro as the parameter count is a u1, not a u2
+            buf.check_bytes(1, "method parameters")?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let len = unsafe { buf.unsafe_read_u8() } as usize;
+            let mut parameters = Array::new(len)
+                .map_err(|_| buf.error(ErrorKind::OutOfMemory))?;
+
+            for i in 0..len {
+                let parameter = MethodParameter::parse(buf)
+                    .map_err(ParseError::wrap(format!("method parameters - idx {i}")))?;
+                parameters.set(i, parameter).expect("array set was somehow out of bounds");
+            }
             Ok(MethodParameters { parameters })
         }
     }
@@ -100,7 +112,7 @@ mod _parse {
             // SAFETY: Guaranteed by check_bytes
             let name_index = unsafe { buf.unsafe_read_u16() };
             let flags = unsafe { buf.unsafe_read_u16() };
-            Ok(MethodParameter { name_index, access_flags: AccessFlags::new(flags) })
+            Ok(MethodParameter { name_index, access_flags: ParameterAccessFlags::new(flags) })
         }
     }
 
@@ -112,3 +124,37 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use crate::loader::{BinaryWriter, Serialize};
+    use super::*;
+
+    impl Serialize for Exceptions {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16_arr(self.indexes());
+        }
+    }
+
+    impl Serialize for MethodParameters {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            // The parameter count is a u1, not a u2
+            buf.write_u8(self.parameters().len() as u8);
+            for parameter in self.parameters() {
+                parameter.serialize(buf);
+            }
+        }
+    }
+
+    impl Serialize for MethodParameter {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.name_index);
+            buf.write_u16(self.access_flags.flags());
+        }
+    }
+
+    impl Serialize for AnnotationDefault {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            self.value.serialize(buf);
+        }
+    }
+}
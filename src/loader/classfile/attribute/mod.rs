@@ -14,22 +14,150 @@
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
 mod names;
-mod classfile;
-mod field;
-mod module;
-mod record;
-mod method;
-mod code;
-mod stackmap;
-mod annotations;
-mod type_annotations;
+pub mod classfile;
+pub mod field;
+pub mod module;
+pub mod record;
+pub mod method;
+pub mod code;
+pub mod stackmap;
+pub mod smap;
+pub mod annotations;
+pub mod type_annotations;
 
 pub use _attr_name::*;
 pub use _parse::*;
+pub use _write::*;
+pub use self::names::Location;
 
+use std::ops::Deref;
 use std::sync::OnceLock;
 use self::names::{Names, Nameable};
-use crate::loader::classfile::constantpool;
+use crate::loader::{BinaryReader, BinaryWriter, Parse, ParseError, Serialize};
+use crate::loader::classfile::constantpool::{self, Pool, Utf8Info};
+use crate::types::{Array, Bytes, Symbol, Version};
+use crate::types::signature::{ClassSignature, FieldSignature, MethodSignature, SignatureError};
+
+/// The attributes that are allowed to appear in a single attribute table, such as the
+/// attributes of a `field_info` structure.
+///
+/// This is implemented by each of the attribute enums, and is what [parse_attributes] uses
+/// to turn an attribute's name into the right variant.
+pub trait AttributeSet: Sized {
+    /// The structure whose attribute table this set is for.
+    const LOCATION: Location;
+
+    /// Parses the body of the attribute with the given name, or returns `None` if this set
+    /// doesn't contain an attribute with that name. The index of the name is kept, so that the
+    /// attribute can be written back out with it.
+    fn parse_named(name: &str, name_index: constantpool::Index, ctx: Context,
+                   buf: &mut BinaryReader) -> Result<Option<Self>, ParseError>;
+
+    /// Wraps an attribute that isn't part of this set.
+    fn unknown(attribute: UnknownAttribute) -> Self;
+
+    /// Writes the whole `attribute_info` structure, including the name and length.
+    fn serialize(&self, ctx: &WriteContext, buf: &mut BinaryWriter);
+
+    /// The name of the attribute. Only unknown attributes need the pool for this, and give
+    /// `None` if their name index isn't a Utf8 entry.
+    fn name<'a>(&self, pool: &'a Pool) -> Option<&'a str>;
+}
+
+/// What an attribute is being read in the context of. Attributes that have attribute tables
+/// of their own need this to read them.
+#[derive(Copy, Clone)]
+pub struct Context<'a> {
+    pub pool: &'a Pool,
+    pub version: Version,
+    /// The `attribute_length` of the attribute being read
+    pub length: usize,
+}
+
+/// Parses the body of an attribute. Attributes that don't need the [Context] get this from
+/// their [Parse] implementation.
+pub trait ParseAttribute: Sized {
+    fn parse_attribute(ctx: Context, buf: &mut BinaryReader) -> Result<Self, ParseError>;
+}
+
+impl<T: Parse<T>> ParseAttribute for T {
+    fn parse_attribute(_: Context, buf: &mut BinaryReader) -> Result<T, ParseError> {
+        T::parse(buf)
+    }
+}
+
+/// What attributes are written in the context of, the counterpart to [Context].
+pub struct WriteContext<'a> {
+    pub pool: &'a Pool,
+}
+
+impl<'a> WriteContext<'a> {
+    pub fn new(pool: &'a Pool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Writes the body of an attribute, the reverse of [ParseAttribute]. Attributes that don't
+/// need the [WriteContext] get this from their [Serialize] implementation.
+pub trait SerializeAttribute {
+    fn serialize_attribute(&self, ctx: &WriteContext, buf: &mut BinaryWriter);
+}
+
+impl<T: Serialize> SerializeAttribute for T {
+    fn serialize_attribute(&self, _: &WriteContext, buf: &mut BinaryWriter) {
+        self.serialize(buf)
+    }
+}
+
+macro_rules! attribute_set {
+    ($set: ident, $location: ident, $($variant: ident => $typ: ty),+) => {
+        impl AttributeSet for $set {
+            const LOCATION: Location = Location::$location;
+
+            fn parse_named(name: &str, name_index: constantpool::Index, ctx: Context,
+                           buf: &mut BinaryReader) -> Result<Option<$set>, ParseError> {
+                $(
+                    if name == <$typ as Nameable>::name() {
+                        return <$typ as ParseAttribute>::parse_attribute(ctx, buf)
+                            .map(|attribute| Some($set::$variant(KnownAttribute { name_index, attribute })));
+                    }
+                )+
+                Ok(None)
+            }
+
+            fn unknown(attribute: UnknownAttribute) -> $set {
+                $set::Unknown(attribute)
+            }
+
+            fn serialize(&self, ctx: &WriteContext, buf: &mut BinaryWriter) {
+                match self {
+                    $(
+                        $set::$variant(attribute) => {
+                            buf.write_u16(attribute.name_index);
+                            buf.write_u32_len_prefixed(|buf| attribute.attribute.serialize_attribute(ctx, buf));
+                        },
+                    )+
+                    $set::Unknown(attribute) => attribute.serialize(buf),
+                }
+            }
+
+            fn name<'a>(&self, pool: &'a Pool) -> Option<&'a str> {
+                match self {
+                    $($set::$variant(_) => Some(<$typ as Nameable>::name()),)+
+                    $set::Unknown(attribute) => attribute.name(pool).map(Utf8Info::as_str),
+                }
+            }
+        }
+
+        #[cfg(test)]
+        impl $set {
+            // The names of the attributes in the set, so that tests can check they all have a rule
+            fn predefined_names() -> Vec<&'static str> {
+                vec![$(<$typ as Nameable>::name()),+]
+            }
+        }
+    };
+}
 
 // TODO: Do we actually need all the names in a Vec? We don't for now
 // macro_rules! attr_names {
@@ -49,24 +177,50 @@ use crate::loader::classfile::constantpool;
 // }
 
 pub enum ClassFileAttribute {
-    Signature(Signature),
-    RuntimeVisibleAnnotations(annotations::RuntimeVisible),
-    RuntimeInvisibleAnnotations(annotations::RuntimeInvisible),
-    Synthetic(Synthetic),
-    Deprecated(Deprecated),
-    SourceFile(classfile::SourceFile),
-    InnerClasses(classfile::InnerClasses),
-    EnclosingMethod(classfile::EnclosingMethod),
-    BootstrapMethods(classfile::BootstrapMethods),
-    Module(module::Module),
-    ModulePackages(module::ModulePackages),
-    ModuleMainClass(module::ModuleMainClass),
-    NestHost(classfile::NestHost),
-    NestMembers(classfile::NestMembers),
-    Record(record::Record),
-    PermittedSubclasses(classfile::PermittedSubclasses),
+    Signature(KnownAttribute<Signature>),
+    RuntimeVisibleAnnotations(KnownAttribute<annotations::RuntimeVisible>),
+    RuntimeInvisibleAnnotations(KnownAttribute<annotations::RuntimeInvisible>),
+    RuntimeVisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeVisible>),
+    RuntimeInvisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeInvisible>),
+    Synthetic(KnownAttribute<Synthetic>),
+    Deprecated(KnownAttribute<Deprecated>),
+    SourceFile(KnownAttribute<classfile::SourceFile>),
+    SourceDebugExtension(KnownAttribute<classfile::SourceDebugExtension>),
+    InnerClasses(KnownAttribute<classfile::InnerClasses>),
+    EnclosingMethod(KnownAttribute<classfile::EnclosingMethod>),
+    BootstrapMethods(KnownAttribute<classfile::BootstrapMethods>),
+    Module(KnownAttribute<module::Module>),
+    ModulePackages(KnownAttribute<module::ModulePackages>),
+    ModuleMainClass(KnownAttribute<module::ModuleMainClass>),
+    NestHost(KnownAttribute<classfile::NestHost>),
+    NestMembers(KnownAttribute<classfile::NestMembers>),
+    Record(KnownAttribute<record::Record>),
+    PermittedSubclasses(KnownAttribute<classfile::PermittedSubclasses>),
+    Unknown(UnknownAttribute),
 }
 
+attribute_set!(ClassFileAttribute, ClassFile,
+    Signature => Signature,
+    RuntimeVisibleAnnotations => annotations::RuntimeVisible,
+    RuntimeInvisibleAnnotations => annotations::RuntimeInvisible,
+    RuntimeVisibleTypeAnnotations => type_annotations::RuntimeVisible,
+    RuntimeInvisibleTypeAnnotations => type_annotations::RuntimeInvisible,
+    Synthetic => Synthetic,
+    Deprecated => Deprecated,
+    SourceFile => classfile::SourceFile,
+    SourceDebugExtension => classfile::SourceDebugExtension,
+    InnerClasses => classfile::InnerClasses,
+    EnclosingMethod => classfile::EnclosingMethod,
+    BootstrapMethods => classfile::BootstrapMethods,
+    Module => module::Module,
+    ModulePackages => module::ModulePackages,
+    ModuleMainClass => module::ModuleMainClass,
+    NestHost => classfile::NestHost,
+    NestMembers => classfile::NestMembers,
+    Record => record::Record,
+    PermittedSubclasses => classfile::PermittedSubclasses
+);
+
 impl ClassFileAttribute {
     // attr_names!(
     //     SIGNATURE,
@@ -77,7 +231,7 @@ impl ClassFileAttribute {
     //     SOURCE_FILE,
     //     INNER_CLASSES,
     //     ENCLOSING_METHOD,
-    //     // SOURCE_DEBUG_EXTENSION,
+    //     SOURCE_DEBUG_EXTENSION,
     //     BOOTSTRAP_METHODS,
     //     MODULE,
     //     MODULE_PACKAGES,
@@ -90,14 +244,28 @@ impl ClassFileAttribute {
 }
 
 pub enum FieldAttribute {
-    Signature(Signature),
-    RuntimeVisibleAnnotations(annotations::RuntimeVisible),
-    RuntimeInvisibleAnnotations(annotations::RuntimeInvisible),
-    Synthetic(Synthetic),
-    Deprecated(Deprecated),
-    ConstantValue(field::ConstantValue),
+    Signature(KnownAttribute<Signature>),
+    RuntimeVisibleAnnotations(KnownAttribute<annotations::RuntimeVisible>),
+    RuntimeInvisibleAnnotations(KnownAttribute<annotations::RuntimeInvisible>),
+    RuntimeVisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeVisible>),
+    RuntimeInvisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeInvisible>),
+    Synthetic(KnownAttribute<Synthetic>),
+    Deprecated(KnownAttribute<Deprecated>),
+    ConstantValue(KnownAttribute<field::ConstantValue>),
+    Unknown(UnknownAttribute),
 }
 
+attribute_set!(FieldAttribute, Field,
+    Signature => Signature,
+    RuntimeVisibleAnnotations => annotations::RuntimeVisible,
+    RuntimeInvisibleAnnotations => annotations::RuntimeInvisible,
+    RuntimeVisibleTypeAnnotations => type_annotations::RuntimeVisible,
+    RuntimeInvisibleTypeAnnotations => type_annotations::RuntimeInvisible,
+    Synthetic => Synthetic,
+    Deprecated => Deprecated,
+    ConstantValue => field::ConstantValue
+);
+
 impl FieldAttribute {
     // attr_names!(
     //     SIGNATURE,
@@ -110,19 +278,38 @@ impl FieldAttribute {
 }
 
 pub enum MethodAttribute {
-    Signature(Signature),
-    RuntimeVisibleAnnotations(annotations::RuntimeVisible),
-    RuntimeInvisibleAnnotations(annotations::RuntimeInvisible),
-    Synthetic(Synthetic),
-    Deprecated(Deprecated),
-    Code(code::Code),
-    Exceptions(method::Exceptions),
-    RuntimeVisibleParameterAnnotations(annotations::ParameterRuntimeVisible),
-    RuntimeInvisibleParameterAnnotations(annotations::ParameterRuntimeInvisible),
-    AnnotationDefault(method::AnnotationDefault),
-    MethodParameters(method::MethodParameters),
+    Signature(KnownAttribute<Signature>),
+    RuntimeVisibleAnnotations(KnownAttribute<annotations::RuntimeVisible>),
+    RuntimeInvisibleAnnotations(KnownAttribute<annotations::RuntimeInvisible>),
+    RuntimeVisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeVisible>),
+    RuntimeInvisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeInvisible>),
+    Synthetic(KnownAttribute<Synthetic>),
+    Deprecated(KnownAttribute<Deprecated>),
+    Code(KnownAttribute<code::Code>),
+    Exceptions(KnownAttribute<method::Exceptions>),
+    RuntimeVisibleParameterAnnotations(KnownAttribute<annotations::ParameterRuntimeVisible>),
+    RuntimeInvisibleParameterAnnotations(KnownAttribute<annotations::ParameterRuntimeInvisible>),
+    AnnotationDefault(KnownAttribute<method::AnnotationDefault>),
+    MethodParameters(KnownAttribute<method::MethodParameters>),
+    Unknown(UnknownAttribute),
 }
 
+attribute_set!(MethodAttribute, Method,
+    Signature => Signature,
+    RuntimeVisibleAnnotations => annotations::RuntimeVisible,
+    RuntimeInvisibleAnnotations => annotations::RuntimeInvisible,
+    RuntimeVisibleTypeAnnotations => type_annotations::RuntimeVisible,
+    RuntimeInvisibleTypeAnnotations => type_annotations::RuntimeInvisible,
+    Synthetic => Synthetic,
+    Deprecated => Deprecated,
+    Code => code::Code,
+    Exceptions => method::Exceptions,
+    RuntimeVisibleParameterAnnotations => annotations::ParameterRuntimeVisible,
+    RuntimeInvisibleParameterAnnotations => annotations::ParameterRuntimeInvisible,
+    AnnotationDefault => method::AnnotationDefault,
+    MethodParameters => method::MethodParameters
+);
+
 impl MethodAttribute {
     // attr_names!(
     //     SIGNATURE,
@@ -140,11 +327,22 @@ impl MethodAttribute {
 }
 
 pub enum RecordAttribute {
-    Signature(Signature),
-    RuntimeVisibleAnnotations(annotations::RuntimeVisible),
-    RuntimeInvisibleAnnotations(annotations::RuntimeInvisible),
+    Signature(KnownAttribute<Signature>),
+    RuntimeVisibleAnnotations(KnownAttribute<annotations::RuntimeVisible>),
+    RuntimeInvisibleAnnotations(KnownAttribute<annotations::RuntimeInvisible>),
+    RuntimeVisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeVisible>),
+    RuntimeInvisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeInvisible>),
+    Unknown(UnknownAttribute),
 }
 
+attribute_set!(RecordAttribute, RecordComponent,
+    Signature => Signature,
+    RuntimeVisibleAnnotations => annotations::RuntimeVisible,
+    RuntimeInvisibleAnnotations => annotations::RuntimeInvisible,
+    RuntimeVisibleTypeAnnotations => type_annotations::RuntimeVisible,
+    RuntimeInvisibleTypeAnnotations => type_annotations::RuntimeInvisible
+);
+
 impl RecordAttribute {
     // attr_names!(
     //     SIGNATURE,
@@ -154,15 +352,32 @@ impl RecordAttribute {
 }
 
 pub enum CodeAttribute {
-    StackMapTable(code::StackMapTable),
+    StackMapTable(KnownAttribute<code::StackMapTable>),
+    LineNumberTable(KnownAttribute<code::LineNumberTable>),
+    LocalVariableTable(KnownAttribute<code::LocalVariableTable>),
+    LocalVariableTypeTable(KnownAttribute<code::LocalVariableTypeTable>),
+    RuntimeVisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeVisible>),
+    RuntimeInvisibleTypeAnnotations(KnownAttribute<type_annotations::RuntimeInvisible>),
+    Unknown(UnknownAttribute),
 }
 
+attribute_set!(CodeAttribute, Code,
+    StackMapTable => code::StackMapTable,
+    LineNumberTable => code::LineNumberTable,
+    LocalVariableTable => code::LocalVariableTable,
+    LocalVariableTypeTable => code::LocalVariableTypeTable,
+    RuntimeVisibleTypeAnnotations => type_annotations::RuntimeVisible,
+    RuntimeInvisibleTypeAnnotations => type_annotations::RuntimeInvisible
+);
+
 impl CodeAttribute {
     // attr_names!(
-    //     STACK_MAP_TABLE//,
-    //     //LINE_NUMBER_TABLE,
-    //     //LOCAL_VARIABLE_TABLE,
-    //     //LOCAL_VARIABLE_TYPE_TABLE
+    //     STACK_MAP_TABLE,
+    //     LINE_NUMBER_TABLE,
+    //     LOCAL_VARIABLE_TABLE,
+    //     LOCAL_VARIABLE_TYPE_TABLE,
+    //     RUNTIME_VISIBLE_TYPE_ANNOTATIONS,
+    //     RUNTIME_INVISIBLE_TYPE_ANNOTATIONS
     // );
 }
 
@@ -174,6 +389,84 @@ impl Signature {
     pub fn signature_index(&self) -> constantpool::Index {
         self.signature_index
     }
+
+    pub fn signature(&self, pool: &Pool) -> Option<Symbol> {
+        pool.resolve_utf8(self.signature_index).map(Utf8Info::symbol)
+    }
+
+    // These give `None` if the index doesn't point to a Utf8 entry, which pool validation
+    // doesn't rule out
+
+    /// Parses the signature of a class, from a `Signature` attribute on a `ClassFile`.
+    pub fn class_signature(&self, pool: &Pool) -> Option<Result<ClassSignature, SignatureError>> {
+        let utf8 = pool.resolve_utf8(self.signature_index)?;
+        Some(ClassSignature::parse(utf8.as_str()))
+    }
+
+    /// Parses the signature of a method, from a `Signature` attribute on a `method_info`.
+    pub fn method_signature(&self, pool: &Pool) -> Option<Result<MethodSignature, SignatureError>> {
+        let utf8 = pool.resolve_utf8(self.signature_index)?;
+        Some(MethodSignature::parse(utf8.as_str()))
+    }
+
+    /// Parses the signature of a field or record component.
+    pub fn field_signature(&self, pool: &Pool) -> Option<Result<FieldSignature, SignatureError>> {
+        let utf8 = pool.resolve_utf8(self.signature_index)?;
+        Some(FieldSignature::parse(utf8.as_str()))
+    }
+}
+
+/// An attribute that's recognised, along with the index of its name. Compilers only put each
+/// name in the pool once, but nothing stops there being more than one entry with the same
+/// name, so the index is kept to be able to write the attribute back out exactly.
+///
+/// This dereferences to the attribute itself.
+pub struct KnownAttribute<T> {
+    name_index: constantpool::Index,
+    attribute: T,
+}
+
+impl<T> KnownAttribute<T> {
+    pub fn name_index(&self) -> constantpool::Index {
+        self.name_index
+    }
+
+    pub fn attribute(&self) -> &T {
+        &self.attribute
+    }
+}
+
+impl<T> Deref for KnownAttribute<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.attribute
+    }
+}
+
+/// An attribute that isn't recognised, or isn't recognised in the version of the class file
+/// it's in. These don't affect anything, but are kept so the class can be written back out.
+pub struct UnknownAttribute {
+    name_index: constantpool::Index,
+    info: Bytes,
+}
+
+impl UnknownAttribute {
+    pub(crate) fn new(name_index: constantpool::Index, info: Bytes) -> Self {
+        Self { name_index, info }
+    }
+
+    pub fn name_index(&self) -> constantpool::Index {
+        self.name_index
+    }
+
+    pub fn name<'a>(&self, pool: &'a Pool) -> Option<&'a Utf8Info> {
+        pool.resolve_utf8(self.name_index)
+    }
+
+    pub fn info(&self) -> &[u8] {
+        &self.info
+    }
 }
 
 pub struct Synthetic {}
@@ -190,8 +483,130 @@ mod _attr_name {
 }
 
 mod _parse {
+    use std::ptr;
     use super::*;
-    use crate::loader::{BinaryReader, Parse, ParseError};
+    use super::names::AttributeRule;
+    use crate::loader::ErrorKind;
+    use crate::loader::classfile::constantpool::Tag;
+    use crate::types::Array;
+
+    /// Reads an attribute table (`attributes_count` followed by that many `attribute_info`
+    /// structures) in to the attributes of the set `A`.
+    ///
+    /// Attributes that aren't part of the set are kept as an [UnknownAttribute] without being
+    /// looked at, as the JVMS requires us to silently ignore attributes we don't recognise.
+    /// This includes predefined attributes in class files older than the version that
+    /// introduced them. Predefined attributes that are on the wrong structure, or that appear
+    /// more than once when they may only appear once, are errors. Each one is parsed from just
+    /// its `attribute_length` bytes, so one that needs more than that is malformed, rather than
+    /// being read in to whatever comes after it.
+    pub fn parse_attributes<A: AttributeSet>(pool: &Pool, version: Version,
+                                             buf: &mut BinaryReader) -> Result<Array<A>, ParseError> {
+        buf.check_bytes(2, "attributes")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let len = unsafe { buf.unsafe_read_u16() } as usize;
+
+        // See buf_read_named_type_arr for why this goes through a Vec
+        let mut attributes = Vec::with_capacity(len);
+        let mut seen = Vec::new();
+        for i in 0..len {
+            let attribute = parse_attribute(pool, version, &mut seen, buf)
+                .map_err(ParseError::wrap(format!("attributes - idx {i}")))?;
+            attributes.push(attribute);
+        }
+
+        Array::from_vec(attributes).map_err(|_| buf.error(ErrorKind::OutOfMemory))
+    }
+
+    fn parse_attribute<A: AttributeSet>(pool: &Pool, version: Version, seen: &mut Vec<&'static AttributeRule>,
+                                        buf: &mut BinaryReader) -> Result<A, ParseError> {
+        // 2 name index, 4 length
+        buf.check_bytes(2 + 4, "attribute name index, attribute length")?;
+
+        let header = buf.position();
+        // SAFETY: Guaranteed by check_bytes
+        let name_index = unsafe { buf.unsafe_read_u16() };
+        let length = unsafe { buf.unsafe_read_u32() } as usize;
+        let start = buf.position();
+        let info = buf.read_bytes(length, "attribute info")?;
+
+        let name = pool.resolve_utf8(name_index)
+            .ok_or_else(|| ParseError::bad_reference(name_index, Tag::Utf8, header))?;
+        let name = name.as_str();
+
+        let Some(rule) = Names::rule(name).filter(|rule| rule.is_recognised_in(version)) else {
+            return Ok(A::unknown(UnknownAttribute { name_index, info }));
+        };
+
+        let malformed = |reason: String| {
+            let kind = ErrorKind::MalformedAttribute { name: name.to_string(), reason };
+            ParseError::new(kind, header)
+        };
+        if !rule.is_allowed_on(A::LOCATION) {
+            return malformed(format!("attribute is not allowed on {:?}", A::LOCATION)).into();
+        }
+        if rule.is_unique() {
+            if seen.iter().any(|seen| ptr::eq(*seen, rule)) {
+                return malformed("attribute may only appear once".into()).into();
+            }
+            seen.push(rule);
+        }
+
+        // The body is parsed on its own, so it can't run in to whatever comes after it
+        let mut body = buf.bounded(info.clone(), start);
+        let ctx = Context { pool, version, length };
+        let attribute = A::parse_named(name, name_index, ctx, &mut body)
+            .map_err(|err| overrun(name, length, err))
+            .map_err(ParseError::wrap(format!("{name} attribute")))?;
+        let Some(attribute) = attribute else {
+            return Ok(A::unknown(UnknownAttribute { name_index, info }));
+        };
+
+        let read = body.position() - start;
+        if read != length {
+            let kind = ErrorKind::MalformedAttribute {
+                name: name.to_string(),
+                reason: format!("attribute has length {length}, but {read} bytes were read"),
+            };
+            return ParseError::new(kind, start).into();
+        }
+        Ok(attribute)
+    }
+
+    // Running out of the body means the attribute is longer than it says it is, rather than the
+    // class file being truncated
+    fn overrun(name: &str, length: usize, err: ParseError) -> ParseError {
+        let ErrorKind::Truncated { what } = err.kind() else {
+            return err;
+        };
+        let kind = ErrorKind::MalformedAttribute {
+            name: name.to_string(),
+            reason: format!("attribute has length {length}, which is too short for its {what}"),
+        };
+        err.with_kind(kind)
+    }
+
+    /// Skips over an entire attribute table without parsing any of the attributes in it.
+    pub fn skip_attributes(buf: &mut BinaryReader) -> Result<(), ParseError> {
+        buf.check_bytes(2, "attributes")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let len = unsafe { buf.unsafe_read_u16() };
+        for i in 0..len {
+            // 2 name index, 4 length
+            buf.check_bytes(2 + 4, format!("attributes - idx {i}"))?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let _ = unsafe { buf.unsafe_read_u16() };
+            let length = unsafe { buf.unsafe_read_u32() } as usize;
+
+            buf.check_bytes(length, format!("attributes - idx {i}"))?;
+            // Can't fail, as the length is guaranteed by check_bytes above
+            let _ = buf.skip(length);
+        }
+        Ok(())
+    }
 
     impl Parse<Signature> for Signature {
         fn parse(buf: &mut BinaryReader) -> Result<Signature, ParseError> {
@@ -215,3 +630,209 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use super::*;
+
+    /// Writes an attribute table, the reverse of [parse_attributes].
+    pub fn serialize_attributes<A: AttributeSet>(attributes: &[A], ctx: &WriteContext, buf: &mut BinaryWriter) {
+        buf.write_u16(attributes.len() as u16);
+        for attribute in attributes {
+            attribute.serialize(ctx, buf);
+        }
+    }
+
+    impl Serialize for UnknownAttribute {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.name_index);
+            buf.write_u32(self.info().len() as u32);
+            buf.write_bytes(self.info());
+        }
+    }
+
+    impl Serialize for Signature {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.signature_index);
+        }
+    }
+
+    impl Serialize for Synthetic {
+        fn serialize(&self, _: &mut BinaryWriter) {}
+    }
+
+    impl Serialize for Deprecated {
+        fn serialize(&self, _: &mut BinaryWriter) {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::testing::*;
+    use crate::loader::ErrorKind;
+    use crate::loader::classfile::constantpool::Tag;
+    use super::*;
+
+    #[test]
+    fn dispatches_on_name() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let value = builder.entry(INTEGER, &5_i32.to_be_bytes());
+        let constant = builder.attribute("ConstantValue", &u16_bytes(value));
+        let field = builder.member(ACC_STATIC | ACC_FINAL, "FIVE", "I", &[constant]);
+        builder.fields.push(field);
+        let code = builder.code(0, 0, &[0xB1], &[]);
+        let method = builder.member(ACC_STATIC, "run", "()V", &[code]);
+        builder.methods.push(method);
+        let source = builder.utf8("Test.java");
+        builder.attributes = vec![builder.attribute("SourceFile", &u16_bytes(source))];
+
+        let class = builder.parse(69).expect("class should parse");
+        let [FieldAttribute::ConstantValue(constant)] = class.fields()[0].attributes() else {
+            panic!("expected a ConstantValue attribute");
+        };
+        assert_eq!(constant.value_index(), value);
+        assert!(matches!(class.methods()[0].attributes(), [MethodAttribute::Code(_)]));
+        let [ClassFileAttribute::SourceFile(source_file)] = class.attributes() else {
+            panic!("expected a SourceFile attribute");
+        };
+        assert_eq!(source_file.source_file_index(), source);
+    }
+
+    #[test]
+    fn keeps_unknown_attributes() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let custom = builder.attribute("com.example.Custom", &[1, 2, 3]);
+        builder.attributes = vec![custom, builder.attribute("Deprecated", &[])];
+
+        // Parsing carries on after the unknown attribute, which is skipped by its length
+        let class = builder.parse(69).expect("class should parse");
+        let [ClassFileAttribute::Unknown(unknown), ClassFileAttribute::Deprecated(_)] = class.attributes() else {
+            panic!("expected an unknown attribute, then Deprecated");
+        };
+        assert_eq!(unknown.info(), &[1, 2, 3]);
+        assert_eq!(unknown.name(class.constant_pool()).map(Utf8Info::as_str), Some("com.example.Custom"));
+    }
+
+    #[test]
+    fn dispatches_nested_tables() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let line_numbers = builder.attribute("LineNumberTable", &table(&[[u16_bytes(0), u16_bytes(7)].concat()]));
+        let custom = builder.attribute("Custom", &[]);
+        let code = builder.code(0, 0, &[0xB1], &[custom, line_numbers]);
+        let method = builder.member(ACC_STATIC, "run", "()V", &[code]);
+        builder.methods.push(method);
+
+        let class = builder.parse(69).expect("class should parse");
+        let code = class.methods()[0].code().expect("method should have code");
+        assert!(matches!(code.attributes(), [CodeAttribute::Unknown(_), CodeAttribute::LineNumberTable(_)]));
+        assert_eq!(code.line_number(0), Some(7));
+    }
+
+    #[test]
+    fn recognised_from_feature_version() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let host = builder.class("Host");
+        builder.attributes = vec![builder.attribute("NestHost", &u16_bytes(host))];
+
+        // Before nest mates, the attribute is just an unknown one
+        let class = builder.parse(54).expect("class should parse");
+        assert!(matches!(class.attributes(), [ClassFileAttribute::Unknown(_)]));
+        let class = builder.parse(55).expect("class should parse");
+        assert!(matches!(class.attributes(), [ClassFileAttribute::NestHost(_)]));
+    }
+
+    #[test]
+    fn name_must_be_utf8() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let this_class = builder.this_class;
+        builder.attributes.push([u16_bytes(this_class), vec![0, 0, 0, 0]].concat());
+
+        let err = builder.parse(69).err().expect("name should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::BadConstantPoolReference { index, expected: Tag::Utf8 }
+            if *index == this_class));
+        assert_eq!(err.context(), ["attributes", "attributes - idx 0"]);
+    }
+
+    #[test]
+    fn body_is_bounded_by_length() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let source = builder.utf8("Test.java");
+        // One byte of the index is left out of the length, and the Deprecated after it is
+        // there so that there's something to run in to
+        let mut source_file = builder.attribute("SourceFile", &u16_bytes(source));
+        source_file[5] = 1;
+        source_file.pop();
+        builder.attributes = vec![source_file, builder.attribute("Deprecated", &[])];
+        let body_offset = builder.build(69, 0).len() - builder.attributes.concat().len() + 6;
+
+        let err = builder.parse(69).err().expect("attribute should overrun");
+        assert!(matches!(err.kind(), ErrorKind::MalformedAttribute { name, .. } if name == "SourceFile"));
+        assert_eq!(err.offset(), body_offset);
+        assert_eq!(err.context(), ["attributes", "attributes - idx 0", "SourceFile attribute"]);
+    }
+
+    #[test]
+    fn nested_overrun_is_on_the_outer_attribute() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let mut line_numbers = builder.attribute("LineNumberTable", &table(&[]));
+        // Claim more than there is left of the Code attribute
+        line_numbers[5] = 3;
+        let code = builder.code(0, 0, &[0xB1], &[line_numbers]);
+        let method = builder.member(ACC_STATIC, "run", "()V", &[code]);
+        builder.methods.push(method);
+
+        let err = builder.parse(69).err().expect("attribute should overrun");
+        assert!(matches!(err.kind(), ErrorKind::MalformedAttribute { name, .. } if name == "Code"));
+    }
+
+    #[test]
+    fn every_attribute_has_a_rule() {
+        fn check<A: AttributeSet>(names: Vec<&'static str>) {
+            for name in names {
+                let rule = Names::rule(name).unwrap_or_else(|| panic!("{name} has no rule"));
+                assert!(rule.is_allowed_on(A::LOCATION), "{name} is not allowed on {:?}", A::LOCATION);
+            }
+        }
+
+        check::<ClassFileAttribute>(ClassFileAttribute::predefined_names());
+        check::<FieldAttribute>(FieldAttribute::predefined_names());
+        check::<MethodAttribute>(MethodAttribute::predefined_names());
+        check::<RecordAttribute>(RecordAttribute::predefined_names());
+        check::<CodeAttribute>(CodeAttribute::predefined_names());
+    }
+
+    #[test]
+    fn location_and_uniqueness() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let code = builder.code(0, 0, &[0xB1], &[]);
+        let field = builder.member(0, "x", "I", &[code]);
+        builder.fields.push(field);
+        let err = builder.parse(69).err().expect("Code should be rejected on a field");
+        assert!(matches!(err.kind(), ErrorKind::MalformedAttribute { name, .. } if name == "Code"));
+
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let source = builder.utf8("Test.java");
+        let source_file = builder.attribute("SourceFile", &u16_bytes(source));
+        builder.attributes = vec![source_file.clone(), source_file];
+        let err = builder.parse(69).err().expect("SourceFile should be unique");
+        assert!(matches!(err.kind(), ErrorKind::MalformedAttribute { name, .. } if name == "SourceFile"));
+        assert_eq!(err.context(), ["attributes", "attributes - idx 1"]);
+
+        // Unlike Deprecated, which can appear any number of times
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let deprecated = builder.attribute("Deprecated", &[]);
+        builder.attributes = vec![deprecated.clone(), deprecated];
+        assert!(builder.parse(69).is_ok());
+    }
+
+    #[test]
+    fn length_must_fit() {
+        let mut builder = ClassBuilder::new("Test", ACC_PUBLIC | ACC_SUPER);
+        let mut custom = builder.attribute("Custom", &[1, 2]);
+        // Claim there's one more byte than there is
+        custom[5] = 3;
+        builder.attributes.push(custom);
+
+        let err = builder.parse(69).err().expect("attribute should be truncated");
+        assert!(matches!(err.kind(), ErrorKind::Truncated { .. }));
+    }
+}
@@ -14,11 +14,11 @@
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
 use crate::loader::classfile::constantpool;
-use crate::types::{AccessFlags, Array};
+use crate::types::{Array, ExportsFlags, ModuleFlags, RequiresFlags};
 
 pub struct Module {
     name_index: constantpool::Index,
-    flags: AccessFlags,
+    flags: ModuleFlags,
     version_index: constantpool::Index,
     requires: Array<ModuleRequires>,
     exports: Array<ModuleExports>,
@@ -32,7 +32,7 @@ impl Module {
         self.name_index
     }
 
-    pub fn flags(&self) -> AccessFlags {
+    pub fn flags(&self) -> ModuleFlags {
         self.flags
     }
 
@@ -100,10 +100,10 @@ mod _attr_name {
 // requires, exports, and opens all have three variables, two of them (index and flags)
 // being shared amongst them.
 macro_rules! module_part {
-    ($name: ident, $attr_name: ident, $attr_ty: ty) => {
+    ($name: ident, $flags_ty: ty, $attr_name: ident, $attr_ty: ty) => {
         pub struct $name {
             index: constantpool::Index,
-            flags: AccessFlags,
+            flags: $flags_ty,
             $attr_name: $attr_ty,
         }
 
@@ -112,16 +112,16 @@ macro_rules! module_part {
                 self.index
             }
 
-            pub fn flags(&self) -> AccessFlags {
+            pub fn flags(&self) -> $flags_ty {
                 self.flags
             }
         }
     };
 }
 
-module_part!(ModuleRequires, version_index, constantpool::Index);
-module_part!(ModuleExports, to_index, Array<constantpool::Index>);
-module_part!(ModuleOpens, to_index, Array<constantpool::Index>);
+module_part!(ModuleRequires, RequiresFlags, version_index, constantpool::Index);
+module_part!(ModuleExports, ExportsFlags, to_index, Array<constantpool::Index>);
+module_part!(ModuleOpens, ExportsFlags, to_index, Array<constantpool::Index>);
 
 impl ModuleRequires {
     pub fn version_index(&self) -> constantpool::Index {
@@ -148,6 +148,17 @@ pub struct ModuleProvides {
     with_index: Array<constantpool::Index>,
 }
 
+impl ModuleProvides {
+    pub fn index(&self) -> constantpool::Index {
+        self.index
+    }
+
+    pub fn with_index(&self) -> &[constantpool::Index] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.with_index.as_slice() }
+    }
+}
+
 mod _parse {
     use crate::{buf_read_named_type_arr, buf_read_u16_arr};
     use crate::loader::{BinaryReader, Parse, ParseError};
@@ -175,7 +186,7 @@ mod _parse {
 
             Ok(Module {
                 name_index,
-                flags: AccessFlags::new(flags),
+                flags: ModuleFlags::new(flags),
                 version_index,
                 requires,
                 exports,
@@ -195,7 +206,7 @@ mod _parse {
             let index = unsafe { buf.unsafe_read_u16() };
             let flags = unsafe { buf.unsafe_read_u16() };
             let version_index = unsafe { buf.unsafe_read_u16() };
-            Ok(ModuleRequires { index, flags: AccessFlags::new(flags), version_index })
+            Ok(ModuleRequires { index, flags: RequiresFlags::new(flags), version_index })
         }
     }
 
@@ -211,7 +222,7 @@ mod _parse {
                     let flags = unsafe { buf.unsafe_read_u16() };
                     buf_read_u16_arr!(to_index, buf, "module exports");
 
-                    Ok($name { index, flags: AccessFlags::new(flags), to_index })
+                    Ok($name { index, flags: ExportsFlags::new(flags), to_index })
                 }
             }
         };
@@ -219,6 +230,23 @@ mod _parse {
     parse_exports_opens!(ModuleExports, "module exports");
     parse_exports_opens!(ModuleOpens, "module opens");
 
+    impl Parse<ModulePackages> for ModulePackages {
+        fn parse(buf: &mut BinaryReader) -> Result<ModulePackages, ParseError> {
+            buf_read_u16_arr!(package_index, buf, "module packages");
+            Ok(ModulePackages { package_index })
+        }
+    }
+
+    impl Parse<ModuleMainClass> for ModuleMainClass {
+        fn parse(buf: &mut BinaryReader) -> Result<ModuleMainClass, ParseError> {
+            buf.check_bytes(2, "module main class")?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let main_class_index = unsafe { buf.unsafe_read_u16() };
+            Ok(ModuleMainClass { main_class_index })
+        }
+    }
+
     impl Parse<ModuleProvides> for ModuleProvides {
         fn parse(buf: &mut BinaryReader) -> Result<ModuleProvides, ParseError> {
             buf.check_bytes(2, "module provides")?;
@@ -231,3 +259,62 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use crate::loader::{BinaryWriter, Serialize};
+    use super::*;
+
+    impl Serialize for Module {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.name_index);
+            buf.write_u16(self.flags.flags());
+            buf.write_u16(self.version_index);
+            buf.write_arr(self.requires());
+            buf.write_arr(self.exports());
+            buf.write_arr(self.opens());
+            buf.write_u16_arr(self.uses());
+            buf.write_arr(self.provides());
+        }
+    }
+
+    impl Serialize for ModuleRequires {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.index);
+            buf.write_u16(self.flags.flags());
+            buf.write_u16(self.version_index);
+        }
+    }
+
+    macro_rules! serialize_exports_opens {
+        ($name: ident) => {
+            impl Serialize for $name {
+                fn serialize(&self, buf: &mut BinaryWriter) {
+                    buf.write_u16(self.index);
+                    buf.write_u16(self.flags.flags());
+                    buf.write_u16_arr(self.to_index());
+                }
+            }
+        };
+    }
+    serialize_exports_opens!(ModuleExports);
+    serialize_exports_opens!(ModuleOpens);
+
+    impl Serialize for ModulePackages {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16_arr(self.package_index());
+        }
+    }
+
+    impl Serialize for ModuleMainClass {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.main_class_index);
+        }
+    }
+
+    impl Serialize for ModuleProvides {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.index);
+            buf.write_u16_arr(self.with_index());
+        }
+    }
+}
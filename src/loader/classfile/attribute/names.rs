@@ -13,6 +13,8 @@
 // You should have received a copy of the GNU General Public License along
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
+use crate::types::{Feature, Version};
+
 macro_rules! name_const {
     ($const_name: ident, $str: expr) => {
         pub const $const_name: &'static str = $str;
@@ -39,6 +41,7 @@ pub struct Names;
 
 // ClassFile | field_info | method_info | record_component_info | Code
 impl Names {
+    // MAJOR 52 and above
     name_const!(RUNTIME_VISIBLE_TYPE_ANNOTATIONS, "RuntimeVisibleTypeAnnotations");
     name_const!(RUNTIME_INVISIBLE_TYPE_ANNOTATIONS, "RuntimeInvisibleTypeAnnotations");
 }
@@ -66,8 +69,7 @@ impl Names {
 
     // MAJOR 49 and above
     name_const!(ENCLOSING_METHOD, "EnclosingMethod");
-    // Debugging attribute we don't use in the VM
-    //name_const!(SOURCE_DEBUG_EXTENSION, "SourceDebugExtension");
+    name_const!(SOURCE_DEBUG_EXTENSION, "SourceDebugExtension");
 
     // MAJOR 51 and above
     name_const!(BOOTSTRAP_METHODS, "BootstrapMethods");
@@ -111,10 +113,118 @@ impl Names {
 
 // Code attribute only
 impl Names {
+    // MAJOR 50 and above
     name_const!(STACK_MAP_TABLE, "StackMapTable");
 
     // Debugging attributes we don't use in the VM
-    // name_const!(LINE_NUMBER_TABLE, "LineNumberTable");
-    // name_const!(LOCAL_VARIABLE_TABLE, "LocalVariableTable");
-    // name_const!(LOCAL_VARIABLE_TYPE_TABLE, "LocalVariableTypeTable");
+    // MAJOR 45, MINOR 3 and above
+    name_const!(LINE_NUMBER_TABLE, "LineNumberTable");
+    name_const!(LOCAL_VARIABLE_TABLE, "LocalVariableTable");
+    // MAJOR 49 and above
+    name_const!(LOCAL_VARIABLE_TYPE_TABLE, "LocalVariableTypeTable");
+}
+
+/// The structures in a class file that have an attribute table.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Location {
+    ClassFile,
+    Field,
+    Method,
+    RecordComponent,
+    Code,
+}
+
+/// Where a predefined attribute may appear, and from which version it is recognised.
+///
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7-320
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7-330
+pub struct AttributeRule {
+    /// The feature the attribute is part of, or `None` if it's recognised in every version. In
+    /// versions that don't support the feature, it's just an unknown attribute, and so ignored.
+    feature: Option<Feature>,
+    locations: &'static [Location],
+    /// Whether the attribute may appear at most once in a single attribute table
+    unique: bool,
+}
+
+impl AttributeRule {
+    pub fn is_recognised_in(&self, version: Version) -> bool {
+        self.feature.is_none_or(|feature| version.supports(feature))
+    }
+
+    pub fn is_allowed_on(&self, location: Location) -> bool {
+        self.locations.contains(&location)
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+}
+
+macro_rules! rule {
+    ($name: ident, [$($location: ident),+], $unique: expr) => {
+        (Names::$name, AttributeRule {
+            feature: None,
+            locations: &[$(Location::$location),+],
+            unique: $unique,
+        })
+    };
+    ($name: ident, $feature: ident, [$($location: ident),+], $unique: expr) => {
+        (Names::$name, AttributeRule {
+            feature: Some(Feature::$feature),
+            locations: &[$(Location::$location),+],
+            unique: $unique,
+        })
+    };
+}
+
+// The JVMS gives 45.3 as the first version for the attributes without a feature, but every
+// 45 minor version is treated the same
+static RULES: &[(&str, AttributeRule)] = &[
+    rule!(RUNTIME_VISIBLE_TYPE_ANNOTATIONS, TypeAnnotations, [ClassFile, Field, Method, RecordComponent, Code], true),
+    rule!(RUNTIME_INVISIBLE_TYPE_ANNOTATIONS, TypeAnnotations, [ClassFile, Field, Method, RecordComponent, Code], true),
+
+    rule!(SIGNATURE, Java5Metadata, [ClassFile, Field, Method, RecordComponent], true),
+    rule!(RUNTIME_VISIBLE_ANNOTATIONS, Java5Metadata, [ClassFile, Field, Method, RecordComponent], true),
+    rule!(RUNTIME_INVISIBLE_ANNOTATIONS, Java5Metadata, [ClassFile, Field, Method, RecordComponent], true),
+
+    // The JVMS places no limit on how many of these there can be
+    rule!(SYNTHETIC, [ClassFile, Field, Method], false),
+    rule!(DEPRECATED, [ClassFile, Field, Method], false),
+
+    rule!(SOURCE_FILE, [ClassFile], true),
+    rule!(INNER_CLASSES, [ClassFile], true),
+    rule!(ENCLOSING_METHOD, Java5Metadata, [ClassFile], true),
+    rule!(SOURCE_DEBUG_EXTENSION, Java5Metadata, [ClassFile], true),
+    rule!(BOOTSTRAP_METHODS, InvokeDynamic, [ClassFile], true),
+    rule!(MODULE, Modules, [ClassFile], true),
+    rule!(MODULE_PACKAGES, Modules, [ClassFile], true),
+    rule!(MODULE_MAIN_CLASS, Modules, [ClassFile], true),
+    rule!(NEST_HOST, NestMates, [ClassFile], true),
+    rule!(NEST_MEMBERS, NestMates, [ClassFile], true),
+    rule!(RECORD, Records, [ClassFile], true),
+    rule!(PERMITTED_SUBCLASSES, SealedClasses, [ClassFile], true),
+
+    rule!(CONSTANT_VALUE, [Field], true),
+
+    rule!(CODE, [Method], true),
+    rule!(EXCEPTIONS, [Method], true),
+    rule!(RUNTIME_VISIBLE_PARAMETER_ANNOTATIONS, Java5Metadata, [Method], true),
+    rule!(RUNTIME_INVISIBLE_PARAMETER_ANNOTATIONS, Java5Metadata, [Method], true),
+    rule!(ANNOTATION_DEFAULT, Java5Metadata, [Method], true),
+    rule!(METHOD_PARAMETERS, MethodParameters, [Method], true),
+
+    rule!(STACK_MAP_TABLE, StackMapTable, [Code], true),
+    // Compilers may split these tables up in to as many attributes as they like
+    rule!(LINE_NUMBER_TABLE, [Code], false),
+    rule!(LOCAL_VARIABLE_TABLE, [Code], false),
+    rule!(LOCAL_VARIABLE_TYPE_TABLE, Java5Metadata, [Code], false),
+];
+
+impl Names {
+    /// Gets the rule for the predefined attribute with the given name, or `None` if there's
+    /// no predefined attribute with that name.
+    pub fn rule(name: &str) -> Option<&'static AttributeRule> {
+        RULES.iter().find(|(rule_name, _)| *rule_name == name).map(|(_, rule)| rule)
+    }
 }
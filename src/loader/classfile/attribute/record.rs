@@ -21,12 +21,37 @@ pub struct Record {
     components: Array<Component>,
 }
 
+impl Record {
+    pub fn components(&self) -> &[Component] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.components.as_slice() }
+    }
+}
+
 pub struct Component {
     name_index: constantpool::Index,
     descriptor_index: constantpool::Index,
     attributes: Array<RecordAttribute>,
 }
 
+impl Component {
+    /// The index of the component's name, which is checked to be a Utf8 entry when parsed.
+    pub fn name_index(&self) -> constantpool::Index {
+        self.name_index
+    }
+
+    /// The index of the component's field descriptor, which is checked to be a Utf8 entry
+    /// when parsed.
+    pub fn descriptor_index(&self) -> constantpool::Index {
+        self.descriptor_index
+    }
+
+    pub fn attributes(&self) -> &[RecordAttribute] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.attributes.as_slice() }
+    }
+}
+
 mod _attr_name {
     use super::*;
     use crate::loader::classfile::attribute::names::{Names, Nameable, impl_attr_name};
@@ -35,31 +60,72 @@ mod _attr_name {
 }
 
 mod _parse {
-    use crate::buf_read_named_type_arr;
-    use crate::loader::{BinaryReader, Parse, ParseError};
+    use crate::loader::{BinaryReader, ErrorKind, ParseError};
+    use crate::loader::classfile::attribute::{parse_attributes, Context, ParseAttribute};
+    use crate::loader::classfile::constantpool::Tag;
     use super::*;
 
-    impl Parse<Record> for Record {
-        fn parse(buf: &mut BinaryReader) -> Result<Record, ParseError> {
-            buf_read_named_type_arr!(Component, components, buf,
-                "record - components", "record - components - idx {}");
+    impl ParseAttribute for Record {
+        fn parse_attribute(ctx: Context, buf: &mut BinaryReader) -> Result<Record, ParseError> {
+            buf.check_bytes(2, "record - components")?;
+
+            // SAFETY: Guaranteed by check_bytes
+            let len = unsafe { buf.unsafe_read_u16() } as usize;
+
+            // Cannot use macro as components need the context to read their attributes
+            let mut components = Vec::with_capacity(len);
+            for i in 0..len {
+                let component = parse_component(ctx, buf)
+                    .map_err(ParseError::wrap(format!("record - components - idx {i}")))?;
+                components.push(component);
+            }
+            let components = Array::from_vec(components)
+                .map_err(|_| buf.error(ErrorKind::OutOfMemory))?;
             Ok(Record { components })
         }
     }
 
-    impl Parse<Component> for Component {
-        fn parse(buf: &mut BinaryReader) -> Result<Component, ParseError> {
-            buf.check_bytes(2 + 2, "name index, descriptor index")?;
+    fn parse_component(ctx: Context, buf: &mut BinaryReader) -> Result<Component, ParseError> {
+        buf.check_bytes(2 + 2, "name index, descriptor index")?;
 
-            // SAFETY: Guaranteed by check_bytes
-            let name_index = unsafe { buf.unsafe_read_u16() };
-            let descriptor_index = unsafe { buf.unsafe_read_u16() };
-
-            Ok(Component {
-                name_index,
-                descriptor_index,
-                attributes: Array::empty(), // TODO: Attributes
-            })
+        // SAFETY: Guaranteed by check_bytes
+        let name_index = unsafe { buf.unsafe_read_u16() };
+        let descriptor_index = unsafe { buf.unsafe_read_u16() };
+
+        // Checked here so that the reflection data can rely on them. Class::record_components
+        // resolves both without checking, as a Utf8 entry always resolves
+        let offset = buf.position() - 4;
+        if ctx.pool.get_unresolved_utf8(name_index).is_none() {
+            return ParseError::bad_reference(name_index, Tag::Utf8, offset).into();
+        }
+        if ctx.pool.get_unresolved_utf8(descriptor_index).is_none() {
+            return ParseError::bad_reference(descriptor_index, Tag::Utf8, offset + 2).into();
+        }
+
+        let attributes = parse_attributes(ctx.pool, ctx.version, buf)
+            .map_err(ParseError::wrap("record - component - attributes"))?;
+
+        Ok(Component {
+            name_index,
+            descriptor_index,
+            attributes,
+        })
+    }
+}
+
+mod _write {
+    use crate::loader::BinaryWriter;
+    use crate::loader::classfile::attribute::{serialize_attributes, SerializeAttribute, WriteContext};
+    use super::*;
+
+    impl SerializeAttribute for Record {
+        fn serialize_attribute(&self, ctx: &WriteContext, buf: &mut BinaryWriter) {
+            buf.write_u16(self.components().len() as u16);
+            for component in self.components() {
+                buf.write_u16(component.name_index);
+                buf.write_u16(component.descriptor_index);
+                serialize_attributes(component.attributes(), ctx, buf);
+            }
         }
     }
 }
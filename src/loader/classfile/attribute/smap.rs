@@ -0,0 +1,429 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! JSR-45 source maps (SMAPs), found in the `SourceDebugExtension` attribute, which map the
+//! lines of a class's Java source back to the files it was generated from, such as a JSP
+//! page or an inlined Kotlin function.
+//!
+//! A source map has one or more strata, each of which is a different view of the source,
+//! with its own files and mapping from Java ("output") lines to lines in those files
+//! ("input" lines).
+//!
+//! Ref: https://jcp.org/en/jsr/detail?id=45
+
+use std::fmt;
+
+pub struct Smap {
+    output_file: String,
+    default_stratum: String,
+    strata: Vec<Stratum>,
+}
+
+impl Smap {
+    /// The name of the Java source file the map is for.
+    pub fn output_file(&self) -> &str {
+        &self.output_file
+    }
+
+    /// The ID of the stratum that should be used when no other is asked for.
+    pub fn default_stratum_id(&self) -> &str {
+        &self.default_stratum
+    }
+
+    pub fn strata(&self) -> &[Stratum] {
+        &self.strata
+    }
+
+    pub fn stratum(&self, id: &str) -> Option<&Stratum> {
+        self.strata.iter().find(|stratum| stratum.id == id)
+    }
+
+    /// Gets the default stratum, or `None` if it's the Java stratum, which has no mapping.
+    pub fn default_stratum(&self) -> Option<&Stratum> {
+        self.stratum(&self.default_stratum)
+    }
+
+    /// Maps a Java line to a line in the default stratum, which is what stack traces show.
+    pub fn map_line(&self, output_line: u32) -> Option<SourceLine<'_>> {
+        self.default_stratum()?.map_line(output_line)
+    }
+}
+
+pub struct Stratum {
+    id: String,
+    files: Vec<SmapFile>,
+    lines: Vec<LineInfo>,
+}
+
+impl Stratum {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn files(&self) -> &[SmapFile] {
+        &self.files
+    }
+
+    pub fn lines(&self) -> &[LineInfo] {
+        &self.lines
+    }
+
+    pub fn file(&self, id: u32) -> Option<&SmapFile> {
+        self.files.iter().find(|file| file.id == id)
+    }
+
+    /// Maps a Java line to the line in this stratum that it was generated from. If more than
+    /// one line maps to it, the first in the map is used.
+    pub fn map_line(&self, output_line: u32) -> Option<SourceLine<'_>> {
+        self.lines.iter()
+            .find_map(|info| info.map_line(output_line))
+            .and_then(|(file_id, line)| Some(SourceLine { file: self.file(file_id)?, line }))
+    }
+}
+
+pub struct SmapFile {
+    id: u32,
+    name: String,
+    path: Option<String>,
+}
+
+impl SmapFile {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The path of the file relative to the source path, if it was given.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+}
+
+/// One entry of a line section, which maps `repeat_count` lines starting from
+/// `input_start_line` each to `output_line_increment` lines starting from
+/// `output_start_line`.
+pub struct LineInfo {
+    input_start_line: u32,
+    file_id: u32,
+    repeat_count: u32,
+    output_start_line: u32,
+    output_line_increment: u32,
+}
+
+impl LineInfo {
+    pub fn input_start_line(&self) -> u32 {
+        self.input_start_line
+    }
+
+    pub fn file_id(&self) -> u32 {
+        self.file_id
+    }
+
+    pub fn repeat_count(&self) -> u32 {
+        self.repeat_count
+    }
+
+    pub fn output_start_line(&self) -> u32 {
+        self.output_start_line
+    }
+
+    pub fn output_line_increment(&self) -> u32 {
+        self.output_line_increment
+    }
+
+    fn map_line(&self, output_line: u32) -> Option<(u32, u32)> {
+        if output_line < self.output_start_line || self.repeat_count == 0 {
+            return None;
+        }
+        let offset = (output_line - self.output_start_line) as u64;
+
+        // An increment of 0 maps every input line to the same output line
+        let n = match self.output_line_increment {
+            0 if offset == 0 => 0,
+            0 => return None,
+            increment => offset / increment as u64,
+        };
+        if n >= self.repeat_count as u64 {
+            return None;
+        }
+        Some((self.file_id, self.input_start_line.checked_add(n as u32)?))
+    }
+}
+
+/// A line in one of the files of a stratum.
+#[derive(Copy, Clone)]
+pub struct SourceLine<'a> {
+    pub file: &'a SmapFile,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmapError {
+    line: usize,
+    reason: String,
+}
+
+impl SmapError {
+    /// The line of the source map the error is on, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for SmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid source map at line {}: {}", self.line, self.reason)
+    }
+}
+
+mod _parse {
+    use std::iter::{Enumerate, Peekable};
+    use std::str::Lines;
+    use super::*;
+
+    type Input<'a> = Peekable<Enumerate<Lines<'a>>>;
+
+    fn error<T>(line: usize, reason: impl Into<String>) -> Result<T, SmapError> {
+        Err(SmapError { line: line + 1, reason: reason.into() })
+    }
+
+    impl Smap {
+        pub fn parse(smap: &str) -> Result<Smap, SmapError> {
+            let mut input = smap.lines().enumerate().peekable();
+
+            match input.next() {
+                Some((_, "SMAP")) => {},
+                _ => return error(0, "missing SMAP header"),
+            }
+            let Some((_, output_file)) = input.next() else {
+                return error(1, "missing output file name");
+            };
+            let Some((_, default_stratum)) = input.next() else {
+                return error(2, "missing default stratum");
+            };
+
+            let mut strata: Vec<Stratum> = Vec::new();
+            while let Some((idx, line)) = input.next() {
+                let (section, arg) = line.split_once(' ').unwrap_or((line, ""));
+                match section {
+                    "*E" => break,
+                    "*S" => strata.push(Stratum { id: arg.trim().to_string(), files: Vec::new(), lines: Vec::new() }),
+                    "*F" | "*L" => {
+                        let Some(stratum) = strata.last_mut() else {
+                            return error(idx, format!("{section} section before any stratum section"));
+                        };
+                        if section == "*F" {
+                            parse_files(&mut input, &mut stratum.files)?;
+                        } else {
+                            parse_lines(&mut input, &stratum.files, &mut stratum.lines)?;
+                        }
+                    },
+                    // Embedded source maps are only there for tools to merge, and are already
+                    // accounted for in the strata of this one
+                    "*O" => skip_embedded(&mut input, idx)?,
+                    // Vendor and unknown sections must be ignored
+                    _ if section.starts_with('*') => skip_section(&mut input),
+                    _ => return error(idx, "expected a section"),
+                }
+            }
+
+            Ok(Smap {
+                output_file: output_file.to_string(),
+                default_stratum: default_stratum.trim().to_string(),
+                strata,
+            })
+        }
+    }
+
+    // Takes the next line, if it's part of the current section
+    fn next_in_section<'a>(input: &mut Input<'a>) -> Option<(usize, &'a str)> {
+        input.next_if(|(_, line)| !line.starts_with('*'))
+    }
+
+    fn skip_section(input: &mut Input) {
+        while next_in_section(input).is_some() {}
+    }
+
+    fn skip_embedded(input: &mut Input, start: usize) -> Result<(), SmapError> {
+        let mut depth = 1;
+        for (_, line) in input.by_ref() {
+            if line.starts_with("*O") {
+                depth += 1;
+            } else if line.starts_with("*C") {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+        }
+        error(start, "embedded source map is never closed")
+    }
+
+    // FileInfo is either `id name`, or `+ id name` followed by a line with the file's path
+    fn parse_files(input: &mut Input, files: &mut Vec<SmapFile>) -> Result<(), SmapError> {
+        while let Some((idx, line)) = next_in_section(input) {
+            let (has_path, line) = match line.strip_prefix("+ ") {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            let Some((id, name)) = line.split_once(' ') else {
+                return error(idx, "expected a file ID and name");
+            };
+            let Ok(id) = id.parse() else {
+                return error(idx, format!("invalid file ID {id}"));
+            };
+
+            let path = if has_path {
+                let Some((_, path)) = next_in_section(input) else {
+                    return error(idx + 1, "missing file path");
+                };
+                Some(path.to_string())
+            } else {
+                None
+            };
+            files.push(SmapFile { id, name: name.to_string(), path });
+        }
+        Ok(())
+    }
+
+    // LineInfo is `InputStartLine[#LineFileID][,RepeatCount]:OutputStartLine[,OutputLineIncrement]`,
+    // where the file ID, if not given, is the same as the entry before
+    fn parse_lines(input: &mut Input, files: &[SmapFile], lines: &mut Vec<LineInfo>) -> Result<(), SmapError> {
+        let mut file_id = 0;
+        while let Some((idx, line)) = next_in_section(input) {
+            let number = |s: &str| s.trim().parse::<u32>().or_else(|_| error(idx, format!("invalid number {s}")));
+
+            let Some((input_part, output_part)) = line.split_once(':') else {
+                return error(idx, "expected input and output lines separated by ':'");
+            };
+
+            let (input_part, repeat_count) = match input_part.split_once(',') {
+                Some((input_part, repeat)) => (input_part, number(repeat)?),
+                None => (input_part, 1),
+            };
+            let input_start_line = match input_part.split_once('#') {
+                Some((start, id)) => {
+                    file_id = number(id)?;
+                    number(start)?
+                },
+                None => number(input_part)?,
+            };
+            // The file section always comes before the line section
+            if !files.iter().any(|file| file.id == file_id) {
+                return error(idx, format!("no file with ID {file_id}"));
+            }
+
+            let (output_start_line, output_line_increment) = match output_part.split_once(',') {
+                Some((start, increment)) => (number(start)?, number(increment)?),
+                None => (number(output_part)?, 1),
+            };
+
+            lines.push(LineInfo { input_start_line, file_id, repeat_count, output_start_line, output_line_increment });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example from JSR-45, with a vendor section and an embedded map thrown in
+    const JSP: &str = "SMAP\n\
+        Hi_jsp.java\n\
+        JSP\n\
+        *S JSP\n\
+        *F\n\
+        + 0 Hi.jsp\n\
+        pages/Hi.jsp\n\
+        1 Inc.jsp\n\
+        *L\n\
+        1:11\n\
+        2,4:13,2\n\
+        1#1,2:30\n\
+        5:40,0\n\
+        *V\n\
+        anything at all\n\
+        *O Other\n\
+        *S Other\n\
+        *C Other\n\
+        *S Other\n\
+        *F\n\
+        0 Other.txt\n\
+        *L\n\
+        7:11\n\
+        *E\n";
+
+    fn parse_error(smap: &str) -> usize {
+        Smap::parse(smap).err().expect("source map should be rejected").line()
+    }
+
+    #[test]
+    fn parses_strata() {
+        let smap = Smap::parse(JSP).expect("source map should parse");
+        assert_eq!(smap.output_file(), "Hi_jsp.java");
+        assert_eq!(smap.default_stratum_id(), "JSP");
+        assert_eq!(smap.strata().iter().map(Stratum::id).collect::<Vec<_>>(), ["JSP", "Other"]);
+
+        let jsp = smap.default_stratum().expect("default stratum should exist");
+        let files: Vec<_> = jsp.files().iter().map(|file| (file.id(), file.name(), file.path())).collect();
+        assert_eq!(files, [(0, "Hi.jsp", Some("pages/Hi.jsp")), (1, "Inc.jsp", None)]);
+        // The file ID carries on to the entries after the one that gave it
+        assert_eq!(jsp.lines().iter().map(LineInfo::file_id).collect::<Vec<_>>(), [0, 0, 1, 1]);
+        assert_eq!(smap.stratum("Other").and_then(|stratum| stratum.file(0)).map(SmapFile::name), Some("Other.txt"));
+    }
+
+    #[test]
+    fn maps_lines() {
+        let smap = Smap::parse(JSP).expect("source map should parse");
+        let map = |line| smap.map_line(line).map(|line| (line.file.name(), line.line));
+
+        assert_eq!(map(11), Some(("Hi.jsp", 1)));
+        assert_eq!(map(12), None);
+        // Four lines from 2, each to two lines from 13
+        assert_eq!(map(13), Some(("Hi.jsp", 2)));
+        assert_eq!(map(14), Some(("Hi.jsp", 2)));
+        assert_eq!(map(20), Some(("Hi.jsp", 5)));
+        assert_eq!(map(21), None);
+        assert_eq!(map(31), Some(("Inc.jsp", 2)));
+        // An increment of 0 only maps the start line
+        assert_eq!(map(40), Some(("Inc.jsp", 5)));
+        assert_eq!(map(41), None);
+        assert_eq!(map(1), None);
+    }
+
+    #[test]
+    fn java_stratum_has_no_mapping() {
+        let smap = Smap::parse("SMAP\nA.java\nJava\n*S Kotlin\n*F\n1 A.kt\n*L\n1#1:5\n*E\n").expect("source map should parse");
+        assert!(smap.default_stratum().is_none());
+        assert!(smap.map_line(5).is_none());
+        assert!(smap.stratum("Kotlin").and_then(|stratum| stratum.map_line(5)).is_some());
+    }
+
+    #[test]
+    fn errors_give_lines() {
+        assert_eq!(parse_error("SMAP?\nA.java\nJava\n"), 1);
+        assert_eq!(parse_error("SMAP\nA.java\n"), 3);
+        assert_eq!(parse_error("SMAP\nA.java\nJSP\n*F\n"), 4);
+        assert_eq!(parse_error("SMAP\nA.java\nJSP\n*S JSP\n*F\n0 A.jsp\n*L\n1#2:3\n"), 8);
+        assert_eq!(parse_error("SMAP\nA.java\nJSP\n*S JSP\n*F\n0 A.jsp\n*L\n1:x\n"), 8);
+        assert_eq!(parse_error("SMAP\nA.java\nJSP\n*S JSP\n*F\n+ 0 A.jsp\n"), 7);
+        assert_eq!(parse_error("SMAP\nA.java\nJSP\n*O Other\n*S Other\n"), 4);
+        assert_eq!(parse_error("SMAP\nA.java\nJSP\nnot a section\n"), 4);
+    }
+}
@@ -86,7 +86,7 @@ impl VerificationType {
 mod _parse {
     use crate::buf_read_named_type_arr;
     use super::*;
-    use crate::loader::{BinaryReader, Parse, ParseError};
+    use crate::loader::{BinaryReader, ErrorKind, Parse, ParseError};
 
     impl Parse<Frame> for Frame {
         fn parse(buf: &mut BinaryReader) -> Result<Frame, ParseError> {
@@ -96,7 +96,8 @@ mod _parse {
             let frame_type = unsafe { buf.unsafe_read_u8() };
             if frame_type > 127 && frame_type < 247 {
                 // This range is reserved for future use and is this invalid
-                return ParseError::new(format!("stack map frame - invalid frame type {frame_type}")).into()
+                let kind = ErrorKind::Malformed(format!("stack map frame - invalid frame type {frame_type}"));
+                return ParseError::new(kind, buf.position() - 1).into()
             }
 
             match frame_type {
@@ -154,10 +155,8 @@ mod _parse {
 
         // Cannot use macro as num_locals is not using a length defined in type
         let num_locals = (frame_type - 251) as usize;
-        // TODO: We shouldn't wrap this. When we have proper error handling,
-        //  propagate it.
         let mut locals = Array::new(num_locals)
-            .map_err(|_| ParseError::new("cannot allocate array"))?;
+            .map_err(|_| buf.error(ErrorKind::OutOfMemory))?;
 
         for i in 0..num_locals {
             let local = VerificationType::parse(buf)
@@ -204,7 +203,68 @@ mod _parse {
                     let offset = unsafe { buf.unsafe_read_u16() };
                     Ok(VerificationType::Uninitialized { offset })
                 },
-                _ => ParseError::new(format!("invalid verification type tag {tag}")).into()
+                _ => {
+                    let kind = ErrorKind::Malformed(format!("invalid verification type tag {tag}"));
+                    ParseError::new(kind, buf.position() - 1).into()
+                }
+            }
+        }
+    }
+}
+
+mod _write {
+    use super::*;
+    use crate::loader::{BinaryWriter, Serialize};
+
+    impl Serialize for Frame {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u8(self.frame_type());
+            match self {
+                Frame::Same { .. } => {},
+                Frame::SameLocalsOneStackItem { stack, .. } => stack.serialize(buf),
+                Frame::SameLocalsOneStackItemExtended { offset_delta, stack } => {
+                    buf.write_u16(*offset_delta);
+                    stack.serialize(buf);
+                },
+                Frame::Chop { offset_delta, .. } | Frame::SameExtended { offset_delta } => {
+                    buf.write_u16(*offset_delta);
+                },
+                Frame::Append { offset_delta, locals, .. } => {
+                    buf.write_u16(*offset_delta);
+                    // The number of locals is implied by the frame type
+                    // SAFETY: We know this array is fully initialized
+                    for local in unsafe { locals.as_slice() } {
+                        local.serialize(buf);
+                    }
+                },
+                Frame::Full { offset_delta, locals, stack } => {
+                    buf.write_u16(*offset_delta);
+                    // SAFETY: We know these arrays are fully initialized
+                    buf.write_arr(unsafe { locals.as_slice() });
+                    buf.write_arr(unsafe { stack.as_slice() });
+                },
+            }
+        }
+    }
+
+    impl Serialize for VerificationType {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            match self {
+                VerificationType::Top => buf.write_u8(VerificationType::TOP),
+                VerificationType::Integer => buf.write_u8(VerificationType::INTEGER),
+                VerificationType::Float => buf.write_u8(VerificationType::FLOAT),
+                VerificationType::Double => buf.write_u8(VerificationType::DOUBLE),
+                VerificationType::Long => buf.write_u8(VerificationType::LONG),
+                VerificationType::Null => buf.write_u8(VerificationType::NULL),
+                VerificationType::UninitializedThis => buf.write_u8(VerificationType::UNINIT_THIS),
+                VerificationType::Object { pool_index } => {
+                    buf.write_u8(VerificationType::OBJECT);
+                    buf.write_u16(*pool_index);
+                },
+                VerificationType::Uninitialized { offset } => {
+                    buf.write_u8(VerificationType::UNINIT);
+                    buf.write_u16(*offset);
+                },
             }
         }
     }
@@ -13,8 +13,7 @@
 // You should have received a copy of the GNU General Public License along
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
-use crate::loader::classfile::attribute::annotations;
-use crate::loader::classfile::constantpool::Index;
+use crate::loader::classfile::attribute::annotations::Annotation;
 use crate::types::Array;
 
 pub struct RuntimeVisible {
@@ -25,6 +24,19 @@ pub struct RuntimeInvisible {
     annotations: Array<TypeAnnotation>,
 }
 
+macro_rules! impl_type_annotations {
+    ($name: ident) => {
+        impl $name {
+            pub fn annotations(&self) -> &[TypeAnnotation] {
+                // SAFETY: We know this array is fully initialized
+                unsafe { self.annotations.as_slice() }
+            }
+        }
+    };
+}
+impl_type_annotations!(RuntimeVisible);
+impl_type_annotations!(RuntimeInvisible);
+
 mod _attr_name {
     use super::*;
     use crate::loader::classfile::attribute::names::{Nameable, Names, impl_attr_name};
@@ -33,12 +45,33 @@ mod _attr_name {
     impl_attr_name!(RuntimeInvisible, RUNTIME_INVISIBLE_TYPE_ANNOTATIONS);
 }
 
+/// An annotation on a use of a type, which is an ordinary [Annotation] along with which type
+/// it's on.
 pub struct TypeAnnotation {
     target_type: TargetType,
     target_info: TargetInfo,
     target_path: Path,
-    type_index: Index,
-    elements: Array<annotations::Element>,
+    annotation: Annotation,
+}
+
+impl TypeAnnotation {
+    pub fn target_type(&self) -> TargetType {
+        self.target_type
+    }
+
+    pub fn target_info(&self) -> &TargetInfo {
+        &self.target_info
+    }
+
+    /// Which part of the type given by the target the annotation is on.
+    pub fn target_path(&self) -> &[PathPart] {
+        // SAFETY: We know this array is fully initialized
+        unsafe { self.target_path.as_slice() }
+    }
+
+    pub fn annotation(&self) -> &Annotation {
+        &self.annotation
+    }
 }
 
 pub type Path = Array<PathPart>;
@@ -48,6 +81,16 @@ pub struct PathPart {
     type_argument_index: u8
 }
 
+impl PathPart {
+    pub fn type_path_kind(&self) -> u8 {
+        self.type_path_kind
+    }
+
+    pub fn type_argument_index(&self) -> u8 {
+        self.type_argument_index
+    }
+}
+
 #[repr(u8)]
 #[derive(Primitive, Debug, PartialEq, Copy, Clone)]
 pub enum TargetType {
@@ -97,11 +140,24 @@ pub struct LocalVarInfoEntry {
     index: u16
 }
 
+impl LocalVarInfoEntry {
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
 mod _parse {
     use num_traits::FromPrimitive;
     use crate::buf_read_named_type_arr;
-    use crate::loader::{BinaryReader, Parse, ParseError};
-    use crate::loader::classfile::attribute::annotations::Element;
+    use crate::loader::{BinaryReader, ErrorKind, Parse, ParseError};
     use super::*;
 
     macro_rules! impl_type_annotation_attr {
@@ -131,26 +187,19 @@ mod _parse {
             let target_type = TargetType::from_u8(raw_type)
                 .ok_or_else(|| {
                     let msg = format!("type annotation - invalid target type {raw_type}");
-                    return ParseError::new(msg)
+                    ParseError::new(ErrorKind::Malformed(msg), buf.position() - 1)
                 })?;
             let target_info = parse_target_info(buf, target_type)?;
 
-            buf_read_named_type_arr!(PathPart, target_path, buf,
-                "type annotation - path", "type annotation - path - idx {}");
-
-            buf.check_bytes(2, "type annotation - type index")?;
-            // SAFETY: Guaranteed by check_bytes
-            let type_index = unsafe { buf.unsafe_read_u16() };
-
-            buf_read_named_type_arr!(Element, elements, buf,
-                "type annotation - elements", "type annotation - elements - idx {}");
+            let target_path = parse_path(buf)?;
+            let annotation = Annotation::parse(buf)
+                .map_err(ParseError::wrap("type annotation"))?;
 
             Ok(TypeAnnotation {
                 target_type,
                 target_info,
                 target_path,
-                type_index,
-                elements
+                annotation,
             })
         }
     }
@@ -181,6 +230,22 @@ mod _parse {
         r.map_err(ParseError::wrap("type annotation"))
     }
 
+    fn parse_path(buf: &mut BinaryReader) -> Result<Path, ParseError> {
+        // Cannot use macro as the path length is a u1, not a u2
+        buf.check_bytes(1, "type annotation - path")?;
+
+        // SAFETY: Guaranteed by check_bytes
+        let len = unsafe { buf.unsafe_read_u8() } as usize;
+
+        let mut parts = Vec::with_capacity(len);
+        for i in 0..len {
+            let part = PathPart::parse(buf)
+                .map_err(ParseError::wrap(format!("type annotation - path - idx {i}")))?;
+            parts.push(part);
+        }
+        Array::from_vec(parts).map_err(|_| buf.error(ErrorKind::OutOfMemory))
+    }
+
     fn parse_info_type_param(buf: &mut BinaryReader) -> Result<TargetInfo, ParseError> {
         buf.check_bytes(1, "type parameter target - index")?;
 
@@ -280,3 +345,128 @@ mod _parse {
         }
     }
 }
+
+mod _write {
+    use super::*;
+    use crate::loader::{BinaryWriter, Serialize};
+
+    macro_rules! impl_type_annotation_attr {
+        ($name: ident) => {
+            impl Serialize for $name {
+                fn serialize(&self, buf: &mut BinaryWriter) {
+                    buf.write_arr(self.annotations());
+                }
+            }
+        };
+    }
+    impl_type_annotation_attr!(RuntimeVisible);
+    impl_type_annotation_attr!(RuntimeInvisible);
+
+    impl Serialize for TypeAnnotation {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u8(self.target_type as u8);
+            self.target_info.serialize(buf);
+
+            // The path length is a u1, unlike most other arrays
+            let path = self.target_path();
+            buf.write_u8(path.len() as u8);
+            for part in path {
+                buf.write_u8(part.type_path_kind);
+                buf.write_u8(part.type_argument_index);
+            }
+            self.annotation.serialize(buf);
+        }
+    }
+
+    impl Serialize for TargetInfo {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            match self {
+                TargetInfo::TypeParameter { index } | TargetInfo::FormalParameter { index } => buf.write_u8(*index),
+                TargetInfo::Supertype { index } => buf.write_u16(*index),
+                TargetInfo::ParameterBound { type_parameter_index, bound_index } => {
+                    buf.write_u8(*type_parameter_index);
+                    buf.write_u8(*bound_index);
+                },
+                TargetInfo::Empty => {},
+                TargetInfo::Throws { type_index } => buf.write_u16(*type_index),
+                // SAFETY: We know this array is fully initialized
+                TargetInfo::LocalVar { table } => buf.write_arr(unsafe { table.as_slice() }),
+                TargetInfo::Catch { exception_table_index } => buf.write_u16(*exception_table_index),
+                TargetInfo::Offset(offset) => buf.write_u16(*offset),
+                TargetInfo::TypeArgument { offset, index } => {
+                    buf.write_u16(*offset);
+                    buf.write_u8(*index);
+                },
+            }
+        }
+    }
+
+    impl Serialize for LocalVarInfoEntry {
+        fn serialize(&self, buf: &mut BinaryWriter) {
+            buf.write_u16(self.start_pc);
+            buf.write_u16(self.length);
+            buf.write_u16(self.index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{BinaryReader, BinaryWriter, ErrorKind, Parse, Serialize};
+
+    fn parse(bytes: &[u8]) -> RuntimeVisible {
+        let attribute = RuntimeVisible::parse(&mut BinaryReader::new(bytes.to_vec())).expect("type annotations should parse");
+        let mut buf = BinaryWriter::new();
+        attribute.serialize(&mut buf);
+        assert_eq!(buf.into_bytes(), bytes);
+        attribute
+    }
+
+    #[test]
+    fn parses_targets_and_paths() {
+        let attribute = parse(&[
+            0, 2,
+            // A field type, on the argument of its first type argument, with a u1 path length
+            0x13, 2, 3, 0, 3, 1, 0, 9, 0, 0,
+            // A local variable with two live ranges
+            0x40, 0, 2, 0, 0, 0, 5, 0, 1, 0, 8, 0, 2, 0, 1, 0, 0, 9, 0, 0,
+        ]);
+
+        let [field, local] = attribute.annotations() else {
+            panic!("expected two type annotations");
+        };
+        assert_eq!(field.target_type(), TargetType::FieldOrRecord);
+        assert!(matches!(field.target_info(), TargetInfo::Empty));
+        let path: Vec<_> = field.target_path().iter().map(|part| (part.type_path_kind(), part.type_argument_index())).collect();
+        assert_eq!(path, [(3, 0), (3, 1)]);
+        assert_eq!(field.annotation().type_index(), 9);
+
+        assert_eq!(local.target_type(), TargetType::LocalVar);
+        let TargetInfo::LocalVar { table } = local.target_info() else {
+            panic!("expected a local variable target");
+        };
+        // SAFETY: We know this array is fully initialized
+        let ranges: Vec<_> = unsafe { table.as_slice() }.iter().map(|entry| (entry.start_pc(), entry.length(), entry.index())).collect();
+        assert_eq!(ranges, [(0, 5, 1), (8, 2, 1)]);
+        assert!(local.target_path().is_empty());
+    }
+
+    #[test]
+    fn parses_code_targets() {
+        let attribute = parse(&[0, 2, 0x47, 0, 4, 1, 0, 0, 9, 0, 0, 0x42, 0, 3, 0, 0, 9, 0, 0]);
+        let [cast, catch] = attribute.annotations() else {
+            panic!("expected two type annotations");
+        };
+        assert!(matches!(cast.target_info(), TargetInfo::TypeArgument { offset: 4, index: 1 }));
+        assert!(matches!(catch.target_info(), TargetInfo::Catch { exception_table_index: 3 }));
+    }
+
+    #[test]
+    fn rejects_unknown_target_types() {
+        let err = RuntimeVisible::parse(&mut BinaryReader::new(vec![0, 1, 0x20, 0, 0, 9, 0, 0])).err()
+            .expect("target type should be rejected");
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+        assert_eq!(err.offset(), 2);
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+pub mod classfile;
+
+pub use crate::class::parse::{BinaryReader, ErrorKind, ParseError};
+pub use crate::class::write::BinaryWriter;
+
+/// A structure that can be read from a class file on its own, without needing anything
+/// other than the bytes in front of it.
+pub trait Parse<T> {
+    fn parse(buf: &mut BinaryReader) -> Result<T, ParseError>;
+}
+
+/// A structure that can be written to a class file on its own, the reverse of [Parse].
+pub trait Serialize {
+    fn serialize(&self, buf: &mut BinaryWriter);
+}
@@ -1,88 +1,231 @@
-#[repr(transparent)]
-#[derive(Copy, Clone, Debug)]
-pub struct AccessFlags(u16);
-
-impl AccessFlags {
-    // Class, Field, Method
-    pub const PUBLIC: u16 = 0x0001;
-    pub const FINAL: u16 = 0x0010;
-    pub const SYNTHETIC: u16 = 0x1000;
-
-    // Class, Field
-    pub const ENUM: u16 = 0x4000;
-
-    // Field, Method
-    pub const PRIVATE: u16 = 0x0002;
-    pub const PROTECTED: u16 = 0x0004;
-    pub const STATIC: u16 = 0x0008;
-
-    // Class, Method
-    pub const ABSTRACT: u16 = 0x0400;
-
-    // Class
-    pub const SUPER: u16 = 0x0020;
-    pub const INTERFACE: u16 = 0x0200;
-    pub const MODULE: u16 = 0x8000;
-    pub const ANNOTATION: u16 = 0x2000;
-
-    // Field
-    pub const VOLATILE: u16 = 0x0040;
-    pub const TRANSIENT: u16 = 0x0080;
-
-    // Method
-    pub const SYNCHRONIZED: u16 = 0x0020;
-    pub const BRIDGE: u16 = 0x0040;
-    pub const VARARGS: u16 = 0x0080;
-    pub const NATIVE: u16 = 0x0100;
-    pub const STRICT: u16 = 0x0800;
-
-    // Method Parameter, Module
-    pub const MANDATED: u16 = 0x8000;
-
-    // Module & Requires, Opens, Exports
-    pub const OPEN: u16 = 0x0020;
-    // Module - Requires only
-    pub const STATIC_PHASE: u16 = 0x0040;
-}
+use std::fmt;
+
+// Defines a set of access flags for one kind of structure. Structures reuse bits for
+// unrelated flags, so each gets its own type, and a flag can only be checked on the
+// structures it has a meaning for.
+macro_rules! access_flags {
+    ($(#[$meta: meta])* $name: ident { $($flag: ident = $value: literal => $is_flag: ident),* $(,)? }) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        #[derive(Copy, Clone, PartialEq, Eq, Default)]
+        pub struct $name(u16);
+
+        impl $name {
+            $(pub const $flag: u16 = $value;)*
+
+            const NAMES: &'static [(u16, &'static str)] = &[$(($value, stringify!($flag))),*];
+
+            pub const fn new(v: u16) -> Self {
+                Self(v)
+            }
+
+            #[inline]
+            pub const fn flags(&self) -> u16 {
+                self.0
+            }
+
+            /// Whether all the given flags are set.
+            pub const fn contains(&self, flags: u16) -> bool {
+                self.0 & flags == flags
+            }
+
+            /// Whether any of the given flags are set.
+            pub const fn intersects(&self, flags: u16) -> bool {
+                self.0 & flags != 0
+            }
+
+            /// The names of the flags that are set, such as `PUBLIC`, not including any bits
+            /// that aren't defined for the structure.
+            pub fn names(&self) -> impl Iterator<Item = &'static str> {
+                let flags = self.0;
+                Self::NAMES.iter().filter(move |&&(flag, _)| flags & flag != 0).map(|&(_, name)| name)
+            }
+
+            /// The flag with the given name, such as `PUBLIC`.
+            pub fn from_name(name: &str) -> Option<u16> {
+                Self::NAMES.iter().find(|&&(_, flag_name)| flag_name == name).map(|&(flag, _)| flag)
+            }
 
-macro_rules! is_flag {
-    ($name: ident, $cons: ident) => {
-        pub fn $name(&self) -> bool {
-            self.0 & Self::$cons != 0
+            $(
+                pub const fn $is_flag(&self) -> bool {
+                    self.0 & Self::$flag != 0
+                }
+            )*
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}(", stringify!($name))?;
+                write_flag_names(f, self.0, Self::NAMES)?;
+                f.write_str(")")
+            }
         }
     };
 }
 
-impl AccessFlags {
-    pub const fn new(v: u16) -> Self {
-        Self(v)
+// Writes the names of the flags that are set, such as `PUBLIC | FINAL`, along with any
+// bits that aren't defined for the structure
+fn write_flag_names(f: &mut fmt::Formatter<'_>, flags: u16, names: &[(u16, &str)]) -> fmt::Result {
+    if flags == 0 {
+        return f.write_str("empty");
+    }
+
+    let mut remaining = flags;
+    let mut first = true;
+    for &(flag, name) in names {
+        if flags & flag != 0 {
+            if !first {
+                f.write_str(" | ")?;
+            }
+            f.write_str(name)?;
+            remaining &= !flag;
+            first = false;
+        }
+    }
+
+    if remaining != 0 {
+        if !first {
+            f.write_str(" | ")?;
+        }
+        write!(f, "{remaining:#06X}")?;
     }
+    Ok(())
+}
 
-    #[inline]
-    pub const fn flags(&self) -> u16 {
-        self.0
+access_flags!(
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.1-200-E.1
+    ClassAccessFlags {
+        PUBLIC = 0x0001 => is_public,
+        FINAL = 0x0010 => is_final,
+        SUPER = 0x0020 => is_super,
+        INTERFACE = 0x0200 => is_interface,
+        ABSTRACT = 0x0400 => is_abstract,
+        SYNTHETIC = 0x1000 => is_synthetic,
+        ANNOTATION = 0x2000 => is_annotation,
+        ENUM = 0x4000 => is_enum,
+        MODULE = 0x8000 => is_module,
     }
+);
 
-    is_flag!(is_public, PUBLIC);
-    is_flag!(is_final, FINAL);
-    is_flag!(is_synthetic, SYNTHETIC);
-    is_flag!(is_enum, ENUM);
-    is_flag!(is_private, PRIVATE);
-    is_flag!(is_protected, PROTECTED);
-    is_flag!(is_static, STATIC);
-    is_flag!(is_abstract, ABSTRACT);
-    is_flag!(is_super, SUPER);
-    is_flag!(is_interface, INTERFACE);
-    is_flag!(is_module, MODULE);
-    is_flag!(is_annotation, ANNOTATION);
-    is_flag!(is_volatile, VOLATILE);
-    is_flag!(is_transient, TRANSIENT);
-    is_flag!(is_synchronized, SYNCHRONIZED);
-    is_flag!(is_bridge, BRIDGE);
-    is_flag!(is_varargs, VARARGS);
-    is_flag!(is_native, NATIVE);
-    is_flag!(is_strict, STRICT);
-    is_flag!(is_mandated, MANDATED);
-    is_flag!(is_open, OPEN);
-    is_flag!(is_static_phase, STATIC_PHASE);
+access_flags!(
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.5-200-A.1
+    FieldAccessFlags {
+        PUBLIC = 0x0001 => is_public,
+        PRIVATE = 0x0002 => is_private,
+        PROTECTED = 0x0004 => is_protected,
+        STATIC = 0x0008 => is_static,
+        FINAL = 0x0010 => is_final,
+        VOLATILE = 0x0040 => is_volatile,
+        TRANSIENT = 0x0080 => is_transient,
+        SYNTHETIC = 0x1000 => is_synthetic,
+        ENUM = 0x4000 => is_enum,
+    }
+);
+
+access_flags!(
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.6-200-A.1
+    MethodAccessFlags {
+        PUBLIC = 0x0001 => is_public,
+        PRIVATE = 0x0002 => is_private,
+        PROTECTED = 0x0004 => is_protected,
+        STATIC = 0x0008 => is_static,
+        FINAL = 0x0010 => is_final,
+        SYNCHRONIZED = 0x0020 => is_synchronized,
+        BRIDGE = 0x0040 => is_bridge,
+        VARARGS = 0x0080 => is_varargs,
+        NATIVE = 0x0100 => is_native,
+        ABSTRACT = 0x0400 => is_abstract,
+        STRICT = 0x0800 => is_strict,
+        SYNTHETIC = 0x1000 => is_synthetic,
+    }
+);
+
+access_flags!(
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.6
+    InnerClassAccessFlags {
+        PUBLIC = 0x0001 => is_public,
+        PRIVATE = 0x0002 => is_private,
+        PROTECTED = 0x0004 => is_protected,
+        STATIC = 0x0008 => is_static,
+        FINAL = 0x0010 => is_final,
+        INTERFACE = 0x0200 => is_interface,
+        ABSTRACT = 0x0400 => is_abstract,
+        SYNTHETIC = 0x1000 => is_synthetic,
+        ANNOTATION = 0x2000 => is_annotation,
+        ENUM = 0x4000 => is_enum,
+    }
+);
+
+access_flags!(
+    /// The flags of a `Module` attribute.
+    ///
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.25
+    ModuleFlags {
+        OPEN = 0x0020 => is_open,
+        SYNTHETIC = 0x1000 => is_synthetic,
+        MANDATED = 0x8000 => is_mandated,
+    }
+);
+
+access_flags!(
+    /// The flags of a module's `requires` entry.
+    RequiresFlags {
+        TRANSITIVE = 0x0020 => is_transitive,
+        STATIC_PHASE = 0x0040 => is_static_phase,
+        SYNTHETIC = 0x1000 => is_synthetic,
+        MANDATED = 0x8000 => is_mandated,
+    }
+);
+
+access_flags!(
+    /// The flags of a module's `exports` and `opens` entries.
+    ExportsFlags {
+        SYNTHETIC = 0x1000 => is_synthetic,
+        MANDATED = 0x8000 => is_mandated,
+    }
+);
+
+access_flags!(
+    /// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.24
+    ParameterAccessFlags {
+        FINAL = 0x0010 => is_final,
+        SYNTHETIC = 0x1000 => is_synthetic,
+        MANDATED = 0x8000 => is_mandated,
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_flags() {
+        let flags = MethodAccessFlags::new(MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC);
+        assert!(flags.is_public() && flags.is_static() && !flags.is_final());
+        assert!(flags.contains(MethodAccessFlags::PUBLIC));
+        assert!(!flags.contains(MethodAccessFlags::PUBLIC | MethodAccessFlags::FINAL));
+        assert!(flags.intersects(MethodAccessFlags::PUBLIC | MethodAccessFlags::FINAL));
+        assert_eq!(flags.names().collect::<Vec<_>>(), ["PUBLIC", "STATIC"]);
+        assert_eq!(MethodAccessFlags::from_name("VARARGS"), Some(MethodAccessFlags::VARARGS));
+        assert_eq!(MethodAccessFlags::from_name("TRANSIENT"), None);
+    }
+
+    #[test]
+    fn bits_mean_different_things() {
+        // 0x0040 is volatile on a field, but bridge on a method
+        assert!(FieldAccessFlags::new(0x0040).is_volatile());
+        assert!(MethodAccessFlags::new(0x0040).is_bridge());
+        assert_eq!(FieldAccessFlags::from_name("VOLATILE"), Some(0x0040));
+        assert_eq!(MethodAccessFlags::from_name("BRIDGE"), Some(0x0040));
+    }
+
+    #[test]
+    fn debug_shows_unknown_bits() {
+        assert_eq!(format!("{:?}", ClassAccessFlags::new(0)), "ClassAccessFlags(empty)");
+        assert_eq!(format!("{:?}", ClassAccessFlags::new(ClassAccessFlags::PUBLIC | ClassAccessFlags::FINAL)),
+            "ClassAccessFlags(PUBLIC | FINAL)");
+        assert_eq!(format!("{:?}", FieldAccessFlags::new(FieldAccessFlags::PUBLIC | 0x0100)),
+            "FieldAccessFlags(PUBLIC | 0x0100)");
+        assert_eq!(format!("{:?}", ExportsFlags::new(0x0001)), "ExportsFlags(0x0001)");
+    }
 }
@@ -34,6 +34,11 @@ pub struct Array<T> {
     len: usize
 }
 
+// SAFETY: Array owns its elements in the same way as Vec does, so is Send and Sync whenever
+// its elements are.
+unsafe impl<T: Send> Send for Array<T> {}
+unsafe impl<T: Sync> Sync for Array<T> {}
+
 impl<T> Array<T> {
     pub fn new(len: usize) -> Result<Self, errors::OutOfMemoryError> {
         if len == 0 {
@@ -45,6 +50,16 @@ impl<T> Array<T> {
         Ok(Self { ptr, len })
     }
 
+    /// Moves all the elements of the given vector in to a new array of the same length.
+    pub fn from_vec(vec: Vec<T>) -> Result<Self, errors::OutOfMemoryError> {
+        let mut result = Self::new(vec.len())?;
+        for (i, v) in vec.into_iter().enumerate() {
+            // SAFETY: The array has the same length as the vector, so i is always in bounds
+            unsafe { let _ = result.set_unchecked(i, v); }
+        }
+        Ok(result)
+    }
+
     pub const fn empty() -> Array<T> {
         let ptr = NonNull::dangling();
         Self { ptr, len: 0 }
@@ -65,6 +80,10 @@ impl<T> Array<T> {
         self.len
     }
 
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn get(&self, index: usize) -> Option<&T> {
         // This also safeguards for len == 0
         if index >= self.len {
@@ -92,8 +111,10 @@ impl<T> Array<T> {
         unsafe { ptr.drop_in_place() }
     }
 
-    // Same as get, but without bounds checking
-    // SAFETY: If the value at index is null or out of bounds, behaviour is undefined
+    /// Same as get, but without bounds checking
+    ///
+    /// # Safety
+    /// If the value at index is null or out of bounds, behaviour is undefined
     pub unsafe fn get_unchecked(&self, index: usize) -> &T {
         // SAFETY: Must be guaranteed by caller
         let ptr = unsafe { self.ptr().add(index) };
@@ -131,16 +152,18 @@ impl<T> Array<T> {
         ptr
     }
 
-    // SAFETY: Caller must guarantee that Array has been fully initialized
-    // as slices are assumed to be initialized, or must guarantee not to perform
-    // get operations on the slice, else behaviour is undefined.
+    /// # Safety
+    /// Caller must guarantee that Array has been fully initialized
+    /// as slices are assumed to be initialized, or must guarantee not to perform
+    /// get operations on the slice, else behaviour is undefined.
     pub unsafe fn as_slice(&self) -> &[T] {
         unsafe { slice::from_raw_parts(self.ptr(), self.len) }
     }
 
-    // SAFETY: Caller must guarantee that Array has been fully initialized
-    // as slices are assumed to be initialized, or must guarantee not to perform
-    // get operations on the slice, else behaviour is undefined.
+    /// # Safety
+    /// Caller must guarantee that Array has been fully initialized
+    /// as slices are assumed to be initialized, or must guarantee not to perform
+    /// get operations on the slice, else behaviour is undefined.
     pub unsafe fn as_slice_mut(&mut self) -> &mut [T] {
         unsafe { slice::from_raw_parts_mut(self.ptr(), self.len) }
     }
@@ -152,8 +175,9 @@ impl<T> Array<T> {
 }
 
 impl<T: Clone> Array<T> {
-    // SAFETY: Caller must guarantee that Array has been fully initialized
-    // See: as_slice and as_slice_mut
+    /// # Safety
+    /// Caller must guarantee that Array has been fully initialized
+    /// See: as_slice and as_slice_mut
     pub unsafe fn to_vec(&self) -> Vec<T> {
         // SAFETY: On the caller
         let slice = unsafe { self.as_slice() };
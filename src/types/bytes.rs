@@ -0,0 +1,203 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::ops::{Deref, Range};
+use std::slice;
+use std::sync::Arc;
+use memmap2::Mmap;
+
+/// An immutable view in to a shared buffer of bytes, such as a whole class file.
+///
+/// Cloning and slicing don't copy the bytes, but instead share the buffer, which is kept
+/// alive for as long as any view in to it is. This lets parsed classes keep hold of parts
+/// of their class file, like UTF-8 entries and bytecode, without copying them out of it.
+#[derive(Clone)]
+pub struct Bytes {
+    // Only held to keep the bytes alive, and never accessed through
+    _owner: Arc<dyn Send + Sync>,
+    ptr: *const u8,
+    len: usize,
+    // The length of the whole buffer
+    buffer_len: usize,
+}
+
+// SAFETY: The bytes are never modified, and the owner that keeps them alive is Send and Sync
+unsafe impl Send for Bytes {}
+unsafe impl Sync for Bytes {}
+
+impl Bytes {
+    /// Takes ownership of the given buffer, without copying it. The buffer must always give
+    /// back the same bytes, which is the case for anything that owns them, like a `Vec` or
+    /// an `Arc<[u8]>`.
+    pub fn from_owner<T: AsRef<[u8]> + Send + Sync + 'static>(owner: T) -> Self {
+        let owner = Arc::new(owner);
+        let bytes = (*owner).as_ref();
+        let (ptr, len) = (bytes.as_ptr(), bytes.len());
+        // The bytes are either in the Arc's allocation, which doesn't move, or in one that
+        // the owner keeps hold of and can't change without mutable access
+        Self { _owner: owner, ptr, len, buffer_len: len }
+    }
+
+    pub fn copy_from_slice(bytes: &[u8]) -> Self {
+        Self::from_owner(bytes.to_vec())
+    }
+
+    /// Maps the whole of the given file in to memory. Nothing is read until it's used, so
+    /// this is cheap even for large files, like jars that only some classes are needed from.
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated while any view of it is alive, by this
+    /// process or any other, as the bytes are assumed to never change. Depending on the
+    /// platform, this is either undefined behaviour or a crash.
+    pub unsafe fn map_file(file: &File) -> io::Result<Self> {
+        // SAFETY: Guaranteed by the caller
+        let map = unsafe { Mmap::map(file) }?;
+        Ok(Self::from_owner(map))
+    }
+
+    /// Creates a view of part of these bytes, which shares the same buffer.
+    ///
+    /// # Panics
+    /// If the range is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(range.start <= range.end && range.end <= self.len,
+            "range {range:?} out of bounds for {} bytes", self.len);
+        Self {
+            _owner: self._owner.clone(),
+            // SAFETY: In bounds, as checked above
+            ptr: unsafe { self.ptr.add(range.start) },
+            len: range.end - range.start,
+            buffer_len: self.buffer_len,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The length of the whole buffer this is a view in to, all of which is kept alive for as
+    /// long as this is.
+    pub const fn buffer_len(&self) -> usize {
+        self.buffer_len
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: The owner keeps the bytes alive and unchanged for as long as we hold it
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::from_owner(bytes)
+    }
+}
+
+impl From<Arc<[u8]>> for Bytes {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        Self::from_owner(bytes)
+    }
+}
+
+impl PartialEq for Bytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for Bytes {}
+
+impl fmt::Debug for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bytes({} bytes)", self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::parse::BinaryReader;
+    use super::*;
+
+    #[test]
+    fn slices_share_the_buffer() {
+        let bytes = Bytes::from(vec![1, 2, 3, 4, 5]);
+        let slice = bytes.slice(1..4);
+        assert_eq!(slice.as_slice(), &[2, 3, 4]);
+        assert_eq!(slice.as_ptr(), bytes[1..].as_ptr());
+        assert_eq!(slice.buffer_len(), 5);
+
+        // Views keep the buffer alive on their own
+        drop(bytes);
+        assert_eq!(slice.slice(2..3).as_slice(), &[4]);
+        assert!(slice.slice(3..3).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_out_of_bounds() {
+        Bytes::from(vec![1, 2, 3]).slice(2..4);
+    }
+
+    #[test]
+    fn maps_files() {
+        let path = std::env::temp_dir().join(format!("astatine-map-{}", std::process::id()));
+        std::fs::write(&path, b"\xCA\xFE\xBA\xBE").unwrap();
+        // SAFETY: The file is only ours, and is only removed once the view is dropped
+        let bytes = unsafe { Bytes::map_file(&File::open(&path).unwrap()) };
+        let bytes = bytes.expect("should be able to map the file");
+        assert_eq!(bytes.as_slice(), b"\xCA\xFE\xBA\xBE");
+
+        let mut reader = BinaryReader::new(bytes.slice(2..4));
+        assert_eq!(reader.read_u16().ok(), Some(0xBABE));
+        drop((bytes, reader));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reader_does_not_copy() {
+        let bytes = Bytes::from(vec![0, 3, b'a', b'b', b'c', 0]);
+        let mut reader = BinaryReader::new(bytes.clone());
+        assert_eq!(reader.read_u16().ok(), Some(3));
+
+        let read = reader.read_bytes(3, "string").expect("should be enough bytes");
+        assert_eq!(read.as_slice(), b"abc");
+        assert_eq!(read.as_ptr(), bytes[2..].as_ptr());
+
+        let err = reader.read_bytes(2, "string").expect_err("should be past the end");
+        assert_eq!(err.offset(), 5);
+        assert_eq!(reader.position(), 5);
+    }
+}
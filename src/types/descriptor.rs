@@ -0,0 +1,442 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Field and method descriptors, which give the erased types of fields, method parameters
+//! and return values.
+//!
+//! Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.3
+
+use std::fmt;
+use std::mem::size_of;
+use super::{Jboolean, Jbyte, Jchar, Jdouble, Jfloat, Jint, Jlong, Jshort};
+
+/// The most dimensions an array type can have.
+pub const MAX_ARRAY_DIMENSIONS: usize = 255;
+
+/// The most local variable slots a method's parameters can take up, including `this`.
+///
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.3.3
+pub const MAX_PARAMETER_SLOTS: usize = 255;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BaseType {
+    /// `B`, a [Jbyte]
+    Byte,
+    /// `C`, a [Jchar]
+    Char,
+    /// `D`, a [Jdouble]
+    Double,
+    /// `F`, a [Jfloat]
+    Float,
+    /// `I`, a [Jint]
+    Int,
+    /// `J`, a [Jlong]
+    Long,
+    /// `S`, a [Jshort]
+    Short,
+    /// `Z`, a [Jboolean]
+    Boolean,
+}
+
+impl BaseType {
+    pub const fn from_char(c: u8) -> Option<BaseType> {
+        match c {
+            b'B' => Some(BaseType::Byte),
+            b'C' => Some(BaseType::Char),
+            b'D' => Some(BaseType::Double),
+            b'F' => Some(BaseType::Float),
+            b'I' => Some(BaseType::Int),
+            b'J' => Some(BaseType::Long),
+            b'S' => Some(BaseType::Short),
+            b'Z' => Some(BaseType::Boolean),
+            _ => None,
+        }
+    }
+
+    pub const fn descriptor_char(self) -> u8 {
+        match self {
+            BaseType::Byte => b'B',
+            BaseType::Char => b'C',
+            BaseType::Double => b'D',
+            BaseType::Float => b'F',
+            BaseType::Int => b'I',
+            BaseType::Long => b'J',
+            BaseType::Short => b'S',
+            BaseType::Boolean => b'Z',
+        }
+    }
+
+    pub const fn java_name(self) -> &'static str {
+        match self {
+            BaseType::Byte => "byte",
+            BaseType::Char => "char",
+            BaseType::Double => "double",
+            BaseType::Float => "float",
+            BaseType::Int => "int",
+            BaseType::Long => "long",
+            BaseType::Short => "short",
+            BaseType::Boolean => "boolean",
+        }
+    }
+
+    /// The size in bytes of the type we represent values of this type with.
+    pub const fn size(self) -> usize {
+        match self {
+            BaseType::Byte => size_of::<Jbyte>(),
+            BaseType::Char => size_of::<Jchar>(),
+            BaseType::Double => size_of::<Jdouble>(),
+            BaseType::Float => size_of::<Jfloat>(),
+            BaseType::Int => size_of::<Jint>(),
+            BaseType::Long => size_of::<Jlong>(),
+            BaseType::Short => size_of::<Jshort>(),
+            BaseType::Boolean => size_of::<Jboolean>(),
+        }
+    }
+
+    /// The number of local variable or operand stack slots a value of this type takes up.
+    pub const fn slots(self) -> usize {
+        match self {
+            BaseType::Long | BaseType::Double => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// `FieldType`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FieldType {
+    Base(BaseType),
+    /// A class or interface, given by its binary name, such as `java/lang/Object`.
+    Object(String),
+    /// An array, with the type of its innermost elements, which is never an array itself.
+    Array { dimensions: u8, element: Box<FieldType> },
+}
+
+impl FieldType {
+    /// The number of local variable or operand stack slots a value of this type takes up.
+    pub fn slots(&self) -> usize {
+        match self {
+            FieldType::Base(base) => base.slots(),
+            FieldType::Object(_) | FieldType::Array { .. } => 1,
+        }
+    }
+
+    pub fn is_reference(&self) -> bool {
+        !matches!(self, FieldType::Base(_))
+    }
+
+    /// The type of the array's components, which has one less dimension than the array, or
+    /// `None` if this isn't an array.
+    pub fn component_type(&self) -> Option<FieldType> {
+        match self {
+            FieldType::Array { dimensions: 1, element } => Some(element.as_ref().clone()),
+            FieldType::Array { dimensions, element } => {
+                Some(FieldType::Array { dimensions: dimensions - 1, element: element.clone() })
+            },
+            _ => None,
+        }
+    }
+
+    /// Writes the type back out as a descriptor.
+    pub fn descriptor(&self) -> String {
+        let mut out = String::new();
+        self.write_descriptor(&mut out);
+        out
+    }
+
+    fn write_descriptor(&self, out: &mut String) {
+        match self {
+            FieldType::Base(base) => out.push(base.descriptor_char() as char),
+            FieldType::Object(name) => {
+                out.push('L');
+                out.push_str(name);
+                out.push(';');
+            },
+            FieldType::Array { dimensions, element } => {
+                out.extend(std::iter::repeat_n('[', *dimensions as usize));
+                element.write_descriptor(out);
+            },
+        }
+    }
+}
+
+/// `MethodDescriptor`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MethodDescriptor {
+    parameters: Vec<FieldType>,
+    return_type: Option<FieldType>,
+}
+
+impl MethodDescriptor {
+    pub fn parameters(&self) -> &[FieldType] {
+        &self.parameters
+    }
+
+    /// The return type, or `None` if the method returns void.
+    pub fn return_type(&self) -> Option<&FieldType> {
+        self.return_type.as_ref()
+    }
+
+    /// The number of local variable slots the parameters take up, not including `this`.
+    pub fn parameter_slots(&self) -> usize {
+        self.parameters.iter().map(FieldType::slots).sum()
+    }
+
+    /// Writes the descriptor back out.
+    pub fn descriptor(&self) -> String {
+        let mut out = String::from("(");
+        for parameter in &self.parameters {
+            parameter.write_descriptor(&mut out);
+        }
+        out.push(')');
+        match &self.return_type {
+            Some(return_type) => return_type.write_descriptor(&mut out),
+            None => out.push('V'),
+        }
+        out
+    }
+}
+
+/// The string given is not a valid descriptor.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DescriptorError {
+    offset: usize,
+    reason: &'static str,
+}
+
+impl DescriptorError {
+    /// The offset in to the descriptor, in bytes, at which the error was found.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid descriptor at byte {}: {}", self.offset, self.reason)
+    }
+}
+
+mod _parse {
+    use super::*;
+
+    impl FieldType {
+        pub fn parse(descriptor: &str) -> Result<FieldType, DescriptorError> {
+            let mut parser = Parser { input: descriptor, pos: 0 };
+            let field_type = parser.field_type()?;
+            parser.expect_end()?;
+            Ok(field_type)
+        }
+    }
+
+    impl MethodDescriptor {
+        pub fn parse(descriptor: &str) -> Result<MethodDescriptor, DescriptorError> {
+            let mut parser = Parser { input: descriptor, pos: 0 };
+            if !parser.eat(b'(') {
+                return parser.error("expected '(' before parameters");
+            }
+
+            let mut parameters = Vec::new();
+            while !parser.eat(b')') {
+                parameters.push(parser.field_type()?);
+            }
+
+            let return_type = if parser.eat(b'V') { None } else { Some(parser.field_type()?) };
+            parser.expect_end()?;
+
+            Ok(MethodDescriptor { parameters, return_type })
+        }
+    }
+
+    // Works on bytes, as everything that ends a name is ASCII, so can't appear in the middle
+    // of a multi-byte character
+    struct Parser<'a> {
+        input: &'a str,
+        pos: usize,
+    }
+
+    impl Parser<'_> {
+        fn error<T>(&self, reason: &'static str) -> Result<T, DescriptorError> {
+            Err(DescriptorError { offset: self.pos, reason })
+        }
+
+        fn peek(&self) -> Option<u8> {
+            self.input.as_bytes().get(self.pos).copied()
+        }
+
+        fn eat(&mut self, c: u8) -> bool {
+            let matches = self.peek() == Some(c);
+            if matches {
+                self.pos += 1;
+            }
+            matches
+        }
+
+        fn expect_end(&self) -> Result<(), DescriptorError> {
+            if self.pos == self.input.len() { Ok(()) } else { self.error("unexpected characters after end of descriptor") }
+        }
+
+        fn field_type(&mut self) -> Result<FieldType, DescriptorError> {
+            let start = self.pos;
+            while self.eat(b'[') {}
+            let dimensions = self.pos - start;
+            if dimensions > MAX_ARRAY_DIMENSIONS {
+                return Err(DescriptorError { offset: start, reason: "array has more than 255 dimensions" });
+            }
+
+            let element = match self.peek() {
+                Some(b'L') => {
+                    self.pos += 1;
+                    FieldType::Object(self.class_name()?)
+                },
+                Some(c) if let Some(base) = BaseType::from_char(c) => {
+                    self.pos += 1;
+                    FieldType::Base(base)
+                },
+                _ => return self.error("expected a field type"),
+            };
+
+            Ok(match dimensions {
+                0 => element,
+                // Checked above
+                _ => FieldType::Array { dimensions: dimensions as u8, element: Box::new(element) },
+            })
+        }
+
+        // A binary name, in its internal form, ended by ';'
+        // Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.2.1
+        fn class_name(&mut self) -> Result<String, DescriptorError> {
+            let start = self.pos;
+            let mut segment_start = self.pos;
+            loop {
+                match self.peek() {
+                    Some(b';') | Some(b'/') if self.pos == segment_start => {
+                        return self.error("empty name in class name");
+                    },
+                    Some(b';') => break,
+                    Some(b'/') => segment_start = self.pos + 1,
+                    Some(b'.') | Some(b'[') => return self.error("invalid character in class name"),
+                    None => return self.error("expected ';' after class name"),
+                    _ => {},
+                }
+                self.pos += 1;
+            }
+
+            let name = self.input[start..self.pos].to_string();
+            self.pos += 1;
+            Ok(name)
+        }
+    }
+}
+
+mod _display {
+    use super::*;
+
+    impl fmt::Display for BaseType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.java_name())
+        }
+    }
+
+    impl fmt::Display for FieldType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                FieldType::Base(base) => write!(f, "{base}"),
+                FieldType::Object(name) => f.write_str(&name.replace('/', ".")),
+                FieldType::Array { dimensions, element } => {
+                    write!(f, "{element}")?;
+                    for _ in 0..*dimensions {
+                        f.write_str("[]")?;
+                    }
+                    Ok(())
+                },
+            }
+        }
+    }
+
+    impl fmt::Display for MethodDescriptor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.return_type {
+                Some(return_type) => write!(f, "{return_type}")?,
+                None => f.write_str("void")?,
+            }
+            f.write_str(" (")?;
+            for (i, parameter) in self.parameters.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{parameter}")?;
+            }
+            f.write_str(")")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_offset(result: Result<impl fmt::Debug, DescriptorError>) -> usize {
+        result.expect_err("descriptor should be rejected").offset()
+    }
+
+    #[test]
+    fn parses_field_types() {
+        assert_eq!(FieldType::parse("J"), Ok(FieldType::Base(BaseType::Long)));
+        assert_eq!(FieldType::parse("Ljava/lang/String;"), Ok(FieldType::Object("java/lang/String".into())));
+
+        let array = FieldType::parse("[[Ljava/lang/Object;").expect("descriptor should parse");
+        assert_eq!(array, FieldType::Array { dimensions: 2, element: Box::new(FieldType::Object("java/lang/Object".into())) });
+        assert_eq!(array.to_string(), "java.lang.Object[][]");
+        assert_eq!(array.descriptor(), "[[Ljava/lang/Object;");
+        assert_eq!(array.component_type().map(|component| component.descriptor()), Some("[Ljava/lang/Object;".into()));
+        assert_eq!(FieldType::parse("[I").ok().and_then(|array| array.component_type()), Some(FieldType::Base(BaseType::Int)));
+    }
+
+    #[test]
+    fn parses_method_descriptors() {
+        let descriptor = MethodDescriptor::parse("(IDLjava/lang/Thread;[J)Ljava/lang/Object;").expect("descriptor should parse");
+        assert_eq!(descriptor.parameters().len(), 4);
+        // Doubles take two slots, but arrays of longs only take one
+        assert_eq!(descriptor.parameter_slots(), 5);
+        assert_eq!(descriptor.to_string(), "java.lang.Object (int, double, java.lang.Thread, long[])");
+        assert_eq!(descriptor.descriptor(), "(IDLjava/lang/Thread;[J)Ljava/lang/Object;");
+
+        let descriptor = MethodDescriptor::parse("()V").expect("descriptor should parse");
+        assert!(descriptor.return_type().is_none());
+        assert_eq!(descriptor.to_string(), "void ()");
+    }
+
+    #[test]
+    fn limits_array_dimensions() {
+        let descriptor = format!("{}I", "[".repeat(MAX_ARRAY_DIMENSIONS));
+        assert!(matches!(FieldType::parse(&descriptor), Ok(FieldType::Array { dimensions: 255, .. })));
+        assert_eq!(error_offset(FieldType::parse(&format!("[{descriptor}"))), 0);
+        assert_eq!(error_offset(MethodDescriptor::parse(&format!("(I[{descriptor})V"))), 2);
+    }
+
+    #[test]
+    fn rejects_bad_descriptors() {
+        assert_eq!(error_offset(FieldType::parse("V")), 0);
+        assert_eq!(error_offset(FieldType::parse("II")), 1);
+        assert_eq!(error_offset(FieldType::parse("Ljava/lang/String")), 17);
+        assert_eq!(error_offset(FieldType::parse("L;")), 1);
+        assert_eq!(error_offset(FieldType::parse("Ljava//String;")), 6);
+        assert_eq!(error_offset(FieldType::parse("Ljava.lang.String;")), 5);
+        assert_eq!(error_offset(MethodDescriptor::parse("I)V")), 0);
+        assert_eq!(error_offset(MethodDescriptor::parse("(V)V")), 1);
+        assert_eq!(error_offset(MethodDescriptor::parse("()")), 2);
+    }
+}
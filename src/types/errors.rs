@@ -13,4 +13,5 @@
 // You should have received a copy of the GNU General Public License along
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
+#[derive(Debug)]
 pub struct OutOfMemoryError;
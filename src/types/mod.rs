@@ -15,15 +15,21 @@
 
 mod primitives;
 pub mod methodhandle;
+pub mod descriptor;
+pub mod mutf8;
+pub mod signature;
 mod versions;
 mod array;
+mod bytes;
 mod errors;
 mod access_flags;
-pub mod annotations;
-pub mod type_annotations;
+mod symbol;
 
 pub use primitives::{Jbyte, Jshort, Jint, Jlong, Jchar, Jfloat, Jdouble, Jboolean};
-pub use versions::{ClassFileVersion, CURRENT_VIRTUAL_MACHINE_VERSION};
+pub use versions::{ClassFileVersion, Feature, Version, CURRENT_VIRTUAL_MACHINE_VERSION, PREVIEW_MINOR_VERSION};
 pub use array::{Array, OutOfBoundsError};
+pub use bytes::Bytes;
 pub use errors::*;
-pub use access_flags::AccessFlags;
+pub use access_flags::{ClassAccessFlags, ExportsFlags, FieldAccessFlags, InnerClassAccessFlags, MethodAccessFlags,
+    ModuleFlags, ParameterAccessFlags, RequiresFlags};
+pub use symbol::Symbol;
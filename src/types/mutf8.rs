@@ -0,0 +1,246 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Modified UTF-8, the string encoding used by class files.
+//!
+//! This differs from standard UTF-8 in two ways: the null character is encoded as the two
+//! bytes `0xC0 0x80` so that encoded strings never contain a zero byte, and characters
+//! outside of the Basic Multilingual Plane are encoded as a surrogate pair, each half of
+//! which is encoded separately as a three byte sequence. No sequences longer than three
+//! bytes are allowed, and other than null, characters must be encoded in as few bytes as
+//! they can be.
+//!
+//! Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.7
+
+use std::char;
+use std::fmt;
+use crate::types::Jchar;
+
+/// The bytes given are not valid modified UTF-8.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct MalformedError {
+    offset: usize,
+}
+
+impl MalformedError {
+    /// The offset in to the input of the first byte of the malformed sequence.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for MalformedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed modified UTF-8 sequence at byte {}", self.offset)
+    }
+}
+
+/// Checks that the given bytes are valid modified UTF-8, without decoding them.
+pub fn validate(bytes: &[u8]) -> Result<(), MalformedError> {
+    let mut decoder = Decoder { bytes, off: 0 };
+    while decoder.next().transpose()?.is_some() {}
+    Ok(())
+}
+
+/// Decodes the given bytes to UTF-16, which is how Java represents strings.
+///
+/// This is exact, unlike [decode], as Java strings may contain surrogates that aren't part
+/// of a pair, which a Rust string cannot represent.
+pub fn decode_utf16(bytes: &[u8]) -> Result<Vec<Jchar>, MalformedError> {
+    let mut decoder = Decoder { bytes, off: 0 };
+
+    // Every character takes at least one byte, so this is an upper bound
+    let mut result = Vec::with_capacity(bytes.len());
+    while let Some(c) = decoder.next().transpose()? {
+        result.push(c);
+    }
+    Ok(result)
+}
+
+/// Decodes the given bytes to a Rust string.
+///
+/// Any surrogates that aren't part of a pair are replaced with U+FFFD, as they cannot be
+/// represented in a Rust string. Use [decode_utf16] if these need to be preserved.
+pub fn decode(bytes: &[u8]) -> Result<String, MalformedError> {
+    // Most strings in class files (names, descriptors) are entirely ASCII, and the
+    // encodings are identical for those, so skip all the work of decoding.
+    if bytes.iter().all(|b| (0x01..0x80).contains(b)) {
+        // SAFETY: We just checked that every byte is ASCII, which is valid UTF-8
+        return Ok(unsafe { String::from_utf8_unchecked(bytes.to_vec()) });
+    }
+
+    let utf16 = decode_utf16(bytes)?;
+    let result = char::decode_utf16(utf16)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    Ok(result)
+}
+
+/// Encodes the given string as modified UTF-8.
+pub fn encode(s: &str) -> Vec<u8> {
+    let mut result = Vec::with_capacity(s.len());
+    for c in s.encode_utf16() {
+        encode_jchar(c, &mut result);
+    }
+    result
+}
+
+/// Encodes the given UTF-16 string as modified UTF-8. Unlike [encode], this preserves
+/// surrogates that aren't part of a pair.
+pub fn encode_utf16(s: &[Jchar]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(s.len());
+    for &c in s {
+        encode_jchar(c, &mut result);
+    }
+    result
+}
+
+/// The number of bytes the given string will take up once encoded as modified UTF-8.
+pub fn encoded_len(s: &str) -> usize {
+    s.encode_utf16().map(jchar_len).sum()
+}
+
+const fn jchar_len(c: Jchar) -> usize {
+    match c {
+        0x0001..=0x007F => 1,
+        // Null is encoded as two bytes rather than one
+        0x0000 | 0x0080..=0x07FF => 2,
+        _ => 3,
+    }
+}
+
+fn encode_jchar(c: Jchar, out: &mut Vec<u8>) {
+    match jchar_len(c) {
+        1 => out.push(c as u8),
+        2 => {
+            out.push(0xC0 | (c >> 6) as u8);
+            out.push(0x80 | (c & 0x3F) as u8);
+        }
+        _ => {
+            out.push(0xE0 | (c >> 12) as u8);
+            out.push(0x80 | ((c >> 6) & 0x3F) as u8);
+            out.push(0x80 | (c & 0x3F) as u8);
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    off: usize,
+}
+
+impl Decoder<'_> {
+    fn next(&mut self) -> Option<Result<Jchar, MalformedError>> {
+        let start = self.off;
+        let first = *self.bytes.get(start)?;
+        let malformed = Err(MalformedError { offset: start });
+
+        let result = match first {
+            // Zero bytes and anything in 0xF0..=0xFF can never appear
+            0x00 | 0xF0..=0xFF => malformed,
+            0x01..=0x7F => {
+                self.off += 1;
+                Ok(first as Jchar)
+            },
+            // Continuation byte without a leading byte
+            0x80..=0xBF => malformed,
+            // Characters must be encoded in as few bytes as they can be, other than null,
+            // which is always encoded as 0xC0 0x80
+            0xC0..=0xDF => match self.continuation(start + 1) {
+                Some(second) => match ((first as Jchar & 0x1F) << 6) | second {
+                    c @ (0x0000 | 0x0080..) => {
+                        self.off += 2;
+                        Ok(c)
+                    }
+                    _ => malformed,
+                },
+                None => malformed,
+            },
+            0xE0..=0xEF => match (self.continuation(start + 1), self.continuation(start + 2)) {
+                (Some(second), Some(third)) => match ((first as Jchar & 0x0F) << 12) | (second << 6) | third {
+                    c @ 0x0800.. => {
+                        self.off += 3;
+                        Ok(c)
+                    }
+                    _ => malformed,
+                },
+                _ => malformed,
+            },
+        };
+        Some(result)
+    }
+
+    // Returns the lower 6 bits of the byte at the given offset, if it is a continuation byte
+    fn continuation(&self, off: usize) -> Option<Jchar> {
+        match *self.bytes.get(off)? {
+            b @ 0x80..=0xBF => Some((b & 0x3F) as Jchar),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_is_two_bytes() {
+        assert_eq!(encode("a\0b"), [b'a', 0xC0, 0x80, b'b']);
+        assert_eq!(encoded_len("a\0b"), 4);
+        assert_eq!(decode(&[b'a', 0xC0, 0x80, b'b']).as_deref(), Ok("a\0b"));
+        // A plain zero byte is never allowed
+        assert_eq!(validate(&[b'a', 0]), Err(MalformedError { offset: 1 }));
+    }
+
+    #[test]
+    fn supplementary_characters_are_surrogate_pairs() {
+        let encoded = [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+        assert_eq!(encode("\u{1F600}"), encoded);
+        assert_eq!(decode_utf16(&encoded), Ok(vec![0xD83D, 0xDE00]));
+        assert_eq!(decode(&encoded).as_deref(), Ok("\u{1F600}"));
+        // Standard UTF-8's four byte form isn't allowed
+        assert_eq!(validate("\u{1F600}".as_bytes()), Err(MalformedError { offset: 0 }));
+    }
+
+    #[test]
+    fn lone_surrogates() {
+        let encoded = [b'a', 0xED, 0xA0, 0x80];
+        assert_eq!(decode_utf16(&encoded), Ok(vec![b'a' as Jchar, 0xD800]));
+        assert_eq!(encode_utf16(&[b'a' as Jchar, 0xD800]), encoded);
+        // They can't be represented in a Rust string
+        assert_eq!(decode(&encoded).as_deref(), Ok("a\u{FFFD}"));
+    }
+
+    #[test]
+    fn rejects_overlong_forms() {
+        // 'A' in two and three bytes, and U+07FF in three
+        assert_eq!(validate(&[b'x', 0xC1, 0x81]), Err(MalformedError { offset: 1 }));
+        assert_eq!(validate(&[0xE0, 0x81, 0x81]), Err(MalformedError { offset: 0 }));
+        assert_eq!(validate(&[0xE0, 0x9F, 0xBF]), Err(MalformedError { offset: 0 }));
+        // Null in three bytes
+        assert_eq!(validate(&[0xE0, 0x80, 0x80]), Err(MalformedError { offset: 0 }));
+        // The shortest forms of the same characters
+        assert_eq!(validate(&[0xC2, 0x80, 0xDF, 0xBF, 0xE0, 0xA0, 0x80]), Ok(()));
+    }
+
+    #[test]
+    fn rejects_bad_sequences() {
+        // A lone continuation byte, a missing continuation byte, and one cut off at the end
+        assert_eq!(validate(&[0x80]), Err(MalformedError { offset: 0 }));
+        assert_eq!(validate(&[b'a', 0xC2, b'b']), Err(MalformedError { offset: 1 }));
+        assert_eq!(validate(&[b'a', b'b', 0xE2, 0x82]), Err(MalformedError { offset: 2 }));
+        assert_eq!(decode(&[0xFF]), Err(MalformedError { offset: 0 }));
+    }
+}
@@ -0,0 +1,647 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+//! Generic signatures, as found in `Signature` attributes, which give the generic types of
+//! classes, methods and fields that their descriptors erase.
+//!
+//! Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.9.1
+
+use std::fmt;
+
+pub use super::descriptor::BaseType;
+
+/// `JavaTypeSignature`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum JavaType {
+    Base(BaseType),
+    Reference(ReferenceType),
+}
+
+/// `ReferenceTypeSignature`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ReferenceType {
+    Class(ClassType),
+    TypeVariable(String),
+    Array(Box<JavaType>),
+}
+
+/// `ClassTypeSignature`, such as `Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClassType {
+    package: Vec<String>,
+    classes: Vec<SimpleClassType>,
+}
+
+impl ClassType {
+    /// The names of the packages the class is in, outermost first.
+    pub fn package(&self) -> &[String] {
+        &self.package
+    }
+
+    /// The class, followed by each of the inner classes in the suffix, such as `Map` then
+    /// `Entry` for `Map.Entry`. This is never empty.
+    pub fn classes(&self) -> &[SimpleClassType] {
+        &self.classes
+    }
+
+    /// The binary name of the class, such as `java/util/Map$Entry`, which is what the
+    /// class's descriptor would have.
+    pub fn binary_name(&self) -> String {
+        let mut name = String::new();
+        for package in &self.package {
+            name.push_str(package);
+            name.push('/');
+        }
+        let classes: Vec<&str> = self.classes.iter().map(|class| class.name.as_str()).collect();
+        name.push_str(&classes.join("$"));
+        name
+    }
+}
+
+/// `SimpleClassTypeSignature`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SimpleClassType {
+    name: String,
+    type_arguments: Vec<TypeArgument>,
+}
+
+impl SimpleClassType {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn type_arguments(&self) -> &[TypeArgument] {
+        &self.type_arguments
+    }
+}
+
+/// `TypeArgument`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TypeArgument {
+    /// `?`
+    Wildcard,
+    /// `? extends T`
+    Extends(ReferenceType),
+    /// `? super T`
+    Super(ReferenceType),
+    Exact(ReferenceType),
+}
+
+/// `TypeParameter`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TypeParameter {
+    name: String,
+    class_bound: Option<ReferenceType>,
+    interface_bounds: Vec<ReferenceType>,
+}
+
+impl TypeParameter {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The bound that isn't an interface, which is missing if all the bounds are interfaces.
+    pub fn class_bound(&self) -> Option<&ReferenceType> {
+        self.class_bound.as_ref()
+    }
+
+    pub fn interface_bounds(&self) -> &[ReferenceType] {
+        &self.interface_bounds
+    }
+
+    /// All the bounds of the parameter, as `getBounds()` would give them.
+    pub fn bounds(&self) -> impl Iterator<Item = &ReferenceType> {
+        self.class_bound.iter().chain(self.interface_bounds.iter())
+    }
+}
+
+/// `ClassSignature`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClassSignature {
+    type_parameters: Vec<TypeParameter>,
+    superclass: ClassType,
+    superinterfaces: Vec<ClassType>,
+}
+
+impl ClassSignature {
+    pub fn type_parameters(&self) -> &[TypeParameter] {
+        &self.type_parameters
+    }
+
+    pub fn superclass(&self) -> &ClassType {
+        &self.superclass
+    }
+
+    pub fn superinterfaces(&self) -> &[ClassType] {
+        &self.superinterfaces
+    }
+}
+
+/// `MethodSignature`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MethodSignature {
+    type_parameters: Vec<TypeParameter>,
+    parameters: Vec<JavaType>,
+    result: Option<JavaType>,
+    throws: Vec<ReferenceType>,
+}
+
+impl MethodSignature {
+    pub fn type_parameters(&self) -> &[TypeParameter] {
+        &self.type_parameters
+    }
+
+    pub fn parameters(&self) -> &[JavaType] {
+        &self.parameters
+    }
+
+    /// The return type, or `None` if the method returns void.
+    pub fn result(&self) -> Option<&JavaType> {
+        self.result.as_ref()
+    }
+
+    /// The exceptions the method throws, which are always class types or type variables.
+    pub fn throws(&self) -> &[ReferenceType] {
+        &self.throws
+    }
+}
+
+/// `FieldSignature`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldSignature {
+    field_type: ReferenceType,
+}
+
+impl FieldSignature {
+    pub fn field_type(&self) -> &ReferenceType {
+        &self.field_type
+    }
+}
+
+/// The string given is not a valid signature.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SignatureError {
+    offset: usize,
+    reason: &'static str,
+}
+
+impl SignatureError {
+    /// The offset in to the signature, in bytes, at which the error was found.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid signature at byte {}: {}", self.offset, self.reason)
+    }
+}
+
+mod _parse {
+    use super::*;
+
+    /// How deeply reference types can be nested inside each other, through array components,
+    /// type arguments and bounds. Signatures are parsed recursively, so without this a long
+    /// enough signature could overflow the stack, both while parsing it and when dropping it.
+    /// This is enough for the 255 array dimensions that a descriptor can have.
+    const MAX_NESTING_DEPTH: usize = 256;
+
+    impl ClassSignature {
+        pub fn parse(signature: &str) -> Result<ClassSignature, SignatureError> {
+            let mut parser = Parser::new(signature);
+            let type_parameters = parser.type_parameters()?;
+            let superclass = parser.class_type()?;
+
+            let mut superinterfaces = Vec::new();
+            while !parser.at_end() {
+                superinterfaces.push(parser.class_type()?);
+            }
+            Ok(ClassSignature { type_parameters, superclass, superinterfaces })
+        }
+    }
+
+    impl MethodSignature {
+        pub fn parse(signature: &str) -> Result<MethodSignature, SignatureError> {
+            let mut parser = Parser::new(signature);
+            let type_parameters = parser.type_parameters()?;
+
+            parser.expect(b'(', "expected '(' before parameters")?;
+            let mut parameters = Vec::new();
+            while !parser.eat(b')') {
+                parameters.push(parser.java_type()?);
+            }
+
+            let result = if parser.eat(b'V') { None } else { Some(parser.java_type()?) };
+
+            let mut throws = Vec::new();
+            while parser.eat(b'^') {
+                let exception = match parser.peek() {
+                    Some(b'L') => ReferenceType::Class(parser.class_type()?),
+                    Some(b'T') => parser.type_variable()?,
+                    _ => return parser.error("expected a class type or type variable after '^'"),
+                };
+                throws.push(exception);
+            }
+            parser.expect_end()?;
+
+            Ok(MethodSignature { type_parameters, parameters, result, throws })
+        }
+    }
+
+    impl FieldSignature {
+        pub fn parse(signature: &str) -> Result<FieldSignature, SignatureError> {
+            let mut parser = Parser::new(signature);
+            let field_type = parser.reference_type()?;
+            parser.expect_end()?;
+            Ok(FieldSignature { field_type })
+        }
+    }
+
+    struct Parser<'a> {
+        input: &'a str,
+        pos: usize,
+        // How many reference types are being parsed, one inside the other
+        depth: usize,
+    }
+
+    impl<'a> Parser<'a> {
+        fn new(input: &'a str) -> Self {
+            Parser { input, pos: 0, depth: 0 }
+        }
+
+        fn error<T>(&self, reason: &'static str) -> Result<T, SignatureError> {
+            Err(SignatureError { offset: self.pos, reason })
+        }
+
+        fn at_end(&self) -> bool {
+            self.pos >= self.input.len()
+        }
+
+        fn peek(&self) -> Option<u8> {
+            self.input.as_bytes().get(self.pos).copied()
+        }
+
+        fn eat(&mut self, c: u8) -> bool {
+            let matches = self.peek() == Some(c);
+            if matches {
+                self.pos += 1;
+            }
+            matches
+        }
+
+        fn expect(&mut self, c: u8, reason: &'static str) -> Result<(), SignatureError> {
+            if self.eat(c) { Ok(()) } else { self.error(reason) }
+        }
+
+        fn expect_end(&self) -> Result<(), SignatureError> {
+            if self.at_end() { Ok(()) } else { self.error("unexpected characters after end of signature") }
+        }
+
+        // Identifiers can contain anything but these, which are all ASCII, so can't appear
+        // in the middle of a multi-byte character
+        fn identifier(&mut self) -> Result<String, SignatureError> {
+            let start = self.pos;
+            while let Some(c) = self.peek() && !matches!(c, b'.' | b';' | b'[' | b'/' | b'<' | b'>' | b':') {
+                self.pos += 1;
+            }
+            if self.pos == start {
+                return self.error("expected an identifier");
+            }
+            Ok(self.input[start..self.pos].to_string())
+        }
+
+        fn type_parameters(&mut self) -> Result<Vec<TypeParameter>, SignatureError> {
+            let mut parameters = Vec::new();
+            if !self.eat(b'<') {
+                return Ok(parameters);
+            }
+
+            loop {
+                let name = self.identifier()?;
+                self.expect(b':', "expected ':' before class bound")?;
+                // The class bound is left out when all bounds are interfaces
+                let class_bound = match self.peek() {
+                    Some(b':') => None,
+                    _ => Some(self.reference_type()?),
+                };
+
+                let mut interface_bounds = Vec::new();
+                while self.eat(b':') {
+                    interface_bounds.push(self.reference_type()?);
+                }
+                parameters.push(TypeParameter { name, class_bound, interface_bounds });
+
+                if self.eat(b'>') {
+                    return Ok(parameters);
+                }
+            }
+        }
+
+        fn java_type(&mut self) -> Result<JavaType, SignatureError> {
+            if let Some(base) = self.peek().and_then(BaseType::from_char) {
+                self.pos += 1;
+                return Ok(JavaType::Base(base));
+            }
+            Ok(JavaType::Reference(self.reference_type()?))
+        }
+
+        // Every nested type is parsed through here, so this is where the depth is limited
+        fn reference_type(&mut self) -> Result<ReferenceType, SignatureError> {
+            if self.depth >= MAX_NESTING_DEPTH {
+                return self.error("types are nested too deeply (max depth is 256)");
+            }
+            self.depth += 1;
+            let reference = self.nested_reference_type();
+            self.depth -= 1;
+            reference
+        }
+
+        fn nested_reference_type(&mut self) -> Result<ReferenceType, SignatureError> {
+            match self.peek() {
+                Some(b'L') => Ok(ReferenceType::Class(self.class_type()?)),
+                Some(b'T') => self.type_variable(),
+                Some(b'[') => {
+                    self.pos += 1;
+                    Ok(ReferenceType::Array(Box::new(self.java_type()?)))
+                },
+                _ => self.error("expected a reference type"),
+            }
+        }
+
+        fn type_variable(&mut self) -> Result<ReferenceType, SignatureError> {
+            self.expect(b'T', "expected a type variable")?;
+            let name = self.identifier()?;
+            self.expect(b';', "expected ';' after type variable")?;
+            Ok(ReferenceType::TypeVariable(name))
+        }
+
+        fn class_type(&mut self) -> Result<ClassType, SignatureError> {
+            self.expect(b'L', "expected a class type")?;
+
+            // Everything up to the last '/' before the class name is the package
+            let mut package = Vec::new();
+            let mut name = self.identifier()?;
+            while self.eat(b'/') {
+                package.push(name);
+                name = self.identifier()?;
+            }
+
+            let mut classes = vec![self.simple_class_type(name)?];
+            while self.eat(b'.') {
+                let name = self.identifier()?;
+                classes.push(self.simple_class_type(name)?);
+            }
+            self.expect(b';', "expected ';' after class type")?;
+
+            Ok(ClassType { package, classes })
+        }
+
+        fn simple_class_type(&mut self, name: String) -> Result<SimpleClassType, SignatureError> {
+            let mut type_arguments = Vec::new();
+            if self.eat(b'<') {
+                loop {
+                    type_arguments.push(self.type_argument()?);
+                    if self.eat(b'>') {
+                        break;
+                    }
+                }
+            }
+            Ok(SimpleClassType { name, type_arguments })
+        }
+
+        fn type_argument(&mut self) -> Result<TypeArgument, SignatureError> {
+            if self.eat(b'*') {
+                Ok(TypeArgument::Wildcard)
+            } else if self.eat(b'+') {
+                Ok(TypeArgument::Extends(self.reference_type()?))
+            } else if self.eat(b'-') {
+                Ok(TypeArgument::Super(self.reference_type()?))
+            } else {
+                Ok(TypeArgument::Exact(self.reference_type()?))
+            }
+        }
+    }
+}
+
+mod _display {
+    use super::*;
+
+    // Writes each item with the separator between them
+    fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T], separator: &str) -> fmt::Result {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(separator)?;
+            }
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+
+    fn write_type_parameters(f: &mut fmt::Formatter<'_>, parameters: &[TypeParameter]) -> fmt::Result {
+        if parameters.is_empty() {
+            return Ok(());
+        }
+        f.write_str("<")?;
+        write_list(f, parameters, ", ")?;
+        f.write_str("> ")
+    }
+
+    impl fmt::Display for JavaType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                JavaType::Base(base) => write!(f, "{base}"),
+                JavaType::Reference(reference) => write!(f, "{reference}"),
+            }
+        }
+    }
+
+    impl fmt::Display for ReferenceType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ReferenceType::Class(class) => write!(f, "{class}"),
+                ReferenceType::TypeVariable(name) => f.write_str(name),
+                ReferenceType::Array(component) => write!(f, "{component}[]"),
+            }
+        }
+    }
+
+    impl fmt::Display for ClassType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for package in &self.package {
+                write!(f, "{package}.")?;
+            }
+            write_list(f, &self.classes, ".")
+        }
+    }
+
+    impl fmt::Display for SimpleClassType {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.name)?;
+            if !self.type_arguments.is_empty() {
+                f.write_str("<")?;
+                write_list(f, &self.type_arguments, ", ")?;
+                f.write_str(">")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Display for TypeArgument {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TypeArgument::Wildcard => f.write_str("?"),
+                TypeArgument::Extends(bound) => write!(f, "? extends {bound}"),
+                TypeArgument::Super(bound) => write!(f, "? super {bound}"),
+                TypeArgument::Exact(argument) => write!(f, "{argument}"),
+            }
+        }
+    }
+
+    impl fmt::Display for TypeParameter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.name)?;
+
+            // An Object bound is what a parameter with no bounds gets, so it isn't shown
+            let is_object = |bound: &ReferenceType| matches!(bound,
+                ReferenceType::Class(class) if class.binary_name() == "java/lang/Object");
+            let bounds: Vec<&ReferenceType> = match (&self.class_bound, self.interface_bounds.is_empty()) {
+                (Some(bound), true) if is_object(bound) => Vec::new(),
+                _ => self.bounds().collect(),
+            };
+
+            for (i, bound) in bounds.iter().enumerate() {
+                f.write_str(if i == 0 { " extends " } else { " & " })?;
+                write!(f, "{bound}")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Display for ClassSignature {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_type_parameters(f, &self.type_parameters)?;
+            write!(f, "extends {}", self.superclass)?;
+            if !self.superinterfaces.is_empty() {
+                f.write_str(" implements ")?;
+                write_list(f, &self.superinterfaces, ", ")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Display for MethodSignature {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_type_parameters(f, &self.type_parameters)?;
+            match &self.result {
+                Some(result) => write!(f, "{result}")?,
+                None => f.write_str("void")?,
+            }
+            f.write_str(" (")?;
+            write_list(f, &self.parameters, ", ")?;
+            f.write_str(")")?;
+            if !self.throws.is_empty() {
+                f.write_str(" throws ")?;
+                write_list(f, &self.throws, ", ")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Display for FieldSignature {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.field_type)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_offset(result: Result<impl fmt::Debug, SignatureError>) -> usize {
+        result.expect_err("signature should be rejected").offset()
+    }
+
+    #[test]
+    fn parses_class_signatures() {
+        let signature = ClassSignature::parse(
+            "<K:Ljava/lang/Object;V::Ljava/lang/Comparable<-TV;>;>Ljava/util/AbstractMap<TK;TV;>;Ljava/io/Serializable;")
+            .expect("signature should parse");
+
+        let [k, v] = signature.type_parameters() else {
+            panic!("expected two type parameters");
+        };
+        assert_eq!(k.name(), "K");
+        assert!(k.interface_bounds().is_empty());
+        assert!(v.class_bound().is_none());
+        assert_eq!(v.bounds().count(), 1);
+        assert_eq!(signature.superclass().binary_name(), "java/util/AbstractMap");
+        assert_eq!(signature.superclass().package(), ["java", "util"]);
+        assert_eq!(signature.superinterfaces().len(), 1);
+        assert_eq!(signature.to_string(),
+            "<K, V extends java.lang.Comparable<? super V>> extends java.util.AbstractMap<K, V> implements java.io.Serializable");
+    }
+
+    #[test]
+    fn parses_method_signatures() {
+        let signature = MethodSignature::parse("<T:Ljava/lang/Object;>([TT;ILjava/util/List<*>;)V^TE;^Ljava/io/IOException;")
+            .expect("signature should parse");
+        assert_eq!(signature.parameters().len(), 3);
+        assert!(signature.result().is_none());
+        assert!(matches!(signature.throws(), [ReferenceType::TypeVariable(name), ReferenceType::Class(_)] if name == "E"));
+        assert_eq!(signature.to_string(), "<T> void (T[], int, java.util.List<?>) throws E, java.io.IOException");
+
+        let signature = MethodSignature::parse("()[[J").expect("signature should parse");
+        assert_eq!(signature.to_string(), "long[][] ()");
+    }
+
+    #[test]
+    fn parses_inner_classes() {
+        let signature = FieldSignature::parse("Ljava/util/Map<TK;TV;>.Entry<+Ljava/lang/Number;[I>;")
+            .expect("signature should parse");
+        let ReferenceType::Class(class) = signature.field_type() else {
+            panic!("expected a class type");
+        };
+        assert_eq!(class.binary_name(), "java/util/Map$Entry");
+        assert_eq!(class.classes().iter().map(SimpleClassType::name).collect::<Vec<_>>(), ["Map", "Entry"]);
+        assert_eq!(signature.to_string(), "java.util.Map<K, V>.Entry<? extends java.lang.Number, int[]>");
+    }
+
+    #[test]
+    fn rejects_bad_signatures() {
+        assert_eq!(error_offset(FieldSignature::parse("I")), 0);
+        assert_eq!(error_offset(FieldSignature::parse("Ljava/lang/String")), 17);
+        assert_eq!(error_offset(FieldSignature::parse("TT;x")), 3);
+        assert_eq!(error_offset(MethodSignature::parse("(I")), 2);
+        assert_eq!(error_offset(MethodSignature::parse("()V^I")), 4);
+        assert_eq!(error_offset(ClassSignature::parse("<T>Ljava/lang/Object;")), 2);
+        assert_eq!(error_offset(ClassSignature::parse("Ljava/lang/Object;TT;")), 18);
+    }
+
+    #[test]
+    fn limits_nesting() {
+        // As many dimensions as a descriptor can have is fine
+        let signature = FieldSignature::parse(&format!("{}I", "[".repeat(255))).expect("signature should parse");
+        assert_eq!(signature.to_string(), format!("int{}", "[]".repeat(255)));
+        assert!(FieldSignature::parse(&format!("{}I", "[".repeat(256))).is_ok());
+        assert_eq!(error_offset(FieldSignature::parse(&format!("{}I", "[".repeat(257)))), 256);
+
+        // Neither of these overflow the stack
+        assert_eq!(error_offset(FieldSignature::parse(&"[".repeat(1_000_000))), 256);
+        let nested = format!("{}TT;{}", "LA<".repeat(1_000_000), ">;".repeat(1_000_000));
+        assert_eq!(error_offset(FieldSignature::parse(&nested)), 256 * 3);
+    }
+}
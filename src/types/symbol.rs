@@ -0,0 +1,228 @@
+// Copyright (C) 2026 Callum Jay Seabrook Hefford (BomBardyGamer)
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ptr;
+use std::sync::{OnceLock, RwLock};
+use crate::types::Jchar;
+
+/// An interned string, such as a class, method or field name, or a descriptor.
+///
+/// Every symbol with the same contents shares the same underlying string, so comparing two
+/// symbols is a pointer comparison rather than a string comparison, and copying one is as
+/// cheap as copying a reference.
+///
+/// Symbols are interned by their exact UTF-16 value, as Java strings can contain surrogates
+/// that aren't part of a pair, which a Rust string can't. Such symbols are distinct from
+/// every other symbol, including the one for their string value, which has U+FFFD in place
+/// of the lone surrogates.
+///
+/// Interned strings live for the rest of the program, so only strings that are likely to be
+/// needed again (i.e. names from class files) should be interned.
+#[derive(Copy, Clone)]
+pub struct Symbol(&'static Interned);
+
+struct Interned {
+    value: &'static str,
+    // Only kept when the string value isn't exact
+    exact: Option<&'static [Jchar]>,
+}
+
+#[derive(Default)]
+struct Table {
+    strings: HashMap<&'static str, &'static Interned>,
+    // Keyed by the exact value, for the symbols that can't be represented as a string
+    inexact: HashMap<&'static [Jchar], &'static Interned>,
+}
+
+impl Symbol {
+    /// Returns the symbol for the given string, adding it to the symbol table if this is the
+    /// first time it has been seen.
+    pub fn intern(s: &str) -> Symbol {
+        Self::intern_in(|table| table.strings.get(s).copied(), |table| {
+            let interned = leak(Interned { value: Box::leak(s.into()), exact: None });
+            table.strings.insert(interned.value, interned);
+            interned
+        })
+    }
+
+    /// Returns the symbol for the given UTF-16 string, which is the same as the symbol for
+    /// the equivalent Rust string if it doesn't have any surrogates that aren't in a pair.
+    pub fn intern_utf16(s: &[Jchar]) -> Symbol {
+        let Ok(value) = char::decode_utf16(s.iter().copied()).collect::<Result<String, _>>() else {
+            return Self::intern_in(|table| table.inexact.get(s).copied(), |table| {
+                let value = char::decode_utf16(s.iter().copied())
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect::<String>();
+                let exact: &'static [Jchar] = Box::leak(s.into());
+                let interned = leak(Interned { value: Box::leak(value.into_boxed_str()), exact: Some(exact) });
+                table.inexact.insert(exact, interned);
+                interned
+            });
+        };
+        Self::intern(&value)
+    }
+
+    fn intern_in(get: impl Fn(&Table) -> Option<&'static Interned>,
+                 insert: impl FnOnce(&mut Table) -> &'static Interned) -> Symbol {
+        let table = table();
+
+        // Almost every lookup will be for a symbol that already exists, so try that first
+        // without holding the write lock
+        if let Some(existing) = get(&table.read().expect("symbol table poisoned")) {
+            return Symbol(existing);
+        }
+
+        let mut table = table.write().expect("symbol table poisoned");
+        // Someone else may have added it between us releasing the read lock and acquiring the
+        // write lock, so check again
+        if let Some(existing) = get(&table) {
+            return Symbol(existing);
+        }
+        Symbol(insert(&mut table))
+    }
+
+    /// The string value of the symbol, which has U+FFFD in place of any surrogates that aren't
+    /// part of a pair.
+    pub const fn as_str(&self) -> &'static str {
+        self.0.value
+    }
+
+    /// Whether [Symbol::as_str] is the exact value of the symbol.
+    pub const fn is_exact(&self) -> bool {
+        self.0.exact.is_none()
+    }
+
+    /// The exact UTF-16 value of the symbol.
+    pub fn to_utf16(&self) -> Vec<Jchar> {
+        match self.0.exact {
+            Some(exact) => exact.to_vec(),
+            None => self.0.value.encode_utf16().collect(),
+        }
+    }
+}
+
+fn leak(interned: Interned) -> &'static Interned {
+    Box::leak(Box::new(interned))
+}
+
+fn table() -> &'static RwLock<Table> {
+    static TABLE: OnceLock<RwLock<Table>> = OnceLock::new();
+    TABLE.get_or_init(|| RwLock::new(Table::default()))
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        // Symbols are unique, so if the contents are the same, so is the pointer
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.0, state)
+    }
+}
+
+// A symbol that isn't exact isn't equal to any string, not even its own string value
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.is_exact() && self.0.value == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0.value, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use super::*;
+
+    #[test]
+    fn same_contents_same_symbol() {
+        let a = Symbol::intern("java/lang/Object");
+        let b = Symbol::intern(&String::from("java/lang/Object"));
+        assert_eq!(a, b);
+        assert!(ptr::eq(a.as_str(), b.as_str()));
+        assert_ne!(a, Symbol::intern("java/lang/String"));
+        assert_eq!(a, "java/lang/Object");
+    }
+
+    #[test]
+    fn interned_from_many_threads() {
+        let symbols: Vec<Symbol> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8).map(|_| scope.spawn(|| Symbol::intern("symbol::threads"))).collect();
+            handles.into_iter().map(|h| h.join().expect("thread panicked")).collect()
+        });
+        assert!(symbols.iter().all(|&symbol| symbol == symbols[0]));
+    }
+
+    #[test]
+    fn utf16_without_lone_surrogates_is_a_string() {
+        let utf16: Vec<Jchar> = "caf\u{E9} \u{1F600}".encode_utf16().collect();
+        let symbol = Symbol::intern_utf16(&utf16);
+        assert_eq!(symbol, Symbol::intern("caf\u{E9} \u{1F600}"));
+        assert!(symbol.is_exact());
+        assert_eq!(symbol.to_utf16(), utf16);
+    }
+
+    #[test]
+    fn lone_surrogates_are_kept() {
+        let high = Symbol::intern_utf16(&[b'a' as Jchar, 0xD800]);
+        let low = Symbol::intern_utf16(&[b'a' as Jchar, 0xDC00]);
+        assert_ne!(high, low);
+        assert_eq!(high, Symbol::intern_utf16(&[b'a' as Jchar, 0xD800]));
+
+        // Both have the same string value, but aren't the symbol for that string
+        assert_eq!(high.as_str(), "a\u{FFFD}");
+        assert_eq!(low.as_str(), "a\u{FFFD}");
+        assert_ne!(high, Symbol::intern("a\u{FFFD}"));
+        assert!(high != "a\u{FFFD}");
+        assert!(!high.is_exact());
+        assert_eq!(high.to_utf16(), [b'a' as Jchar, 0xD800]);
+    }
+
+    #[test]
+    fn hashes_by_identity() {
+        let mut counts = HashMap::new();
+        for name in ["a", "b", "a"] {
+            *counts.entry(Symbol::intern(name)).or_insert(0) += 1;
+        }
+        *counts.entry(Symbol::intern_utf16(&[0xDFFF])).or_insert(0) += 1;
+        assert_eq!(counts[&Symbol::intern("a")], 2);
+        assert_eq!(counts.len(), 3);
+    }
+}
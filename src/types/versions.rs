@@ -14,8 +14,11 @@
 // with this program; if not, see <https://www.gnu.org/licenses/>.
 
 use std::fmt;
+use enum_primitive_derive::Primitive;
+use num_traits::FromPrimitive;
 
-#[derive(Debug)]
+#[repr(u16)]
+#[derive(Primitive, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
 pub enum ClassFileVersion {
     Java1_1 = 45,
     Java1_2 = 46,
@@ -51,3 +54,125 @@ impl fmt::Display for ClassFileVersion {
 }
 
 pub const CURRENT_VIRTUAL_MACHINE_VERSION: ClassFileVersion = ClassFileVersion::Java25;
+
+/// The minor version that marks a class file as depending on the preview features of its
+/// major version.
+pub const PREVIEW_MINOR_VERSION: u16 = 65535;
+
+impl ClassFileVersion {
+    pub fn from_major(major: u16) -> Option<ClassFileVersion> {
+        ClassFileVersion::from_u16(major)
+    }
+
+    pub const fn major(self) -> u16 {
+        self as u16
+    }
+}
+
+/// The full version of a class file, as a major and minor version pair.
+///
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.1-200-B.2
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Version {
+    major: u16,
+    minor: u16,
+}
+
+impl Version {
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Version { major, minor }
+    }
+
+    pub const fn major(&self) -> u16 {
+        self.major
+    }
+
+    pub const fn minor(&self) -> u16 {
+        self.minor
+    }
+
+    /// Whether this version depends on preview features. Before Java 12 the minor version had
+    /// no such meaning, so only versions from 56 onwards can be preview versions.
+    pub const fn is_preview(&self) -> bool {
+        self.major >= ClassFileVersion::Java12 as u16 && self.minor == PREVIEW_MINOR_VERSION
+    }
+
+    pub fn class_file_version(&self) -> Option<ClassFileVersion> {
+        ClassFileVersion::from_major(self.major)
+    }
+
+    /// Whether a class file of this version may use the given feature.
+    pub const fn supports(&self, feature: Feature) -> bool {
+        match feature.until() {
+            Some(until) => self.major >= feature.since().major() && self.major <= until.major(),
+            None => self.major >= feature.since().major(),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Features of the class file format that are only legal in some versions of it.
+///
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4-310
+/// Ref: https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7-320
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Feature {
+    /// The `jsr`, `jsr_w` and `ret` instructions
+    JsrRet,
+    /// Generic signatures, enclosing methods, annotations, `SourceDebugExtension` and
+    /// `LocalVariableTypeTable`
+    Java5Metadata,
+    /// The `StackMapTable` attribute and verification by type checking
+    StackMapTable,
+    /// Method handle, method type and invoke dynamic constants, the `invokedynamic` instruction
+    /// and the `BootstrapMethods` attribute
+    InvokeDynamic,
+    /// Static and private interface methods, and invoking them through interface method refs
+    InterfaceMethods,
+    /// The `MethodParameters` attribute
+    MethodParameters,
+    /// The `RuntimeVisibleTypeAnnotations` and `RuntimeInvisibleTypeAnnotations` attributes
+    TypeAnnotations,
+    /// Module and package constants and the module attributes
+    Modules,
+    /// Dynamically-computed constants
+    DynamicConstants,
+    /// The `NestHost` and `NestMembers` attributes
+    NestMates,
+    /// The `Record` attribute
+    Records,
+    /// The `PermittedSubclasses` attribute
+    SealedClasses,
+}
+
+impl Feature {
+    /// The first version that supports this feature.
+    pub const fn since(self) -> ClassFileVersion {
+        match self {
+            Feature::JsrRet => ClassFileVersion::Java1_1,
+            Feature::Java5Metadata => ClassFileVersion::Java5,
+            Feature::StackMapTable => ClassFileVersion::Java6,
+            Feature::InvokeDynamic => ClassFileVersion::Java7,
+            Feature::InterfaceMethods | Feature::MethodParameters | Feature::TypeAnnotations => {
+                ClassFileVersion::Java8
+            },
+            Feature::Modules => ClassFileVersion::Java9,
+            Feature::DynamicConstants | Feature::NestMates => ClassFileVersion::Java11,
+            Feature::Records => ClassFileVersion::Java16,
+            Feature::SealedClasses => ClassFileVersion::Java17,
+        }
+    }
+
+    /// The last version that supports this feature, if it has since been removed.
+    pub const fn until(self) -> Option<ClassFileVersion> {
+        match self {
+            Feature::JsrRet => Some(ClassFileVersion::Java6),
+            _ => None,
+        }
+    }
+}